@@ -383,6 +383,78 @@ mod consts {
     pub const ZN_QUERIES_DEFAULT_TIMEOUT_KEY: u64 = 0x88;
     pub const ZN_QUERIES_DEFAULT_TIMEOUT_STR: &str = "local_routing";
     pub const ZN_QUERIES_DEFAULT_TIMEOUT_DEFAULT: &str = "10000";
+
+    /// Configures the scheduling of the messages on the links of a unicast session.
+    /// String key : `"link_scheduling"`.
+    /// Accepted values : `"active_standby"`, `"round_robin"`, `"least_queued"`.
+    /// Default value : `"active_standby"`.
+    pub const ZN_LINK_SCHEDULING_KEY: u64 = 0x89;
+    pub const ZN_LINK_SCHEDULING_STR: &str = "link_scheduling";
+    pub const ZN_LINK_SCHEDULING_DEFAULT: &str = "active_standby";
+
+    /// Indicates if the reliable frames sent on a multicast session are retransmitted
+    /// when reported as missing by the receivers.
+    /// String key : `"multicast_reliable"`.
+    /// Accepted values : `"true"`, `"false"`.
+    /// Default value : `"false"`.
+    pub const ZN_MULTICAST_RELIABLE_KEY: u64 = 0x8A;
+    pub const ZN_MULTICAST_RELIABLE_STR: &str = "multicast_reliable";
+    pub const ZN_MULTICAST_RELIABLE_DEFAULT: &str = ZN_FALSE;
+
+    /// Indicates if the links opened by this instance request the compression of the batches.
+    /// String key : `"compression"`.
+    /// Accepted values : `"true"`, `"false"`.
+    /// Default value : `"false"`.
+    pub const ZN_COMPRESSION_KEY: u64 = 0x8B;
+    pub const ZN_COMPRESSION_STR: &str = "compression";
+    pub const ZN_COMPRESSION_DEFAULT: &str = ZN_FALSE;
+
+    /// The size in bytes under which the batches are sent uncompressed.
+    /// String key : `"compression_threshold"`.
+    /// Accepted values : `<unsigned integer>`.
+    /// Default value : `256`.
+    pub const ZN_COMPRESSION_THRESHOLD_KEY: u64 = 0x8C;
+    pub const ZN_COMPRESSION_THRESHOLD_STR: &str = "compression_threshold";
+    pub const ZN_COMPRESSION_THRESHOLD_DEFAULT: &str = "256";
+
+    /// Indicates if the batches are encrypted with the session key derived during the RSA handshake.
+    /// String key : `"auth_rsa_encryption"`.
+    /// Accepted values : `"true"`, `"false"`.
+    /// Default value : `"false"`.
+    pub const ZN_AUTH_RSA_ENCRYPTION_KEY: u64 = 0x8D;
+    pub const ZN_AUTH_RSA_ENCRYPTION_STR: &str = "auth_rsa_encryption";
+    pub const ZN_AUTH_RSA_ENCRYPTION_DEFAULT: &str = ZN_FALSE;
+
+    /// The server name used for SNI and for the verification of the TLS server certificate.
+    /// String key : `"tls_server_name"`.
+    /// Accepted values : `<DNS name>`.
+    /// Default value : The host of the locator.
+    pub const ZN_TLS_SERVER_NAME_KEY: u64 = 0x8E;
+    pub const ZN_TLS_SERVER_NAME_STR: &str = "tls_server_name";
+
+    /// The application protocols negotiated via ALPN on TLS and QUIC links.
+    /// String key : `"tls_alpn"`.
+    /// Accepted values : `<protocol>[,<protocol>]*`.
+    /// Default value : None for TLS, `"hq-29"` for QUIC.
+    pub const ZN_TLS_ALPN_KEY: u64 = 0x8F;
+    pub const ZN_TLS_ALPN_STR: &str = "tls_alpn";
+
+    /// The weight of the links in the link state routing graph.
+    /// It is also the endpoint configuration key overriding the weight of a link.
+    /// String key : `"link_weight"`.
+    /// Accepted values : `<unsigned integer>` greater than 0.
+    /// Default value : `100`.
+    pub const ZN_LINK_WEIGHT_KEY: u64 = 0x90;
+    pub const ZN_LINK_WEIGHT_STR: &str = "link_weight";
+    pub const ZN_LINK_WEIGHT_DEFAULT: &str = "100";
+
+    /// Indicates if the links opened by this instance are weighted by their round-trip time.
+    /// String key : `"link_weight_rtt"`.
+    /// Accepted values : `"true"`, `"false"`.
+    /// Default value : `"false"`.
+    pub const ZN_LINK_WEIGHT_RTT_KEY: u64 = 0x91;
+    pub const ZN_LINK_WEIGHT_RTT_STR: &str = "link_weight_rtt";
+    pub const ZN_LINK_WEIGHT_RTT_DEFAULT: &str = ZN_FALSE;
 }
 
 pub use consts::*;
@@ -443,6 +515,15 @@ impl KeyTranscoder for ConfigTranscoder {
             ZN_TLS_CLIENT_PRIVATE_KEY_STR => Some(ZN_TLS_CLIENT_PRIVATE_KEY_KEY),
             ZN_TLS_CLIENT_CERTIFICATE_STR => Some(ZN_TLS_CLIENT_CERTIFICATE_KEY),
             ZN_TLS_CLIENT_AUTH_STR => Some(ZN_TLS_CLIENT_AUTH_KEY),
+            ZN_LINK_SCHEDULING_STR => Some(ZN_LINK_SCHEDULING_KEY),
+            ZN_MULTICAST_RELIABLE_STR => Some(ZN_MULTICAST_RELIABLE_KEY),
+            ZN_COMPRESSION_STR => Some(ZN_COMPRESSION_KEY),
+            ZN_COMPRESSION_THRESHOLD_STR => Some(ZN_COMPRESSION_THRESHOLD_KEY),
+            ZN_AUTH_RSA_ENCRYPTION_STR => Some(ZN_AUTH_RSA_ENCRYPTION_KEY),
+            ZN_TLS_SERVER_NAME_STR => Some(ZN_TLS_SERVER_NAME_KEY),
+            ZN_TLS_ALPN_STR => Some(ZN_TLS_ALPN_KEY),
+            ZN_LINK_WEIGHT_STR => Some(ZN_LINK_WEIGHT_KEY),
+            ZN_LINK_WEIGHT_RTT_STR => Some(ZN_LINK_WEIGHT_RTT_KEY),
             _ => None,
         }
     }
@@ -501,6 +582,15 @@ impl KeyTranscoder for ConfigTranscoder {
             ZN_TLS_CLIENT_PRIVATE_KEY_KEY => Some(ZN_TLS_CLIENT_PRIVATE_KEY_STR.to_string()),
             ZN_TLS_CLIENT_CERTIFICATE_KEY => Some(ZN_TLS_CLIENT_CERTIFICATE_STR.to_string()),
             ZN_TLS_CLIENT_AUTH_KEY => Some(ZN_TLS_CLIENT_AUTH_STR.to_string()),
+            ZN_LINK_SCHEDULING_KEY => Some(ZN_LINK_SCHEDULING_STR.to_string()),
+            ZN_MULTICAST_RELIABLE_KEY => Some(ZN_MULTICAST_RELIABLE_STR.to_string()),
+            ZN_COMPRESSION_KEY => Some(ZN_COMPRESSION_STR.to_string()),
+            ZN_COMPRESSION_THRESHOLD_KEY => Some(ZN_COMPRESSION_THRESHOLD_STR.to_string()),
+            ZN_AUTH_RSA_ENCRYPTION_KEY => Some(ZN_AUTH_RSA_ENCRYPTION_STR.to_string()),
+            ZN_TLS_SERVER_NAME_KEY => Some(ZN_TLS_SERVER_NAME_STR.to_string()),
+            ZN_TLS_ALPN_KEY => Some(ZN_TLS_ALPN_STR.to_string()),
+            ZN_LINK_WEIGHT_KEY => Some(ZN_LINK_WEIGHT_STR.to_string()),
+            ZN_LINK_WEIGHT_RTT_KEY => Some(ZN_LINK_WEIGHT_RTT_STR.to_string()),
             _ => None,
         }
     }
@@ -148,6 +148,32 @@ validated_struct::validator! {
         local_routing: Option<bool>,
         /// The default timeout to apply to queries in milliseconds.
         queries_default_timeout: Option<ZInt>,
+        pub routing: RoutingConf {
+            /// The weight of the links in the link state routing graph (default: 100).
+            /// The traffic follows the paths of lowest total weight.
+            /// It can be overridden per connect endpoint with the `link_weight` endpoint configuration.
+            link_weight: Option<ZInt>,
+            /// Whether the links opened by this instance are weighted by their round-trip time, measured
+            /// with Pings once they are established (default: false). A round-trip time of 1ms weighs as
+            /// the default weight of 100.
+            /// The `link_weight` endpoint configuration takes precedence.
+            link_weight_rtt: Option<bool>,
+        },
+        /// The access control applied by this instance to the messages received from the remote sessions.
+        /// It can be reloaded at runtime through the admin space, e.g. by putting the new rules
+        /// on `/@/router/<pid>/config/access_control/rules`.
+        pub access_control: AccessControlConf {
+            /// Whether the access control is enabled (default: false).
+            enabled: Option<bool>,
+            /// The permission of the messages matching none of the rules: "allow" (default) or "deny".
+            default_permission: Option<Permission>,
+            /// The access control rules. The first rule applying to a message decides its permission.
+            rules: Vec<AclRule>,
+        },
+        /// The downsampling rules applied by this instance to the data sent to the remote sessions.
+        /// They can be reloaded at runtime through the admin space, e.g. by putting the new rules
+        /// on `/@/router/<pid>/config/downsampling`.
+        downsampling: Vec<DownsamplingRule>,
         pub transport: TransportConf {
             /// If set to `false`, the shared-memory transports will be disabled. (default `true`).
             shared_memory: Option<bool>,
@@ -165,12 +191,23 @@ validated_struct::validator! {
                 max_sessions: Option<usize>,
                 /// Maximum number of unicast incoming links per transport session (default: 1)
                 max_links: Option<usize>,
+                /// How the messages are scheduled on the links of a transport session:
+                /// "active_standby" (default), "round_robin" or "least_queued".
+                link_scheduling: Option<LinkScheduling>,
+                /// Whether the links opened by this instance request the LZ4 compression of the batches (default: false).
+                /// It can be overridden per endpoint with the `compression` endpoint configuration.
+                compression: Option<bool>,
+                /// The size in bytes under which the batches are sent uncompressed (default: 256).
+                /// It can be overridden per endpoint with the `compression_threshold` endpoint configuration.
+                compression_threshold: Option<usize>,
             },
             pub multicast: TransportMulticastConf {
                 /// Link keep-alive duration in milliseconds (default: 2500)
                 join_interval: Option<ZInt>,
                 /// Maximum number of multicast sessions (default: 1024)
                 max_sessions: Option<usize>,
+                /// Whether the reliable frames reported as missing by the receivers are retransmitted (default: false)
+                reliable: Option<bool>,
             },
             pub link: TransportLinkConf {
                 /// Zenoh's MTU equivalent (default: 2^16-1)
@@ -186,11 +223,19 @@ validated_struct::validator! {
                 defrag_buffer_size: Option<usize>,
                 pub tls: TLSConf {
                     root_ca_certificate: Option<String>,
+                    /// The server private key and certificate files are reloaded for the new handshakes
+                    /// whenever they change on disk. The established links are not affected.
                     server_private_key: Option<String>,
                     server_certificate: Option<String>,
                     client_auth: Option<bool>,
                     client_private_key: Option<String>,
                     client_certificate: Option<String>,
+                    /// The name used for SNI and for the verification of the server certificate.
+                    /// Defaults to the host of the locator.
+                    server_name: Option<String>,
+                    /// The application protocols negotiated via ALPN, in order of preference.
+                    /// Defaults to none for TLS and to `hq-29` for QUIC.
+                    alpn: Option<Vec<String>>,
                 },
             },
             pub auth: AuthConf {
@@ -209,6 +254,12 @@ validated_struct::validator! {
                     private_key_file: Option<String>,
                     key_size: Option<usize>,
                     known_keys_file: Option<String>,
+                    /// Whether the batches exchanged on the links are encrypted and authenticated with a session key
+                    /// derived during the public key handshake. A peer requiring encryption rejects the links of
+                    /// peers not requesting it. Multicast links are not encrypted.
+                    /// The handshake does not authenticate the public keys of the peers: the encryption only
+                    /// protects against passive eavesdroppers, not against an active man-in-the-middle.
+                    encryption: Option<bool>,
                 },
             },
         },
@@ -332,6 +383,104 @@ impl Config {
     }
 }
 
+/// The policy used to schedule the messages on the links of a unicast transport session.
+///
+/// The links matching the reliability of a message are always preferred.
+#[derive(serde::Deserialize, serde::Serialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum LinkScheduling {
+    /// All the messages are sent on the first link, the other links being used only when it fails.
+    #[default]
+    ActiveStandby,
+    /// The messages are sent on each link in turn.
+    RoundRobin,
+    /// The messages are sent on the link with the smallest transmission backlog.
+    LeastQueued,
+}
+
+impl std::str::FromStr for LinkScheduling {
+    type Err = zenoh_core::Error;
+
+    fn from_str(s: &str) -> ZResult<Self> {
+        match s {
+            "active_standby" => Ok(LinkScheduling::ActiveStandby),
+            "round_robin" => Ok(LinkScheduling::RoundRobin),
+            "least_queued" => Ok(LinkScheduling::LeastQueued),
+            _ => bail!("Unknown link scheduling policy: {}", s),
+        }
+    }
+}
+
+/// The permission given by an access control rule.
+#[derive(serde::Deserialize, serde::Serialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Permission {
+    #[default]
+    Allow,
+    Deny,
+}
+
+/// The kinds of messages subject to the access control.
+#[derive(serde::Deserialize, serde::Serialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum AclMessage {
+    Put,
+    Delete,
+    DeclareSubscriber,
+    DeclareQueryable,
+    Query,
+    Reply,
+}
+
+/// An access control rule.
+///
+/// A rule applies to the messages of the given kinds received from the sessions matching any
+/// of its subjects (the users, public keys and interfaces), or from all the sessions if it has none.
+/// An `allow` rule applies to a message whose key expression is included in one of the rule
+/// key expressions, a `deny` rule to a message whose key expression intersects one of them.
+#[derive(serde::Deserialize, serde::Serialize, Clone, Debug, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct AclRule {
+    pub permission: Permission,
+    /// The kinds of messages the rule applies to, all of them if empty.
+    #[serde(default)]
+    pub messages: Vec<AclMessage>,
+    pub key_exprs: Vec<String>,
+    /// The names of the users authenticated with a password.
+    #[serde(default)]
+    pub users: Vec<String>,
+    /// The fingerprints of the authenticated public keys, i.e. the hexadecimal
+    /// SHA3-256 digests of their PKCS#1 DER encoding.
+    #[serde(default)]
+    pub public_keys: Vec<String>,
+    /// The names of the network interfaces the sessions are established on.
+    #[serde(default)]
+    pub interfaces: Vec<String>,
+}
+
+/// A downsampling rule.
+///
+/// A rule applies to the data whose key expression is included in one of the rule key expressions,
+/// sent to the sessions established on one of its interfaces or with one of its peer ids,
+/// or to all the remote sessions if it has none. It limits the data sent to each session either
+/// to `max_rate` messages per second and key expression, keeping the latest sample of each
+/// period, or to `max_byte_rate` bytes per second.
+#[derive(serde::Deserialize, serde::Serialize, Clone, Debug, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct DownsamplingRule {
+    pub key_exprs: Vec<String>,
+    /// The names of the network interfaces the sessions are established on.
+    #[serde(default)]
+    pub interfaces: Vec<String>,
+    /// The ids of the remote sessions.
+    #[serde(default)]
+    pub peer_ids: Vec<String>,
+    #[serde(default)]
+    pub max_rate: Option<f64>,
+    #[serde(default)]
+    pub max_byte_rate: Option<u64>,
+}
+
 #[derive(Debug)]
 pub enum ConfigOpenErr {
     IoError(std::io::Error),
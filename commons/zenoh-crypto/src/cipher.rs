@@ -15,8 +15,10 @@ use super::PseudoRng;
 use aes::cipher::generic_array::GenericArray;
 use aes::cipher::NewBlockCipher;
 use aes::{Aes128, BlockDecrypt, BlockEncrypt};
+use aes_gcm::aead::{AeadInPlace, NewAead};
+use aes_gcm::Aes128Gcm;
 use rand::Rng;
-use zenoh_core::{bail, Result as ZResult};
+use zenoh_core::{bail, zerror, Result as ZResult};
 
 pub struct BlockCipher {
     inner: Aes128,
@@ -66,6 +68,49 @@ impl BlockCipher {
     }
 }
 
+/// Authenticated encryption with AES-128-GCM.
+pub struct AeadCipher {
+    inner: Aes128Gcm,
+}
+
+impl AeadCipher {
+    pub const KEY_SIZE: usize = 16;
+    pub const NONCE_SIZE: usize = 12;
+    pub const TAG_SIZE: usize = 16;
+
+    pub fn new(key: [u8; Self::KEY_SIZE]) -> AeadCipher {
+        AeadCipher {
+            inner: Aes128Gcm::new(&key.into()),
+        }
+    }
+
+    /// Encrypts the bytes in place and returns their authentication tag.
+    /// A nonce must never be reused with the same key.
+    pub fn encrypt_in_place(
+        &self,
+        nonce: &[u8; Self::NONCE_SIZE],
+        bytes: &mut [u8],
+    ) -> ZResult<[u8; Self::TAG_SIZE]> {
+        let tag = self
+            .inner
+            .encrypt_in_place_detached(nonce.into(), &[], bytes)
+            .map_err(|_| zerror!("Encryption error"))?;
+        Ok(tag.into())
+    }
+
+    /// Authenticates the bytes with their tag and decrypts them in place.
+    pub fn decrypt_in_place(
+        &self,
+        nonce: &[u8; Self::NONCE_SIZE],
+        bytes: &mut [u8],
+        tag: &[u8; Self::TAG_SIZE],
+    ) -> ZResult<()> {
+        self.inner
+            .decrypt_in_place_detached(nonce.into(), &[], bytes, tag.into())
+            .map_err(|_| zerror!("Invalid authentication tag").into())
+    }
+}
+
 mod tests {
     #[test]
     fn cipher() {
@@ -121,4 +166,47 @@ mod tests {
             encrypt_decrypt(&cipher, &mut prng);
         }
     }
+
+    #[test]
+    fn aead() {
+        use super::{AeadCipher, PseudoRng};
+        use rand::{RngCore, SeedableRng};
+
+        let mut prng = PseudoRng::from_entropy();
+        let mut key = [0_u8; AeadCipher::KEY_SIZE];
+        prng.fill_bytes(&mut key);
+        let cipher = AeadCipher::new(key);
+        let nonce = [7_u8; AeadCipher::NONCE_SIZE];
+
+        for len in [0, 1, 15, 16, 17, 1_000] {
+            let mut clear = vec![0_u8; len];
+            prng.fill_bytes(&mut clear);
+
+            let mut bytes = clear.clone();
+            let tag = cipher.encrypt_in_place(&nonce, &mut bytes).unwrap();
+            if len > 16 {
+                assert_ne!(bytes, clear);
+            }
+
+            // Tampered bytes, tags and nonces are rejected
+            let mut tampered = bytes.clone();
+            if len > 0 {
+                tampered[0] ^= 1;
+                assert!(cipher
+                    .decrypt_in_place(&nonce, &mut tampered, &tag)
+                    .is_err());
+            }
+            let mut other = tag;
+            other[0] ^= 1;
+            assert!(cipher
+                .decrypt_in_place(&nonce, &mut bytes.clone(), &other)
+                .is_err());
+            assert!(cipher
+                .decrypt_in_place(&[8_u8; AeadCipher::NONCE_SIZE], &mut bytes.clone(), &tag)
+                .is_err());
+
+            cipher.decrypt_in_place(&nonce, &mut bytes, &tag).unwrap();
+            assert_eq!(bytes, clear);
+        }
+    }
 }
@@ -317,6 +317,7 @@ pub mod queryable {
     pub const ALL_KINDS: super::ZInt = 0x01;
     pub const STORAGE: super::ZInt = 0x02;
     pub const EVAL: super::ZInt = 0x04;
+    pub const LIVELINESS: super::ZInt = 0x10;
 }
 
 /// The kind of consolidation.
@@ -423,6 +424,11 @@ pub enum Target {
     None,
     #[cfg(feature = "complete_n")]
     Complete(ZInt),
+    /// At most `n` matching queryables, the first `n` to reply. The routers ask the nearest
+    /// queryables by routing distance, but when they see fewer than `n` of them (e.g. behind a
+    /// router) they ask every face for the missing ones, and the querier keeps the replies of
+    /// the first `n` responders, which are not necessarily the nearest.
+    Nearest(ZInt),
 }
 
 impl Default for Target {
@@ -105,6 +105,7 @@ pub mod tmsg {
     pub mod flag {
         pub const A: u8 = 1 << 5; // 0x20 Ack           if A==1 then the message is an acknowledgment
         pub const C: u8 = 1 << 6; // 0x40 Count         if C==1 then number of unacknowledged messages is present
+        pub const D: u8 = 1 << 6; // 0x40 Destination   if D==1 then the PeerID of the destination is present
         pub const E: u8 = 1 << 7; // 0x80 End           if E==1 then it is the last FRAME fragment
         pub const F: u8 = 1 << 6; // 0x40 Fragment      if F==1 then the FRAME is a fragment
         pub const I: u8 = 1 << 5; // 0x20 PeerID        if I==1 then the PeerID is requested or present
@@ -133,6 +134,7 @@ pub mod tmsg {
         use super::ZInt;
 
         pub const QOS: ZInt = 1 << 0; // 0x01 QoS       if PRIORITY==1 then the transport supports QoS
+        pub const RELIABLE: ZInt = 1 << 1; // 0x02 Reliable  if RELIABLE==1 then the reliable frames are retransmitted on demand
     }
 
     // Reason for the Close message
@@ -184,10 +186,12 @@ pub mod zmsg {
     // Zenoh message flags
     pub mod flag {
         pub const D: u8 = 1 << 5; // 0x20 Drop          if D==1 then the message can be dropped
+        pub const E: u8 = 1 << 6; // 0x40 Error         if E==1 then the reply is an error (e.g., ReplyContext)
         pub const F: u8 = 1 << 5; // 0x20 Final         if F==1 then this is the final message (e.g., ReplyContext, Pull)
         pub const I: u8 = 1 << 6; // 0x40 DataInfo      if I==1 then DataInfo is present
         pub const K: u8 = 1 << 7; // 0x80 KeySuffix     if K==1 then key_expr has suffix
         pub const N: u8 = 1 << 6; // 0x40 MaxSamples    if N==1 then the MaxSamples is indicated
+        pub const O: u8 = 1 << 6; // 0x40 TimeOut       if O==1 then the query timeout is indicated
         pub const P: u8 = 1 << 0; // 0x01 Pid           if P==1 then the pid is present
         pub const Q: u8 = 1 << 6; // 0x40 QueryableInfo if Q==1 then the queryable info is present
         pub const R: u8 = 1 << 5; // 0x20 Reliable      if R==1 then it concerns the reliable channel, best-effort otherwise
@@ -249,6 +253,7 @@ pub mod zmsg {
         pub const PID: ZInt = 1; // 0x01
         pub const WAI: ZInt = 1 << 1; // 0x02
         pub const LOC: ZInt = 1 << 2; // 0x04
+        pub const WGT: ZInt = 1 << 3; // 0x08
     }
 
     pub mod conduit {
@@ -385,7 +390,7 @@ impl Attachment {
 ///
 ///  7 6 5 4 3 2 1 0
 /// +-+-+-+-+-+-+-+-+
-/// |X|X|F|  R_CTX  |
+/// |X|E|F|  R_CTX  |
 /// +-+-+-+---------+
 /// ~      qid      ~
 /// +---------------+
@@ -395,6 +400,7 @@ impl Attachment {
 /// +---------------+
 ///
 /// - if F==1 then the message is a REPLY_FINAL
+/// - if E==1 then the payload of the decorated Data message is an error reported by the replier
 /// ```
 #[derive(Debug, Clone, PartialEq)]
 pub struct ReplierInfo {
@@ -405,6 +411,7 @@ pub struct ReplierInfo {
 pub struct ReplyContext {
     pub qid: ZInt,
     pub replier: Option<ReplierInfo>,
+    pub is_error: bool,
 }
 
 impl Header for ReplyContext {
@@ -414,6 +421,9 @@ impl Header for ReplyContext {
         if self.is_final() {
             header |= zmsg::flag::F;
         }
+        if self.is_error {
+            header |= zmsg::flag::E;
+        }
         header
     }
 }
@@ -421,8 +431,12 @@ impl Header for ReplyContext {
 impl ReplyContext {
     // Note: id replier_id=None flag F is set, meaning it's a REPLY_FINAL
     #[inline(always)]
-    pub fn new(qid: ZInt, replier: Option<ReplierInfo>) -> ReplyContext {
-        ReplyContext { qid, replier }
+    pub fn new(qid: ZInt, replier: Option<ReplierInfo>, is_error: bool) -> ReplyContext {
+        ReplyContext {
+            qid,
+            replier,
+            is_error,
+        }
     }
 
     #[inline(always)]
@@ -969,7 +983,7 @@ impl Header for Pull {
 /// ```text
 ///  7 6 5 4 3 2 1 0
 /// +-+-+-+-+-+-+-+-+
-/// |K|X|T|  QUERY  |
+/// |K|O|T|  QUERY  |
 /// +-+-+-+---------+
 /// ~    KeyExpr     ~ if K==1 then key_expr has suffix
 /// +---------------+
@@ -981,6 +995,8 @@ impl Header for Pull {
 /// +---------------+
 /// ~ consolidation ~
 /// +---------------+
+/// ~    timeout    ~ if O==1 (in milliseconds)
+/// +---------------+
 /// ```
 #[derive(Debug, Clone, PartialEq)]
 pub struct Query {
@@ -989,6 +1005,7 @@ pub struct Query {
     pub qid: ZInt,
     pub target: Option<QueryTarget>,
     pub consolidation: ConsolidationStrategy,
+    pub timeout: Option<ZInt>,
 }
 
 impl Header for Query {
@@ -998,6 +1015,9 @@ impl Header for Query {
         if self.target.is_some() {
             header |= zmsg::flag::T;
         }
+        if self.timeout.is_some() {
+            header |= zmsg::flag::O;
+        }
         if self.key.has_suffix() {
             header |= zmsg::flag::K;
         }
@@ -1007,7 +1027,7 @@ impl Header for Query {
 
 //  7 6 5 4 3 2 1 0
 // +-+-+-+-+-+-+-+-+
-// ~X|X|X|X|X|L|W|P~
+// ~X|X|X|X|G|L|W|P~
 // +-+-+-+-+-+-+-+-+
 // ~     psid      ~
 // +---------------+
@@ -1021,6 +1041,11 @@ impl Header for Query {
 // +---------------+
 // ~    [links]    ~
 // +---------------+
+// ~[link_weights] ~ if G == 1
+// +---------------+
+//
+// The link weights are given in the order of the links, a weight of 0 meaning
+// that no weight has been configured for the corresponding link.
 #[derive(Debug, Clone, PartialEq)]
 pub struct LinkState {
     pub psid: ZInt,
@@ -1029,6 +1054,7 @@ pub struct LinkState {
     pub whatami: Option<WhatAmI>,
     pub locators: Option<Vec<Locator>>,
     pub links: Vec<ZInt>,
+    pub link_weights: Option<Vec<ZInt>>,
 }
 
 impl Options for LinkState {
@@ -1043,6 +1069,9 @@ impl Options for LinkState {
         if self.locators.is_some() {
             opts |= zmsg::link_state::LOC;
         }
+        if self.link_weights.is_some() {
+            opts |= zmsg::link_state::WGT;
+        }
         opts
     }
 
@@ -1203,12 +1232,14 @@ impl ZenohMessage {
     }
 
     #[inline(always)]
+    #[allow(clippy::too_many_arguments)]
     pub fn make_query(
         key: KeyExpr<'static>,
         value_selector: String,
         qid: ZInt,
         target: Option<QueryTarget>,
         consolidation: ConsolidationStrategy,
+        timeout: Option<ZInt>,
         routing_context: Option<RoutingContext>,
         attachment: Option<Attachment>,
     ) -> ZenohMessage {
@@ -1219,6 +1250,7 @@ impl ZenohMessage {
                 qid,
                 target,
                 consolidation,
+                timeout,
             }),
             channel: zmsg::default_channel::QUERY,
             routing_context,
@@ -1583,7 +1615,7 @@ impl Header for OpenAck {
 /// +-+-+-+-+-+-+-+-+
 /// |O|S|T|   JOIN  |
 /// +-+-+-+-+-------+
-/// ~             |Q~ if O==1
+/// ~           |R|Q~ if O==1
 /// +---------------+
 /// | v_maj | v_min | -- Protocol Version VMaj.VMin
 /// +-------+-------+
@@ -1599,6 +1631,7 @@ impl Header for OpenAck {
 /// +---------------+
 ///
 /// - if Q==1 then the sender supports QoS.
+/// - if R==1 then the sender retransmits its reliable frames reported as missing by an ACKNACK.
 ///
 /// (*)   if T==1 then the lease period is expressed in seconds, otherwise in milliseconds
 /// (**)  if S==0 then 2^28 is assumed.
@@ -1614,6 +1647,7 @@ pub struct Join {
     pub lease: Duration,
     pub sn_resolution: ZInt,
     pub next_sns: ConduitSnList,
+    pub is_reliable: bool,
 }
 
 impl Join {
@@ -1648,6 +1682,9 @@ impl Options for Join {
         if self.is_qos() {
             options |= tmsg::join_options::QOS;
         }
+        if self.is_reliable {
+            options |= tmsg::join_options::RELIABLE;
+        }
         options
     }
 
@@ -1728,9 +1765,13 @@ impl Header for Close {
 /// +---------------+
 ///
 /// - if R==1 then the SYNC concerns the reliable channel, otherwise the best-effort channel.
+///
+/// NOTE: the SYNC concerns the conduit of the priority indicated by the PRIORITY decorator, if any,
+///       or of the default priority otherwise.
 /// ```
 #[derive(Debug, Clone, PartialEq)]
 pub struct Sync {
+    pub priority: Priority,
     pub reliability: Reliability,
     pub sn: ZInt,
     pub count: Option<ZInt>,
@@ -1764,15 +1805,24 @@ impl Header for Sync {
 ///
 ///  7 6 5 4 3 2 1 0
 /// +-+-+-+-+-+-+-+-+
-/// |X|X|M| ACKNACK |
+/// |X|D|M| ACKNACK |
 /// +-+-+-+-+-------+
+/// ~    peer_id    ~ if D==1 -- PID of the sender of the acknowledged messages.
+/// +---------------+
 /// ~      sn       ~
 /// +---------------+
 /// ~     mask      ~ if M==1
 /// +---------------+
+///
+/// NOTE: the ACKNACK concerns the conduit of the priority indicated by the PRIORITY decorator, if
+///       any, or of the default priority otherwise.
+/// NOTE: the PID is present when the ACKNACK is sent on a multicast locator, where it is received
+///       by all the peers.
 /// ```
 #[derive(Debug, Clone, PartialEq)]
 pub struct AckNack {
+    pub priority: Priority,
+    pub pid: Option<PeerId>,
     pub sn: ZInt,
     pub mask: Option<ZInt>,
 }
@@ -1781,6 +1831,9 @@ impl Header for AckNack {
     #[inline(always)]
     fn header(&self) -> u8 {
         let mut header = tmsg::id::ACK_NACK;
+        if self.pid.is_some() {
+            header |= tmsg::flag::D;
+        }
         if self.mask.is_some() {
             header |= tmsg::flag::M;
         }
@@ -2102,6 +2155,7 @@ impl TransportMessage {
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn make_join(
         version: u8,
         whatami: WhatAmI,
@@ -2109,6 +2163,7 @@ impl TransportMessage {
         lease: Duration,
         sn_resolution: ZInt,
         next_sns: ConduitSnList,
+        is_reliable: bool,
         attachment: Option<Attachment>,
     ) -> TransportMessage {
         TransportMessage {
@@ -2119,6 +2174,7 @@ impl TransportMessage {
                 lease,
                 sn_resolution,
                 next_sns,
+                is_reliable,
             }),
             attachment,
             #[cfg(feature = "stats")]
@@ -2145,6 +2201,7 @@ impl TransportMessage {
     }
 
     pub fn make_sync(
+        priority: Priority,
         reliability: Reliability,
         sn: ZInt,
         count: Option<ZInt>,
@@ -2152,6 +2209,7 @@ impl TransportMessage {
     ) -> TransportMessage {
         TransportMessage {
             body: TransportBody::Sync(Sync {
+                priority,
                 reliability,
                 sn,
                 count,
@@ -2163,12 +2221,19 @@ impl TransportMessage {
     }
 
     pub fn make_ack_nack(
+        priority: Priority,
+        pid: Option<PeerId>,
         sn: ZInt,
         mask: Option<ZInt>,
         attachment: Option<Attachment>,
     ) -> TransportMessage {
         TransportMessage {
-            body: TransportBody::AckNack(AckNack { sn, mask }),
+            body: TransportBody::AckNack(AckNack {
+                priority,
+                pid,
+                sn,
+                mask,
+            }),
             attachment,
             #[cfg(feature = "stats")]
             size: None,
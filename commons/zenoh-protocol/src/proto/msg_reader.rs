@@ -33,8 +33,8 @@ pub trait MessageReader {
     fn read_open_ack(&mut self, header: u8) -> Option<TransportBody>;
     fn read_join(&mut self, header: u8) -> Option<TransportBody>;
     fn read_close(&mut self, header: u8) -> Option<TransportBody>;
-    fn read_sync(&mut self, header: u8) -> Option<TransportBody>;
-    fn read_ack_nack(&mut self, header: u8) -> Option<TransportBody>;
+    fn read_sync(&mut self, header: u8, priority: Priority) -> Option<TransportBody>;
+    fn read_ack_nack(&mut self, header: u8, priority: Priority) -> Option<TransportBody>;
     fn read_keep_alive(&mut self, header: u8) -> Option<TransportBody>;
     fn read_ping(&mut self, _header: u8) -> Option<TransportBody>;
     fn read_pong(&mut self, _header: u8) -> Option<TransportBody>;
@@ -129,8 +129,8 @@ impl MessageReader for ZBufReader<'_> {
                 }
                 JOIN => break self.read_join(header)?,
                 CLOSE => break self.read_close(header)?,
-                SYNC => break self.read_sync(header)?,
-                ACK_NACK => break self.read_ack_nack(header)?,
+                SYNC => break self.read_sync(header, priority)?,
+                ACK_NACK => break self.read_ack_nack(header, priority)?,
                 KEEP_ALIVE => break self.read_keep_alive(header)?,
                 PING_PONG => {
                     if imsg::has_flag(header, tmsg::flag::P) {
@@ -331,6 +331,7 @@ impl MessageReader for ZBufReader<'_> {
             SEQ_NUM_RES
         };
         let is_qos = imsg::has_option(options, tmsg::join_options::QOS);
+        let is_reliable = imsg::has_option(options, tmsg::join_options::RELIABLE);
         let next_sns = if is_qos {
             let mut sns = Box::new([ConduitSn::default(); Priority::NUM]);
             for i in 0..Priority::NUM {
@@ -352,6 +353,7 @@ impl MessageReader for ZBufReader<'_> {
             lease,
             sn_resolution,
             next_sns,
+            is_reliable,
         }))
     }
 
@@ -371,7 +373,7 @@ impl MessageReader for ZBufReader<'_> {
         }))
     }
 
-    fn read_sync(&mut self, header: u8) -> Option<TransportBody> {
+    fn read_sync(&mut self, header: u8, priority: Priority) -> Option<TransportBody> {
         let reliability = match imsg::has_flag(header, tmsg::flag::R) {
             true => Reliability::Reliable,
             false => Reliability::BestEffort,
@@ -384,13 +386,19 @@ impl MessageReader for ZBufReader<'_> {
         };
 
         Some(TransportBody::Sync(Sync {
+            priority,
             reliability,
             sn,
             count,
         }))
     }
 
-    fn read_ack_nack(&mut self, header: u8) -> Option<TransportBody> {
+    fn read_ack_nack(&mut self, header: u8, priority: Priority) -> Option<TransportBody> {
+        let pid = if imsg::has_flag(header, tmsg::flag::D) {
+            Some(self.read_peeexpr_id()?)
+        } else {
+            None
+        };
         let sn = self.read_zint()?;
         let mask = if imsg::has_flag(header, tmsg::flag::M) {
             Some(self.read_zint()?)
@@ -398,7 +406,12 @@ impl MessageReader for ZBufReader<'_> {
             None
         };
 
-        Some(TransportBody::AckNack(AckNack { sn, mask }))
+        Some(TransportBody::AckNack(AckNack {
+            priority,
+            pid,
+            sn,
+            mask,
+        }))
     }
 
     fn read_keep_alive(&mut self, header: u8) -> Option<TransportBody> {
@@ -443,7 +456,13 @@ impl MessageReader for ZBufReader<'_> {
             })
         };
 
-        Some(ReplyContext { qid, replier })
+        let is_error = imsg::has_flag(header, zmsg::flag::E);
+
+        Some(ReplyContext {
+            qid,
+            replier,
+            is_error,
+        })
     }
 
     fn read_zenoh_message(&mut self, reliability: Reliability) -> Option<ZenohMessage> {
@@ -732,6 +751,11 @@ impl MessageReader for ZBufReader<'_> {
             None
         };
         let consolidation = self.read_consolidation()?;
+        let timeout = if imsg::has_flag(header, zmsg::flag::O) {
+            Some(self.read_zint()?)
+        } else {
+            None
+        };
 
         Some(ZenohBody::Query(Query {
             key,
@@ -739,6 +763,7 @@ impl MessageReader for ZBufReader<'_> {
             qid,
             target,
             consolidation,
+            timeout,
         }))
     }
 
@@ -775,6 +800,15 @@ impl MessageReader for ZBufReader<'_> {
         for _ in 0..len {
             links.push(self.read_zint()?);
         }
+        let link_weights = if imsg::has_option(options, zmsg::link_state::WGT) {
+            let mut link_weights: Vec<ZInt> = Vec::with_capacity(links.len());
+            for _ in 0..links.len() {
+                link_weights.push(self.read_zint()?);
+            }
+            Some(link_weights)
+        } else {
+            None
+        };
 
         Some(LinkState {
             psid,
@@ -783,6 +817,7 @@ impl MessageReader for ZBufReader<'_> {
             whatami,
             locators,
             links,
+            link_weights,
         })
     }
 
@@ -829,6 +864,10 @@ impl MessageReader for ZBufReader<'_> {
                 let n = self.read_zint()?;
                 Some(Target::Complete(n))
             }
+            5 => {
+                let n = self.read_zint()?;
+                Some(Target::Nearest(n))
+            }
             id => {
                 log::trace!("UNEXPECTED ID FOR Target: {}", id);
                 None
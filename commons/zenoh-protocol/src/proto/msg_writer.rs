@@ -274,6 +274,10 @@ impl MessageWriter for WBuf {
     }
 
     fn write_sync(&mut self, sync: &Sync) -> bool {
+        if sync.priority != Priority::default() {
+            zcheck!(self.write_deco_priority(sync.priority))
+        }
+
         zcheck!(self.write_byte(sync.header()).is_some());
         zcheck!(self.write_zint(sync.sn));
         if let Some(c) = sync.count {
@@ -283,7 +287,14 @@ impl MessageWriter for WBuf {
     }
 
     fn write_ack_nack(&mut self, ack_nack: &AckNack) -> bool {
+        if ack_nack.priority != Priority::default() {
+            zcheck!(self.write_deco_priority(ack_nack.priority))
+        }
+
         zcheck!(self.write_byte(ack_nack.header()).is_some());
+        if let Some(p) = ack_nack.pid.as_ref() {
+            zcheck!(self.write_peeexpr_id(p));
+        }
         zcheck!(self.write_zint(ack_nack.sn));
         if let Some(m) = ack_nack.mask {
             zcheck!(self.write_zint(m));
@@ -543,7 +554,11 @@ impl MessageWriter for WBuf {
         if let Some(t) = query.target.as_ref() {
             zcheck!(self.write_query_target(t));
         }
-        self.write_consolidation(&query.consolidation)
+        zcheck!(self.write_consolidation(&query.consolidation));
+        if let Some(timeout) = query.timeout {
+            zcheck!(self.write_zint(timeout));
+        }
+        true
     }
 
     fn write_link_state_list(&mut self, link_state_list: &LinkStateList) -> bool {
@@ -572,6 +587,12 @@ impl MessageWriter for WBuf {
         for link in &link_state.links {
             zcheck!(self.write_zint(*link));
         }
+        if let Some(link_weights) = link_state.link_weights.as_ref() {
+            zcheck!(link_weights.len() == link_state.links.len());
+            for weight in link_weights {
+                zcheck!(self.write_zint(*weight));
+            }
+        }
 
         true
     }
@@ -590,6 +611,7 @@ impl MessageWriter for WBuf {
             Target::None => self.write_zint(3 as ZInt),
             #[cfg(feature = "complete_n")]
             Target::Complete(n) => self.write_zint(4 as ZInt) && self.write_zint(*n),
+            Target::Nearest(n) => self.write_zint(5 as ZInt) && self.write_zint(*n),
         }
     }
 
@@ -22,7 +22,7 @@ use zenoh_protocol::io::{WBufCodec, ZBufCodec};
 use zenoh_protocol::proto::defaults::SEQ_NUM_RES;
 use zenoh_protocol::proto::{
     Attachment, DataInfo, Declaration, ForgetPublisher, ForgetQueryable, ForgetResource,
-    ForgetSubscriber, FramePayload, MessageReader, MessageWriter, Publisher, Queryable,
+    ForgetSubscriber, FramePayload, LinkState, MessageReader, MessageWriter, Publisher, Queryable,
     ReplierInfo, ReplyContext, Resource, RoutingContext, Subscriber, TransportMessage,
     ZenohMessage,
 };
@@ -90,7 +90,8 @@ fn gen_reply_context(is_final: bool) -> ReplyContext {
     } else {
         None
     };
-    ReplyContext::new(qid, replier)
+    let is_error = !is_final && gen!(bool);
+    ReplyContext::new(qid, replier, is_error)
 }
 
 fn gen_attachment() -> Attachment {
@@ -209,13 +210,14 @@ fn gen_query_target() -> QueryTarget {
 }
 
 fn gen_target() -> Target {
-    let num: u8 = thread_rng().gen_range(0..4);
+    let num: u8 = thread_rng().gen_range(0..6);
     match num {
         0 => Target::BestMatching,
         1 => Target::All,
         2 => Target::AllComplete,
         #[cfg(feature = "complete_n")]
         4 => Target::Complete(3),
+        5 => Target::Nearest(2),
         _ => Target::None,
     }
 }
@@ -437,23 +439,27 @@ fn codec_join() {
             ConduitSnList::Plain(gen_initial_sn()),
             ConduitSnList::QoS(Box::new([gen_initial_sn(); Priority::NUM])),
         ];
+        let is_reliable = [true, false];
         let attachment = [None, Some(gen_attachment())];
 
         for l in lease.iter() {
             for w in wami.iter() {
                 for s in sn_resolution.iter() {
                     for i in initial_sns.iter() {
-                        for a in attachment.iter() {
-                            let msg = TransportMessage::make_join(
-                                gen!(u8),
-                                *w,
-                                gen_pid(),
-                                *l,
-                                *s,
-                                i.clone(),
-                                a.clone(),
-                            );
-                            test_write_read_transport_message(msg);
+                        for r in is_reliable.iter() {
+                            for a in attachment.iter() {
+                                let msg = TransportMessage::make_join(
+                                    gen!(u8),
+                                    *w,
+                                    gen_pid(),
+                                    *l,
+                                    *s,
+                                    i.clone(),
+                                    *r,
+                                    a.clone(),
+                                );
+                                test_write_read_transport_message(msg);
+                            }
                         }
                     }
                 }
@@ -483,15 +489,18 @@ fn codec_close() {
 #[test]
 fn codec_sync() {
     for _ in 0..NUM_ITER {
+        let priority = [Priority::default(), Priority::RealTime];
         let ch = [Reliability::Reliable, Reliability::BestEffort];
         let count = [None, Some(gen!(ZInt))];
         let attachment = [None, Some(gen_attachment())];
 
-        for c in ch.iter() {
-            for n in count.iter() {
-                for a in attachment.iter() {
-                    let msg = TransportMessage::make_sync(*c, gen!(ZInt), *n, a.clone());
-                    test_write_read_transport_message(msg);
+        for p in priority.iter() {
+            for c in ch.iter() {
+                for n in count.iter() {
+                    for a in attachment.iter() {
+                        let msg = TransportMessage::make_sync(*p, *c, gen!(ZInt), *n, a.clone());
+                        test_write_read_transport_message(msg);
+                    }
                 }
             }
         }
@@ -501,13 +510,20 @@ fn codec_sync() {
 #[test]
 fn codec_ack_nack() {
     for _ in 0..NUM_ITER {
+        let priority = [Priority::default(), Priority::RealTime];
+        let pid = [None, Some(gen_pid())];
         let mask = [None, Some(gen!(ZInt))];
         let attachment = [None, Some(gen_attachment())];
 
-        for m in mask.iter() {
-            for a in attachment.iter() {
-                let msg = TransportMessage::make_ack_nack(gen!(ZInt), *m, a.clone());
-                test_write_read_transport_message(msg);
+        for p in priority.iter() {
+            for i in pid.iter() {
+                for m in mask.iter() {
+                    for a in attachment.iter() {
+                        let msg =
+                            TransportMessage::make_ack_nack(*p, *i, gen!(ZInt), *m, a.clone());
+                        test_write_read_transport_message(msg);
+                    }
+                }
             }
         }
     }
@@ -863,26 +879,65 @@ fn codec_query() {
     for _ in 0..NUM_ITER {
         let value_selector = [String::default(), "my_value_selector".to_string()];
         let target = [None, Some(gen_query_target())];
+        let timeout = [None, Some(gen!(ZInt))];
         let routing_context = [None, Some(gen_routing_context())];
         let attachment = [None, Some(gen_attachment())];
 
         for p in value_selector.iter() {
             for t in target.iter() {
-                for roc in routing_context.iter() {
-                    for a in attachment.iter() {
-                        let msg = ZenohMessage::make_query(
-                            gen_key(),
-                            p.clone(),
-                            gen!(ZInt),
-                            t.clone(),
-                            gen_consolidation(),
-                            *roc,
-                            a.clone(),
-                        );
-                        test_write_read_zenoh_message(msg);
+                for to in timeout.iter() {
+                    for roc in routing_context.iter() {
+                        for a in attachment.iter() {
+                            let msg = ZenohMessage::make_query(
+                                gen_key(),
+                                p.clone(),
+                                gen!(ZInt),
+                                t.clone(),
+                                gen_consolidation(),
+                                *to,
+                                *roc,
+                                a.clone(),
+                            );
+                            test_write_read_zenoh_message(msg);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn codec_link_state_list() {
+    for _ in 0..NUM_ITER {
+        let pid = [None, Some(gen_pid())];
+        let whatami = [None, Some(WhatAmI::Router)];
+        let locators = [None, Some(vec!["tcp/1.2.3.4:1234".parse().unwrap()])];
+        let links = vec![gen!(ZInt), gen!(ZInt), gen!(ZInt)];
+        let link_weights = [None, Some(vec![0, gen!(ZInt), gen!(ZInt)])];
+        let attachment = [None, Some(gen_attachment())];
+
+        let mut link_states = vec![];
+        for p in pid.iter() {
+            for w in whatami.iter() {
+                for l in locators.iter() {
+                    for lw in link_weights.iter() {
+                        link_states.push(LinkState {
+                            psid: gen!(ZInt),
+                            sn: gen!(ZInt),
+                            pid: *p,
+                            whatami: *w,
+                            locators: l.clone(),
+                            links: links.clone(),
+                            link_weights: lw.clone(),
+                        });
                     }
                 }
             }
         }
+        for a in attachment.iter() {
+            let msg = ZenohMessage::make_link_state_list(link_states.clone(), a.clone());
+            test_write_read_zenoh_message(msg);
+        }
     }
 }
@@ -0,0 +1,132 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ADLINK zenoh team, <zenoh@adlink-labs.tech>
+//
+use async_std::task::sleep;
+use clap::{App, Arg};
+use futures::prelude::*;
+use futures::select;
+use std::time::Duration;
+use zenoh::config::Config;
+use zenoh::prelude::*;
+
+#[async_std::main]
+async fn main() {
+    // Initiate logging
+    env_logger::init();
+
+    let (config, key_expr, watched) = parse_args();
+
+    println!("Opening session...");
+    let session = zenoh::open(config).await.unwrap();
+
+    println!("Declaring LivelinessToken on '{}'...", key_expr);
+    let _token = session.liveliness().declare_token(&key_expr).await.unwrap();
+
+    println!("Subscribing to liveliness tokens on '{}'...", watched);
+    let mut subscriber = session
+        .liveliness()
+        .subscribe(&watched)
+        .unwrap()
+        .await
+        .unwrap();
+
+    let mut replies = session.liveliness().get(&watched).unwrap().await.unwrap();
+    while let Some(reply) = replies.next().await {
+        if let Ok(sample) = reply.sample {
+            println!(">> Alive token ('{}')", sample.key_expr.as_str());
+        }
+    }
+
+    println!("Enter 'q' to quit...");
+    let mut stdin = async_std::io::stdin();
+    let mut input = [0_u8];
+    loop {
+        select!(
+            sample = subscriber.next() => {
+                let sample = sample.unwrap();
+                match sample.kind {
+                    SampleKind::Delete => println!(">> Dropped token ('{}')", sample.key_expr.as_str()),
+                    _ => println!(">> New alive token ('{}')", sample.key_expr.as_str()),
+                }
+            },
+
+            _ = stdin.read_exact(&mut input).fuse() => {
+                match input[0] {
+                    b'q' => break,
+                    0 => sleep(Duration::from_secs(1)).await,
+                    _ => (),
+                }
+            }
+        );
+    }
+}
+
+fn parse_args() -> (Config, String, String) {
+    let args = App::new("zenoh liveliness example")
+        .arg(
+            Arg::from_usage("-m, --mode=[MODE]  'The zenoh session mode (peer by default).")
+                .possible_values(&["peer", "client"]),
+        )
+        .arg(Arg::from_usage(
+            "-e, --connect=[ENDPOINT]...   'Endpoints to connect to.'",
+        ))
+        .arg(Arg::from_usage(
+            "-l, --listen=[ENDPOINT]...   'Endpoints to listen on.'",
+        ))
+        .arg(
+            Arg::from_usage("-k, --key=[KEYEXPR] 'The key expression of the liveliness token.'")
+                .default_value("/demo/example/zenoh-rs-liveliness"),
+        )
+        .arg(
+            Arg::from_usage(
+                "-w, --watch=[KEYEXPR] 'The key expression of the liveliness tokens to watch.'",
+            )
+            .default_value("/demo/example/**"),
+        )
+        .arg(Arg::from_usage(
+            "-c, --config=[FILE]      'A configuration file.'",
+        ))
+        .arg(Arg::from_usage(
+            "--no-multicast-scouting 'Disable the multicast-based scouting mechanism.'",
+        ))
+        .get_matches();
+
+    let mut config = if let Some(conf_file) = args.value_of("config") {
+        Config::from_file(conf_file).unwrap()
+    } else {
+        Config::default()
+    };
+    if let Some(Ok(mode)) = args.value_of("mode").map(|mode| mode.parse()) {
+        config.set_mode(Some(mode)).unwrap();
+    }
+    if let Some(values) = args.values_of("connect") {
+        config
+            .connect
+            .endpoints
+            .extend(values.map(|v| v.parse().unwrap()))
+    }
+    if let Some(values) = args.values_of("listen") {
+        config
+            .listen
+            .endpoints
+            .extend(values.map(|v| v.parse().unwrap()))
+    }
+    if args.is_present("no-multicast-scouting") {
+        config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    }
+
+    let key_expr = args.value_of("key").unwrap().to_string();
+    let watched = args.value_of("watch").unwrap().to_string();
+
+    (config, key_expr, watched)
+}
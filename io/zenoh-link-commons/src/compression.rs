@@ -0,0 +1,340 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ADLINK zenoh team, <zenoh@adlink-labs.tech>
+//
+
+//! Batch compression for links.
+//!
+//! A compressed link wraps a [`LinkUnicast`] once the compression has been negotiated
+//! with the peer during the session establishment. Every batch written on the link is
+//! prefixed by a one byte header telling if it is compressed or not: the batches smaller
+//! than the threshold and the ones that do not shrink are sent as they are.
+//! On streamed links the 16 bits length prefix is rewritten to frame the compressed batch.
+use super::{LinkUnicast, LinkUnicastTrait};
+use async_std::sync::Mutex as AsyncMutex;
+use async_trait::async_trait;
+use lz4_flex::block;
+use std::fmt;
+use zenoh_core::{bail, zasynclock, zerror, Result as ZResult};
+use zenoh_protocol_core::Locator;
+
+pub mod config {
+    /// Whether the batches sent on the link are compressed: `"true"` or `"false"`.
+    pub const COMPRESSION: &str = "compression";
+    /// The size in bytes under which the batches are sent uncompressed.
+    pub const COMPRESSION_THRESHOLD: &str = "compression_threshold";
+}
+
+/// The identifier of the LZ4 algorithm used during the negotiation.
+pub const COMPRESSION_LZ4: u8 = 1;
+
+const HEADER_RAW: u8 = 0;
+const HEADER_LZ4: u8 = 1;
+const HEADER_LEN: usize = 1;
+const LENGTH_LEN: usize = 2;
+
+/*************************************/
+/*            UNICAST                */
+/*************************************/
+struct CompressedWriter {
+    buffer: Vec<u8>,
+}
+
+struct CompressedReader {
+    buffer: Vec<u8>,
+    // The decompressed batches of streamed links, with their length prefix
+    pending: Vec<u8>,
+    offset: usize,
+}
+
+pub struct LinkUnicastCompressed {
+    inner: LinkUnicast,
+    threshold: usize,
+    writer: AsyncMutex<CompressedWriter>,
+    reader: AsyncMutex<CompressedReader>,
+}
+
+impl LinkUnicastCompressed {
+    pub fn new(inner: LinkUnicast, threshold: usize) -> LinkUnicastCompressed {
+        let size = inner.get_mtu() as usize + LENGTH_LEN;
+        LinkUnicastCompressed {
+            inner,
+            threshold,
+            writer: AsyncMutex::new(CompressedWriter {
+                buffer: vec![0_u8; size],
+            }),
+            reader: AsyncMutex::new(CompressedReader {
+                buffer: vec![0_u8; size],
+                pending: Vec::with_capacity(size),
+                offset: 0,
+            }),
+        }
+    }
+
+    // Encodes the batch with its header in the buffer, returning the encoded length
+    fn encode(&self, batch: &[u8], buffer: &mut [u8]) -> usize {
+        let (header, data) = buffer.split_at_mut(HEADER_LEN);
+        if batch.len() >= self.threshold {
+            // Only keep the compressed batch if it is smaller than the original one
+            let limit = batch.len().saturating_sub(HEADER_LEN).min(data.len());
+            if let Ok(n) = block::compress_into(batch, &mut data[..limit]) {
+                header[0] = HEADER_LZ4;
+                return HEADER_LEN + n;
+            }
+        }
+        header[0] = HEADER_RAW;
+        data[..batch.len()].copy_from_slice(batch);
+        HEADER_LEN + batch.len()
+    }
+
+    // Decodes the encoded batch in the output, returning the decoded length
+    fn decode(&self, encoded: &[u8], output: &mut [u8]) -> ZResult<usize> {
+        match encoded.split_first() {
+            Some((&HEADER_RAW, data)) => {
+                if data.len() > output.len() {
+                    bail!("Batch too large on compressed link: {}", self);
+                }
+                output[..data.len()].copy_from_slice(data);
+                Ok(data.len())
+            }
+            Some((&HEADER_LZ4, data)) => block::decompress_into(data, output)
+                .map_err(|e| zerror!("Invalid compressed batch on link {}: {}", self, e).into()),
+            Some((h, _)) => bail!("Invalid compression header {} on link: {}", h, self),
+            None => bail!("Empty batch on compressed link: {}", self),
+        }
+    }
+}
+
+#[async_trait]
+impl LinkUnicastTrait for LinkUnicastCompressed {
+    // One byte of each batch is reserved for the compression header
+    fn get_mtu(&self) -> u16 {
+        self.inner.get_mtu() - HEADER_LEN as u16
+    }
+
+    fn get_src(&self) -> &Locator {
+        self.inner.get_src()
+    }
+
+    fn get_dst(&self) -> &Locator {
+        self.inner.get_dst()
+    }
+
+    fn is_reliable(&self) -> bool {
+        self.inner.is_reliable()
+    }
+
+    fn is_streamed(&self) -> bool {
+        self.inner.is_streamed()
+    }
+
+    async fn write(&self, buffer: &[u8]) -> ZResult<usize> {
+        self.write_all(buffer).await?;
+        Ok(buffer.len())
+    }
+
+    // The transport writes one whole batch at a time
+    async fn write_all(&self, buffer: &[u8]) -> ZResult<()> {
+        let mut guard = zasynclock!(self.writer);
+        let encoded = &mut guard.buffer;
+        if self.is_streamed() {
+            if buffer.len() < LENGTH_LEN {
+                bail!("Invalid batch on compressed link: {}", self);
+            }
+            let n = self.encode(&buffer[LENGTH_LEN..], &mut encoded[LENGTH_LEN..]);
+            encoded[..LENGTH_LEN].copy_from_slice(&(n as u16).to_le_bytes());
+            self.inner.write_all(&encoded[..LENGTH_LEN + n]).await
+        } else {
+            let n = self.encode(buffer, encoded);
+            self.inner.write_all(&encoded[..n]).await
+        }
+    }
+
+    async fn read(&self, buffer: &mut [u8]) -> ZResult<usize> {
+        let mut guard = zasynclock!(self.reader);
+        let CompressedReader {
+            buffer: encoded,
+            pending,
+            offset,
+        } = &mut *guard;
+        if !self.is_streamed() {
+            let n = self.inner.read(encoded).await?;
+            if n == 0 {
+                return Ok(0);
+            }
+            return self.decode(&encoded[..n], buffer);
+        }
+
+        if *offset == pending.len() {
+            let mut length = [0_u8; LENGTH_LEN];
+            self.inner.read_exact(&mut length).await?;
+            let n = u16::from_le_bytes(length) as usize;
+            if n > encoded.len() {
+                bail!("Batch too large on compressed link: {}", self);
+            }
+            self.inner.read_exact(&mut encoded[..n]).await?;
+            pending.resize(encoded.len(), 0);
+            let n = self.decode(&encoded[..n], &mut pending[LENGTH_LEN..])?;
+            pending[..LENGTH_LEN].copy_from_slice(&(n as u16).to_le_bytes());
+            pending.truncate(LENGTH_LEN + n);
+            *offset = 0;
+        }
+        let n = buffer.len().min(pending.len() - *offset);
+        buffer[..n].copy_from_slice(&pending[*offset..*offset + n]);
+        *offset += n;
+        Ok(n)
+    }
+
+    async fn read_exact(&self, buffer: &mut [u8]) -> ZResult<()> {
+        let mut read: usize = 0;
+        while read < buffer.len() {
+            let n = self.read(&mut buffer[read..]).await?;
+            if n == 0 {
+                bail!("Read error on compressed link: {}", self);
+            }
+            read += n;
+        }
+        Ok(())
+    }
+
+    async fn close(&self) -> ZResult<()> {
+        log::trace!("Closing compressed link: {}", self);
+        self.inner.close().await
+    }
+}
+
+impl fmt::Display for LinkUnicastCompressed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} => {}", self.get_src(), self.get_dst())
+    }
+}
+
+impl fmt::Debug for LinkUnicastCompressed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Compressed")
+            .field("src", &self.get_src())
+            .field("dst", &self.get_dst())
+            .field("threshold", &self.threshold)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_std::task;
+    use rand::{Rng, SeedableRng};
+    use std::sync::Arc;
+
+    // A streamed link reading the given bytes
+    struct StreamLink {
+        locator: Locator,
+        bytes: AsyncMutex<Vec<u8>>,
+    }
+
+    #[async_trait]
+    impl LinkUnicastTrait for StreamLink {
+        fn get_mtu(&self) -> u16 {
+            64
+        }
+
+        fn get_src(&self) -> &Locator {
+            &self.locator
+        }
+
+        fn get_dst(&self) -> &Locator {
+            &self.locator
+        }
+
+        fn is_reliable(&self) -> bool {
+            true
+        }
+
+        fn is_streamed(&self) -> bool {
+            true
+        }
+
+        async fn write(&self, buffer: &[u8]) -> ZResult<usize> {
+            Ok(buffer.len())
+        }
+
+        async fn write_all(&self, _buffer: &[u8]) -> ZResult<()> {
+            Ok(())
+        }
+
+        async fn read(&self, buffer: &mut [u8]) -> ZResult<usize> {
+            let mut bytes = zasynclock!(self.bytes);
+            let n = buffer.len().min(bytes.len());
+            buffer[..n].copy_from_slice(&bytes[..n]);
+            bytes.drain(..n);
+            Ok(n)
+        }
+
+        async fn read_exact(&self, buffer: &mut [u8]) -> ZResult<()> {
+            if self.read(buffer).await? < buffer.len() {
+                bail!("Read error on stream link");
+            }
+            Ok(())
+        }
+
+        async fn close(&self) -> ZResult<()> {
+            Ok(())
+        }
+    }
+
+    fn roundtrip(input: &[u8]) -> usize {
+        let mut compressed = vec![0_u8; block::get_maximum_output_size(input.len())];
+        let n = block::compress_into(input, &mut compressed).unwrap();
+        let mut output = vec![0_u8; input.len()];
+        let m = block::decompress_into(&compressed[..n], &mut output).unwrap();
+        assert_eq!(&output[..m], input);
+        n
+    }
+
+    #[test]
+    fn lz4_roundtrip() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0);
+
+        // Text compresses well
+        let text = "{\"key\": \"/demo/example\", \"value\": \"Hello World!\"}\n".repeat(100);
+        let n = roundtrip(text.as_bytes());
+        assert!(n < text.len() / 10);
+
+        // Random data does not compress: the batches are then sent as they are
+        let input: Vec<u8> = (0..4_096).map(|_| rng.gen()).collect();
+        let n = roundtrip(&input);
+        assert!(n >= input.len());
+        let mut compressed = vec![0_u8; input.len() - 1];
+        assert!(block::compress_into(&input, &mut compressed).is_err());
+
+        // Corrupted blocks are rejected
+        let mut output = vec![0_u8; 1_024];
+        assert!(block::decompress_into(&[0x0f, 0x01, 0x00], &mut output).is_err());
+        assert!(block::decompress_into(&[0xf0], &mut output).is_err());
+    }
+
+    #[test]
+    fn forged_length() {
+        task::block_on(async {
+            // The length of the batch is larger than the MTU of the link
+            let mut bytes = u16::MAX.to_le_bytes().to_vec();
+            bytes.extend_from_slice(&[HEADER_RAW; 128]);
+            let link = StreamLink {
+                locator: "tcp/127.0.0.1:7447".parse().unwrap(),
+                bytes: AsyncMutex::new(bytes),
+            };
+            let link = LinkUnicastCompressed::new(LinkUnicast(Arc::new(link)), 0);
+            let mut buffer = vec![0_u8; 128];
+            assert!(link.read(&mut buffer).await.is_err());
+        });
+    }
+}
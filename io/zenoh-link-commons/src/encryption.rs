@@ -0,0 +1,366 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ADLINK zenoh team, <zenoh@adlink-labs.tech>
+//
+
+//! Batch encryption for links.
+//!
+//! An encrypted link wraps a [`LinkUnicast`] once a session secret has been agreed with the
+//! peer during the session establishment. Every batch is encrypted and authenticated with
+//! AES-128-GCM:
+//!
+//! ```text
+//! +---------+------------------+---------+
+//! |  nonce  |    ciphertext    |   tag   |
+//! +---------+------------------+---------+
+//!      8            batch           16
+//! ```
+//!
+//! Each direction uses its own key derived from the secret, and the nonce is a per-direction
+//! counter, padded with zeros to the 12 bytes of the GCM nonce. Batches that fail the
+//! authentication or that are replayed are rejected.
+//! On streamed links the 16 bits length prefix is rewritten to frame the encrypted batch.
+//!
+//! The secret is agreed with public keys that the handshake does not authenticate: the encryption
+//! protects against passive eavesdroppers, not against an active man-in-the-middle.
+//!
+//! Multicast links are not covered: no session secret is agreed on a multicast group.
+use super::{LinkUnicast, LinkUnicastTrait};
+use async_std::sync::Mutex as AsyncMutex;
+use async_trait::async_trait;
+use std::fmt;
+use zenoh_core::{bail, zasynclock, zerror, Result as ZResult};
+use zenoh_crypto::{hmac, AeadCipher};
+use zenoh_protocol_core::Locator;
+
+const NONCE_LEN: usize = 8;
+const TAG_LEN: usize = AeadCipher::TAG_SIZE;
+const OVERHEAD: usize = NONCE_LEN + TAG_LEN;
+const LENGTH_LEN: usize = 2;
+// The number of nonces older than the highest received one that are still accepted
+const REPLAY_WINDOW: u64 = 64;
+
+const LABEL_INITIATOR: &[u8] = b"zenoh encryption initiator";
+const LABEL_RESPONDER: &[u8] = b"zenoh encryption responder";
+
+/*************************************/
+/*              KEYS                 */
+/*************************************/
+fn derive(secret: &[u8], label: &[u8]) -> ZResult<AeadCipher> {
+    let material = hmac::sign(secret, label)?;
+    let mut key = [0_u8; AeadCipher::KEY_SIZE];
+    key.copy_from_slice(&material[..AeadCipher::KEY_SIZE]);
+    Ok(AeadCipher::new(key))
+}
+
+// The counter of the batches in the first bytes of the nonce
+fn nonce(counter: u64) -> [u8; AeadCipher::NONCE_SIZE] {
+    let mut nonce = [0_u8; AeadCipher::NONCE_SIZE];
+    nonce[..NONCE_LEN].copy_from_slice(&counter.to_le_bytes());
+    nonce
+}
+
+// Sliding window over the received nonces
+struct ReplayWindow {
+    highest: u64,
+    bitmap: u64,
+}
+
+impl ReplayWindow {
+    fn new() -> ReplayWindow {
+        // The nonces start from 1
+        ReplayWindow {
+            highest: 0,
+            bitmap: 1,
+        }
+    }
+
+    fn check(&self, nonce: u64) -> bool {
+        if nonce > self.highest {
+            return true;
+        }
+        let age = self.highest - nonce;
+        age < REPLAY_WINDOW && self.bitmap & (1 << age) == 0
+    }
+
+    fn update(&mut self, nonce: u64) {
+        if nonce > self.highest {
+            let shift = nonce - self.highest;
+            self.bitmap = if shift < REPLAY_WINDOW {
+                self.bitmap << shift
+            } else {
+                0
+            };
+            self.highest = nonce;
+            self.bitmap |= 1;
+        } else {
+            self.bitmap |= 1 << (self.highest - nonce);
+        }
+    }
+}
+
+/*************************************/
+/*            UNICAST                */
+/*************************************/
+struct EncryptedWriter {
+    nonce: u64,
+    buffer: Vec<u8>,
+}
+
+struct EncryptedReader {
+    buffer: Vec<u8>,
+    window: ReplayWindow,
+    // The decrypted batches of streamed links, with their length prefix
+    pending: Vec<u8>,
+    offset: usize,
+}
+
+pub struct LinkUnicastEncrypted {
+    inner: LinkUnicast,
+    tx: AeadCipher,
+    rx: AeadCipher,
+    writer: AsyncMutex<EncryptedWriter>,
+    reader: AsyncMutex<EncryptedReader>,
+}
+
+impl LinkUnicastEncrypted {
+    /// Creates an encrypted link from the session `secret` shared with the peer.
+    /// Exactly one of the two sides of the link must be the initiator.
+    pub fn new(
+        inner: LinkUnicast,
+        secret: &[u8],
+        is_initiator: bool,
+    ) -> ZResult<LinkUnicastEncrypted> {
+        let initiator = derive(secret, LABEL_INITIATOR)?;
+        let responder = derive(secret, LABEL_RESPONDER)?;
+        let (tx, rx) = if is_initiator {
+            (initiator, responder)
+        } else {
+            (responder, initiator)
+        };
+
+        let size = inner.get_mtu() as usize + LENGTH_LEN;
+        Ok(LinkUnicastEncrypted {
+            inner,
+            tx,
+            rx,
+            writer: AsyncMutex::new(EncryptedWriter {
+                nonce: 0,
+                buffer: vec![0_u8; size],
+            }),
+            reader: AsyncMutex::new(EncryptedReader {
+                buffer: vec![0_u8; size],
+                window: ReplayWindow::new(),
+                pending: Vec::with_capacity(size),
+                offset: 0,
+            }),
+        })
+    }
+
+    // Encrypts the batch in the buffer, returning the encrypted length
+    fn encrypt(&self, nonce: u64, batch: &[u8], buffer: &mut [u8]) -> ZResult<usize> {
+        let len = NONCE_LEN + batch.len();
+        if len + TAG_LEN > buffer.len() {
+            bail!("Batch too large on encrypted link: {}", self);
+        }
+        buffer[..NONCE_LEN].copy_from_slice(&nonce.to_le_bytes());
+        buffer[NONCE_LEN..len].copy_from_slice(batch);
+        let tag = self
+            .tx
+            .encrypt_in_place(&self::nonce(nonce), &mut buffer[NONCE_LEN..len])?;
+        buffer[len..len + TAG_LEN].copy_from_slice(&tag);
+        Ok(len + TAG_LEN)
+    }
+
+    // Authenticates and decrypts in place the encrypted batch, returning the decrypted range
+    fn decrypt(&self, window: &mut ReplayWindow, encrypted: &mut [u8]) -> ZResult<(usize, usize)> {
+        if encrypted.len() < OVERHEAD {
+            bail!("Invalid batch on encrypted link: {}", self);
+        }
+        let len = encrypted.len() - TAG_LEN;
+        let mut nonce = [0_u8; NONCE_LEN];
+        nonce.copy_from_slice(&encrypted[..NONCE_LEN]);
+        let nonce = u64::from_le_bytes(nonce);
+        if !window.check(nonce) {
+            bail!("Replayed batch {} on encrypted link: {}", nonce, self);
+        }
+        let mut tag = [0_u8; TAG_LEN];
+        tag.copy_from_slice(&encrypted[len..]);
+        self.rx
+            .decrypt_in_place(&self::nonce(nonce), &mut encrypted[NONCE_LEN..len], &tag)
+            .map_err(|e| zerror!("{} on encrypted link: {}", e, self))?;
+        window.update(nonce);
+        Ok((NONCE_LEN, len))
+    }
+}
+
+#[async_trait]
+impl LinkUnicastTrait for LinkUnicastEncrypted {
+    // Each batch carries the nonce and the authentication tag
+    fn get_mtu(&self) -> u16 {
+        self.inner.get_mtu() - OVERHEAD as u16
+    }
+
+    fn get_src(&self) -> &Locator {
+        self.inner.get_src()
+    }
+
+    fn get_dst(&self) -> &Locator {
+        self.inner.get_dst()
+    }
+
+    fn is_reliable(&self) -> bool {
+        self.inner.is_reliable()
+    }
+
+    fn is_streamed(&self) -> bool {
+        self.inner.is_streamed()
+    }
+
+    async fn write(&self, buffer: &[u8]) -> ZResult<usize> {
+        self.write_all(buffer).await?;
+        Ok(buffer.len())
+    }
+
+    // The transport writes one whole batch at a time
+    async fn write_all(&self, buffer: &[u8]) -> ZResult<()> {
+        let mut guard = zasynclock!(self.writer);
+        let EncryptedWriter {
+            nonce,
+            buffer: encrypted,
+        } = &mut *guard;
+        *nonce += 1;
+        if self.is_streamed() {
+            if buffer.len() < LENGTH_LEN {
+                bail!("Invalid batch on encrypted link: {}", self);
+            }
+            let n = self.encrypt(*nonce, &buffer[LENGTH_LEN..], &mut encrypted[LENGTH_LEN..])?;
+            encrypted[..LENGTH_LEN].copy_from_slice(&(n as u16).to_le_bytes());
+            self.inner.write_all(&encrypted[..LENGTH_LEN + n]).await
+        } else {
+            let n = self.encrypt(*nonce, buffer, encrypted)?;
+            self.inner.write_all(&encrypted[..n]).await
+        }
+    }
+
+    async fn read(&self, buffer: &mut [u8]) -> ZResult<usize> {
+        let mut guard = zasynclock!(self.reader);
+        let EncryptedReader {
+            buffer: encrypted,
+            window,
+            pending,
+            offset,
+        } = &mut *guard;
+        if !self.is_streamed() {
+            loop {
+                let n = self.inner.read(encrypted).await?;
+                if n == 0 {
+                    return Ok(0);
+                }
+                // Forged or replayed datagrams are discarded without affecting the link
+                match self.decrypt(window, &mut encrypted[..n]) {
+                    Ok((start, end)) => {
+                        let n = end - start;
+                        if n > buffer.len() {
+                            bail!("Batch too large on encrypted link: {}", self);
+                        }
+                        buffer[..n].copy_from_slice(&encrypted[start..end]);
+                        return Ok(n);
+                    }
+                    Err(e) => log::warn!("{}", e),
+                }
+            }
+        }
+
+        if *offset == pending.len() {
+            let mut length = [0_u8; LENGTH_LEN];
+            self.inner.read_exact(&mut length).await?;
+            let n = u16::from_le_bytes(length) as usize;
+            if n > encrypted.len() {
+                bail!("Batch too large on encrypted link: {}", self);
+            }
+            self.inner.read_exact(&mut encrypted[..n]).await?;
+            let (start, end) = self.decrypt(window, &mut encrypted[..n])?;
+            pending.clear();
+            pending.extend_from_slice(&((end - start) as u16).to_le_bytes());
+            pending.extend_from_slice(&encrypted[start..end]);
+            *offset = 0;
+        }
+        let n = buffer.len().min(pending.len() - *offset);
+        buffer[..n].copy_from_slice(&pending[*offset..*offset + n]);
+        *offset += n;
+        Ok(n)
+    }
+
+    async fn read_exact(&self, buffer: &mut [u8]) -> ZResult<()> {
+        let mut read: usize = 0;
+        while read < buffer.len() {
+            let n = self.read(&mut buffer[read..]).await?;
+            if n == 0 {
+                bail!("Read error on encrypted link: {}", self);
+            }
+            read += n;
+        }
+        Ok(())
+    }
+
+    async fn close(&self) -> ZResult<()> {
+        log::trace!("Closing encrypted link: {}", self);
+        self.inner.close().await
+    }
+}
+
+impl fmt::Display for LinkUnicastEncrypted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} => {}", self.get_src(), self.get_dst())
+    }
+}
+
+impl fmt::Debug for LinkUnicastEncrypted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Encrypted")
+            .field("src", &self.get_src())
+            .field("dst", &self.get_dst())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ReplayWindow;
+
+    #[test]
+    fn replay_window() {
+        let mut window = ReplayWindow::new();
+        assert!(!window.check(0));
+
+        // In order
+        for nonce in 1..=10 {
+            assert!(window.check(nonce));
+            window.update(nonce);
+            assert!(!window.check(nonce));
+        }
+
+        // Out of order within the window
+        window.update(20);
+        assert!(window.check(15));
+        window.update(15);
+        assert!(!window.check(15));
+        assert!(!window.check(10));
+
+        // Too old
+        window.update(200);
+        assert!(!window.check(200 - super::REPLAY_WINDOW));
+        assert!(window.check(200 - super::REPLAY_WINDOW + 1));
+        assert!(window.check(201));
+    }
+}
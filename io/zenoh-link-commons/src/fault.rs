@@ -0,0 +1,801 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ADLINK zenoh team, <zenoh@adlink-labs.tech>
+//
+
+//! Fault injection for links.
+//!
+//! A faulty link wraps any [`LinkUnicast`] or [`LinkMulticast`] and applies loss,
+//! latency, jitter, reordering, duplication and timed partitions to the frames
+//! flowing through it, in both directions. Faults are configured through the
+//! endpoint configuration, e.g.:
+//! `udp/127.0.0.1:7447#fault_loss=0.1;fault_delay=20;fault_jitter=5`.
+//!
+//! Faults operate on whole frames as written by the transport. On streamed links
+//! the frames are delimited by the 16 bits length prefix so that the stream is
+//! never corrupted.
+//!
+//! The frames held by a faulty link in each direction are bounded by `fault_queue`
+//! bytes, i.e. a number of frames of the link MTU: writing on a full link waits
+//! until some frames have been delivered, as on a congested link.
+use super::{LinkMulticast, LinkMulticastTrait, LinkUnicast, LinkUnicastTrait};
+use async_std::future;
+use async_std::sync::{Arc, Mutex as AsyncMutex};
+use async_std::task;
+use async_trait::async_trait;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::borrow::Cow;
+use std::cmp::Ordering as CmpOrdering;
+use std::collections::{BinaryHeap, HashMap};
+use std::fmt;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use zenoh_core::{bail, zasynclock, zerror, zlock, Result as ZResult};
+use zenoh_protocol_core::{EndPoint, Locator};
+
+pub mod config {
+    /// Probability in [0, 1] of dropping a frame.
+    pub const FAULT_LOSS: &str = "fault_loss";
+    /// Constant latency in milliseconds added to every frame.
+    pub const FAULT_DELAY: &str = "fault_delay";
+    /// Maximum random latency in milliseconds added on top of `fault_delay`.
+    pub const FAULT_JITTER: &str = "fault_jitter";
+    /// Probability in [0, 1] of delaying a frame past the following ones.
+    pub const FAULT_REORDER: &str = "fault_reorder";
+    /// Additional latency in milliseconds applied to the reordered frames.
+    pub const FAULT_REORDER_DELAY: &str = "fault_reorder_delay";
+    /// Probability in [0, 1] of delivering a frame twice.
+    pub const FAULT_DUPLICATE: &str = "fault_duplicate";
+    /// Period in milliseconds of the partitions.
+    pub const FAULT_PARTITION_PERIOD: &str = "fault_partition_period";
+    /// Duration in milliseconds of the partition at the end of every period.
+    pub const FAULT_PARTITION_DURATION: &str = "fault_partition_duration";
+    /// Time in milliseconds after the link creation before any fault is injected.
+    pub const FAULT_START: &str = "fault_start";
+    /// Seed of the random generator, for reproducible runs.
+    pub const FAULT_SEED: &str = "fault_seed";
+    /// Maximum number of bytes of the frames held by the link in each direction.
+    pub const FAULT_QUEUE: &str = "fault_queue";
+
+    pub const FAULT_REORDER_DELAY_DEFAULT: u64 = 10;
+    pub const FAULT_QUEUE_DEFAULT: usize = 4 * 1_024 * 1_024;
+}
+
+/*************************************/
+/*            CONFIG                 */
+/*************************************/
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FaultConfig {
+    pub loss: f64,
+    pub delay: Duration,
+    pub jitter: Duration,
+    pub reorder: f64,
+    pub reorder_delay: Duration,
+    pub duplicate: f64,
+    pub partition: Option<(Duration, Duration)>,
+    pub start: Duration,
+    pub seed: Option<u64>,
+    pub queue: usize,
+}
+
+impl FaultConfig {
+    /// Parses the fault configuration of an endpoint.
+    /// Returns `None` if the endpoint does not configure any fault.
+    pub fn from_endpoint(endpoint: &EndPoint) -> ZResult<Option<FaultConfig>> {
+        match endpoint.config.as_ref() {
+            Some(config) => FaultConfig::from_properties(config),
+            None => Ok(None),
+        }
+    }
+
+    pub fn from_properties(config: &HashMap<String, String>) -> ZResult<Option<FaultConfig>> {
+        use config::*;
+
+        if !config.keys().any(|k| k.starts_with("fault_")) {
+            return Ok(None);
+        }
+
+        fn probability(config: &HashMap<String, String>, key: &str) -> ZResult<f64> {
+            match config.get(key) {
+                Some(v) => {
+                    let p: f64 = parse(key, v)?;
+                    if !(0.0..=1.0).contains(&p) {
+                        bail!("Invalid {}: {} is not in [0, 1]", key, v);
+                    }
+                    Ok(p)
+                }
+                None => Ok(0.0),
+            }
+        }
+
+        fn millis(config: &HashMap<String, String>, key: &str) -> ZResult<Option<Duration>> {
+            config
+                .get(key)
+                .map(|v| parse(key, v).map(Duration::from_millis))
+                .transpose()
+        }
+
+        let partition = match (
+            millis(config, FAULT_PARTITION_PERIOD)?,
+            millis(config, FAULT_PARTITION_DURATION)?,
+        ) {
+            (Some(period), Some(duration)) => {
+                if period.is_zero() || duration > period {
+                    bail!(
+                        "Invalid partition: duration ({:?}) must not exceed the non-null period ({:?})",
+                        duration,
+                        period
+                    );
+                }
+                Some((period, duration))
+            }
+            (None, None) => None,
+            _ => bail!(
+                "Both {} and {} are required to configure a partition",
+                FAULT_PARTITION_PERIOD,
+                FAULT_PARTITION_DURATION
+            ),
+        };
+
+        Ok(Some(FaultConfig {
+            loss: probability(config, FAULT_LOSS)?,
+            delay: millis(config, FAULT_DELAY)?.unwrap_or_default(),
+            jitter: millis(config, FAULT_JITTER)?.unwrap_or_default(),
+            reorder: probability(config, FAULT_REORDER)?,
+            reorder_delay: millis(config, FAULT_REORDER_DELAY)?
+                .unwrap_or_else(|| Duration::from_millis(FAULT_REORDER_DELAY_DEFAULT)),
+            duplicate: probability(config, FAULT_DUPLICATE)?,
+            partition,
+            start: millis(config, FAULT_START)?.unwrap_or_default(),
+            seed: config
+                .get(FAULT_SEED)
+                .map(|v| parse(FAULT_SEED, v))
+                .transpose()?,
+            queue: config
+                .get(FAULT_QUEUE)
+                .map(|v| parse(FAULT_QUEUE, v))
+                .transpose()?
+                .unwrap_or(FAULT_QUEUE_DEFAULT),
+        }))
+    }
+
+    // The number of frames of the given MTU held by a faulty link in each direction
+    fn capacity(&self, mtu: u16) -> usize {
+        (self.queue / mtu.max(1) as usize).max(1)
+    }
+}
+
+fn parse<T: FromStr>(key: &str, value: &str) -> ZResult<T> {
+    value
+        .parse()
+        .map_err(|_| zerror!("Invalid {}: {}", key, value).into())
+}
+
+/*************************************/
+/*            INJECTOR               */
+/*************************************/
+struct FaultInjector {
+    config: FaultConfig,
+    origin: Instant,
+    rng: Mutex<StdRng>,
+}
+
+impl FaultInjector {
+    fn new(config: FaultConfig) -> FaultInjector {
+        let rng = match config.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+        FaultInjector {
+            config,
+            origin: Instant::now(),
+            rng: Mutex::new(rng),
+        }
+    }
+
+    fn is_partitioned(&self, elapsed: Duration) -> bool {
+        match self.config.partition {
+            Some((period, duration)) => {
+                let phase = elapsed.as_nanos() % period.as_nanos();
+                phase >= (period - duration).as_nanos()
+            }
+            None => false,
+        }
+    }
+
+    // Returns the delays after which a frame has to be delivered and whether it is
+    // reordered: no delay means that the frame is dropped, two delays that it is duplicated.
+    fn schedule(&self) -> Vec<(Duration, bool)> {
+        let elapsed = self.origin.elapsed();
+        if elapsed < self.config.start {
+            return vec![(Duration::ZERO, false)];
+        }
+        if self.is_partitioned(elapsed) {
+            return vec![];
+        }
+
+        let mut rng = zlock!(self.rng);
+        if rng.gen_bool(self.config.loss) {
+            return vec![];
+        }
+        let copies = if rng.gen_bool(self.config.duplicate) {
+            2
+        } else {
+            1
+        };
+        (0..copies)
+            .map(|_| {
+                let mut delay = self.config.delay;
+                if !self.config.jitter.is_zero() {
+                    delay += self.config.jitter.mul_f64(rng.gen::<f64>());
+                }
+                let reordered = rng.gen_bool(self.config.reorder);
+                if reordered {
+                    delay += self.config.reorder_delay;
+                }
+                (delay, reordered)
+            })
+            .collect()
+    }
+}
+
+/*************************************/
+/*            SCHEDULER              */
+/*************************************/
+struct Scheduled<T> {
+    deadline: Instant,
+    sn: u64,
+    item: T,
+}
+
+impl<T> PartialEq for Scheduled<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.deadline == other.deadline && self.sn == other.sn
+    }
+}
+
+impl<T> Eq for Scheduled<T> {}
+
+impl<T> PartialOrd for Scheduled<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<CmpOrdering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for Scheduled<T> {
+    // Reversed to turn the BinaryHeap into a min-heap on the deadline
+    fn cmp(&self, other: &Self) -> CmpOrdering {
+        other
+            .deadline
+            .cmp(&self.deadline)
+            .then_with(|| other.sn.cmp(&self.sn))
+    }
+}
+
+// Releases the items received on `input` on `output` once their deadline has expired.
+// Items with the same deadline are released in the order they have been received.
+// No more than `capacity` items are held, the next ones wait in `input`.
+async fn scheduler<T: Send + 'static>(
+    input: flume::Receiver<(Instant, T)>,
+    output: flume::Sender<T>,
+    capacity: usize,
+) {
+    let mut heap: BinaryHeap<Scheduled<T>> = BinaryHeap::new();
+    let mut sn: u64 = 0;
+    let mut is_open = true;
+    loop {
+        let next = match heap.peek() {
+            Some(s) => Some(s.deadline),
+            None if is_open => None,
+            None => break,
+        };
+
+        let received = match next {
+            Some(deadline) => {
+                let now = Instant::now();
+                if deadline <= now {
+                    let s = heap.pop().unwrap();
+                    if output.send_async(s.item).await.is_err() {
+                        break;
+                    }
+                    continue;
+                }
+                if is_open && heap.len() < capacity {
+                    match future::timeout(deadline - now, input.recv_async()).await {
+                        Ok(res) => res.ok(),
+                        Err(_) => continue,
+                    }
+                } else {
+                    task::sleep(deadline - now).await;
+                    continue;
+                }
+            }
+            None => input.recv_async().await.ok(),
+        };
+
+        match received {
+            Some((deadline, item)) => {
+                heap.push(Scheduled { deadline, sn, item });
+                sn += 1;
+            }
+            // Deliver the pending items before terminating
+            None => is_open = false,
+        }
+    }
+}
+
+// One direction of a faulty link
+struct FaultyChannel<T> {
+    input: flume::Sender<(Instant, T)>,
+    last: Mutex<Instant>,
+}
+
+impl<T: Clone + Send + 'static> FaultyChannel<T> {
+    fn new(capacity: usize) -> (FaultyChannel<T>, flume::Receiver<T>) {
+        let (input_tx, input_rx) = flume::bounded(capacity);
+        let (output_tx, output_rx) = flume::bounded(capacity);
+        task::spawn(scheduler(input_rx, output_tx, capacity));
+        let channel = FaultyChannel {
+            input: input_tx,
+            last: Mutex::new(Instant::now()),
+        };
+        (channel, output_rx)
+    }
+
+    // Pushes the copies of the frame, if any, in the scheduler, waiting for space if the
+    // channel is full. Jitter alone never reorders the frames, only the frames explicitly
+    // reordered may overtake the others. Returns false if the scheduler is no longer running.
+    async fn inject(&self, injector: &FaultInjector, item: T) -> bool {
+        let deadlines: Vec<Instant> = {
+            let now = Instant::now();
+            let mut last = zlock!(self.last);
+            injector
+                .schedule()
+                .into_iter()
+                .map(|(delay, reordered)| {
+                    let mut deadline = now + delay;
+                    if !reordered {
+                        deadline = deadline.max(*last);
+                        *last = deadline;
+                    }
+                    deadline
+                })
+                .collect()
+        };
+        for deadline in deadlines {
+            if self
+                .input
+                .send_async((deadline, item.clone()))
+                .await
+                .is_err()
+            {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/*************************************/
+/*            UNICAST                */
+/*************************************/
+struct UnicastReader {
+    output: flume::Receiver<Vec<u8>>,
+    frame: Vec<u8>,
+    offset: usize,
+}
+
+pub struct LinkUnicastFaulty {
+    inner: LinkUnicast,
+    injector: Arc<FaultInjector>,
+    egress: FaultyChannel<Vec<u8>>,
+    is_broken: Arc<AtomicBool>,
+    reader: AsyncMutex<UnicastReader>,
+}
+
+impl LinkUnicastFaulty {
+    pub fn new(inner: LinkUnicast, config: FaultConfig) -> LinkUnicastFaulty {
+        let capacity = config.capacity(inner.get_mtu());
+        let injector = Arc::new(FaultInjector::new(config));
+        let is_broken = Arc::new(AtomicBool::new(false));
+
+        // Egress: schedule the frames and write them on the inner link
+        let (egress, egress_rx) = FaultyChannel::<Vec<u8>>::new(capacity);
+        let c_inner = inner.clone();
+        let c_is_broken = is_broken.clone();
+        task::spawn(async move {
+            while let Ok(frame) = egress_rx.recv_async().await {
+                if let Err(e) = c_inner.write_all(&frame).await {
+                    log::trace!("Faulty link {} failed to write: {}", c_inner, e);
+                    c_is_broken.store(true, Ordering::Release);
+                    break;
+                }
+            }
+        });
+
+        // Ingress: read the frames from the inner link and schedule them
+        let (ingress, ingress_rx) = FaultyChannel::<Vec<u8>>::new(capacity);
+        let c_inner = inner.clone();
+        let c_injector = injector.clone();
+        task::spawn(async move {
+            let mut buffer = vec![0_u8; c_inner.get_mtu() as usize];
+            loop {
+                let frame = if c_inner.is_streamed() {
+                    let mut length = [0_u8; 2];
+                    if c_inner.read_exact(&mut length).await.is_err() {
+                        break;
+                    }
+                    let n = u16::from_le_bytes(length) as usize;
+                    let mut frame = vec![0_u8; n + length.len()];
+                    frame[..length.len()].copy_from_slice(&length);
+                    if c_inner
+                        .read_exact(&mut frame[length.len()..])
+                        .await
+                        .is_err()
+                    {
+                        break;
+                    }
+                    frame
+                } else {
+                    match c_inner.read(&mut buffer).await {
+                        Ok(0) | Err(_) => break,
+                        Ok(n) => buffer[..n].to_vec(),
+                    }
+                };
+                if !ingress.inject(&c_injector, frame).await {
+                    break;
+                }
+            }
+        });
+
+        LinkUnicastFaulty {
+            inner,
+            injector,
+            egress,
+            is_broken,
+            reader: AsyncMutex::new(UnicastReader {
+                output: ingress_rx,
+                frame: vec![],
+                offset: 0,
+            }),
+        }
+    }
+}
+
+#[async_trait]
+impl LinkUnicastTrait for LinkUnicastFaulty {
+    fn get_mtu(&self) -> u16 {
+        self.inner.get_mtu()
+    }
+
+    fn get_src(&self) -> &Locator {
+        self.inner.get_src()
+    }
+
+    fn get_dst(&self) -> &Locator {
+        self.inner.get_dst()
+    }
+
+    fn is_reliable(&self) -> bool {
+        self.inner.is_reliable()
+    }
+
+    fn is_streamed(&self) -> bool {
+        self.inner.is_streamed()
+    }
+
+    async fn write(&self, buffer: &[u8]) -> ZResult<usize> {
+        self.write_all(buffer).await?;
+        Ok(buffer.len())
+    }
+
+    async fn write_all(&self, buffer: &[u8]) -> ZResult<()> {
+        if self.is_broken.load(Ordering::Acquire)
+            || !self.egress.inject(&self.injector, buffer.to_vec()).await
+        {
+            bail!("Write error on faulty link: {}", self);
+        }
+        Ok(())
+    }
+
+    async fn read(&self, buffer: &mut [u8]) -> ZResult<usize> {
+        let mut guard = zasynclock!(self.reader);
+        if guard.offset == guard.frame.len() {
+            match guard.output.recv_async().await {
+                Ok(frame) => {
+                    guard.frame = frame;
+                    guard.offset = 0;
+                }
+                Err(_) => return Ok(0),
+            }
+        }
+        let n = buffer.len().min(guard.frame.len() - guard.offset);
+        buffer[..n].copy_from_slice(&guard.frame[guard.offset..guard.offset + n]);
+        guard.offset += n;
+        Ok(n)
+    }
+
+    async fn read_exact(&self, buffer: &mut [u8]) -> ZResult<()> {
+        let mut read: usize = 0;
+        while read < buffer.len() {
+            let n = self.read(&mut buffer[read..]).await?;
+            if n == 0 {
+                bail!("Read error on faulty link: {}", self);
+            }
+            read += n;
+        }
+        Ok(())
+    }
+
+    async fn close(&self) -> ZResult<()> {
+        log::trace!("Closing faulty link: {}", self);
+        self.inner.close().await
+    }
+}
+
+impl fmt::Display for LinkUnicastFaulty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} => {}", self.get_src(), self.get_dst())
+    }
+}
+
+impl fmt::Debug for LinkUnicastFaulty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Faulty")
+            .field("src", &self.get_src())
+            .field("dst", &self.get_dst())
+            .field("faults", &self.injector.config)
+            .finish()
+    }
+}
+
+/*************************************/
+/*            MULTICAST              */
+/*************************************/
+pub struct LinkMulticastFaulty {
+    inner: LinkMulticast,
+    injector: Arc<FaultInjector>,
+    egress: FaultyChannel<Vec<u8>>,
+    is_broken: Arc<AtomicBool>,
+    ingress: flume::Receiver<(Vec<u8>, Locator)>,
+}
+
+impl LinkMulticastFaulty {
+    pub fn new(inner: LinkMulticast, config: FaultConfig) -> LinkMulticastFaulty {
+        let capacity = config.capacity(inner.get_mtu());
+        let injector = Arc::new(FaultInjector::new(config));
+        let is_broken = Arc::new(AtomicBool::new(false));
+
+        // Egress: schedule the frames and write them on the inner link
+        let (egress, egress_rx) = FaultyChannel::<Vec<u8>>::new(capacity);
+        let c_inner = inner.clone();
+        let c_is_broken = is_broken.clone();
+        task::spawn(async move {
+            while let Ok(frame) = egress_rx.recv_async().await {
+                if let Err(e) = c_inner.write_all(&frame).await {
+                    log::trace!("Faulty link {} failed to write: {}", c_inner.get_src(), e);
+                    c_is_broken.store(true, Ordering::Release);
+                    break;
+                }
+            }
+        });
+
+        // Ingress: read the frames from the inner link and schedule them
+        let (ingress_tx, ingress) = FaultyChannel::<(Vec<u8>, Locator)>::new(capacity);
+        let c_inner = inner.clone();
+        let c_injector = injector.clone();
+        task::spawn(async move {
+            let mut buffer = vec![0_u8; c_inner.get_mtu() as usize];
+            loop {
+                let item = match c_inner.read(&mut buffer).await {
+                    Ok((0, _)) | Err(_) => break,
+                    Ok((n, locator)) => (buffer[..n].to_vec(), locator.into_owned()),
+                };
+                if !ingress_tx.inject(&c_injector, item).await {
+                    break;
+                }
+            }
+        });
+
+        LinkMulticastFaulty {
+            inner,
+            injector,
+            egress,
+            is_broken,
+            ingress,
+        }
+    }
+}
+
+#[async_trait]
+impl LinkMulticastTrait for LinkMulticastFaulty {
+    fn get_mtu(&self) -> u16 {
+        self.inner.get_mtu()
+    }
+
+    fn get_src(&self) -> &Locator {
+        self.inner.get_src()
+    }
+
+    fn get_dst(&self) -> &Locator {
+        self.inner.get_dst()
+    }
+
+    fn is_reliable(&self) -> bool {
+        self.inner.is_reliable()
+    }
+
+    async fn write(&self, buffer: &[u8]) -> ZResult<usize> {
+        self.write_all(buffer).await?;
+        Ok(buffer.len())
+    }
+
+    async fn write_all(&self, buffer: &[u8]) -> ZResult<()> {
+        if self.is_broken.load(Ordering::Acquire)
+            || !self.egress.inject(&self.injector, buffer.to_vec()).await
+        {
+            bail!("Write error on faulty link: {}", self);
+        }
+        Ok(())
+    }
+
+    async fn read<'a>(&'a self, buffer: &mut [u8]) -> ZResult<(usize, Cow<'a, Locator>)> {
+        match self.ingress.recv_async().await {
+            Ok((frame, locator)) => {
+                let n = buffer.len().min(frame.len());
+                buffer[..n].copy_from_slice(&frame[..n]);
+                Ok((n, Cow::Owned(locator)))
+            }
+            Err(_) => bail!("Read error on faulty link: {}", self),
+        }
+    }
+
+    async fn close(&self) -> ZResult<()> {
+        log::trace!("Closing faulty link: {}", self);
+        self.inner.close().await
+    }
+}
+
+impl fmt::Display for LinkMulticastFaulty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} => {}", self.get_src(), self.get_dst())
+    }
+}
+
+impl fmt::Debug for LinkMulticastFaulty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Faulty")
+            .field("src", &self.get_src())
+            .field("dst", &self.get_dst())
+            .field("faults", &self.injector.config)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::config::*;
+    use super::*;
+
+    fn properties(s: &[(&str, &str)]) -> HashMap<String, String> {
+        s.iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn fault_config() {
+        assert_eq!(FaultConfig::from_properties(&HashMap::new()).unwrap(), None);
+
+        let config = FaultConfig::from_properties(&properties(&[
+            (FAULT_LOSS, "0.5"),
+            (FAULT_DELAY, "20"),
+            (FAULT_PARTITION_PERIOD, "1000"),
+            (FAULT_PARTITION_DURATION, "100"),
+        ]))
+        .unwrap()
+        .unwrap();
+        assert_eq!(config.loss, 0.5);
+        assert_eq!(config.delay, Duration::from_millis(20));
+        assert_eq!(
+            config.partition,
+            Some((Duration::from_millis(1_000), Duration::from_millis(100)))
+        );
+        assert_eq!(config.queue, FAULT_QUEUE_DEFAULT);
+        assert_eq!(config.capacity(u16::MAX), 64);
+
+        let config = FaultConfig::from_properties(&properties(&[(FAULT_QUEUE, "100")]))
+            .unwrap()
+            .unwrap();
+        assert_eq!(config.capacity(1_500), 1);
+
+        assert!(FaultConfig::from_properties(&properties(&[(FAULT_LOSS, "2")])).is_err());
+        assert!(FaultConfig::from_properties(&properties(&[(FAULT_DELAY, "-1")])).is_err());
+        assert!(
+            FaultConfig::from_properties(&properties(&[(FAULT_PARTITION_PERIOD, "10")])).is_err()
+        );
+        assert!(FaultConfig::from_properties(&properties(&[
+            (FAULT_PARTITION_PERIOD, "10"),
+            (FAULT_PARTITION_DURATION, "20")
+        ]))
+        .is_err());
+    }
+
+    #[test]
+    fn fault_injector() {
+        let injector = FaultInjector::new(FaultConfig {
+            loss: 0.25,
+            duplicate: 0.25,
+            delay: Duration::from_millis(10),
+            jitter: Duration::from_millis(5),
+            seed: Some(0),
+            ..Default::default()
+        });
+        let (mut lost, mut duplicated) = (0, 0);
+        for _ in 0..10_000 {
+            let delays = injector.schedule();
+            match delays.len() {
+                0 => lost += 1,
+                2 => duplicated += 1,
+                _ => {}
+            }
+            for (d, _) in delays {
+                assert!(d >= Duration::from_millis(10) && d <= Duration::from_millis(15));
+            }
+        }
+        assert!((2_000..3_000).contains(&lost));
+        assert!((1_500..2_250).contains(&duplicated));
+
+        let injector = FaultInjector::new(FaultConfig {
+            partition: Some((Duration::from_millis(100), Duration::from_millis(40))),
+            ..Default::default()
+        });
+        assert!(!injector.is_partitioned(Duration::from_millis(30)));
+        assert!(injector.is_partitioned(Duration::from_millis(70)));
+        assert!(!injector.is_partitioned(Duration::from_millis(130)));
+        assert!(injector.is_partitioned(Duration::from_millis(199)));
+    }
+
+    #[test]
+    fn fault_channel() {
+        task::block_on(async {
+            let injector = FaultInjector::new(FaultConfig {
+                delay: Duration::from_millis(100),
+                ..Default::default()
+            });
+            let (channel, output) = FaultyChannel::<usize>::new(2);
+
+            // The frames held by the scheduler and the ones waiting for it fill the channel
+            for i in 0..4 {
+                assert!(
+                    future::timeout(Duration::from_millis(50), channel.inject(&injector, i))
+                        .await
+                        .unwrap()
+                );
+            }
+            assert!(
+                future::timeout(Duration::from_millis(50), channel.inject(&injector, 4))
+                    .await
+                    .is_err()
+            );
+
+            // The delivery of the frames makes space for the next ones
+            assert!(channel.inject(&injector, 4).await);
+            for i in 0..5 {
+                assert_eq!(output.recv_async().await.unwrap(), i);
+            }
+        });
+    }
+}
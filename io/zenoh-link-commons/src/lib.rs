@@ -11,6 +11,12 @@
 // Contributors:
 //   ADLINK zenoh team, <zenoh@adlink-labs.tech>
 //
+pub mod compression;
+pub mod encryption;
+pub mod fault;
+#[cfg(feature = "tls")]
+pub mod tls;
+pub mod watch;
 
 use async_std::sync::Arc;
 use async_trait::async_trait;
@@ -155,7 +161,7 @@ impl LinkUnicast {
         }
         let contiguous = wbuf.contiguous();
         // Send the message on the link
-        self.0.write_all(&contiguous).await.unwrap();
+        self.0.write_all(&contiguous).await?;
         let len = contiguous.len();
         #[cfg(test)]
         {
@@ -0,0 +1,170 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ADLINK zenoh team, <zenoh@adlink-labs.tech>
+//
+
+//! The server certificates and the ALPN protocols shared by the links secured with rustls,
+//! i.e. TLS and QUIC.
+use super::watch::FileWatcher;
+use async_std::task;
+use rustls::internal::pemfile;
+use rustls::{sign, ClientHello, ResolvesServerCert};
+use std::io::Cursor;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
+use zenoh_core::{bail, zerror, zlock, zread, zwrite, Result as ZResult};
+
+/// The separator of the ALPN protocols in the endpoint configuration.
+pub const ALPN_SEPARATOR: char = ',';
+
+/// Parses the ALPN protocols of the endpoint configuration.
+pub fn get_alpn(protocols: &str) -> Vec<Vec<u8>> {
+    protocols
+        .split(ALPN_SEPARATOR)
+        .map(str::trim)
+        .filter(|p| !p.is_empty())
+        .map(|p| p.as_bytes().to_vec())
+        .collect()
+}
+
+/*************************************/
+/*        SERVER CERTIFICATES        */
+/*************************************/
+/// A PEM content, given inline in the configuration or in a file.
+pub enum PemSource {
+    Raw(Vec<u8>),
+    File(PathBuf),
+}
+
+impl PemSource {
+    fn read(&self) -> ZResult<Vec<u8>> {
+        match self {
+            PemSource::Raw(bytes) => Ok(bytes.clone()),
+            PemSource::File(path) => std::fs::read(path)
+                .map_err(|e| zerror!("Can not read {}: {}", path.display(), e).into()),
+        }
+    }
+
+    fn path(&self) -> Option<PathBuf> {
+        match self {
+            PemSource::Raw(_) => None,
+            PemSource::File(path) => Some(path.clone()),
+        }
+    }
+}
+
+/// Provides the server certificate to every handshake. Whenever the private key or the
+/// certificate files change on disk they are loaded again in the background, so that the
+/// new handshakes use the rotated certificate while the established links are left untouched.
+pub struct ServerCertResolver {
+    // The name of the protocol, for the logs and the errors
+    protocol: &'static str,
+    key: PemSource,
+    cert: PemSource,
+    watcher: Option<Mutex<FileWatcher>>,
+    current: RwLock<sign::CertifiedKey>,
+}
+
+impl ServerCertResolver {
+    pub fn new(
+        protocol: &'static str,
+        key: PemSource,
+        cert: PemSource,
+    ) -> ZResult<ServerCertResolver> {
+        let paths: Vec<PathBuf> = key.path().into_iter().chain(cert.path()).collect();
+        let watcher = if paths.is_empty() {
+            None
+        } else {
+            Some(Mutex::new(FileWatcher::new(paths)))
+        };
+        let current = RwLock::new(Self::load(protocol, &key, &cert)?);
+        Ok(ServerCertResolver {
+            protocol,
+            key,
+            cert,
+            watcher,
+            current,
+        })
+    }
+
+    fn load(protocol: &str, key: &PemSource, cert: &PemSource) -> ZResult<sign::CertifiedKey> {
+        let key = key.read()?;
+        // Both PKCS8 and RSA private keys are accepted
+        let mut keys = pemfile::pkcs8_private_keys(&mut Cursor::new(&key))
+            .map_err(|_| zerror!("Invalid {} server private key", protocol))?;
+        if keys.is_empty() {
+            keys = pemfile::rsa_private_keys(&mut Cursor::new(&key))
+                .map_err(|_| zerror!("Invalid {} server private key", protocol))?;
+        }
+        if keys.is_empty() {
+            bail!("Missing {} server private key", protocol);
+        }
+        let key = sign::any_supported_type(&keys.remove(0))
+            .map_err(|_| zerror!("Unsupported {} server private key", protocol))?;
+
+        let cert = cert.read()?;
+        let certs = pemfile::certs(&mut Cursor::new(&cert))
+            .map_err(|_| zerror!("Invalid {} server certificate", protocol))?;
+        if certs.is_empty() {
+            bail!("Missing {} server certificate", protocol);
+        }
+
+        Ok(sign::CertifiedKey::new(certs, Arc::new(key)))
+    }
+
+    // Loads the files again if they have changed since the last successful load
+    fn reload(&self) {
+        let mut watcher = match &self.watcher {
+            Some(watcher) => zlock!(watcher),
+            None => return,
+        };
+        if let Some(stamps) = watcher.changed() {
+            match Self::load(self.protocol, &self.key, &self.cert) {
+                Ok(certified) => {
+                    log::debug!("Reloaded {} server certificate", self.protocol);
+                    *zwrite!(self.current) = certified;
+                    watcher.commit(stamps);
+                }
+                Err(e) => log::warn!(
+                    "Can not reload {} server certificate, keeping the previous one: {}",
+                    self.protocol,
+                    e
+                ),
+            }
+        }
+    }
+
+    /// Checks the files every period in the background as long as the resolver is in use,
+    /// out of the path of the handshakes.
+    pub fn watch(resolver: &Arc<ServerCertResolver>, period: Duration) {
+        if resolver.watcher.is_none() {
+            return;
+        }
+        let resolver = Arc::downgrade(resolver);
+        task::spawn(async move {
+            loop {
+                task::sleep(period).await;
+                match resolver.upgrade() {
+                    Some(resolver) => task::spawn_blocking(move || resolver.reload()).await,
+                    None => break,
+                }
+            }
+        });
+    }
+}
+
+impl ResolvesServerCert for ServerCertResolver {
+    fn resolve(&self, _client_hello: ClientHello) -> Option<sign::CertifiedKey> {
+        Some(zread!(self.current).clone())
+    }
+}
@@ -0,0 +1,102 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ADLINK zenoh team, <zenoh@adlink-labs.tech>
+//
+
+//! Detection of the changes of the files used by the links, e.g. the certificates
+//! that are periodically rotated on disk.
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+// The modification time and the size of a file, if it can be read
+type FileStamp = Option<(SystemTime, u64)>;
+
+fn stamp(path: &Path) -> FileStamp {
+    let metadata = fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+pub struct FileWatcher {
+    files: Vec<(PathBuf, FileStamp)>,
+}
+
+/// The state of the watched files when they have been found changed.
+pub struct FileStamps(Vec<FileStamp>);
+
+impl FileWatcher {
+    pub fn new<I: IntoIterator<Item = PathBuf>>(paths: I) -> FileWatcher {
+        let files = paths
+            .into_iter()
+            .map(|p| {
+                let s = stamp(&p);
+                (p, s)
+            })
+            .collect();
+        FileWatcher { files }
+    }
+
+    /// Returns the new state of the files if any of them has been modified, replaced or
+    /// removed since the last commit.
+    pub fn changed(&self) -> Option<FileStamps> {
+        let current: Vec<FileStamp> = self.files.iter().map(|(p, _)| stamp(p)).collect();
+        if current
+            .iter()
+            .zip(self.files.iter())
+            .all(|(c, (_, p))| c == p)
+        {
+            return None;
+        }
+        Some(FileStamps(current))
+    }
+
+    /// Records the state of the files once their content has been loaded, so that a failed
+    /// load is retried at the next check.
+    pub fn commit(&mut self, stamps: FileStamps) {
+        for ((_, previous), current) in self.files.iter_mut().zip(stamps.0) {
+            *previous = current;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FileWatcher;
+
+    #[test]
+    fn file_watcher() {
+        let dir = std::env::temp_dir().join(format!("zenoh-test-watch-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("file.pem");
+        std::fs::write(&path, "first").unwrap();
+
+        let mut watcher = FileWatcher::new(vec![path.clone()]);
+        assert!(watcher.changed().is_none());
+
+        // The size changes even if the modification time has a coarse resolution
+        std::fs::write(&path, "second").unwrap();
+        let stamps = watcher.changed().unwrap();
+        // The change is reported until committed, e.g. after a failed load
+        assert!(watcher.changed().is_some());
+        watcher.commit(stamps);
+        assert!(watcher.changed().is_none());
+
+        std::fs::remove_file(&path).unwrap();
+        watcher.commit(watcher.changed().unwrap());
+        assert!(watcher.changed().is_none());
+
+        std::fs::write(&path, "third").unwrap();
+        assert!(watcher.changed().is_some());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
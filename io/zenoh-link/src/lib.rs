@@ -19,6 +19,10 @@ use zenoh_cfg_properties::Properties;
 use zenoh_config::Config;
 use zenoh_core::{bail, Result as ZResult};
 
+#[cfg(feature = "transport_mem")]
+pub use zenoh_link_mem as mem;
+#[cfg(feature = "transport_mem")]
+use zenoh_link_mem::{LinkManagerUnicastMem, MemLocatorInspector, MEM_LOCATOR_PREFIX};
 #[cfg(feature = "transport_quic")]
 pub use zenoh_link_quic as quic;
 #[cfg(feature = "transport_quic")]
@@ -41,18 +45,28 @@ pub use zenoh_link_udp as udp;
 use zenoh_link_udp::{
     LinkManagerMulticastUdp, LinkManagerUnicastUdp, UdpLocatorInspector, UDP_LOCATOR_PREFIX,
 };
+#[cfg(all(feature = "transport_unixsock-dgram", target_family = "unix"))]
+pub use zenoh_link_unixsock_dgram as unixsock_dgram;
+#[cfg(all(feature = "transport_unixsock-dgram", target_family = "unix"))]
+use zenoh_link_unixsock_dgram::{LinkManagerUnicastUnixSocketDgram, UNIXSOCKDGRAM_LOCATOR_PREFIX};
 #[cfg(all(feature = "transport_unixsock-stream", target_family = "unix"))]
 pub use zenoh_link_unixsock_stream as unixsock_stream;
 #[cfg(all(feature = "transport_unixsock-stream", target_family = "unix"))]
 use zenoh_link_unixsock_stream::{
     LinkManagerUnicastUnixSocketStream, UNIXSOCKSTREAM_LOCATOR_PREFIX,
 };
+#[cfg(feature = "transport_ws")]
+pub use zenoh_link_ws as ws;
+#[cfg(feature = "transport_ws")]
+use zenoh_link_ws::{LinkManagerUnicastWs, WsLocatorInspector, WS_LOCATOR_PREFIX};
 
 pub use zenoh_link_commons::*;
 pub use zenoh_protocol_core::{EndPoint, Locator};
 
 #[derive(Default, Clone)]
 pub struct LocatorInspector {
+    #[cfg(feature = "transport_mem")]
+    mem_inspector: MemLocatorInspector,
     #[cfg(feature = "transport_quic")]
     quic_inspector: QuicLocatorInspector,
     #[cfg(feature = "transport_tcp")]
@@ -61,6 +75,8 @@ pub struct LocatorInspector {
     tls_inspector: TlsLocatorInspector,
     #[cfg(feature = "transport_udp")]
     udp_inspector: UdpLocatorInspector,
+    #[cfg(feature = "transport_ws")]
+    ws_inspector: WsLocatorInspector,
 }
 impl LocatorInspector {
     pub async fn is_multicast(&self, locator: &Locator) -> ZResult<bool> {
@@ -68,6 +84,8 @@ impl LocatorInspector {
         use zenoh_link_commons::LocatorInspector;
         let protocol = locator.protocol();
         match protocol {
+            #[cfg(feature = "transport_mem")]
+            MEM_LOCATOR_PREFIX => self.mem_inspector.is_multicast(locator).await,
             #[cfg(feature = "transport_tcp")]
             TCP_LOCATOR_PREFIX => self.tcp_inspector.is_multicast(locator).await,
             #[cfg(feature = "transport_udp")]
@@ -78,6 +96,10 @@ impl LocatorInspector {
             QUIC_LOCATOR_PREFIX => self.quic_inspector.is_multicast(locator).await,
             #[cfg(all(feature = "transport_unixsock-stream", target_family = "unix"))]
             UNIXSOCKSTREAM_LOCATOR_PREFIX => Ok(false),
+            #[cfg(all(feature = "transport_unixsock-dgram", target_family = "unix"))]
+            UNIXSOCKDGRAM_LOCATOR_PREFIX => Ok(false),
+            #[cfg(feature = "transport_ws")]
+            WS_LOCATOR_PREFIX => self.ws_inspector.is_multicast(locator).await,
             _ => bail!("Unsupported protocol: {}.", protocol),
         }
     }
@@ -135,6 +157,8 @@ pub struct LinkManagerBuilderUnicast;
 impl LinkManagerBuilderUnicast {
     pub fn make(_manager: NewLinkChannelSender, protocol: &str) -> ZResult<LinkManagerUnicast> {
         match protocol {
+            #[cfg(feature = "transport_mem")]
+            MEM_LOCATOR_PREFIX => Ok(Arc::new(LinkManagerUnicastMem::new(_manager))),
             #[cfg(feature = "transport_tcp")]
             TCP_LOCATOR_PREFIX => Ok(Arc::new(LinkManagerUnicastTcp::new(_manager))),
             #[cfg(feature = "transport_udp")]
@@ -147,6 +171,12 @@ impl LinkManagerBuilderUnicast {
             UNIXSOCKSTREAM_LOCATOR_PREFIX => {
                 Ok(Arc::new(LinkManagerUnicastUnixSocketStream::new(_manager)))
             }
+            #[cfg(all(feature = "transport_unixsock-dgram", target_family = "unix"))]
+            UNIXSOCKDGRAM_LOCATOR_PREFIX => {
+                Ok(Arc::new(LinkManagerUnicastUnixSocketDgram::new(_manager)))
+            }
+            #[cfg(feature = "transport_ws")]
+            WS_LOCATOR_PREFIX => Ok(Arc::new(LinkManagerUnicastWs::new(_manager))),
             _ => bail!("Unicast not supported for {} protocol", protocol),
         }
     }
@@ -0,0 +1,54 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ADLINK zenoh team, <zenoh@adlink-labs.tech>
+//
+
+//! In-process links between the sessions of a same process.
+//!
+//! A listener on `mem/<name>` is registered in a process-global registry, and connecting to it
+//! creates a pair of channels: the batches are passed from one session to the other without
+//! going through any socket, hence without the risk of port collisions.
+use async_trait::async_trait;
+use zenoh_link_commons::LocatorInspector;
+
+use zenoh_core::{zconfigurable, Result as ZResult};
+use zenoh_protocol_core::Locator;
+
+mod unicast;
+pub use unicast::*;
+
+// Maximum MTU (Mem PDU) in bytes.
+// NOTE: Each batch is passed as a single message on the channel. Given the usage of 16 bits
+//       in Zenoh to encode the size of a batch, the Mem MTU is constrained to 2^16 - 1 bytes
+//       (i.e., 65535).
+const MEM_MAX_MTU: u16 = u16::MAX;
+
+pub const MEM_LOCATOR_PREFIX: &str = "mem";
+
+#[derive(Default, Clone, Copy)]
+pub struct MemLocatorInspector;
+#[async_trait]
+impl LocatorInspector for MemLocatorInspector {
+    fn protocol(&self) -> &str {
+        MEM_LOCATOR_PREFIX
+    }
+    async fn is_multicast(&self, _locator: &Locator) -> ZResult<bool> {
+        Ok(false)
+    }
+}
+
+zconfigurable! {
+    // Default MTU (Mem PDU) in bytes.
+    static ref MEM_DEFAULT_MTU: u16 = MEM_MAX_MTU;
+    // The number of batches that can be queued on a link before the writer is blocked.
+    static ref MEM_QUEUE_SIZE: usize = 16;
+}
@@ -0,0 +1,306 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ADLINK zenoh team, <zenoh@adlink-labs.tech>
+//
+use async_std::sync::Mutex as AsyncMutex;
+use async_trait::async_trait;
+use flume::{Receiver, Sender};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use zenoh_core::Result as ZResult;
+use zenoh_core::{bail, lazy_static, zasynclock, zerror, zlock, zread, zwrite};
+use zenoh_link_commons::{
+    LinkManagerUnicastTrait, LinkUnicast, LinkUnicastTrait, NewLinkChannelSender,
+};
+use zenoh_protocol_core::{EndPoint, Locator};
+
+use super::{MEM_DEFAULT_MTU, MEM_LOCATOR_PREFIX, MEM_QUEUE_SIZE};
+
+lazy_static! {
+    // The listeners of the process, by name, with the identifier of their manager
+    static ref MEM_LISTENERS: Mutex<HashMap<String, (usize, NewLinkChannelSender)>> =
+        Mutex::new(HashMap::new());
+}
+
+// The identifier of the next link manager
+static MEM_MANAGER_ID: AtomicUsize = AtomicUsize::new(0);
+
+// The identifier of the next connection, to distinguish the links to a same listener
+static MEM_CONNECTION_ID: AtomicUsize = AtomicUsize::new(0);
+
+pub struct LinkUnicastMem {
+    src_locator: Locator,
+    dst_locator: Locator,
+    // The channel to the remote end, dropped when closing the link
+    tx: Mutex<Option<Sender<Vec<u8>>>>,
+    // The channel from the remote end
+    rx: Receiver<Vec<u8>>,
+    // The batch being read and the amount of bytes already read from it
+    leftover: AsyncMutex<Option<(Vec<u8>, usize)>>,
+}
+
+impl LinkUnicastMem {
+    fn pair(listener: Locator, connector: Locator) -> (LinkUnicastMem, LinkUnicastMem) {
+        let (l_tx, c_rx) = flume::bounded(*MEM_QUEUE_SIZE);
+        let (c_tx, l_rx) = flume::bounded(*MEM_QUEUE_SIZE);
+        let l_link = LinkUnicastMem {
+            src_locator: listener.clone(),
+            dst_locator: connector.clone(),
+            tx: Mutex::new(Some(l_tx)),
+            rx: l_rx,
+            leftover: AsyncMutex::new(None),
+        };
+        let c_link = LinkUnicastMem {
+            src_locator: connector,
+            dst_locator: listener,
+            tx: Mutex::new(Some(c_tx)),
+            rx: c_rx,
+            leftover: AsyncMutex::new(None),
+        };
+        (l_link, c_link)
+    }
+}
+
+#[async_trait]
+impl LinkUnicastTrait for LinkUnicastMem {
+    async fn close(&self) -> ZResult<()> {
+        log::trace!("Closing Mem link: {}", self);
+        // Dropping the channel notifies the remote end
+        zlock!(self.tx).take();
+        Ok(())
+    }
+
+    async fn write(&self, buffer: &[u8]) -> ZResult<usize> {
+        let tx = zlock!(self.tx).clone();
+        match tx {
+            Some(tx) => tx.send_async(buffer.to_vec()).await.map_err(|_| {
+                let e = zerror!("Write error on Mem link {}: link closed", self);
+                log::trace!("{}", e);
+                e
+            })?,
+            None => bail!("Write error on Mem link {}: link closed", self),
+        }
+        Ok(buffer.len())
+    }
+
+    async fn write_all(&self, buffer: &[u8]) -> ZResult<()> {
+        self.write(buffer).await.map(|_| ())
+    }
+
+    async fn read(&self, buffer: &mut [u8]) -> ZResult<usize> {
+        let mut guard = zasynclock!(self.leftover);
+        let (batch, start) = match guard.take() {
+            Some(leftover) => leftover,
+            None => match self.rx.recv_async().await {
+                Ok(batch) => (batch, 0),
+                // The link has been closed by the remote end
+                Err(_) => return Ok(0),
+            },
+        };
+        // Copy the read bytes into the target buffer
+        let len_min = (batch.len() - start).min(buffer.len());
+        let end = start + len_min;
+        buffer[0..len_min].copy_from_slice(&batch[start..end]);
+        if end < batch.len() {
+            // Store the leftover
+            *guard = Some((batch, end));
+        }
+        // Return the amount read
+        Ok(len_min)
+    }
+
+    async fn read_exact(&self, buffer: &mut [u8]) -> ZResult<()> {
+        let mut read: usize = 0;
+        while read < buffer.len() {
+            let n = self.read(&mut buffer[read..]).await?;
+            if n == 0 {
+                bail!("Mem link {} has been closed", self);
+            }
+            read += n;
+        }
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn get_src(&self) -> &Locator {
+        &self.src_locator
+    }
+
+    #[inline(always)]
+    fn get_dst(&self) -> &Locator {
+        &self.dst_locator
+    }
+
+    #[inline(always)]
+    fn get_mtu(&self) -> u16 {
+        *MEM_DEFAULT_MTU
+    }
+
+    #[inline(always)]
+    fn is_reliable(&self) -> bool {
+        true
+    }
+
+    #[inline(always)]
+    fn is_streamed(&self) -> bool {
+        false
+    }
+}
+
+impl fmt::Display for LinkUnicastMem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} => {}", self.src_locator, self.dst_locator)?;
+        Ok(())
+    }
+}
+
+impl fmt::Debug for LinkUnicastMem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Mem")
+            .field("src", &self.src_locator)
+            .field("dst", &self.dst_locator)
+            .finish()
+    }
+}
+
+/*************************************/
+/*          LISTENER                 */
+/*************************************/
+pub struct LinkManagerUnicastMem {
+    id: usize,
+    manager: NewLinkChannelSender,
+    listeners: Arc<RwLock<HashMap<String, EndPoint>>>,
+}
+
+impl LinkManagerUnicastMem {
+    pub fn new(manager: NewLinkChannelSender) -> Self {
+        Self {
+            id: MEM_MANAGER_ID.fetch_add(1, Ordering::Relaxed),
+            manager,
+            listeners: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+}
+
+impl LinkManagerUnicastMem {
+    // Removes a listener from the registry, unless it has been replaced by another manager
+    fn unregister(
+        &self,
+        registry: &mut HashMap<String, (usize, NewLinkChannelSender)>,
+        name: &str,
+    ) {
+        if registry.get(name).is_some_and(|(id, _)| *id == self.id) {
+            registry.remove(name);
+        }
+    }
+}
+
+#[async_trait]
+impl LinkManagerUnicastTrait for LinkManagerUnicastMem {
+    async fn new_link(&self, endpoint: EndPoint) -> ZResult<LinkUnicast> {
+        let name = endpoint.locator.address();
+        let (_, listener) = zlock!(MEM_LISTENERS).get(name).cloned().ok_or_else(|| {
+            zerror!(
+                "Can not create a new Mem link bound to {}: no such listener",
+                name
+            )
+        })?;
+
+        let id = MEM_CONNECTION_ID.fetch_add(1, Ordering::Relaxed);
+        let (remote, local) = LinkUnicastMem::pair(
+            Locator::new(MEM_LOCATOR_PREFIX, &name),
+            Locator::new(MEM_LOCATOR_PREFIX, &format!("{}/{}", name, id)),
+        );
+
+        // Communicate the new link to the transport manager of the listener
+        listener
+            .send_async(LinkUnicast(Arc::new(remote)))
+            .await
+            .map_err(|_| {
+                zerror!(
+                    "Can not create a new Mem link bound to {}: listener closed",
+                    name
+                )
+            })?;
+
+        Ok(LinkUnicast(Arc::new(local)))
+    }
+
+    async fn new_listener(&self, endpoint: EndPoint) -> ZResult<Locator> {
+        let name = endpoint.locator.address();
+        // The '/' is reserved to the locators of the connecting links
+        if name.is_empty() || name.contains('/') {
+            bail!(
+                "Can not create a new Mem listener on {}: invalid name",
+                name
+            );
+        }
+
+        let mut registry = zlock!(MEM_LISTENERS);
+        // A listener whose transport manager is gone can be replaced
+        if let Some((_, listener)) = registry.get(name) {
+            if !listener.is_disconnected() {
+                bail!(
+                    "Can not create a new Mem listener on {}: name already in use",
+                    name
+                );
+            }
+        }
+        registry.insert(name.to_owned(), (self.id, self.manager.clone()));
+        drop(registry);
+
+        let locator = endpoint.locator.clone();
+        zwrite!(self.listeners).insert(name.to_owned(), endpoint);
+
+        Ok(locator)
+    }
+
+    async fn del_listener(&self, endpoint: &EndPoint) -> ZResult<()> {
+        let name = endpoint.locator.address();
+
+        // Stop the listener
+        zwrite!(self.listeners).remove(name).ok_or_else(|| {
+            let e = zerror!(
+                "Can not delete the Mem listener because it has not been found: {}",
+                name
+            );
+            log::trace!("{}", e);
+            e
+        })?;
+        self.unregister(&mut *zlock!(MEM_LISTENERS), name);
+
+        Ok(())
+    }
+
+    fn get_listeners(&self) -> Vec<EndPoint> {
+        zread!(self.listeners).values().cloned().collect()
+    }
+
+    fn get_locators(&self) -> Vec<Locator> {
+        zread!(self.listeners)
+            .values()
+            .map(|e| e.locator.clone())
+            .collect()
+    }
+}
+
+impl Drop for LinkManagerUnicastMem {
+    fn drop(&mut self) {
+        // Release the names of the listeners of this manager
+        let mut registry = zlock!(MEM_LISTENERS);
+        for name in zread!(self.listeners).keys() {
+            self.unregister(&mut registry, name);
+        }
+    }
+}
@@ -14,7 +14,8 @@
 use async_std::net::ToSocketAddrs;
 use async_trait::async_trait;
 use config::{
-    TLS_ROOT_CA_CERTIFICATE_FILE, TLS_SERVER_CERTIFICATE_FILE, TLS_SERVER_PRIVATE_KEY_FILE,
+    TLS_ALPN, TLS_ALPN_SEPARATOR, TLS_ROOT_CA_CERTIFICATE_FILE, TLS_SERVER_CERTIFICATE_FILE,
+    TLS_SERVER_NAME, TLS_SERVER_PRIVATE_KEY_FILE,
 };
 use std::net::SocketAddr;
 use webpki::{DnsName, DnsNameRef};
@@ -75,6 +76,15 @@ impl ConfigurationInspector<Config> for QuicConfigurator {
                 tls_server_certificate.into(),
             );
         }
+        if let Some(tls_server_name) = c.server_name() {
+            properties.insert(TLS_SERVER_NAME.into(), tls_server_name.into());
+        }
+        if let Some(tls_alpn) = c.alpn() {
+            properties.insert(
+                TLS_ALPN.into(),
+                tls_alpn.join(&TLS_ALPN_SEPARATOR.to_string()),
+            );
+        }
 
         Ok(properties)
     }
@@ -91,6 +101,8 @@ zconfigurable! {
     // Amount of time in microseconds to throttle the accept loop upon an error.
     // Default set to 100 ms.
     static ref QUIC_ACCEPT_THROTTLE_TIME: u64 = 100_000;
+    // Period in milliseconds of the checks of the changes of the server certificate files.
+    static ref QUIC_CERTIFICATE_WATCH_PERIOD: u64 = 1_000;
 }
 
 pub mod config {
@@ -104,6 +116,11 @@ pub mod config {
 
     pub const TLS_SERVER_CERTIFICATE_FILE: &str = ZN_TLS_SERVER_CERTIFICATE_STR;
     pub const TLS_SERVER_CERTIFICATE_RAW: &str = "tls_server_certificate_raw";
+
+    pub const TLS_SERVER_NAME: &str = ZN_TLS_SERVER_NAME_STR;
+
+    pub const TLS_ALPN: &str = ZN_TLS_ALPN_STR;
+    pub const TLS_ALPN_SEPARATOR: char = zenoh_link_commons::tls::ALPN_SEPARATOR;
 }
 
 async fn get_quic_addr(address: &Locator) -> ZResult<SocketAddr> {
@@ -116,6 +133,11 @@ async fn get_quic_addr(address: &Locator) -> ZResult<SocketAddr> {
 
 async fn get_quic_dns(address: &Locator) -> ZResult<DnsName> {
     let addr = address.address().split(':').next().unwrap();
-    let domain = DnsNameRef::try_from_ascii(addr.as_bytes()).map_err(|e| zerror!(e))?;
+    get_quic_server_name(addr)
+}
+
+fn get_quic_server_name(name: &str) -> ZResult<DnsName> {
+    let domain = DnsNameRef::try_from_ascii(name.as_bytes())
+        .map_err(|e| zerror!("Invalid QUIC server name {}: {}", name, e))?;
     Ok(domain.to_owned())
 }
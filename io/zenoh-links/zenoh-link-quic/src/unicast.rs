@@ -13,8 +13,9 @@
 //
 
 use crate::{
-    config::*, get_quic_addr, get_quic_dns, ALPN_QUIC_HTTP, QUIC_ACCEPT_THROTTLE_TIME,
-    QUIC_DEFAULT_MTU, QUIC_LOCATOR_PREFIX,
+    config::*, get_quic_addr, get_quic_dns, get_quic_server_name, ALPN_QUIC_HTTP,
+    QUIC_ACCEPT_THROTTLE_TIME, QUIC_CERTIFICATE_WATCH_PERIOD, QUIC_DEFAULT_MTU,
+    QUIC_LOCATOR_PREFIX,
 };
 use async_std::fs;
 use async_std::net::{Ipv4Addr, Ipv6Addr, SocketAddr};
@@ -32,6 +33,7 @@ use std::sync::{Arc, RwLock};
 use std::time::Duration;
 use zenoh_core::{bail, Result as ZResult};
 use zenoh_core::{zasynclock, zerror, zread, zwrite};
+use zenoh_link_commons::tls::{get_alpn, PemSource, ServerCertResolver};
 use zenoh_link_commons::{
     LinkManagerUnicastTrait, LinkUnicast, LinkUnicastTrait, NewLinkChannelSender,
 };
@@ -223,10 +225,20 @@ impl LinkManagerUnicastQuic {
 impl LinkManagerUnicastTrait for LinkManagerUnicastQuic {
     async fn new_link(&self, endpoint: EndPoint) -> ZResult<LinkUnicast> {
         let dst_locator = &endpoint.locator;
-        let domain = get_quic_dns(dst_locator).await?;
+        let config = &endpoint.config;
+        // The server name to verify may differ from the host of the locator,
+        // e.g. when connecting to an IP address
+        let domain = match config.as_ref().and_then(|c| c.get(TLS_SERVER_NAME)) {
+            Some(name) => get_quic_server_name(name)?,
+            None => get_quic_dns(dst_locator).await?,
+        };
         let addr = get_quic_addr(dst_locator).await?;
         let host: &str = domain.as_ref().into();
-        let config = &endpoint.config;
+        let alpn = config
+            .as_ref()
+            .and_then(|c| c.get(TLS_ALPN))
+            .map(|v| get_alpn(v))
+            .unwrap_or_default();
 
         // Initialize the QUIC connection
         let tls_root_ca_certificate = if let Some(config) = config {
@@ -250,14 +262,17 @@ impl LinkManagerUnicastTrait for LinkManagerUnicastQuic {
                 .map_err(|e| zerror!("Invalid QUIC CA certificate file: {}", e))?;
 
             let mut cc = ClientConfigBuilder::default();
-            cc.protocols(ALPN_QUIC_HTTP);
             cc.add_certificate_authority(ca)
                 .map_err(|e| zerror!("Invalid QUIC CA certificate file: {}", e))?;
 
             cc
         };
 
-        config.protocols(ALPN_QUIC_HTTP);
+        if alpn.is_empty() {
+            config.protocols(ALPN_QUIC_HTTP);
+        } else {
+            config.protocols(&alpn.iter().map(|p| p.as_slice()).collect::<Vec<&[u8]>>());
+        }
 
         let mut endpoint = Endpoint::builder();
         endpoint.default_client_config(config.build());
@@ -300,38 +315,50 @@ impl LinkManagerUnicastTrait for LinkManagerUnicastQuic {
         let locator = &endpoint.locator;
         let addr = get_quic_addr(locator).await?;
 
-        let mut tls_server_private_key = Vec::new();
-        let mut tls_server_certificate = Vec::new();
+        let mut tls_server_private_key = None;
+        let mut tls_server_certificate = None;
+        let mut alpn = Vec::new();
         if let Some(config) = &endpoint.config {
             let config = &***config;
             if let Some(value) = config.get(TLS_SERVER_PRIVATE_KEY_RAW) {
-                tls_server_private_key = value.as_bytes().to_vec()
+                tls_server_private_key = Some(PemSource::Raw(value.as_bytes().to_vec()))
             } else if let Some(value) = config.get(TLS_SERVER_PRIVATE_KEY_FILE) {
-                tls_server_private_key = fs::read(value)
-                    .await
-                    .map_err(|e| zerror!("Invalid QUIC server private key file: {}", e))?
+                tls_server_private_key = Some(PemSource::File(value.into()))
             }
             if let Some(value) = config.get(TLS_SERVER_CERTIFICATE_RAW) {
-                tls_server_certificate = value.as_bytes().to_vec();
+                tls_server_certificate = Some(PemSource::Raw(value.as_bytes().to_vec()))
             } else if let Some(value) = config.get(TLS_SERVER_CERTIFICATE_FILE) {
-                fs::read(value)
-                    .await
-                    .map_err(|e| zerror!("Invalid QUIC server certificate file: {}", e))?;
+                tls_server_certificate = Some(PemSource::File(value.into()))
+            }
+            if let Some(value) = config.get(TLS_ALPN) {
+                alpn = get_alpn(value);
             }
         }
 
         // Configure the server private key
-        if tls_server_private_key.is_empty() {
-            bail!(
+        let tls_server_private_key = match tls_server_private_key {
+            Some(key) => key,
+            None => bail!(
                 "Can not create a new QUIC listener on {}: missing server private key",
                 addr
-            )
-        }
-        let keys = PrivateKey::from_pem(&tls_server_private_key).unwrap();
+            ),
+        };
 
         // Configure the server certificate
-        let certs = CertificateChain::from_pem(&tls_server_certificate)
-            .map_err(|e| zerror!("Invalid TLS server certificate: {}", e))?;
+        let tls_server_certificate = match tls_server_certificate {
+            Some(cert) => cert,
+            None => bail!(
+                "Can not create a new QUIC listener on {}: missing server certificate",
+                addr
+            ),
+        };
+
+        let resolver =
+            ServerCertResolver::new("QUIC", tls_server_private_key, tls_server_certificate)
+                .map_err(|e| zerror!("Can not create a new QUIC listener on {}: {}", addr, e))?;
+        if alpn.is_empty() {
+            alpn = ALPN_QUIC_HTTP.iter().map(|p| p.to_vec()).collect();
+        }
 
         let mut tc = TransportConfig::default();
         // We do not accept unidireactional streams.
@@ -342,14 +369,18 @@ impl LinkManagerUnicastTrait for LinkManagerUnicastQuic {
             .map_err(|e| zerror!("Invalid QUIC server configuration: {}", e))?;
         let mut sc = ServerConfig::default();
         sc.transport = Arc::new(tc);
-        let mut sc = ServerConfigBuilder::new(sc);
-        sc.protocols(ALPN_QUIC_HTTP);
-        sc.certificate(certs, keys)
-            .map_err(|e| zerror!("Invalid TLS server configuration: {}", e))?;
+        let crypto = Arc::make_mut(&mut sc.crypto);
+        let resolver = Arc::new(resolver);
+        ServerCertResolver::watch(
+            &resolver,
+            Duration::from_millis(*QUIC_CERTIFICATE_WATCH_PERIOD),
+        );
+        crypto.cert_resolver = resolver;
+        crypto.alpn_protocols = alpn;
 
         // Initialize the Endpoint
         let mut quic_endpoint = QuicEndPoint::builder();
-        quic_endpoint.listen(sc.build());
+        quic_endpoint.listen(sc);
         let (quic_endpoint, acceptor) = quic_endpoint
             .bind(&addr)
             .map_err(|e| zerror!("Can not create a new QUIC listener on {}: {}", addr, e))?;
@@ -16,8 +16,9 @@ use std::net::SocketAddr;
 use async_std::net::ToSocketAddrs;
 use async_trait::async_trait;
 use config::{
-    TLS_CLIENT_AUTH, TLS_CLIENT_CERTIFICATE_FILE, TLS_CLIENT_PRIVATE_KEY_FILE,
-    TLS_ROOT_CA_CERTIFICATE_FILE, TLS_SERVER_CERTIFICATE_FILE, TLS_SERVER_PRIVATE_KEY_FILE,
+    TLS_ALPN, TLS_ALPN_SEPARATOR, TLS_CLIENT_AUTH, TLS_CLIENT_CERTIFICATE_FILE,
+    TLS_CLIENT_PRIVATE_KEY_FILE, TLS_ROOT_CA_CERTIFICATE_FILE, TLS_SERVER_CERTIFICATE_FILE,
+    TLS_SERVER_NAME, TLS_SERVER_PRIVATE_KEY_FILE,
 };
 use zenoh_cfg_properties::Properties;
 use zenoh_config::{Config, ZN_FALSE, ZN_TRUE};
@@ -92,6 +93,15 @@ impl ConfigurationInspector<Config> for TlsConfigurator {
                 tls_client_certificate.into(),
             );
         }
+        if let Some(tls_server_name) = c.server_name() {
+            properties.insert(TLS_SERVER_NAME.into(), tls_server_name.into());
+        }
+        if let Some(tls_alpn) = c.alpn() {
+            properties.insert(
+                TLS_ALPN.into(),
+                tls_alpn.join(&TLS_ALPN_SEPARATOR.to_string()),
+            );
+        }
 
         Ok(properties)
     }
@@ -108,6 +118,8 @@ zconfigurable! {
     // Amount of time in microseconds to throttle the accept loop upon an error.
     // Default set to 100 ms.
     static ref TLS_ACCEPT_THROTTLE_TIME: u64 = 100_000;
+    // Period in milliseconds of the checks of the changes of the server certificate files.
+    static ref TLS_CERTIFICATE_WATCH_PERIOD: u64 = 1_000;
 }
 
 pub mod config {
@@ -129,6 +141,11 @@ pub mod config {
 
     pub const TLS_CLIENT_AUTH: &str = ZN_TLS_CLIENT_AUTH_STR;
     pub const TLS_CLIENT_AUTH_DEFAULT: &str = ZN_TLS_CLIENT_AUTH_DEFAULT;
+
+    pub const TLS_SERVER_NAME: &str = ZN_TLS_SERVER_NAME_STR;
+
+    pub const TLS_ALPN: &str = ZN_TLS_ALPN_STR;
+    pub const TLS_ALPN_SEPARATOR: char = zenoh_link_commons::tls::ALPN_SEPARATOR;
 }
 
 pub async fn get_tls_addr(address: &Locator) -> ZResult<SocketAddr> {
@@ -144,8 +161,12 @@ pub fn get_tls_host(address: &Locator) -> ZResult<&str> {
 }
 
 pub async fn get_tls_dns(address: &Locator) -> ZResult<DNSName> {
-    match DNSNameRef::try_from_ascii_str(get_tls_host(address)?) {
+    get_tls_server_name(get_tls_host(address)?)
+}
+
+pub fn get_tls_server_name(name: &str) -> ZResult<DNSName> {
+    match DNSNameRef::try_from_ascii_str(name) {
         Ok(v) => Ok(v.to_owned()),
-        Err(e) => bail!(e),
+        Err(e) => bail!("Invalid TLS server name {}: {}", name, e),
     }
 }
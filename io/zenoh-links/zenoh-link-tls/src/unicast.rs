@@ -13,10 +13,9 @@ use crate::TLS_LOCATOR_PREFIX;
 //   ADLINK zenoh team, <zenoh@adlink-labs.tech>
 //
 use crate::{
-    config::*, get_tls_addr, get_tls_dns, get_tls_host, TLS_ACCEPT_THROTTLE_TIME, TLS_DEFAULT_MTU,
-    TLS_LINGER_TIMEOUT,
+    config::*, get_tls_addr, get_tls_dns, get_tls_host, get_tls_server_name,
+    TLS_ACCEPT_THROTTLE_TIME, TLS_CERTIFICATE_WATCH_PERIOD, TLS_DEFAULT_MTU, TLS_LINGER_TIMEOUT,
 };
-use async_rustls::rustls::internal::pemfile;
 pub use async_rustls::rustls::*;
 pub use async_rustls::webpki::*;
 use async_rustls::{TlsAcceptor, TlsConnector, TlsStream};
@@ -39,6 +38,7 @@ use std::time::Duration;
 use zenoh_core::bail;
 use zenoh_core::Result as ZResult;
 use zenoh_core::{zasynclock, zerror, zread, zwrite};
+use zenoh_link_commons::tls::{get_alpn, PemSource, ServerCertResolver};
 use zenoh_link_commons::{
     LinkManagerUnicastTrait, LinkUnicast, LinkUnicastTrait, NewLinkChannelSender,
 };
@@ -264,7 +264,16 @@ impl LinkManagerUnicastTls {
 impl LinkManagerUnicastTrait for LinkManagerUnicastTls {
     async fn new_link(&self, endpoint: EndPoint) -> ZResult<LinkUnicast> {
         let locator = &endpoint.locator;
-        let domain = get_tls_dns(locator).await?;
+        // The server name to verify may differ from the host of the locator,
+        // e.g. when connecting to an IP address
+        let server_name = endpoint
+            .config
+            .as_ref()
+            .and_then(|c| c.get(TLS_SERVER_NAME).cloned());
+        let domain = match server_name {
+            Some(name) => get_tls_server_name(&name)?,
+            None => get_tls_dns(locator).await?,
+        };
         let addr = get_tls_addr(locator).await?;
         let host: &str = domain.as_ref().into();
 
@@ -283,6 +292,7 @@ impl LinkManagerUnicastTrait for LinkManagerUnicastTls {
 
         // Initialize the TLS stream
         let mut bytes = Vec::new();
+        let mut alpn = Vec::new();
         if let Some(config) = endpoint.config {
             if let Some(value) = config.get(TLS_ROOT_CA_CERTIFICATE_RAW) {
                 bytes = value.as_bytes().to_vec()
@@ -291,18 +301,20 @@ impl LinkManagerUnicastTrait for LinkManagerUnicastTls {
                     .await
                     .map_err(|e| zerror!("Invalid TLS CA certificate file: {}", e))?
             }
+            if let Some(value) = config.get(TLS_ALPN) {
+                alpn = get_alpn(value);
+            }
         }
 
-        let config = if bytes.is_empty() {
-            Arc::new(ClientConfig::new())
-        } else {
-            let mut cc = ClientConfig::new();
+        let mut cc = ClientConfig::new();
+        if !bytes.is_empty() {
             let _ = cc
                 .root_store
                 .add_pem_file(&mut Cursor::new(&bytes))
                 .map_err(|_| zerror!("Invalid TLS CA certificate file"))?;
-            Arc::new(cc)
-        };
+        }
+        cc.alpn_protocols = alpn;
+        let config = Arc::new(cc);
 
         let connector = TlsConnector::from(config);
         let tls_stream = connector
@@ -322,48 +334,51 @@ impl LinkManagerUnicastTrait for LinkManagerUnicastTls {
         let host = get_tls_host(locator)?;
 
         let mut client_auth: bool = TLS_CLIENT_AUTH_DEFAULT.parse().unwrap();
-        let mut tls_server_private_key = Vec::new();
-        let mut tls_server_certificate = Vec::new();
+        let mut tls_server_private_key = None;
+        let mut tls_server_certificate = None;
+        let mut alpn = Vec::new();
 
         if let Some(config) = &endpoint.config {
             let config = &***config;
             if let Some(value) = config.get(TLS_SERVER_PRIVATE_KEY_RAW) {
-                tls_server_private_key = value.as_bytes().to_vec()
+                tls_server_private_key = Some(PemSource::Raw(value.as_bytes().to_vec()))
             } else if let Some(value) = config.get(TLS_SERVER_PRIVATE_KEY_FILE) {
-                tls_server_private_key = fs::read(value)
-                    .await
-                    .map_err(|e| zerror!("Invalid TLS private key file: {}", e))?
+                tls_server_private_key = Some(PemSource::File(value.into()))
             }
             if let Some(value) = config.get(TLS_SERVER_CERTIFICATE_RAW) {
-                tls_server_certificate = value.as_bytes().to_vec()
+                tls_server_certificate = Some(PemSource::Raw(value.as_bytes().to_vec()))
             } else if let Some(value) = config.get(TLS_SERVER_CERTIFICATE_FILE) {
-                tls_server_certificate = fs::read(value)
-                    .await
-                    .map_err(|e| zerror!("Invalid TLS serer certificate file: {}", e))?
+                tls_server_certificate = Some(PemSource::File(value.into()))
             }
             if let Some(value) = config.get(TLS_CLIENT_AUTH) {
                 client_auth = value.parse()?
             }
+            if let Some(value) = config.get(TLS_ALPN) {
+                alpn = get_alpn(value);
+            }
         }
 
         // Configure the server private key
-        if tls_server_private_key.is_empty() {
-            bail!(
+        let tls_server_private_key = match tls_server_private_key {
+            Some(key) => key,
+            None => bail!(
                 "Can not create a new TLS listener on {}. Missing server private key.",
                 addr,
-            );
-        }
-        let mut keys =
-            pemfile::rsa_private_keys(&mut Cursor::new(&tls_server_private_key)).unwrap();
+            ),
+        };
 
         // Configure the server certificate
-        if tls_server_certificate.is_empty() {
-            bail!(
+        let tls_server_certificate = match tls_server_certificate {
+            Some(cert) => cert,
+            None => bail!(
                 "Can not create a new TLS listener on {}. Missing server certificate.",
                 addr,
-            );
-        }
-        let certs = pemfile::certs(&mut Cursor::new(&tls_server_certificate)).unwrap();
+            ),
+        };
+
+        let resolver =
+            ServerCertResolver::new("TLS", tls_server_private_key, tls_server_certificate)
+                .map_err(|e| zerror!("Can not create a new TLS listener on {}: {}", addr, e))?;
 
         let mut sc = if client_auth {
             // @TODO: implement Client authentication
@@ -375,7 +390,13 @@ impl LinkManagerUnicastTrait for LinkManagerUnicastTls {
             ServerConfig::new(NoClientAuth::new())
         };
 
-        sc.set_single_cert(certs, keys.remove(0)).unwrap();
+        let resolver = Arc::new(resolver);
+        ServerCertResolver::watch(
+            &resolver,
+            Duration::from_millis(*TLS_CERTIFICATE_WATCH_PERIOD),
+        );
+        sc.cert_resolver = resolver;
+        sc.alpn_protocols = alpn;
 
         // Initialize the TcpListener
         let socket = TcpListener::bind(addr)
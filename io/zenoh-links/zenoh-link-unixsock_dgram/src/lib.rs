@@ -0,0 +1,108 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ADLINK zenoh team, <zenoh@adlink-labs.tech>
+//
+#[cfg(target_family = "unix")]
+use std::os::unix::net::SocketAddr;
+
+use zenoh_core::zconfigurable;
+#[cfg(target_family = "unix")]
+use zenoh_core::{zerror, Result as ZResult};
+#[cfg(target_family = "unix")]
+mod unicast;
+#[cfg(target_family = "unix")]
+pub use unicast::*;
+use zenoh_protocol_core::Locator;
+
+// Maximum MTU (UnixSocketDgram PDU) in bytes.
+// NOTE: Since UnixSocketDgram is a message oriented transport, each datagram carries exactly one
+//       batch. Given the usage of 16 bits in Zenoh to encode the size of a batch, the
+//       UNIXSOCKDGRAM MTU is constrained to 2^16 - 1 bytes (i.e., 65535).
+const UNIXSOCKDGRAM_MAX_MTU: u16 = u16::MAX;
+
+#[cfg(target_os = "linux")]
+// Linux limits the size of a datagram to the socket send buffer size (net.core.wmem_default),
+// which is larger than the UNIXSOCKDGRAM MAX MTU by default.
+const UNIXSOCKDGRAM_MTU_LIMIT: u16 = UNIXSOCKDGRAM_MAX_MTU;
+
+#[cfg(target_os = "macos")]
+// Mac OS X default value of a maximum datagram size is set to 2048 bytes (net.local.dgram.maxdgram).
+const UNIXSOCKDGRAM_MTU_LIMIT: u16 = 2_048;
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+const UNIXSOCKDGRAM_MTU_LIMIT: u16 = 8_192;
+
+pub const UNIXSOCKDGRAM_LOCATOR_PREFIX: &str = "unixsock-dgram";
+
+// The addresses starting with '@' belong to the Linux abstract namespace (e.g. unixsock-dgram/@zenoh).
+// They are not bound to a socket file: the kernel releases them as soon as the socket is closed.
+pub const UNIX_ABSTRACT_PREFIX: char = '@';
+
+zconfigurable! {
+    // Default MTU (UNIXSOCKDGRAM PDU) in bytes.
+    static ref UNIXSOCKDGRAM_DEFAULT_MTU: u16 = UNIXSOCKDGRAM_MTU_LIMIT;
+    // Amount of time in microseconds to throttle the accept loop upon an error.
+    // Default set to 100 ms.
+    static ref UNIXSOCKDGRAM_ACCEPT_THROTTLE_TIME: u64 = 100_000;
+}
+
+pub fn get_unix_path_as_string(locator: &Locator) -> String {
+    locator.address().to_owned()
+}
+
+pub fn is_abstract(address: &str) -> bool {
+    address.starts_with(UNIX_ABSTRACT_PREFIX)
+}
+
+#[cfg(target_family = "unix")]
+pub fn get_unix_addr(address: &str) -> ZResult<SocketAddr> {
+    match address.strip_prefix(UNIX_ABSTRACT_PREFIX) {
+        Some(name) => get_abstract_addr(name),
+        None => SocketAddr::from_pathname(address)
+            .map_err(|e| zerror!("Invalid Unix socket path {}: {}", address, e).into()),
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn get_abstract_addr(name: &str) -> ZResult<SocketAddr> {
+    use std::os::linux::net::SocketAddrExt;
+    SocketAddr::from_abstract_name(name)
+        .map_err(|e| zerror!("Invalid abstract Unix socket name {}: {}", name, e).into())
+}
+
+#[cfg(all(target_family = "unix", not(target_os = "linux")))]
+fn get_abstract_addr(name: &str) -> ZResult<SocketAddr> {
+    zenoh_core::bail!(
+        "Abstract Unix socket addresses are only supported on Linux: {}{}",
+        UNIX_ABSTRACT_PREFIX,
+        name
+    )
+}
+
+/// Returns the address of a Unix socket in the format of the locators,
+/// or `None` if the socket is not bound.
+#[cfg(target_family = "unix")]
+pub fn unix_addr_to_string(addr: &SocketAddr) -> Option<String> {
+    if let Some(path) = addr.as_pathname() {
+        return path.to_str().map(|p| p.to_owned());
+    }
+    #[cfg(target_os = "linux")]
+    {
+        use std::os::linux::net::SocketAddrExt;
+        if let Some(name) = addr.as_abstract_name() {
+            return std::str::from_utf8(name)
+                .ok()
+                .map(|n| format!("{}{}", UNIX_ABSTRACT_PREFIX, n));
+        }
+    }
+    None
+}
@@ -0,0 +1,629 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ADLINK zenoh team, <zenoh@adlink-labs.tech>
+//
+use async_io::Async;
+use async_std::prelude::*;
+use async_std::sync::Mutex as AsyncMutex;
+use async_std::task;
+use async_std::task::JoinHandle;
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::remove_file;
+use std::os::unix::io::RawFd;
+use std::os::unix::net::{SocketAddr, UnixDatagram};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, RwLock, Weak};
+use std::time::Duration;
+use uuid::Uuid;
+use zenoh_collections::{RecyclingObject, RecyclingObjectPool};
+use zenoh_core::Result as ZResult;
+use zenoh_core::{bail, zasynclock, zerror, zlock, zread, zwrite};
+use zenoh_link_commons::{
+    LinkManagerUnicastTrait, LinkUnicast, LinkUnicastTrait, NewLinkChannelSender,
+};
+use zenoh_protocol_core::{EndPoint, Locator};
+use zenoh_sync::{Mvar, Signal};
+
+use super::{
+    get_unix_addr, get_unix_path_as_string, is_abstract, unix_addr_to_string,
+    UNIXSOCKDGRAM_ACCEPT_THROTTLE_TIME, UNIXSOCKDGRAM_DEFAULT_MTU, UNIXSOCKDGRAM_LOCATOR_PREFIX,
+    UNIXSOCKDGRAM_MAX_MTU,
+};
+
+type LinkHashMap = Arc<Mutex<HashMap<String, Weak<LinkUnicastUnixSocketDgramUnconnected>>>>;
+type LinkInput = (RecyclingObject<Box<[u8]>>, usize);
+type LinkLeftOver = (RecyclingObject<Box<[u8]>>, usize, usize);
+
+// The address a connecting socket is bound to, so that the listener can send datagrams back to it.
+#[cfg(target_os = "linux")]
+fn new_client_path() -> String {
+    format!("{}zenoh-{}", super::UNIX_ABSTRACT_PREFIX, Uuid::new_v4())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn new_client_path() -> String {
+    std::env::temp_dir()
+        .join(format!("zenoh-{}.sock", Uuid::new_v4()))
+        .to_string_lossy()
+        .into_owned()
+}
+
+// Because of the lack of SO_REUSEADDR we have to check if the socket file
+// is still there and if it is not used by another process, by means of a
+// separate lock file. If the lock CAN NOT be acquired, another process is
+// using the socket. Kernel guarantees that the lock is released if the owner
+// exits or crashes. If the lock CAN be acquired, the stale socket file is removed.
+fn lock_socket_file(path: &str) -> ZResult<RawFd> {
+    let lock_file_path = format!("{}.lock", path);
+
+    // We try to open the lock file, with O_RDONLY | O_CREAT
+    // and mode S_IRUSR | S_IWUSR, user read-write permissions
+    let mut open_flags = nix::fcntl::OFlag::empty();
+    open_flags.insert(nix::fcntl::OFlag::O_CREAT);
+    open_flags.insert(nix::fcntl::OFlag::O_RDONLY);
+
+    let mut open_mode = nix::sys::stat::Mode::empty();
+    open_mode.insert(nix::sys::stat::Mode::S_IRUSR);
+    open_mode.insert(nix::sys::stat::Mode::S_IWUSR);
+
+    let lock_fd = nix::fcntl::open(std::path::Path::new(&lock_file_path), open_flags, open_mode)
+        .map_err(|e| {
+            zerror!(
+            "Can not create a new UnixSocketDgram listener on {} - Unable to open lock file: {}",
+            path,
+            e
+        )
+        })?;
+
+    // We try to acquire the lock
+    nix::fcntl::flock(lock_fd, nix::fcntl::FlockArg::LockExclusiveNonblock).map_err(|e| {
+        let _ = nix::unistd::close(lock_fd);
+        zerror!(
+            "Can not create a new UnixSocketDgram listener on {} - Unable to acquire lock: {}",
+            path,
+            e
+        )
+    })?;
+
+    // Lock is acquired we can remove the socket file
+    let _ = remove_file(path);
+    Ok(lock_fd)
+}
+
+fn unlock_socket_file(path: &str, lock_fd: RawFd) {
+    let _ = nix::fcntl::flock(lock_fd, nix::fcntl::FlockArg::UnlockNonblock);
+    let _ = nix::unistd::close(lock_fd);
+    let _ = remove_file(path);
+    let res = remove_file(format!("{}.lock", path));
+    log::trace!("UnixSocketDgram Domain Socket removal result: {:?}", res);
+}
+
+struct LinkUnicastUnixSocketDgramConnected {
+    socket: Async<UnixDatagram>,
+    // The path of the socket file bound by this link, if not in the abstract namespace
+    path: Option<String>,
+}
+
+impl LinkUnicastUnixSocketDgramConnected {
+    async fn read(&self, buffer: &mut [u8]) -> ZResult<usize> {
+        self.socket
+            .recv(buffer)
+            .await
+            .map_err(|e| zerror!(e).into())
+    }
+
+    async fn write(&self, buffer: &[u8]) -> ZResult<usize> {
+        self.socket
+            .send(buffer)
+            .await
+            .map_err(|e| zerror!(e).into())
+    }
+}
+
+impl Drop for LinkUnicastUnixSocketDgramConnected {
+    fn drop(&mut self) {
+        // Remove the socket file bound by this link
+        if let Some(path) = self.path.as_ref() {
+            let _ = remove_file(path);
+        }
+    }
+}
+
+struct LinkUnicastUnixSocketDgramUnconnected {
+    socket: Weak<Async<UnixDatagram>>,
+    links: LinkHashMap,
+    input: Mvar<LinkInput>,
+    leftover: AsyncMutex<Option<LinkLeftOver>>,
+}
+
+impl LinkUnicastUnixSocketDgramUnconnected {
+    async fn received(&self, buffer: RecyclingObject<Box<[u8]>>, len: usize) {
+        self.input.put((buffer, len)).await;
+    }
+
+    async fn read(&self, buffer: &mut [u8]) -> ZResult<usize> {
+        let mut guard = zasynclock!(self.leftover);
+        let (slice, start, len) = match guard.take() {
+            Some(tuple) => tuple,
+            None => {
+                let (slice, len) = self.input.take().await;
+                (slice, 0, len)
+            }
+        };
+        // Copy the read bytes into the target buffer
+        let len_min = (len - start).min(buffer.len());
+        let end = start + len_min;
+        buffer[0..len_min].copy_from_slice(&slice[start..end]);
+        if end < len {
+            // Store the leftover
+            *guard = Some((slice, end, len));
+        } else {
+            // Recycle the buffer
+            slice.recycle().await;
+        }
+        // Return the amount read
+        Ok(len_min)
+    }
+
+    async fn write(&self, buffer: &[u8], dst_addr: &SocketAddr) -> ZResult<usize> {
+        match self.socket.upgrade() {
+            Some(socket) => socket
+                .write_with(|s| s.send_to_addr(buffer, dst_addr))
+                .await
+                .map_err(|e| zerror!(e).into()),
+            None => bail!("UnixSocketDgram listener has been dropped"),
+        }
+    }
+
+    async fn close(&self, dst_path: &str) -> ZResult<()> {
+        // Delete the link from the list of links
+        zlock!(self.links).remove(dst_path);
+        Ok(())
+    }
+}
+
+enum LinkUnicastUnixSocketDgramVariant {
+    Connected(LinkUnicastUnixSocketDgramConnected),
+    Unconnected(Arc<LinkUnicastUnixSocketDgramUnconnected>, SocketAddr),
+}
+
+pub struct LinkUnicastUnixSocketDgram {
+    // The Unix domain socket source path
+    src_locator: Locator,
+    // The Unix domain socket destination path
+    dst_locator: Locator,
+    // The socket is connected to the peer
+    variant: LinkUnicastUnixSocketDgramVariant,
+}
+
+impl LinkUnicastUnixSocketDgram {
+    fn new(
+        src_path: &str,
+        dst_path: &str,
+        variant: LinkUnicastUnixSocketDgramVariant,
+    ) -> LinkUnicastUnixSocketDgram {
+        LinkUnicastUnixSocketDgram {
+            src_locator: Locator::new(UNIXSOCKDGRAM_LOCATOR_PREFIX, &src_path),
+            dst_locator: Locator::new(UNIXSOCKDGRAM_LOCATOR_PREFIX, &dst_path),
+            variant,
+        }
+    }
+}
+
+#[async_trait]
+impl LinkUnicastTrait for LinkUnicastUnixSocketDgram {
+    async fn close(&self) -> ZResult<()> {
+        log::trace!("Closing UnixSocketDgram link: {}", self);
+        match &self.variant {
+            LinkUnicastUnixSocketDgramVariant::Connected(_) => Ok(()),
+            LinkUnicastUnixSocketDgramVariant::Unconnected(link, _) => {
+                link.close(self.dst_locator.address()).await
+            }
+        }
+    }
+
+    async fn write(&self, buffer: &[u8]) -> ZResult<usize> {
+        match &self.variant {
+            LinkUnicastUnixSocketDgramVariant::Connected(link) => link.write(buffer).await,
+            LinkUnicastUnixSocketDgramVariant::Unconnected(link, dst_addr) => {
+                link.write(buffer, dst_addr).await
+            }
+        }
+        .map_err(|e| {
+            let e = zerror!("Write error on UnixSocketDgram link {}: {}", self, e);
+            log::trace!("{}", e);
+            e.into()
+        })
+    }
+
+    async fn write_all(&self, buffer: &[u8]) -> ZResult<()> {
+        let mut written: usize = 0;
+        while written < buffer.len() {
+            written += self.write(&buffer[written..]).await?;
+        }
+        Ok(())
+    }
+
+    async fn read(&self, buffer: &mut [u8]) -> ZResult<usize> {
+        match &self.variant {
+            LinkUnicastUnixSocketDgramVariant::Connected(link) => link.read(buffer).await,
+            LinkUnicastUnixSocketDgramVariant::Unconnected(link, _) => link.read(buffer).await,
+        }
+        .map_err(|e| {
+            let e = zerror!("Read error on UnixSocketDgram link {}: {}", self, e);
+            log::trace!("{}", e);
+            e.into()
+        })
+    }
+
+    async fn read_exact(&self, buffer: &mut [u8]) -> ZResult<()> {
+        let mut read: usize = 0;
+        while read < buffer.len() {
+            let n = self.read(&mut buffer[read..]).await?;
+            read += n;
+        }
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn get_src(&self) -> &Locator {
+        &self.src_locator
+    }
+
+    #[inline(always)]
+    fn get_dst(&self) -> &Locator {
+        &self.dst_locator
+    }
+
+    #[inline(always)]
+    fn get_mtu(&self) -> u16 {
+        *UNIXSOCKDGRAM_DEFAULT_MTU
+    }
+
+    // NOTE: Unix datagram sockets neither lose nor reorder the datagrams: the sender
+    //       waits for room in the receive queue of the destination socket.
+    #[inline(always)]
+    fn is_reliable(&self) -> bool {
+        true
+    }
+
+    #[inline(always)]
+    fn is_streamed(&self) -> bool {
+        false
+    }
+}
+
+impl fmt::Display for LinkUnicastUnixSocketDgram {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} => {}", &self.src_locator, &self.dst_locator)?;
+        Ok(())
+    }
+}
+
+impl fmt::Debug for LinkUnicastUnixSocketDgram {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UnixSocketDgram")
+            .field("src", &self.src_locator)
+            .field("dst", &self.dst_locator)
+            .finish()
+    }
+}
+
+/*************************************/
+/*          LISTENER                 */
+/*************************************/
+struct ListenerUnixSocketDgram {
+    endpoint: EndPoint,
+    active: Arc<AtomicBool>,
+    signal: Signal,
+    handle: JoinHandle<ZResult<()>>,
+    // The lock on the socket file, if not in the abstract namespace
+    lock_fd: Option<RawFd>,
+}
+
+impl ListenerUnixSocketDgram {
+    fn new(
+        endpoint: EndPoint,
+        active: Arc<AtomicBool>,
+        signal: Signal,
+        handle: JoinHandle<ZResult<()>>,
+        lock_fd: Option<RawFd>,
+    ) -> ListenerUnixSocketDgram {
+        ListenerUnixSocketDgram {
+            endpoint,
+            active,
+            signal,
+            handle,
+            lock_fd,
+        }
+    }
+}
+
+pub struct LinkManagerUnicastUnixSocketDgram {
+    manager: NewLinkChannelSender,
+    listeners: Arc<RwLock<HashMap<String, ListenerUnixSocketDgram>>>,
+}
+
+impl LinkManagerUnicastUnixSocketDgram {
+    pub fn new(manager: NewLinkChannelSender) -> Self {
+        Self {
+            manager,
+            listeners: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+}
+
+#[async_trait]
+impl LinkManagerUnicastTrait for LinkManagerUnicastUnixSocketDgram {
+    async fn new_link(&self, endpoint: EndPoint) -> ZResult<LinkUnicast> {
+        let dst_path = get_unix_path_as_string(&endpoint.locator);
+        let dst_addr = get_unix_addr(&dst_path)?;
+
+        // Bind the local socket, so that the listener can reply to it
+        let src_path = new_client_path();
+        let src_addr = get_unix_addr(&src_path)?;
+        let socket = UnixDatagram::bind_addr(&src_addr).map_err(|e| {
+            let e = zerror!(
+                "Can not create a new UnixSocketDgram link bound to {}: {}",
+                dst_path,
+                e
+            );
+            log::warn!("{}", e);
+            e
+        })?;
+        let path = if is_abstract(&src_path) {
+            None
+        } else {
+            Some(src_path.clone())
+        };
+
+        // Connect the socket to the remote address
+        let socket = socket
+            .connect_addr(&dst_addr)
+            .and_then(|_| Async::new(socket))
+            .map_err(|e| {
+                if let Some(path) = path.as_ref() {
+                    let _ = remove_file(path);
+                }
+                let e = zerror!(
+                    "Can not create a new UnixSocketDgram link bound to {}: {}",
+                    dst_path,
+                    e
+                );
+                log::warn!("{}", e);
+                e
+            })?;
+
+        let link = Arc::new(LinkUnicastUnixSocketDgram::new(
+            &src_path,
+            &dst_path,
+            LinkUnicastUnixSocketDgramVariant::Connected(LinkUnicastUnixSocketDgramConnected {
+                socket,
+                path,
+            }),
+        ));
+
+        Ok(LinkUnicast(link))
+    }
+
+    async fn new_listener(&self, endpoint: EndPoint) -> ZResult<Locator> {
+        let path = get_unix_path_as_string(&endpoint.locator);
+        let addr = get_unix_addr(&path)?;
+
+        // An abstract address is released by the kernel as soon as its socket is closed:
+        // there is no socket file to clean up, hence no need for a lock file.
+        let lock_fd = if is_abstract(&path) {
+            None
+        } else {
+            Some(lock_socket_file(&path).map_err(|e| {
+                log::warn!("{}", e);
+                e
+            })?)
+        };
+
+        // Bind the Unix socket
+        let socket = UnixDatagram::bind_addr(&addr)
+            .and_then(Async::new)
+            .map_err(|e| {
+                if let Some(lock_fd) = lock_fd {
+                    unlock_socket_file(&path, lock_fd);
+                }
+                let e = zerror!(
+                    "Can not create a new UnixSocketDgram listener on {}: {}",
+                    path,
+                    e
+                );
+                log::warn!("{}", e);
+                e
+            })?;
+
+        // Spawn the accept loop for the listener
+        let active = Arc::new(AtomicBool::new(true));
+        let signal = Signal::new();
+
+        let c_active = active.clone();
+        let c_signal = signal.clone();
+        let c_manager = self.manager.clone();
+        let c_listeners = self.listeners.clone();
+        let c_path = path.clone();
+        let handle = task::spawn(async move {
+            // Wait for the accept loop to terminate
+            let res = accept_read_task(socket, &c_path, c_active, c_signal, c_manager).await;
+            zwrite!(c_listeners).remove(&c_path);
+            res
+        });
+
+        let locator = endpoint.locator.clone();
+        let listener = ListenerUnixSocketDgram::new(endpoint, active, signal, handle, lock_fd);
+        zwrite!(self.listeners).insert(path, listener);
+
+        Ok(locator)
+    }
+
+    async fn del_listener(&self, endpoint: &EndPoint) -> ZResult<()> {
+        let path = get_unix_path_as_string(&endpoint.locator);
+
+        // Stop the listener
+        let listener = zwrite!(self.listeners).remove(&path).ok_or_else(|| {
+            let e = zerror!(
+                "Can not delete the UnixSocketDgram listener because it has not been found: {}",
+                path
+            );
+            log::trace!("{}", e);
+            e
+        })?;
+
+        // Send the stop signal
+        listener.active.store(false, Ordering::Release);
+        listener.signal.trigger();
+        let res = listener.handle.await;
+
+        // Release the lock and remove the Unix Domain Socket file
+        if let Some(lock_fd) = listener.lock_fd {
+            unlock_socket_file(&path, lock_fd);
+        }
+        res
+    }
+
+    fn get_listeners(&self) -> Vec<EndPoint> {
+        zread!(self.listeners)
+            .values()
+            .map(|x| x.endpoint.clone())
+            .collect()
+    }
+
+    fn get_locators(&self) -> Vec<Locator> {
+        zread!(self.listeners)
+            .values()
+            .map(|x| x.endpoint.locator.clone())
+            .collect()
+    }
+}
+
+async fn accept_read_task(
+    socket: Async<UnixDatagram>,
+    src_path: &str,
+    active: Arc<AtomicBool>,
+    signal: Signal,
+    manager: NewLinkChannelSender,
+) -> ZResult<()> {
+    let socket = Arc::new(socket);
+    let links: LinkHashMap = Arc::new(Mutex::new(HashMap::new()));
+
+    enum Action {
+        Receive((usize, SocketAddr)),
+        Stop,
+    }
+
+    async fn receive(socket: Arc<Async<UnixDatagram>>, buffer: &mut [u8]) -> ZResult<Action> {
+        let res = socket.recv_from(buffer).await.map_err(|e| zerror!(e))?;
+        Ok(Action::Receive(res))
+    }
+
+    async fn stop(signal: Signal) -> ZResult<Action> {
+        signal.wait().await;
+        Ok(Action::Stop)
+    }
+
+    log::trace!(
+        "Ready to accept UnixSocketDgram connections on: {}",
+        src_path
+    );
+    // Buffers for deserialization
+    let pool = RecyclingObjectPool::new(1, || {
+        vec![0_u8; UNIXSOCKDGRAM_MAX_MTU as usize].into_boxed_slice()
+    });
+    while active.load(Ordering::Acquire) {
+        let mut buff = pool.take().await;
+        // Wait for incoming connections
+        let (n, dst_addr) = match receive(socket.clone(), &mut buff)
+            .race(stop(signal.clone()))
+            .await
+        {
+            Ok(action) => match action {
+                Action::Receive((n, addr)) => (n, addr),
+                Action::Stop => break,
+            },
+            Err(e) => {
+                log::warn!("{}. Hint: increase the system open file limit.", e);
+                // Throttle the accept loop upon an error
+                // NOTE: This might be due to various factors. However, the most common case is that
+                //       the process has reached the maximum number of open files in the system. On
+                //       Linux systems this limit can be changed by using the "ulimit" command line
+                //       tool. In case of systemd-based systems, this can be changed by using the
+                //       "sysctl" command line tool.
+                task::sleep(Duration::from_micros(*UNIXSOCKDGRAM_ACCEPT_THROTTLE_TIME)).await;
+                continue;
+            }
+        };
+
+        // Replies can't be sent to an unbound socket
+        let dst_path = match unix_addr_to_string(&dst_addr) {
+            Some(dst_path) => dst_path,
+            None => {
+                log::debug!(
+                    "Ignoring datagram from an unbound socket on UnixSocketDgram listener {}",
+                    src_path
+                );
+                continue;
+            }
+        };
+
+        let link = loop {
+            let res = zlock!(links).get(&dst_path).cloned();
+            match res {
+                Some(link) => break link.upgrade(),
+                None => {
+                    // A new peer has sent data to this socket
+                    log::debug!(
+                        "Accepted UnixSocketDgram connection on {}: {}",
+                        src_path,
+                        dst_path
+                    );
+                    let unconnected = Arc::new(LinkUnicastUnixSocketDgramUnconnected {
+                        socket: Arc::downgrade(&socket),
+                        links: links.clone(),
+                        input: Mvar::new(),
+                        leftover: AsyncMutex::new(None),
+                    });
+                    zlock!(links).insert(dst_path.clone(), Arc::downgrade(&unconnected));
+                    // Create the new link object
+                    let link = Arc::new(LinkUnicastUnixSocketDgram::new(
+                        src_path,
+                        &dst_path,
+                        LinkUnicastUnixSocketDgramVariant::Unconnected(
+                            unconnected,
+                            dst_addr.clone(),
+                        ),
+                    ));
+                    // Add the new link to the set of connected peers
+                    if let Err(e) = manager.send_async(LinkUnicast(link)).await {
+                        log::error!("{}-{}: {}", file!(), line!(), e)
+                    }
+                }
+            }
+        };
+
+        match link {
+            Some(link) => {
+                link.received(buff, n).await;
+            }
+            None => {
+                zlock!(links).remove(&dst_path);
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -14,8 +14,12 @@
 
 use std::path::Path;
 
+#[cfg(target_family = "unix")]
+use std::os::unix::net::SocketAddr;
 use zenoh_core::zconfigurable;
 #[cfg(target_family = "unix")]
+use zenoh_core::{zerror, Result as ZResult};
+#[cfg(target_family = "unix")]
 mod unicast;
 #[cfg(target_family = "unix")]
 pub use unicast::*;
@@ -30,6 +34,10 @@ const UNIXSOCKSTREAM_MAX_MTU: u16 = u16::MAX;
 
 pub const UNIXSOCKSTREAM_LOCATOR_PREFIX: &str = "unixsock-stream";
 
+// The addresses starting with '@' belong to the Linux abstract namespace (e.g. unixsock-stream/@zenoh).
+// They are not bound to a socket file: the kernel releases them as soon as the socket is closed.
+pub const UNIX_ABSTRACT_PREFIX: char = '@';
+
 zconfigurable! {
     // Default MTU (UNIXSOCKSTREAM PDU) in bytes.
     static ref UNIXSOCKSTREAM_DEFAULT_MTU: u16 = UNIXSOCKSTREAM_MAX_MTU;
@@ -45,3 +53,51 @@ pub fn get_unix_path(locator: &Locator) -> &Path {
 pub fn get_unix_path_as_string(locator: &Locator) -> String {
     locator.address().to_owned()
 }
+
+pub fn is_abstract(address: &str) -> bool {
+    address.starts_with(UNIX_ABSTRACT_PREFIX)
+}
+
+#[cfg(target_family = "unix")]
+pub fn get_unix_addr(address: &str) -> ZResult<SocketAddr> {
+    match address.strip_prefix(UNIX_ABSTRACT_PREFIX) {
+        Some(name) => get_abstract_addr(name),
+        None => SocketAddr::from_pathname(address)
+            .map_err(|e| zerror!("Invalid Unix socket path {}: {}", address, e).into()),
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn get_abstract_addr(name: &str) -> ZResult<SocketAddr> {
+    use std::os::linux::net::SocketAddrExt;
+    SocketAddr::from_abstract_name(name)
+        .map_err(|e| zerror!("Invalid abstract Unix socket name {}: {}", name, e).into())
+}
+
+#[cfg(all(target_family = "unix", not(target_os = "linux")))]
+fn get_abstract_addr(name: &str) -> ZResult<SocketAddr> {
+    zenoh_core::bail!(
+        "Abstract Unix socket addresses are only supported on Linux: {}{}",
+        UNIX_ABSTRACT_PREFIX,
+        name
+    )
+}
+
+/// Returns the address of a Unix socket in the format of the locators,
+/// or `None` if the socket is not bound.
+#[cfg(target_family = "unix")]
+pub fn unix_addr_to_string(addr: &SocketAddr) -> Option<String> {
+    if let Some(path) = addr.as_pathname() {
+        return path.to_str().map(|p| p.to_owned());
+    }
+    #[cfg(target_os = "linux")]
+    {
+        use std::os::linux::net::SocketAddrExt;
+        if let Some(name) = addr.as_abstract_name() {
+            return std::str::from_utf8(name)
+                .ok()
+                .map(|n| format!("{}{}", UNIX_ABSTRACT_PREFIX, n));
+        }
+    }
+    None
+}
@@ -13,7 +13,6 @@
 //
 use super::UNIXSOCKSTREAM_ACCEPT_THROTTLE_TIME;
 use async_std::os::unix::net::{UnixListener, UnixStream};
-use async_std::path::PathBuf;
 use async_std::prelude::*;
 use async_std::task;
 use async_std::task::JoinHandle;
@@ -36,8 +35,8 @@ use zenoh_protocol_core::{EndPoint, Locator};
 use zenoh_sync::Signal;
 
 use super::{
-    get_unix_path, get_unix_path_as_string, UNIXSOCKSTREAM_DEFAULT_MTU,
-    UNIXSOCKSTREAM_LOCATOR_PREFIX,
+    get_unix_addr, get_unix_path, get_unix_path_as_string, is_abstract, unix_addr_to_string,
+    UNIXSOCKSTREAM_DEFAULT_MTU, UNIXSOCKSTREAM_LOCATOR_PREFIX,
 };
 
 pub struct LinkUnicastUnixSocketStream {
@@ -158,7 +157,8 @@ struct ListenerUnixSocketStream {
     active: Arc<AtomicBool>,
     signal: Signal,
     handle: JoinHandle<ZResult<()>>,
-    lock_fd: RawFd,
+    // The lock on the socket file, if not in the abstract namespace
+    lock_fd: Option<RawFd>,
 }
 
 impl ListenerUnixSocketStream {
@@ -167,7 +167,7 @@ impl ListenerUnixSocketStream {
         active: Arc<AtomicBool>,
         signal: Signal,
         handle: JoinHandle<ZResult<()>>,
-        lock_fd: RawFd,
+        lock_fd: Option<RawFd>,
     ) -> ListenerUnixSocketStream {
         ListenerUnixSocketStream {
             endpoint,
@@ -199,7 +199,15 @@ impl LinkManagerUnicastTrait for LinkManagerUnicastUnixSocketStream {
         let path = get_unix_path(&endpoint.locator);
 
         // Create the UnixSocketStream connection
-        let stream = UnixStream::connect(&path).await.map_err(|e| {
+        let stream = if is_abstract(endpoint.locator.address()) {
+            // NOTE: there is no asynchronous connection to an abstract address. However,
+            //       connecting a local socket does not block unless the listener backlog is full.
+            let addr = get_unix_addr(endpoint.locator.address())?;
+            std::os::unix::net::UnixStream::connect_addr(&addr).map(UnixStream::from)
+        } else {
+            UnixStream::connect(&path).await
+        }
+        .map_err(|e| {
             let e = zerror!(
                 "Can not create a new UnixSocketStream link bound to {:?}: {}",
                 path,
@@ -230,40 +238,24 @@ impl LinkManagerUnicastTrait for LinkManagerUnicastUnixSocketStream {
             e
         })?;
 
-        let local_path = match src_addr.as_pathname() {
-            Some(path) => PathBuf::from(path),
+        let local_path_str = match unix_addr_to_string(&src_addr) {
+            Some(path) => path,
             None => {
                 let e = format!(
                     "Can not create a new UnixSocketStream link bound to {:?}",
                     path
                 );
                 log::warn!("{}", e);
-                PathBuf::from(format!("{}", Uuid::new_v4()))
+                format!("{}", Uuid::new_v4())
             }
         };
 
-        let local_path_str = local_path.to_str().ok_or_else(|| {
-            let e = zerror!(
-                "Can not create a new UnixSocketStream link bound to {:?}",
-                path
-            );
-            log::warn!("{}", e);
-            e
-        })?;
-
-        let remote_path_str = path.to_str().ok_or_else(|| {
-            let e = zerror!(
-                "Can not create a new UnixSocketStream link bound to {:?}",
-                path
-            );
-            log::warn!("{}", e);
-            e
-        })?;
+        let remote_path_str = get_unix_path_as_string(&endpoint.locator);
 
         let link = Arc::new(LinkUnicastUnixSocketStream::new(
             stream,
-            local_path_str,
-            remote_path_str,
+            &local_path_str,
+            &remote_path_str,
         ));
 
         Ok(LinkUnicast(link))
@@ -272,34 +264,51 @@ impl LinkManagerUnicastTrait for LinkManagerUnicastUnixSocketStream {
     async fn new_listener(&self, mut endpoint: EndPoint) -> ZResult<Locator> {
         let path = get_unix_path_as_string(&endpoint.locator);
 
-        // Because of the lack of SO_REUSEADDR we have to check if the
-        // file is still there and if it is not used by another process.
-        // In order to do so we use a separate lock file.
-        // If the lock CAN NOT be acquired means that another process is
-        // holding the lock NOW, therefore we cannot use the socket.
-        // Kernel guarantees that the lock is release if the owner exists
-        // or crashes.
-
-        // If the lock CAN be acquired means no one is using the socket.
-        // Therefore we can unlink the socket file and create the new one with
-        // bind(2)
-
-        // We generate the path for the lock file, by adding .lock
-        // to the socket file
-        let lock_file_path = format!("{}.lock", path);
-
-        // We try to open the lock file, with O_RDONLY | O_CREAT
-        // and mode S_IRUSR | S_IWUSR, user read-write permissions
-        let mut open_flags = nix::fcntl::OFlag::empty();
-
-        open_flags.insert(nix::fcntl::OFlag::O_CREAT);
-        open_flags.insert(nix::fcntl::OFlag::O_RDONLY);
-
-        let mut open_mode = nix::sys::stat::Mode::empty();
-        open_mode.insert(nix::sys::stat::Mode::S_IRUSR);
-        open_mode.insert(nix::sys::stat::Mode::S_IWUSR);
-
-        let lock_fd = nix::fcntl::open(
+        let (socket, lock_fd) = if is_abstract(endpoint.locator.address()) {
+            // An abstract address is released by the kernel as soon as its socket is closed:
+            // there is no socket file to clean up, hence no need for a lock file.
+            let addr = get_unix_addr(endpoint.locator.address())?;
+            let socket = std::os::unix::net::UnixListener::bind_addr(&addr)
+                .map(UnixListener::from)
+                .map_err(|e| {
+                    let e = zerror!(
+                        "Can not create a new UnixSocketStream listener on {}: {}",
+                        path,
+                        e
+                    );
+                    log::warn!("{}", e);
+                    e
+                })?;
+            (socket, None)
+        } else {
+            // Because of the lack of SO_REUSEADDR we have to check if the
+            // file is still there and if it is not used by another process.
+            // In order to do so we use a separate lock file.
+            // If the lock CAN NOT be acquired means that another process is
+            // holding the lock NOW, therefore we cannot use the socket.
+            // Kernel guarantees that the lock is release if the owner exists
+            // or crashes.
+
+            // If the lock CAN be acquired means no one is using the socket.
+            // Therefore we can unlink the socket file and create the new one with
+            // bind(2)
+
+            // We generate the path for the lock file, by adding .lock
+            // to the socket file
+            let lock_file_path = format!("{}.lock", path);
+
+            // We try to open the lock file, with O_RDONLY | O_CREAT
+            // and mode S_IRUSR | S_IWUSR, user read-write permissions
+            let mut open_flags = nix::fcntl::OFlag::empty();
+
+            open_flags.insert(nix::fcntl::OFlag::O_CREAT);
+            open_flags.insert(nix::fcntl::OFlag::O_RDONLY);
+
+            let mut open_mode = nix::sys::stat::Mode::empty();
+            open_mode.insert(nix::sys::stat::Mode::S_IRUSR);
+            open_mode.insert(nix::sys::stat::Mode::S_IWUSR);
+
+            let lock_fd = nix::fcntl::open(
             std::path::Path::new(&lock_file_path),
             open_flags,
             open_mode,
@@ -312,33 +321,37 @@ impl LinkManagerUnicastTrait for LinkManagerUnicastUnixSocketStream {
             e
         })?;
 
-        // We try to acquire the lock
-        nix::fcntl::flock(lock_fd, nix::fcntl::FlockArg::LockExclusiveNonblock).map_err(|e| {
-            let _ = nix::unistd::close(lock_fd);
-            let e = zerror!(
+            // We try to acquire the lock
+            nix::fcntl::flock(lock_fd, nix::fcntl::FlockArg::LockExclusiveNonblock).map_err(
+                |e| {
+                    let _ = nix::unistd::close(lock_fd);
+                    let e = zerror!(
                 "Can not create a new UnixSocketStream listener on {} - Unable to acquire look: {}",
                 path,
                 e
             );
-            log::warn!("{}", e);
-            e
-        })?;
-
-        //Lock is acquired we can remove the socket file
-        // If the file does not exist this would return an error.
-        // We are not interested if the file was not existing.
-        let _ = remove_file(path.clone());
-
-        // Bind the Unix socket
-        let socket = UnixListener::bind(&path).await.map_err(|e| {
-            let e = zerror!(
-                "Can not create a new UnixSocketStream listener on {}: {}",
-                path,
+                    log::warn!("{}", e);
+                    e
+                },
+            )?;
+
+            //Lock is acquired we can remove the socket file
+            // If the file does not exist this would return an error.
+            // We are not interested if the file was not existing.
+            let _ = remove_file(path.clone());
+
+            // Bind the Unix socket
+            let socket = UnixListener::bind(&path).await.map_err(|e| {
+                let e = zerror!(
+                    "Can not create a new UnixSocketStream listener on {}: {}",
+                    path,
+                    e
+                );
+                log::warn!("{}", e);
                 e
-            );
-            log::warn!("{}", e);
-            e
-        })?;
+            })?;
+            (socket, Some(lock_fd))
+        };
 
         let local_addr = socket.local_addr().map_err(|e| {
             let e = zerror!(
@@ -350,20 +363,14 @@ impl LinkManagerUnicastTrait for LinkManagerUnicastUnixSocketStream {
             e
         })?;
 
-        let local_path = PathBuf::from(local_addr.as_pathname().ok_or_else(|| {
-            let e = zerror!("Can not create a new UnixSocketStream listener on {}", path);
-            log::warn!("{}", e);
-            e
-        })?);
-
-        let local_path_str = local_path.to_str().ok_or_else(|| {
+        let local_path_str = unix_addr_to_string(&local_addr).ok_or_else(|| {
             let e = zerror!("Can not create a new UnixSocketStream listener on {}", path);
             log::warn!("{}", e);
             e
         })?;
 
         // Update the endpoint with the acutal local path
-        assert!(endpoint.set_addr(local_path_str));
+        assert!(endpoint.set_addr(&local_path_str));
 
         // Spawn the accept loop for the listener
         let active = Arc::new(AtomicBool::new(true));
@@ -373,7 +380,7 @@ impl LinkManagerUnicastTrait for LinkManagerUnicastUnixSocketStream {
         let c_signal = signal.clone();
         let c_manager = self.manager.clone();
         let c_listeners = self.listeners.clone();
-        let c_path = local_path_str.to_owned();
+        let c_path = local_path_str.clone();
         let handle = task::spawn(async move {
             // Wait for the accept loop to terminate
             let res = accept_task(socket, c_active, c_signal, c_manager).await;
@@ -383,7 +390,7 @@ impl LinkManagerUnicastTrait for LinkManagerUnicastUnixSocketStream {
 
         let locator = endpoint.locator.clone();
         let listener = ListenerUnixSocketStream::new(endpoint, active, signal, handle, lock_fd);
-        zwrite!(self.listeners).insert(local_path_str.to_owned(), listener);
+        zwrite!(self.listeners).insert(local_path_str, listener);
 
         Ok(locator)
     }
@@ -406,15 +413,17 @@ impl LinkManagerUnicastTrait for LinkManagerUnicastUnixSocketStream {
         listener.signal.trigger();
         let res = listener.handle.await;
 
-        //Release the lock
-        let _ = nix::fcntl::flock(listener.lock_fd, nix::fcntl::FlockArg::UnlockNonblock);
-        let _ = nix::unistd::close(listener.lock_fd);
-        let _ = remove_file(path.clone());
+        if let Some(lock_fd) = listener.lock_fd {
+            //Release the lock
+            let _ = nix::fcntl::flock(lock_fd, nix::fcntl::FlockArg::UnlockNonblock);
+            let _ = nix::unistd::close(lock_fd);
+            let _ = remove_file(path.clone());
 
-        // Remove the Unix Domain Socket file
-        let lock_file_path = format!("{}.lock", path);
-        let tmp = remove_file(lock_file_path);
-        log::trace!("UnixSocketStream Domain Socket removal result: {:?}", tmp);
+            // Remove the Unix Domain Socket file
+            let lock_file_path = format!("{}.lock", path);
+            let tmp = remove_file(lock_file_path);
+            log::trace!("UnixSocketStream Domain Socket removal result: {:?}", tmp);
+        }
         res
     }
 
@@ -460,16 +469,7 @@ async fn accept_task(
         e
     })?;
 
-    let local_path = PathBuf::from(src_addr.as_pathname().ok_or_else(|| {
-        let e = zerror!(
-            "Can not create a new UnixSocketStream link bound to {:?}",
-            src_addr
-        );
-        log::warn!("{}", e);
-        e
-    })?);
-
-    let src_path = local_path.to_str().ok_or_else(|| {
+    let src_path = unix_addr_to_string(&src_addr).ok_or_else(|| {
         let e = zerror!(
             "Can not create a new UnixSocketStream link bound to {:?}",
             src_addr
@@ -509,7 +509,7 @@ async fn accept_task(
 
         // Create the new link object
         let link = Arc::new(LinkUnicastUnixSocketStream::new(
-            stream, src_path, &dst_path,
+            stream, &src_path, &dst_path,
         ));
 
         // Communicate the new link to the initial transport manager
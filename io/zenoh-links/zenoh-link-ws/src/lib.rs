@@ -0,0 +1,69 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ADLINK zenoh team, <zenoh@adlink-labs.tech>
+//
+use async_std::net::ToSocketAddrs;
+use async_trait::async_trait;
+use std::net::SocketAddr;
+use zenoh_link_commons::LocatorInspector;
+
+use zenoh_core::{bail, zconfigurable, Result as ZResult};
+use zenoh_protocol_core::Locator;
+
+mod unicast;
+pub use unicast::*;
+
+// Default MTU (WebSocket PDU) in bytes.
+// NOTE: WebSocket is a message oriented transport and each binary frame carries
+//       exactly one zenoh batch. Given the usage of 16 bits in Zenoh to encode
+//       the size of a batch, the WebSocket MTU is constrained to 2^16 - 1 bytes
+//       (i.e., 65535).
+const WS_MAX_MTU: u16 = u16::MAX;
+
+pub const WS_LOCATOR_PREFIX: &str = "ws";
+
+#[derive(Default, Clone, Copy)]
+pub struct WsLocatorInspector;
+#[async_trait]
+impl LocatorInspector for WsLocatorInspector {
+    fn protocol(&self) -> &str {
+        WS_LOCATOR_PREFIX
+    }
+    async fn is_multicast(&self, _locator: &Locator) -> ZResult<bool> {
+        Ok(false)
+    }
+}
+
+zconfigurable! {
+    // Default MTU (WebSocket PDU) in bytes.
+    static ref WS_DEFAULT_MTU: u16 = WS_MAX_MTU;
+    // Amount of time in microseconds to throttle the accept loop upon an error.
+    // Default set to 100 ms.
+    static ref WS_ACCEPT_THROTTLE_TIME: u64 = 100_000;
+    // Amount of time in milliseconds granted to a peer to complete the WebSocket handshake.
+    // Default set to 10 s.
+    static ref WS_HANDSHAKE_TIMEOUT: u64 = 10_000;
+}
+
+pub async fn get_ws_addr(address: &Locator) -> ZResult<SocketAddr> {
+    let mut addrs = address.address().to_socket_addrs().await?;
+    match addrs.next() {
+        Some(address) => Ok(address),
+        None => bail!("Couldn't resolve WebSocket locator address: {}", address),
+    }
+}
+
+// The URL requested to the WebSocket server: the locator address is kept as-is so that
+// the Host header carries the host name, as expected by the HTTP proxies.
+pub fn get_ws_url(address: &Locator) -> String {
+    format!("ws://{}/", address.address())
+}
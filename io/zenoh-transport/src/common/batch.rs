@@ -91,6 +91,8 @@ pub(crate) struct SerializationBatch {
     current_frame: CurrentFrame,
     // The last SeqNum serialized on this batch
     pub(crate) sn: SerializationBatchSeqNum,
+    // The offset of each transport message serialized on this batch, with the SN of the reliable frames
+    frames: Vec<(usize, Option<ZInt>)>,
     // Statistics related to this batch
     #[cfg(feature = "stats")]
     pub(crate) stats: SerializationBatchStats,
@@ -126,6 +128,7 @@ impl SerializationBatch {
                 reliable: None,
                 best_effort: None,
             },
+            frames: vec![],
             #[cfg(feature = "stats")]
             stats: SerializationBatchStats::default(),
         };
@@ -169,6 +172,7 @@ impl SerializationBatch {
             self.buffer.write(&LENGTH_BYTES);
         }
         self.sn.clear();
+        self.frames.clear();
         #[cfg(feature = "stats")]
         self.stats.clear();
     }
@@ -193,6 +197,19 @@ impl SerializationBatch {
         self.buffer.as_ref().get_first_slice(..)
     }
 
+    /// Get the reliable frames serialized on the [`SerializationBatch`][SerializationBatch] with their
+    /// sequence numbers, e.g. for retransmitting them individually.
+    pub(crate) fn reliable_frames(&self) -> impl Iterator<Item = (ZInt, &[u8])> {
+        let bytes = self.as_bytes();
+        self.frames
+            .iter()
+            .enumerate()
+            .filter_map(move |(i, (start, sn))| {
+                let end = self.frames.get(i + 1).map_or(bytes.len(), |(end, _)| *end);
+                sn.map(|sn| (sn, &bytes[*start..end]))
+            })
+    }
+
     /// Try to serialize a [`TransportMessage`][TransportMessage] on the [`SerializationBatch`][SerializationBatch].
     ///
     /// # Arguments
@@ -200,11 +217,13 @@ impl SerializationBatch {
     ///
     pub(crate) fn serialize_transport_message(&mut self, message: &mut TransportMessage) -> bool {
         // Mark the write operation
+        let start = self.buffer.as_ref().len();
         self.buffer.mark();
         let res = self.buffer.as_mut().write_transport_message(message);
         if res {
             // Reset the current frame value
             self.current_frame = CurrentFrame::None;
+            self.frames.push((start, None));
         } else {
             // Revert the write operation
             self.buffer.revert();
@@ -257,7 +276,20 @@ impl SerializationBatch {
             }
         }
 
+        // The current frame can't be continued if the SN generator has been used meanwhile,
+        // i.e., if frames have been serialized on other batches in the meantime
+        if new_frame.is_none() {
+            let sn_state = match message.channel.reliability {
+                Reliability::Reliable => self.sn.reliable,
+                Reliability::BestEffort => self.sn.best_effort,
+            };
+            if !matches!(sn_state, Some(s) if s.next == sn_gen.now()) {
+                new_frame = Some(self.current_frame);
+            }
+        }
+
         // Mark the write operation
+        let start = self.buffer.as_ref().len();
         self.buffer.mark();
 
         // If a new sequence number has been provided, it means we are in the case we need
@@ -279,8 +311,14 @@ impl SerializationBatch {
                 self.current_frame = frame;
                 let sn_state = SerializationBatchSeqNumState { next: sn_gen.now() };
                 match message.channel.reliability {
-                    Reliability::Reliable => self.sn.reliable = Some(sn_state),
-                    Reliability::BestEffort => self.sn.best_effort = Some(sn_state),
+                    Reliability::Reliable => {
+                        self.sn.reliable = Some(sn_state);
+                        self.frames.push((start, Some(sn)));
+                    }
+                    Reliability::BestEffort => {
+                        self.sn.best_effort = Some(sn_state);
+                        self.frames.push((start, None));
+                    }
                 }
             } else {
                 // Restore the sequence number
@@ -327,6 +365,7 @@ impl SerializationBatch {
         // Assume first that this is not the final fragment
         let sn = sn_gen.get();
         let mut is_final = false;
+        let start = self.buffer.as_ref().len();
         loop {
             // Mark the buffer for the writing operation
             self.buffer.mark();
@@ -359,8 +398,14 @@ impl SerializationBatch {
                 // Keep track of the latest serialized SN
                 let sn_state = SerializationBatchSeqNumState { next: sn_gen.now() };
                 match reliability {
-                    Reliability::Reliable => self.sn.reliable = Some(sn_state),
-                    Reliability::BestEffort => self.sn.best_effort = Some(sn_state),
+                    Reliability::Reliable => {
+                        self.sn.reliable = Some(sn_state);
+                        self.frames.push((start, Some(sn)));
+                    }
+                    Reliability::BestEffort => {
+                        self.sn.best_effort = Some(sn_state);
+                        self.frames.push((start, None));
+                    }
                 }
 
                 #[cfg(feature = "stats")]
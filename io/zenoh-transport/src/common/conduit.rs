@@ -13,6 +13,7 @@
 //
 use super::defragmentation::DefragBuffer;
 use super::protocol::core::{ConduitSn, Priority, Reliability, ZInt};
+use super::reorder::ReorderBuffer;
 use super::seq_num::{SeqNum, SeqNumGenerator};
 use std::sync::{Arc, Mutex};
 use zenoh_core::zlock;
@@ -40,6 +41,7 @@ impl TransportChannelTx {
 pub(crate) struct TransportChannelRx {
     pub(crate) sn: SeqNum,
     pub(crate) defrag: DefragBuffer,
+    pub(crate) reorder: ReorderBuffer,
 }
 
 impl TransportChannelRx {
@@ -50,7 +52,11 @@ impl TransportChannelRx {
     ) -> ZResult<TransportChannelRx> {
         let sn = SeqNum::make(0, sn_resolution)?;
         let defrag = DefragBuffer::make(reliability, sn_resolution, defrag_buff_size)?;
-        let tch = TransportChannelRx { sn, defrag };
+        let tch = TransportChannelRx {
+            sn,
+            defrag,
+            reorder: ReorderBuffer::default(),
+        };
         Ok(tch)
     }
 
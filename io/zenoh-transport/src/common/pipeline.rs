@@ -195,6 +195,8 @@ pub(crate) struct TransmissionPipeline {
     active: AtomicBool,
     // The conduit TX containing the SN generators
     conduit: Arc<[TransportConduitTx]>,
+    // The size of the serialization batches
+    batch_size: usize,
     // Each conduit queue has its own Mutex
     stage_in: Box<[Mutex<StageIn>]>,
     // Amount of bytes available in each stage IN conduit queue
@@ -285,6 +287,7 @@ impl TransmissionPipeline {
         TransmissionPipeline {
             active: AtomicBool::new(true),
             conduit,
+            batch_size: batch_size as usize,
             stage_in,
             bytes_in,
             stage_out,
@@ -569,6 +572,21 @@ impl TransmissionPipeline {
         }
     }
 
+    /// The approximate amount of bytes waiting for transmission in all the conduit queues.
+    pub(crate) fn backlog(&self) -> usize {
+        self.batches_out
+            .iter()
+            .zip(self.bytes_in.iter())
+            .map(|(b_out, b_in)| {
+                b_out.load(Ordering::Acquire) * self.batch_size + b_in.load(Ordering::Acquire)
+            })
+            .sum()
+    }
+
+    pub(crate) fn is_active(&self) -> bool {
+        self.active.load(Ordering::Acquire)
+    }
+
     pub(crate) fn refill(&self, batch: SerializationBatch, queue: usize) {
         let mut refill_guard = zlock!(self.stage_refill[queue]);
         refill_guard.push(batch);
@@ -13,14 +13,16 @@
 //
 use std::convert::TryInto;
 use std::fmt;
+use std::sync::{Arc, Mutex};
 
-use super::common::seq_num::SeqNum;
-use super::core::ZInt;
+use super::protocol::core::{Priority, Reliability, ZInt};
+use super::protocol::proto::{FramePayload, TransportMessage};
+use super::seq_num::SeqNum;
 
-use zenoh_core::{ZError, ZErrorKind, ZResult};
-use zenoh_util::zerror;
+use zenoh_core::{bail, zlock, Result as ZResult};
+use zenoh_sync::{Condition, ConditionWaiter};
 
-pub(super) struct ReliabilityQueue<T> {
+pub(crate) struct ReliabilityQueue<T> {
     sn: SeqNum,
     index: usize,
     len: usize,
@@ -28,57 +30,72 @@ pub(super) struct ReliabilityQueue<T> {
 }
 
 impl<T> ReliabilityQueue<T> {
-    pub(super) fn new(
+    pub(crate) fn make(
         capacity: usize,
         initial_sn: ZInt,
         sn_resolution: ZInt,
-    ) -> ReliabilityQueue<T> {
+    ) -> ZResult<ReliabilityQueue<T>> {
         let mut inner = Vec::with_capacity(capacity);
         for _ in 0..capacity {
             inner.push(None);
         }
 
-        ReliabilityQueue {
-            sn: SeqNum::new(initial_sn, sn_resolution),
+        Ok(ReliabilityQueue {
+            sn: SeqNum::make(initial_sn, sn_resolution)?,
             index: 0,
             len: 0,
             inner,
-        }
+        })
     }
 
     #[inline]
-    pub(super) fn capacity(&self) -> usize {
-        self.inner.capacity()
+    pub(crate) fn capacity(&self) -> usize {
+        self.inner.len()
     }
 
     #[inline]
-    pub(super) fn len(&self) -> usize {
+    pub(crate) fn len(&self) -> usize {
         self.len
     }
 
     #[inline]
-    pub(super) fn is_empty(&self) -> bool {
+    pub(crate) fn is_empty(&self) -> bool {
         self.len() == 0
     }
 
+    #[allow(dead_code)]
     #[inline]
-    pub(super) fn is_full(&self) -> bool {
+    pub(crate) fn is_full(&self) -> bool {
         self.len() == self.capacity()
     }
 
     #[inline]
-    pub(super) fn get_base(&self) -> ZInt {
+    pub(crate) fn get_base(&self) -> ZInt {
         self.sn.get()
     }
 
-    pub(super) fn set_base(&mut self, sn: ZInt) -> ZResult<()> {
-        let gap: usize = match self.sn.gap(sn) {
-            Ok(gap) => match gap.try_into() {
-                Ok(gap) => gap,
-                Err(_) => usize::MAX,
-            },
-            Err(e) => return Err(e),
-        };
+    pub(crate) fn get_gap(&self, sn: ZInt) -> ZResult<usize> {
+        let gap = self.sn.gap(sn)?;
+        Ok(gap.try_into().unwrap_or(usize::MAX))
+    }
+
+    fn check_window(&self, sn: ZInt) -> ZResult<usize> {
+        let gap = self.get_gap(sn)?;
+        if gap >= self.capacity() {
+            let e = format!(
+                "Sequence number is out of sequence number window: {}. Base: {}. Capacity: {}",
+                sn,
+                self.sn.get(),
+                self.capacity()
+            );
+            log::trace!("{}", e);
+            bail!("{}", e);
+        }
+        Ok(gap)
+    }
+
+    pub(crate) fn set_base(&mut self, sn: ZInt) -> ZResult<()> {
+        let gap = self.get_gap(sn)?;
 
         self.sn.set(sn)?;
 
@@ -103,62 +120,42 @@ impl<T> ReliabilityQueue<T> {
         Ok(())
     }
 
-    pub(super) fn insert(&mut self, t: T, sn: ZInt) -> ZResult<()> {
-        let gap: usize = match self.sn.gap(sn) {
-            Ok(gap) => match gap.try_into() {
-                Ok(gap) => gap,
-                Err(e) => {
-                    return zerror!(ZErrorKind::InvalidResolution {
-                        descr: e.to_string()
-                    })
-                }
-            },
-            Err(e) => return Err(e),
-        };
-
-        if gap >= self.capacity() {
-            let e = format!(
-                "Sequence number is out of sequence number window: {}.\
-                             Base: {}. Capacity: {}",
-                sn,
-                self.sn.get(),
-                self.capacity()
-            );
-            log::trace!("{}", e);
-            return zerror!(ZErrorKind::Other { descr: e });
-        }
+    pub(crate) fn insert(&mut self, t: T, sn: ZInt) -> ZResult<()> {
+        let gap = self.check_window(sn)?;
 
-        self.len += 1;
         let index = (self.index + gap) % self.capacity();
+        if self.inner[index].is_none() {
+            self.len += 1;
+        }
         self.inner[index] = Some(t);
 
         Ok(())
     }
 
-    pub(super) fn remove(&mut self, sn: ZInt) -> ZResult<T> {
-        let gap: usize = match self.sn.gap(sn) {
-            Ok(gap) => match gap.try_into() {
-                Ok(gap) => gap,
-                Err(e) => {
-                    return zerror!(ZErrorKind::InvalidResolution {
-                        descr: e.to_string()
-                    })
-                }
-            },
-            Err(e) => return Err(e),
-        };
+    /// Inserts the item of the given SN, giving up the oldest items when the window is exceeded.
+    /// Returns false if the item is older than the ones in the queue.
+    pub(crate) fn keep(&mut self, t: T, sn: ZInt) -> ZResult<bool> {
+        if self.is_empty() {
+            self.set_base(sn)?;
+        } else if sn != self.get_base() && !self.sn.precedes(sn)? {
+            return Ok(false);
+        }
 
-        if gap >= self.capacity() {
-            let e = format!(
-                "Sequence number is out of sequence number window: {}.\
-                             Base: {}. Capacity: {}",
-                sn,
-                self.sn.get(),
-                self.capacity()
-            );
-            log::trace!("{}", e);
-            return zerror!(ZErrorKind::Other { descr: e });
+        let gap = self.get_gap(sn)?;
+        let capacity = self.capacity();
+        if gap >= capacity {
+            let resolution = self.sn.resolution();
+            let base = (sn + resolution - (capacity - 1) as ZInt) % resolution;
+            log::trace!("Reliable frames given up: {} -> {}", self.get_base(), base);
+            self.set_base(base)?;
         }
+        self.insert(t, sn)?;
+        Ok(true)
+    }
+
+    #[allow(dead_code)]
+    pub(crate) fn remove(&mut self, sn: ZInt) -> ZResult<T> {
+        let gap = self.check_window(sn)?;
 
         let index = (self.index + gap) % self.capacity();
         let res = self.inner[index].take();
@@ -168,13 +165,21 @@ impl<T> ReliabilityQueue<T> {
                 self.len -= 1;
                 Ok(t)
             }
-            None => zerror!(ZErrorKind::Other {
-                descr: "Sequence number not found: {}".to_string()
-            }),
+            None => bail!("Sequence number not found: {}", sn),
+        }
+    }
+
+    pub(crate) fn get_mut(&mut self, sn: ZInt) -> ZResult<&mut T> {
+        let gap = self.check_window(sn)?;
+
+        let index = (self.index + gap) % self.capacity();
+        match self.inner[index].as_mut() {
+            Some(t) => Ok(t),
+            None => bail!("Sequence number not found: {}", sn),
         }
     }
 
-    pub(super) fn pull(&mut self) -> Option<T> {
+    pub(crate) fn pull(&mut self) -> Option<T> {
         let t = self.inner[self.index].take();
         if t.is_some() {
             self.len -= 1;
@@ -187,7 +192,7 @@ impl<T> ReliabilityQueue<T> {
     /// Returns a bitmask of surely missed messages.
     /// A bit is set to 1 iff the position in the queue is empty and
     /// there is at least one message with a higher sequence number.
-    pub(super) fn get_mask(&self) -> ZInt {
+    pub(crate) fn get_mask(&self) -> ZInt {
         let mut mask: ZInt = 0;
         let mut count = 0;
         let mut i = 0;
@@ -202,42 +207,35 @@ impl<T> ReliabilityQueue<T> {
         }
         mask
     }
+
+    /// Returns a bitmask of the missed messages preceding the given sequence number.
+    /// A bit is set to 1 iff the position in the queue is empty.
+    pub(crate) fn get_mask_until(&self, sn: ZInt) -> ZResult<ZInt> {
+        let gap = self
+            .get_gap(sn)?
+            .min(self.capacity())
+            .min(ZInt::BITS as usize);
+        let mut mask: ZInt = 0;
+        for i in 0..gap {
+            let index = (self.index + i) % self.capacity();
+            if self.inner[index].is_none() {
+                mask |= 1 << i;
+            }
+        }
+        Ok(mask)
+    }
 }
 
 impl<T: Clone> ReliabilityQueue<T> {
-    pub(super) fn get(&mut self, sn: ZInt) -> ZResult<T> {
-        let gap: usize = match self.sn.gap(sn) {
-            Ok(gap) => match gap.try_into() {
-                Ok(gap) => gap,
-                Err(e) => {
-                    return zerror!(ZErrorKind::InvalidResolution {
-                        descr: e.to_string()
-                    })
-                }
-            },
-            Err(e) => return Err(e),
-        };
-
-        if gap >= self.capacity() {
-            let e = format!(
-                "Sequence number is out of sequence number window: {}.\
-                             Base: {}. Capacity: {}",
-                sn,
-                self.sn.get(),
-                self.capacity()
-            );
-            log::trace!("{}", e);
-            return zerror!(ZErrorKind::Other { descr: e });
-        }
+    pub(crate) fn get(&self, sn: ZInt) -> ZResult<T> {
+        let gap = self.check_window(sn)?;
 
         let index = (self.index + gap) % self.capacity();
         let res = self.inner[index].clone();
 
         match res {
             Some(t) => Ok(t),
-            None => zerror!(ZErrorKind::Other {
-                descr: "Sequence number not found: {}".to_string()
-            }),
+            None => bail!("Sequence number not found: {}", sn),
         }
     }
 }
@@ -251,6 +249,123 @@ impl<T: fmt::Debug> fmt::Debug for ReliabilityQueue<T> {
     }
 }
 
+// The reliable frames sent and not acknowledged yet
+struct Unacknowledged {
+    frames: ReliabilityQueue<Arc<[u8]>>,
+    // The SN following the last frame sent
+    next: SeqNum,
+}
+
+/// The selective retransmission of the reliable frames of a conduit sent over best-effort links.
+///
+/// The sender keeps the serialized frames until they are acknowledged by an AckNack, and
+/// retransmits the ones reported as missing in its mask. It doesn't send frames beyond the window
+/// of the unacknowledged ones. The receiver holds the frames received ahead of the missing ones
+/// until these are retransmitted.
+pub(crate) struct ConduitReliability {
+    pub(crate) priority: Priority,
+    tx: Mutex<Unacknowledged>,
+    cond_acknowledged: Condition,
+    pub(crate) rx: Mutex<ReliabilityQueue<FramePayload>>,
+}
+
+impl ConduitReliability {
+    pub(crate) fn make(
+        priority: Priority,
+        window: usize,
+        sn_resolution: ZInt,
+    ) -> ZResult<ConduitReliability> {
+        let tx = Unacknowledged {
+            frames: ReliabilityQueue::make(window, 0, sn_resolution)?,
+            next: SeqNum::make(0, sn_resolution)?,
+        };
+        Ok(ConduitReliability {
+            priority,
+            tx: Mutex::new(tx),
+            cond_acknowledged: Condition::new(),
+            rx: Mutex::new(ReliabilityQueue::make(window, 0, sn_resolution)?),
+        })
+    }
+
+    pub(crate) fn has_unacknowledged(&self) -> bool {
+        !zlock!(self.tx).frames.is_empty()
+    }
+
+    /// Returns a waiter for the next acknowledgment if the frame of the given SN exceeds the
+    /// window of the unacknowledged frames.
+    pub(crate) fn window_waiter(&self, sn: ZInt) -> Option<ConditionWaiter> {
+        let guard = zlock!(self.tx);
+        if guard.frames.is_empty() || guard.frames.check_window(sn).is_ok() {
+            None
+        } else {
+            Some(self.cond_acknowledged.waiter(guard))
+        }
+    }
+
+    /// Keeps a reliable frame sent on a best-effort link until it is acknowledged.
+    /// The oldest frames are given up when the window is exceeded.
+    pub(crate) fn keep(&self, sn: ZInt, frame: &[u8]) -> ZResult<()> {
+        let mut guard = zlock!(self.tx);
+        if !guard.frames.keep(frame.into(), sn)? {
+            // The frame has already been given up
+            return Ok(());
+        }
+
+        if sn == guard.next.get() || guard.next.precedes(sn)? {
+            guard.next.set(sn)?;
+            guard.next.increment();
+        }
+        Ok(())
+    }
+
+    /// The Sync soliciting the acknowledgment of the unacknowledged frames, if any.
+    /// Its count tells the receiver the frames that can still be retransmitted.
+    pub(crate) fn sync(&self) -> Option<TransportMessage> {
+        let guard = zlock!(self.tx);
+        if guard.frames.is_empty() {
+            return None;
+        }
+        let next = guard.next.get();
+        let count = guard.frames.sn.gap(next).ok()?;
+        Some(TransportMessage::make_sync(
+            self.priority,
+            Reliability::Reliable,
+            next,
+            Some(count),
+            None,
+        ))
+    }
+
+    /// Acknowledges the frames preceding the given SN, and returns the frames to retransmit
+    /// according to the mask.
+    pub(crate) fn acknowledge(&self, sn: ZInt, mask: Option<ZInt>) -> ZResult<Vec<Arc<[u8]>>> {
+        let mut guard = zlock!(self.tx);
+        if guard.frames.is_empty() {
+            return Ok(vec![]);
+        }
+        // Ignore the AckNacks of frames already given up or not sent yet
+        let next = guard.next.get();
+        if guard.frames.sn.gap(sn)? > guard.frames.sn.gap(next)? {
+            return Ok(vec![]);
+        }
+        guard.frames.set_base(sn)?;
+        self.cond_acknowledged.notify_all();
+
+        let mut frames = vec![];
+        if let Some(mask) = mask {
+            let resolution = guard.frames.sn.resolution();
+            for i in 0..ZInt::BITS as ZInt {
+                if mask & (1 << i) != 0 {
+                    if let Ok(frame) = guard.frames.get((sn + i) % resolution) {
+                        frames.push(frame);
+                    }
+                }
+            }
+        }
+        Ok(frames)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -259,7 +374,7 @@ mod tests {
     #[test]
     fn reliability_queue_simple() {
         let size = 2;
-        let mut queue: ReliabilityQueue<ZInt> = ReliabilityQueue::new(size, 0, 2);
+        let mut queue: ReliabilityQueue<ZInt> = ReliabilityQueue::make(size, 0, 2).unwrap();
 
         let mut sn: ZInt = 0;
         // Add the first element
@@ -269,7 +384,7 @@ mod tests {
         assert_eq!(res, Some(0));
 
         // Add the second element
-        sn = sn + 1;
+        sn += 1;
         let res = queue.insert(1, sn);
         assert!(res.is_ok());
         let res = queue.pull();
@@ -282,7 +397,7 @@ mod tests {
     #[test]
     fn reliability_queue_order() {
         let size = 2;
-        let mut queue: ReliabilityQueue<ZInt> = ReliabilityQueue::new(size, 0, 3);
+        let mut queue: ReliabilityQueue<ZInt> = ReliabilityQueue::make(size, 0, 3).unwrap();
 
         let sn: ZInt = 0;
 
@@ -309,7 +424,7 @@ mod tests {
     #[test]
     fn reliability_queue_full() {
         let size = 2;
-        let mut queue: ReliabilityQueue<ZInt> = ReliabilityQueue::new(size, 0, 3);
+        let mut queue: ReliabilityQueue<ZInt> = ReliabilityQueue::make(size, 0, 3).unwrap();
 
         let mut sn: ZInt = 0;
 
@@ -336,7 +451,7 @@ mod tests {
     #[test]
     fn reliability_queue_out_of_sync() {
         let size = 2;
-        let mut queue: ReliabilityQueue<ZInt> = ReliabilityQueue::new(size, 0, 2);
+        let mut queue: ReliabilityQueue<ZInt> = ReliabilityQueue::make(size, 0, 2).unwrap();
 
         let sn: ZInt = 3;
 
@@ -351,7 +466,7 @@ mod tests {
     fn reliability_queue_overflow() {
         // Test the overflow case
         let size = 4;
-        let mut queue: ReliabilityQueue<ZInt> = ReliabilityQueue::new(size, 0, 4);
+        let mut queue: ReliabilityQueue<ZInt> = ReliabilityQueue::make(size, 0, 4).unwrap();
 
         let min: ZInt = 0;
         let max: ZInt = 3;
@@ -385,13 +500,13 @@ mod tests {
     fn reliability_queue_mask() {
         // Test the deterministic insertion of elements and mask
         let size = 8;
-        let mut queue: ReliabilityQueue<ZInt> = ReliabilityQueue::new(size, 0, 8);
+        let mut queue: ReliabilityQueue<ZInt> = ReliabilityQueue::make(size, 0, 8).unwrap();
 
         let mut sn: ZInt = 0;
         while sn < size as ZInt {
             let res = queue.insert(sn, sn);
             assert!(res.is_ok());
-            sn = sn + 2;
+            sn += 2;
         }
 
         // Verify that the mask is correct
@@ -403,7 +518,7 @@ mod tests {
         while sn < size as ZInt {
             let res = queue.insert(sn, sn);
             assert!(res.is_ok());
-            sn = sn + 2;
+            sn += 2;
         }
 
         // Verify that the mask is correct
@@ -411,7 +526,7 @@ mod tests {
         assert_eq!(queue.get_mask(), mask);
 
         // Drain the queue
-        while let Some(_) = queue.pull() {}
+        while queue.pull().is_some() {}
         // Verify that the queue is empty
         assert!(queue.is_empty());
     }
@@ -420,7 +535,7 @@ mod tests {
     fn reliability_queue_random_mask() {
         // Test the random insertion of elements and the mask
         let size = 64;
-        let mut queue: ReliabilityQueue<ZInt> = ReliabilityQueue::new(size, 0, 64);
+        let mut queue: ReliabilityQueue<ZInt> = ReliabilityQueue::make(size, 0, 64).unwrap();
 
         let mut sequence = Vec::<ZInt>::new();
         for i in 0..size as ZInt {
@@ -431,7 +546,7 @@ mod tests {
         let mut tail = 0;
         let mut mask: ZInt = 0;
         let mut rng = thread_rng();
-        while sequence.len() > 0 {
+        while !sequence.is_empty() {
             // Get random sequence number
             let index = rng.gen_range(0..sequence.len());
             let sn = sequence.remove(index);
@@ -443,9 +558,9 @@ mod tests {
             let res = queue.insert(sn, sn);
             assert!(res.is_ok());
             // Locally compute the mask
-            mask = mask | (1 << sn);
+            mask |= 1 << sn;
             let shift: u32 = tail.wrapping_sub(head) as u32;
-            let window = !ZInt::max_value().wrapping_shl(shift);
+            let window = !ZInt::MAX.wrapping_shl(shift);
             // Verify that the mask is correct
             assert_eq!(queue.get_mask(), !mask & window);
         }
@@ -453,10 +568,10 @@ mod tests {
         // Verify that we have filled the queue
         assert!(queue.is_full());
         // Verify that no elements are marked for retransmission
-        assert_eq!(queue.get_mask(), !ZInt::max_value());
+        assert_eq!(queue.get_mask(), !ZInt::MAX);
 
         // Drain the queue
-        while let Some(_) = queue.pull() {}
+        while queue.pull().is_some() {}
         // Verify that the queue is empty
         assert!(queue.is_empty());
 
@@ -468,7 +583,7 @@ mod tests {
     #[test]
     fn reliability_queue_rebase() {
         let size = 8;
-        let mut queue: ReliabilityQueue<ZInt> = ReliabilityQueue::new(size, 0, 32);
+        let mut queue: ReliabilityQueue<ZInt> = ReliabilityQueue::make(size, 0, 32).unwrap();
 
         // Fill the queue
         for i in 0..size as ZInt {
@@ -549,7 +664,7 @@ mod tests {
     #[test]
     fn reliability_queue_remove() {
         let size = 8;
-        let mut queue: ReliabilityQueue<ZInt> = ReliabilityQueue::new(size, 0, 8);
+        let mut queue: ReliabilityQueue<ZInt> = ReliabilityQueue::make(size, 0, 8).unwrap();
 
         // Fill the queue
         for i in 0..size as ZInt {
@@ -0,0 +1,83 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ADLINK zenoh team, <zenoh@adlink-labs.tech>
+//
+use super::protocol::core::ZInt;
+use super::protocol::proto::FramePayload;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// The frames of a reliable channel received ahead of the next expected one.
+///
+/// When the frames of a transport are scheduled on several links, they may be received
+/// out of order: they are held back until the missing frames are received, or given up.
+#[derive(Debug, Default)]
+pub(crate) struct ReorderBuffer {
+    // The frames following the next expected one, indexed by their distance to it
+    frames: VecDeque<Option<(ZInt, FramePayload)>>,
+    // Since when the next expected frame is missing
+    since: Option<Instant>,
+}
+
+impl ReorderBuffer {
+    #[inline(always)]
+    pub(crate) fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// Holds a frame received at the given distance (greater than 0) from the next expected one.
+    pub(crate) fn hold(&mut self, distance: usize, sn: ZInt, payload: FramePayload) {
+        if self.frames.len() <= distance {
+            self.frames.resize_with(distance + 1, || None);
+        }
+        self.frames[distance] = Some((sn, payload));
+        self.since.get_or_insert_with(Instant::now);
+    }
+
+    /// Moves to the frame following the next expected one, once the latter has been received.
+    pub(crate) fn advance(&mut self) {
+        self.frames.pop_front();
+        self.restart();
+    }
+
+    /// Returns the next expected frame if it has already been received, and moves to the
+    /// frame following it.
+    pub(crate) fn pop_ready(&mut self) -> Option<(ZInt, FramePayload)> {
+        match self.frames.front() {
+            Some(Some(_)) => {
+                let frame = self.frames.pop_front().flatten();
+                self.restart();
+                frame
+            }
+            _ => None,
+        }
+    }
+
+    /// Checks if the next expected frame has been missing for longer than the given timeout.
+    pub(crate) fn is_expired(&self, timeout: Duration) -> bool {
+        self.since.is_some_and(|since| since.elapsed() > timeout)
+    }
+
+    /// Gives up the missing frames and returns all the frames held, in order.
+    pub(crate) fn drain(&mut self) -> Vec<(ZInt, FramePayload)> {
+        self.since = None;
+        self.frames.drain(..).flatten().collect()
+    }
+
+    fn restart(&mut self) {
+        self.since = if self.frames.is_empty() {
+            None
+        } else {
+            Some(Instant::now())
+        };
+    }
+}
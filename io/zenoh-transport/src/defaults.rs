@@ -29,4 +29,28 @@ zenoh_core::zconfigurable! {
 
     // The default backoff time in nanoseconds to allow the batching to potentially progress
     pub static ref ZN_QUEUE_PULL_BACKOFF: u64 = 100;
+
+    // Parameters of the reordering of the reliable frames received on multiple links
+    // - The maximum distance in frames of a frame held back to the next expected one. It must
+    //   exceed the number of frames that can be queued on the links of a transport.
+    // - The maximum time in milliseconds to wait for a missing frame.
+    pub static ref ZN_RX_REORDER_WINDOW: usize = 65_536;
+    pub static ref ZN_RX_REORDER_TIMEOUT: u64 = 1_000;
+
+    // Parameters of the retransmission of the reliable frames sent on best-effort links
+    // - The number of frames kept until acknowledged by the sender, and held until the missing
+    //   ones are retransmitted by the receiver. It can't exceed the 64 bits of an AckNack mask.
+    // - The period in milliseconds of the Sync messages soliciting the acknowledgments.
+    pub static ref ZN_RETRANSMISSION_WINDOW: usize = 64;
+    pub static ref ZN_RETRANSMISSION_SYNC_PERIOD: u64 = 10;
+
+    // Parameters of the repair of the reliable frames sent on multicast transports
+    // - The maximum random delay in milliseconds before a receiver reports its missing frames,
+    //   during which the report of another receiver missing the same frames suppresses its own.
+    // - The time in milliseconds a receiver waits for the retransmission of the frames it reported
+    //   before reporting them again. The sender ignores the reports of a frame retransmitted less
+    //   than half of it ago. It is kept short for the frames to be reported several times before
+    //   the window of the sender moves past them.
+    pub static ref ZN_MULTICAST_NACK_BACKOFF: u64 = 20;
+    pub static ref ZN_MULTICAST_REPAIR_TIMEOUT: u64 = 30;
 }
@@ -12,10 +12,12 @@
 //   ADLINK zenoh team, <zenoh@adlink-labs.tech>
 //
 use super::common::{conduit::TransportConduitTx, pipeline::TransmissionPipeline};
+use super::reliability::ConduitRetransmission;
 use super::transport::TransportMulticastInner;
 #[cfg(feature = "stats")]
 use super::TransportMulticastStatsAtomic;
 use crate::common::batch::SerializationBatch;
+use crate::defaults::ZN_RETRANSMISSION_SYNC_PERIOD;
 use async_std::prelude::*;
 use async_std::task;
 use async_std::task::JoinHandle;
@@ -79,6 +81,7 @@ impl TransportLinkMulticast {
         &mut self,
         config: TransportLinkMulticastConfig,
         conduit_tx: Arc<[TransportConduitTx]>,
+        retransmission: Option<Arc<[ConduitRetransmission]>>,
     ) {
         let initial_sns: Vec<ConduitSn> = conduit_tx
             .iter()
@@ -106,6 +109,7 @@ impl TransportLinkMulticast {
                     c_link.clone(),
                     config,
                     initial_sns,
+                    retransmission,
                     #[cfg(feature = "stats")]
                     c_transport.stats.clone(),
                 )
@@ -189,6 +193,7 @@ async fn tx_task(
     link: LinkMulticast,
     config: TransportLinkMulticastConfig,
     mut next_sns: Vec<ConduitSn>,
+    retransmission: Option<Arc<[ConduitRetransmission]>>,
     #[cfg(feature = "stats")] stats: Arc<TransportMulticastStatsAtomic>,
 ) -> ZResult<()> {
     enum Action {
@@ -218,9 +223,18 @@ async fn tx_task(
         Action::Join
     }
 
+    let sync_period = Duration::from_millis(*ZN_RETRANSMISSION_SYNC_PERIOD).min(config.keep_alive);
+    // Whether reliable frames have been sent since the last Sync
+    let mut is_sync_pending = false;
     let mut last_join = Instant::now() - config.join_interval;
     loop {
-        match pull(&pipeline, config.keep_alive)
+        // Advertise the last reliable frame sent once idle, for the receivers to detect its loss
+        let timeout = if is_sync_pending {
+            sync_period
+        } else {
+            config.keep_alive
+        };
+        match pull(&pipeline, timeout)
             .race(join(last_join, config.join_interval))
             .await
         {
@@ -228,6 +242,13 @@ async fn tx_task(
                 // Send the buffer on the link
                 let bytes = batch.as_bytes();
                 let _ = link.write_all(bytes).await?;
+                // Keep the reliable frames for their retransmission
+                if let Some(retransmission) = retransmission.as_ref() {
+                    for (sn, frame) in batch.reliable_frames() {
+                        retransmission[priority].keep(sn, frame)?;
+                        is_sync_pending = true;
+                    }
+                }
                 // Keep track of next SNs
                 if let Some(sn) = batch.sn.reliable {
                     next_sns[priority].reliable = sn.next;
@@ -259,6 +280,7 @@ async fn tx_task(
                     config.lease,
                     config.sn_resolution,
                     initial_sns,
+                    retransmission.is_some(),
                     attachment,
                 );
 
@@ -273,10 +295,26 @@ async fn tx_task(
                 last_join = Instant::now();
             }
             Action::KeepAlive => {
-                let pid = Some(config.pid);
-                let attachment = None;
-                let message = TransportMessage::make_keep_alive(pid, attachment);
-                pipeline.push_transport_message(message, Priority::Background);
+                // Advertise the last reliable frames again with each keep-alive, for the receivers
+                // to detect the loss of the last frames even if the first Syncs were lost too
+                for r in retransmission.iter().flat_map(|r| r.iter()) {
+                    if let Some(mut message) = r.sync() {
+                        #[allow(unused_variables)] // Used when stats feature is enabled
+                        let n = link.write_transport_message(&mut message).await?;
+                        #[cfg(feature = "stats")]
+                        {
+                            stats.inc_tx_t_msgs(1);
+                            stats.inc_tx_bytes(n);
+                        }
+                    }
+                }
+                if !is_sync_pending {
+                    let pid = Some(config.pid);
+                    let attachment = None;
+                    let message = TransportMessage::make_keep_alive(pid, attachment);
+                    pipeline.push_transport_message(message, Priority::Background);
+                }
+                is_sync_pending = false;
             }
             Action::Stop => {
                 // Drain the transmission pipeline and write remaining bytes on the wire
@@ -21,6 +21,7 @@ use zenoh_cfg_properties::config::*;
 use zenoh_config::Config;
 use zenoh_core::{bail, Result as ZResult};
 use zenoh_core::{zerror, zlock, zparse};
+use zenoh_link::fault::{FaultConfig, LinkMulticastFaulty};
 use zenoh_link::*;
 use zenoh_protocol::proto::tmsg;
 use zenoh_protocol_core::locators::LocatorProtocol;
@@ -31,6 +32,7 @@ pub struct TransportManagerConfigMulticast {
     pub join_interval: Duration,
     pub max_sessions: usize,
     pub is_qos: bool,
+    pub is_reliable: bool,
 }
 
 pub struct TransportManagerBuilderMulticast {
@@ -39,6 +41,7 @@ pub struct TransportManagerBuilderMulticast {
     join_interval: Duration,
     max_sessions: usize,
     is_qos: bool,
+    is_reliable: bool,
 }
 
 pub struct TransportManagerStateMulticast {
@@ -79,6 +82,11 @@ impl TransportManagerBuilderMulticast {
         self
     }
 
+    pub fn reliable(mut self, is_reliable: bool) -> Self {
+        self.is_reliable = is_reliable;
+        self
+    }
+
     pub async fn from_config(
         mut self,
         properties: &Config,
@@ -98,6 +106,9 @@ impl TransportManagerBuilderMulticast {
         if let Some(v) = properties.transport().qos() {
             self = self.qos(*v);
         }
+        if let Some(v) = properties.transport().multicast().reliable() {
+            self = self.reliable(*v);
+        }
 
         Ok(self)
     }
@@ -109,6 +120,7 @@ impl TransportManagerBuilderMulticast {
             join_interval: self.join_interval,
             max_sessions: self.max_sessions,
             is_qos: self.is_qos,
+            is_reliable: self.is_reliable,
         };
 
         let state = TransportManagerStateMulticast {
@@ -130,6 +142,7 @@ impl Default for TransportManagerBuilderMulticast {
             join_interval: Duration::from_millis(zparse!(ZN_JOIN_INTERVAL_DEFAULT).unwrap()),
             max_sessions: zparse!(ZN_MAX_SESSIONS_MULTICAST_DEFAULT).unwrap(),
             is_qos: zparse!(ZN_QOS_DEFAULT).unwrap(),
+            is_reliable: zparse!(ZN_MULTICAST_RELIABLE_DEFAULT).unwrap(),
         }
     }
 }
@@ -215,7 +228,11 @@ impl TransportManager {
         }
 
         // Open the link
-        let link = manager.new_link(&endpoint).await?;
+        let mut link = manager.new_link(&endpoint).await?;
+        // Inject the faults configured on the endpoint, if any
+        if let Some(faults) = FaultConfig::from_endpoint(&endpoint)? {
+            link = LinkMulticast(Arc::new(LinkMulticastFaulty::new(link, faults)));
+        }
         super::establishment::open_link(self, link).await
     }
 
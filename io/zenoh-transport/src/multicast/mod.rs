@@ -14,6 +14,7 @@
 pub(crate) mod establishment;
 pub(crate) mod link;
 pub(crate) mod manager;
+pub(crate) mod reliability;
 pub(crate) mod rx;
 pub(crate) mod transport;
 pub(crate) mod tx;
@@ -106,6 +107,12 @@ impl TransportMulticast {
         Ok(transport.is_qos())
     }
 
+    #[inline(always)]
+    pub fn is_reliable(&self) -> ZResult<bool> {
+        let transport = self.get_transport()?;
+        Ok(transport.is_reliable())
+    }
+
     #[inline(always)]
     pub fn get_callback(&self) -> ZResult<Option<Arc<dyn TransportMulticastEventHandler>>> {
         let transport = self.get_transport()?;
@@ -180,6 +187,7 @@ impl fmt::Debug for TransportMulticast {
                     .field("sn_resolution", &transport.get_sn_resolution())
                     .field("is_qos", &transport.is_qos())
                     .field("is_shm", &transport.is_shm())
+                    .field("is_reliable", &transport.is_reliable())
                     .field("peers", &peers)
                     .finish()
             }
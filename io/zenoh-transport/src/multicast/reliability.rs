@@ -0,0 +1,256 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ADLINK zenoh team, <zenoh@adlink-labs.tech>
+//
+use super::common::reliability::ReliabilityQueue;
+use super::common::seq_num::SeqNum;
+use super::protocol::core::{Priority, Reliability, ZInt};
+use super::protocol::proto::{FramePayload, TransportMessage};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use zenoh_core::{zlock, Result as ZResult};
+
+// A reliable frame kept for its retransmission
+struct KeptFrame {
+    frame: Arc<[u8]>,
+    // When the frame has been retransmitted for the last time
+    retransmitted: Option<Instant>,
+}
+
+struct Sent {
+    frames: ReliabilityQueue<KeptFrame>,
+    // The SN following the last frame sent
+    next: SeqNum,
+}
+
+/// The retransmission of the reliable frames of a conduit sent on a multicast transport.
+///
+/// The receivers don't acknowledge the frames: the last frames sent are kept within a window,
+/// and retransmitted on the multicast locator when reported as missing by an AckNack. A frame is
+/// retransmitted at most once per hold-off period, whatever the number of receivers missing it.
+pub(super) struct ConduitRetransmission {
+    pub(super) priority: Priority,
+    sent: Mutex<Sent>,
+}
+
+impl ConduitRetransmission {
+    pub(super) fn make(
+        priority: Priority,
+        window: usize,
+        sn_resolution: ZInt,
+    ) -> ZResult<ConduitRetransmission> {
+        let sent = Sent {
+            frames: ReliabilityQueue::make(window, 0, sn_resolution)?,
+            next: SeqNum::make(0, sn_resolution)?,
+        };
+        Ok(ConduitRetransmission {
+            priority,
+            sent: Mutex::new(sent),
+        })
+    }
+
+    /// Keeps a reliable frame sent on the multicast locator, giving up the oldest frame when the
+    /// window is exceeded.
+    pub(super) fn keep(&self, sn: ZInt, frame: &[u8]) -> ZResult<()> {
+        let mut guard = zlock!(self.sent);
+        let frame = KeptFrame {
+            frame: frame.into(),
+            retransmitted: None,
+        };
+        if guard.frames.keep(frame, sn)? && (sn == guard.next.get() || guard.next.precedes(sn)?) {
+            guard.next.set(sn)?;
+            guard.next.increment();
+        }
+        Ok(())
+    }
+
+    /// The Sync advertising the last frame sent, if any. Its count tells the receivers the
+    /// frames that can still be retransmitted.
+    pub(super) fn sync(&self) -> Option<TransportMessage> {
+        let guard = zlock!(self.sent);
+        if guard.frames.is_empty() {
+            return None;
+        }
+        let next = guard.next.get();
+        let count = guard.frames.get_gap(next).ok()? as ZInt;
+        Some(TransportMessage::make_sync(
+            self.priority,
+            Reliability::Reliable,
+            next,
+            Some(count),
+            None,
+        ))
+    }
+
+    /// Returns the frames reported as missing by the mask of an AckNack, except the ones
+    /// retransmitted less than the hold-off period ago, and whether some of them can't be
+    /// retransmitted anymore.
+    pub(super) fn retransmit(
+        &self,
+        sn: ZInt,
+        mask: ZInt,
+        hold_off: Duration,
+    ) -> (Vec<Arc<[u8]>>, bool) {
+        let mut guard = zlock!(self.sent);
+        let resolution = guard.next.resolution();
+        let now = Instant::now();
+        let mut frames = vec![];
+        let mut is_lost = false;
+        for i in 0..ZInt::BITS as ZInt {
+            if mask & (1 << i) == 0 {
+                continue;
+            }
+            match guard.frames.get_mut((sn + i) % resolution) {
+                Ok(kept) => {
+                    if kept
+                        .retransmitted
+                        .is_none_or(|t| now.duration_since(t) >= hold_off)
+                    {
+                        kept.retransmitted = Some(now);
+                        frames.push(kept.frame.clone());
+                    }
+                }
+                Err(_) => is_lost = true,
+            }
+        }
+        (frames, is_lost)
+    }
+}
+
+/// The repair of the reliable frames of a conduit received from a peer on a multicast transport.
+///
+/// The frames received ahead of the missing ones are held until these are retransmitted. The
+/// missing frames are reported with an AckNack after a random back-off, which is postponed when
+/// the AckNack of another receiver reports them in the meantime (NACK suppression).
+pub(super) struct ConduitRepair {
+    pub(super) priority: Priority,
+    pub(super) frames: ReliabilityQueue<FramePayload>,
+    // The SN following the last frame sent by the peer, as advertised by its Sync
+    pub(super) next: Option<ZInt>,
+    // When the missing frames are reported, if they are
+    pub(super) nack: Option<Instant>,
+    sn_resolution: ZInt,
+}
+
+impl ConduitRepair {
+    pub(super) fn make(
+        priority: Priority,
+        window: usize,
+        sn_resolution: ZInt,
+    ) -> ZResult<ConduitRepair> {
+        Ok(ConduitRepair {
+            priority,
+            frames: ReliabilityQueue::make(window, 0, sn_resolution)?,
+            next: None,
+            nack: None,
+            sn_resolution,
+        })
+    }
+
+    /// Returns the bitmask of the missing frames, starting from the base of the queue.
+    pub(super) fn missing(&self) -> ZResult<ZInt> {
+        let mut mask = self.frames.get_mask();
+        if let Some(next) = self.next {
+            let base = SeqNum::make(self.frames.get_base(), self.sn_resolution)?;
+            if base.precedes(next)? {
+                mask |= self.frames.get_mask_until(next)?;
+            }
+        }
+        Ok(mask)
+    }
+
+    /// Checks if all the missing frames are reported by the given AckNack.
+    pub(super) fn is_reported(&self, sn: ZInt, mask: ZInt) -> ZResult<bool> {
+        let missing = self.missing()?;
+        if missing == 0 {
+            return Ok(false);
+        }
+        let offset = SeqNum::make(sn, self.sn_resolution)?.gap(self.frames.get_base())?;
+        for i in 0..ZInt::BITS as ZInt {
+            if missing & (1 << i) != 0 {
+                let j = offset + i;
+                if j >= ZInt::BITS as ZInt || mask & (1 << j) == 0 {
+                    return Ok(false);
+                }
+            }
+        }
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zenoh_protocol::proto::TransportBody;
+
+    const RESOLUTION: ZInt = 1 << 8;
+
+    fn payload() -> FramePayload {
+        FramePayload::Messages { messages: vec![] }
+    }
+
+    #[test]
+    fn retransmission_window() {
+        let r = ConduitRetransmission::make(Priority::default(), 4, RESOLUTION).unwrap();
+        for sn in 250..RESOLUTION {
+            r.keep(sn, &[sn as u8]).unwrap();
+        }
+        for sn in 0..2 {
+            r.keep(sn, &[sn as u8]).unwrap();
+        }
+
+        // The frames 250..254 have been given up
+        let (frames, is_lost) = r.retransmit(253, 0b1011, Duration::from_secs(60));
+        assert!(is_lost);
+        assert_eq!(frames.len(), 2);
+        assert_eq!(&*frames[0], &[254]);
+        assert_eq!(&*frames[1], &[0]);
+
+        // The frames retransmitted are held off
+        let (frames, is_lost) = r.retransmit(254, 0b111, Duration::from_secs(60));
+        assert!(!is_lost);
+        assert_eq!(frames.len(), 1);
+        assert_eq!(&*frames[0], &[255]);
+        let (frames, _) = r.retransmit(254, 0b1, Duration::from_secs(0));
+        assert_eq!(frames.len(), 1);
+
+        match r.sync().unwrap().body {
+            TransportBody::Sync(sync) => {
+                assert_eq!(sync.sn, 2);
+                assert_eq!(sync.count, Some(4));
+            }
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn repair_missing() {
+        let mut r = ConduitRepair::make(Priority::default(), 16, RESOLUTION).unwrap();
+        r.frames.set_base(254).unwrap();
+        r.frames.insert(payload(), 255).unwrap();
+        r.frames.insert(payload(), 1).unwrap();
+        assert_eq!(r.missing().unwrap(), 0b101);
+
+        // The frames following the last one received are missing too
+        r.next = Some(4);
+        assert_eq!(r.missing().unwrap(), 0b110101);
+        // The frames preceding the base are not
+        r.next = Some(250);
+        assert_eq!(r.missing().unwrap(), 0b101);
+
+        // The AckNacks of the other receivers may start before or after the base
+        assert!(r.is_reported(254, 0b101).unwrap());
+        assert!(r.is_reported(252, 0b10111).unwrap());
+        assert!(!r.is_reported(252, 0b00111).unwrap());
+        assert!(!r.is_reported(0, 0b1).unwrap());
+    }
+}
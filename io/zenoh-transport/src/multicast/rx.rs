@@ -12,18 +12,31 @@
 //   ADLINK zenoh team, <zenoh@adlink-labs.tech>
 //
 use super::common::conduit::TransportChannelRx;
-use super::protocol::core::{Priority, Reliability, ZInt};
+use super::protocol::core::{PeerId, Priority, Reliability, ZInt};
 #[cfg(feature = "stats")]
 use super::protocol::proto::ZenohBody;
 use super::protocol::proto::{
-    Frame, FramePayload, Join, TransportBody, TransportMessage, ZenohMessage,
+    AckNack, Frame, FramePayload, Join, Sync, TransportBody, TransportMessage, ZenohMessage,
 };
+use super::reliability::ConduitRepair;
 use super::transport::{TransportMulticastInner, TransportMulticastPeer};
+use crate::defaults::{ZN_MULTICAST_NACK_BACKOFF, ZN_MULTICAST_REPAIR_TIMEOUT};
+use async_std::task;
+use rand::Rng;
+use std::collections::HashMap;
 use std::sync::MutexGuard;
+use std::time::{Duration, Instant};
 use zenoh_core::{bail, zerror, zread};
 use zenoh_core::{zlock, Result as ZResult};
 use zenoh_protocol_core::Locator;
 
+// The next step of the report of the missing frames of a peer
+enum Nack {
+    Wait(Instant),
+    Send(TransportMessage),
+    Done,
+}
+
 /*************************************/
 /*            TRANSPORT RX           */
 /*************************************/
@@ -70,7 +83,7 @@ impl TransportMulticastInner {
         &self,
         sn: ZInt,
         payload: FramePayload,
-        mut guard: MutexGuard<'_, TransportChannelRx>,
+        guard: &mut TransportChannelRx,
         peer: &TransportMulticastPeer,
     ) -> ZResult<()> {
         let precedes = guard.sn.precedes(sn)?;
@@ -121,6 +134,327 @@ impl TransportMulticastInner {
         }
     }
 
+    /// Handles a reliable frame of a peer retransmitting the missing ones: the frames received
+    /// ahead of the missing ones are held until these are retransmitted, and the report of the
+    /// new gaps is scheduled.
+    fn handle_lossy_frame(
+        &self,
+        sn: ZInt,
+        payload: FramePayload,
+        guard: &mut TransportChannelRx,
+        peer: &TransportMulticastPeer,
+        index: usize,
+    ) -> ZResult<()> {
+        if !guard.sn.precedes(sn)? {
+            // The frame is dropped, e.g. it has been retransmitted for another receiver
+            return self.handle_frame(sn, payload, guard, peer);
+        }
+
+        let mut repair = self.get_repair(peer, index)?;
+        let resolution = guard.sn.resolution();
+        if repair.frames.is_empty() {
+            repair.frames.set_base((guard.sn.get() + 1) % resolution)?;
+        }
+
+        // Give up the frames missing for too long to hold the frame
+        let capacity = repair.frames.capacity() as ZInt;
+        if guard.sn.gap(sn)? > capacity {
+            let first = (sn + resolution - (capacity - 1)) % resolution;
+            self.skip_lost(guard, &mut repair, first, peer)?;
+        }
+
+        let distance = (sn + resolution - repair.frames.get_base()) % resolution;
+        repair.frames.insert(payload, sn)?;
+        if distance == 0 {
+            self.pull_ready(guard, &mut repair, peer)
+        } else {
+            self.schedule_nack(peer, index, &mut repair);
+            Ok(())
+        }
+    }
+
+    fn pull_ready(
+        &self,
+        guard: &mut TransportChannelRx,
+        repair: &mut ConduitRepair,
+        peer: &TransportMulticastPeer,
+    ) -> ZResult<()> {
+        loop {
+            let sn = repair.frames.get_base();
+            match repair.frames.pull() {
+                Some(payload) => self.handle_frame(sn, payload, guard, peer)?,
+                None => return Ok(()),
+            }
+        }
+    }
+
+    /// Gives up the reliable frames missing before the given SN, and handles the frames
+    /// received before it and the ones ready from it.
+    fn skip_lost(
+        &self,
+        guard: &mut TransportChannelRx,
+        repair: &mut ConduitRepair,
+        sn: ZInt,
+        peer: &TransportMulticastPeer,
+    ) -> ZResult<()> {
+        let resolution = guard.sn.resolution();
+        let gap = (sn + resolution - repair.frames.get_base()) % resolution;
+        for _ in 0..gap.min(repair.frames.capacity() as ZInt) {
+            let base = repair.frames.get_base();
+            match repair.frames.pull() {
+                Some(payload) => self.handle_frame(base, payload, guard, peer)?,
+                None => {
+                    log::debug!(
+                        "Transport {}: {}. Reliable frame lost from {}: {}.",
+                        self.manager.config.pid,
+                        self.locator,
+                        peer.locator,
+                        base
+                    );
+                    repair.frames.set_base((base + 1) % resolution)?;
+                    guard.sn.set(base)?;
+                    guard.defrag.clear();
+                }
+            }
+        }
+        if repair.frames.get_base() != sn {
+            repair.frames.set_base(sn)?;
+            guard.sn.set((sn + resolution - 1) % resolution)?;
+            guard.defrag.clear();
+        }
+        // The frames held after the given up ones may now be ready
+        self.pull_ready(guard, repair, peer)
+    }
+
+    /// Handles a Sync advertising the last reliable frame sent by a peer: the frames missing
+    /// before it are reported, and the ones it can't retransmit anymore are given up.
+    fn handle_sync(
+        &self,
+        sn: ZInt,
+        count: Option<ZInt>,
+        guard: &mut TransportChannelRx,
+        peer: &TransportMulticastPeer,
+        index: usize,
+    ) -> ZResult<()> {
+        let mut repair = self.get_repair(peer, index)?;
+        let resolution = guard.sn.resolution();
+        if repair.frames.is_empty() {
+            repair.frames.set_base((guard.sn.get() + 1) % resolution)?;
+        }
+
+        if let Some(count) = count {
+            let first = (sn + resolution - count % resolution) % resolution;
+            if first != repair.frames.get_base() && guard.sn.precedes(first)? {
+                self.skip_lost(guard, &mut repair, first, peer)?;
+            }
+        }
+
+        repair.next = Some(sn);
+        if repair.missing()? != 0 {
+            self.schedule_nack(peer, index, &mut repair);
+        }
+        Ok(())
+    }
+
+    /// Schedules the report of the frames missing from a peer after a random back-off,
+    /// if not scheduled yet.
+    fn schedule_nack(
+        &self,
+        peer: &TransportMulticastPeer,
+        index: usize,
+        repair: &mut ConduitRepair,
+    ) {
+        if repair.nack.is_some() {
+            return;
+        }
+        let backoff = rand::thread_rng().gen_range(0..=*ZN_MULTICAST_NACK_BACKOFF);
+        repair.nack = Some(Instant::now() + Duration::from_millis(backoff));
+
+        let c_transport = self.clone();
+        let c_locator = peer.locator.clone();
+        task::spawn(async move { c_transport.nack_task(c_locator, index).await });
+    }
+
+    /// Reports the frames missing from a peer with an AckNack on the multicast locator, and
+    /// reports them again until they are received or given up.
+    async fn nack_task(&self, locator: Locator, index: usize) {
+        loop {
+            match self.poll_nack(&locator, index) {
+                Ok(Nack::Wait(deadline)) => {
+                    task::sleep(deadline.saturating_duration_since(Instant::now())).await
+                }
+                Ok(Nack::Send(mut message)) => {
+                    log::trace!(
+                        "Transport {}: {}. Reporting reliable frames missing from {}: {:?}",
+                        self.manager.config.pid,
+                        self.locator,
+                        locator,
+                        message
+                    );
+                    let link = match zread!(self.link).as_ref() {
+                        Some(l) => l.link.clone(),
+                        None => break,
+                    };
+                    if let Err(e) = link.write_transport_message(&mut message).await {
+                        log::debug!("{}", e);
+                        break;
+                    }
+                }
+                Ok(Nack::Done) => break,
+                Err(e) => {
+                    log::debug!("{}", e);
+                    break;
+                }
+            }
+        }
+    }
+
+    fn poll_nack(&self, locator: &Locator, index: usize) -> ZResult<Nack> {
+        let guard = zread!(self.peers);
+        let peer = match guard.get(locator) {
+            Some(peer) => peer,
+            None => return Ok(Nack::Done),
+        };
+        let mut repair = self.get_repair(peer, index)?;
+        let now = Instant::now();
+        match repair.nack {
+            Some(deadline) if deadline > now => return Ok(Nack::Wait(deadline)),
+            Some(_) => {}
+            None => return Ok(Nack::Done),
+        }
+
+        let mask = match repair.missing() {
+            Ok(mask) if mask != 0 => mask,
+            res => {
+                // Nothing to report anymore
+                repair.nack = None;
+                return res.map(|_| Nack::Done);
+            }
+        };
+        repair.nack = Some(now + Duration::from_millis(*ZN_MULTICAST_REPAIR_TIMEOUT));
+        Ok(Nack::Send(TransportMessage::make_ack_nack(
+            repair.priority,
+            Some(peer.pid),
+            repair.frames.get_base(),
+            Some(mask),
+            None,
+        )))
+    }
+
+    /// Handles the AckNack of another receiver reporting frames missing from a peer: the report
+    /// of the same missing frames is postponed, waiting for their retransmission.
+    fn suppress_nack(
+        &self,
+        peers: &HashMap<Locator, TransportMulticastPeer>,
+        pid: PeerId,
+        priority: Priority,
+        sn: ZInt,
+        mask: Option<ZInt>,
+    ) -> ZResult<()> {
+        let peer = match peers.values().find(|p| p.pid == pid && p.is_reliable()) {
+            Some(peer) => peer,
+            None => return Ok(()),
+        };
+        let index = self.conduit_index(peer, priority)?;
+        let mut repair = self.get_repair(peer, index)?;
+        if repair.nack.is_some() && repair.is_reported(sn, mask.unwrap_or(0))? {
+            log::trace!(
+                "Transport {}: {}. Report of the reliable frames missing from {} suppressed.",
+                self.manager.config.pid,
+                self.locator,
+                peer.locator
+            );
+            repair.nack =
+                Some(Instant::now() + Duration::from_millis(*ZN_MULTICAST_REPAIR_TIMEOUT));
+        }
+        Ok(())
+    }
+
+    /// Handles an AckNack reporting reliable frames missed by a receiver, and retransmits them
+    /// on the multicast locator.
+    fn handle_ack_nack(&self, priority: Priority, sn: ZInt, mask: Option<ZInt>) -> ZResult<()> {
+        let retransmission = match (self.retransmission.as_ref(), mask) {
+            (Some(retransmission), Some(_)) => retransmission,
+            _ => return Ok(()),
+        };
+        let index = if self.is_qos() {
+            priority as usize
+        } else if priority == Priority::default() {
+            0
+        } else {
+            bail!(
+                "Transport {}: {}. Unknown conduit {:?}.",
+                self.manager.config.pid,
+                self.locator,
+                priority
+            );
+        };
+
+        // A frame is retransmitted once for all the receivers reporting it within the hold-off
+        let r = &retransmission[index];
+        let hold_off = Duration::from_millis(*ZN_MULTICAST_REPAIR_TIMEOUT) / 2;
+        let (frames, is_lost) = r.retransmit(sn, mask.unwrap_or(0), hold_off);
+        // The receivers give up the frames which can't be retransmitted anymore on the Sync
+        let sync = if is_lost { r.sync() } else { None };
+        if frames.is_empty() && sync.is_none() {
+            return Ok(());
+        }
+
+        log::trace!(
+            "Transport {}: {}. Retransmitting {} reliable frames.",
+            self.manager.config.pid,
+            self.locator,
+            frames.len()
+        );
+        let link = self.get_link();
+        task::spawn(async move {
+            for frame in frames {
+                if let Err(e) = link.write_all(&frame).await {
+                    log::debug!("{}", e);
+                    return;
+                }
+            }
+            if let Some(mut sync) = sync {
+                if let Err(e) = link.write_transport_message(&mut sync).await {
+                    log::debug!("{}", e);
+                }
+            }
+        });
+        Ok(())
+    }
+
+    fn get_repair<'a>(
+        &self,
+        peer: &'a TransportMulticastPeer,
+        index: usize,
+    ) -> ZResult<MutexGuard<'a, ConduitRepair>> {
+        match peer.repair.as_ref() {
+            Some(repair) => Ok(zlock!(repair[index])),
+            None => bail!(
+                "Transport {}: {}. Peer {} doesn't retransmit its reliable frames.",
+                self.manager.config.pid,
+                self.locator,
+                peer.locator
+            ),
+        }
+    }
+
+    fn conduit_index(&self, peer: &TransportMulticastPeer, priority: Priority) -> ZResult<usize> {
+        if peer.is_qos() {
+            Ok(priority as usize)
+        } else if priority == Priority::default() {
+            Ok(0)
+        } else {
+            bail!(
+                "Transport {}: {}. Unknown conduit {:?} from {}.",
+                self.manager.config.pid,
+                self.locator,
+                priority,
+                peer.locator
+            );
+        }
+    }
+
     pub(super) fn handle_join_from_peer(
         &self,
         join: Join,
@@ -133,6 +467,7 @@ impl TransportMulticastInner {
             || join.sn_resolution != peer.sn_resolution
             || join.lease != peer.lease
             || join.is_qos() != peer.is_qos()
+            || join.is_reliable != peer.is_reliable()
         {
             let e = format!(
                 "Ingoring Join on {} of peer: {}. Inconsistent parameters. Version",
@@ -205,25 +540,45 @@ impl TransportMulticastInner {
                         sn,
                         payload,
                     }) => {
-                        let c = if self.is_qos() {
-                            &peer.conduit_rx[channel.priority as usize]
-                        } else if channel.priority == Priority::default() {
-                            &peer.conduit_rx[0]
+                        let index = self.conduit_index(peer, channel.priority)?;
+                        let c = &peer.conduit_rx[index];
+                        match channel.reliability {
+                            Reliability::Reliable if peer.is_reliable() => self.handle_lossy_frame(
+                                sn,
+                                payload,
+                                &mut *zlock!(c.reliable),
+                                peer,
+                                index,
+                            ),
+                            Reliability::Reliable => {
+                                self.handle_frame(sn, payload, &mut *zlock!(c.reliable), peer)
+                            }
+                            Reliability::BestEffort => {
+                                self.handle_frame(sn, payload, &mut *zlock!(c.best_effort), peer)
+                            }
+                        }
+                    }
+                    TransportBody::Sync(Sync {
+                        priority,
+                        reliability: Reliability::Reliable,
+                        sn,
+                        count,
+                    }) if peer.is_reliable() => {
+                        let index = self.conduit_index(peer, priority)?;
+                        let c = &peer.conduit_rx[index];
+                        self.handle_sync(sn, count, &mut *zlock!(c.reliable), peer, index)
+                    }
+                    TransportBody::AckNack(AckNack {
+                        priority,
+                        pid: Some(pid),
+                        sn,
+                        mask,
+                    }) => {
+                        if pid == self.manager.config.pid {
+                            self.handle_ack_nack(priority, sn, mask)
                         } else {
-                            bail!(
-                                "Transport {}: {}. Unknown conduit {:?} from {}.",
-                                self.manager.config.pid,
-                                self.locator,
-                                channel.priority,
-                                peer.locator
-                            );
-                        };
-
-                        let guard = match channel.reliability {
-                            Reliability::Reliable => zlock!(c.reliable),
-                            Reliability::BestEffort => zlock!(c.best_effort),
-                        };
-                        self.handle_frame(sn, payload, guard, peer)
+                            self.suppress_nack(&r_guard, pid, priority, sn, mask)
+                        }
                     }
                     TransportBody::Join(join) => self.handle_join_from_peer(join, peer),
                     TransportBody::Close(close) => {
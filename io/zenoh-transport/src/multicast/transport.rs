@@ -13,16 +13,19 @@
 //
 use super::common::conduit::{TransportConduitRx, TransportConduitTx};
 use super::link::{TransportLinkMulticast, TransportLinkMulticastConfig};
+use super::reliability::{ConduitRepair, ConduitRetransmission};
 #[cfg(feature = "stats")]
 use super::TransportMulticastStatsAtomic;
+use crate::defaults::ZN_RETRANSMISSION_WINDOW;
 use crate::{
     TransportManager, TransportMulticastEventHandler, TransportPeer, TransportPeerEventHandler,
 };
+use async_std::task;
 use async_trait::async_trait;
 use std::collections::HashMap;
 use std::convert::TryInto;
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::{Arc, RwLock};
+use std::sync::{Arc, Mutex, RwLock};
 use std::time::Duration;
 use zenoh_collections::{Timed, TimedEvent, TimedHandle, Timer};
 use zenoh_core::{bail, zread, zwrite, Result as ZResult};
@@ -44,6 +47,8 @@ pub(super) struct TransportMulticastPeer {
     pub(super) whatchdog: Arc<AtomicBool>,
     pub(super) handle: TimedHandle,
     pub(super) conduit_rx: Box<[TransportConduitRx]>,
+    // The repair of the reliable frames, if the peer retransmits them
+    pub(super) repair: Option<Arc<[Mutex<ConduitRepair>]>>,
     pub(super) handler: Arc<dyn TransportPeerEventHandler>,
 }
 
@@ -55,6 +60,10 @@ impl TransportMulticastPeer {
     pub(super) fn is_qos(&self) -> bool {
         self.conduit_rx[0].priority != Priority::default()
     }
+
+    pub(super) fn is_reliable(&self) -> bool {
+        self.repair.is_some()
+    }
 }
 
 #[derive(Clone)]
@@ -84,6 +93,8 @@ pub(crate) struct TransportMulticastInner {
     pub(super) locator: Locator,
     // Tx conduits
     pub(super) conduit_tx: Arc<[TransportConduitTx]>,
+    // The reliable frames kept for their retransmission, if enabled
+    pub(super) retransmission: Option<Arc<[ConduitRetransmission]>>,
     // Remote peers
     pub(super) peers: Arc<RwLock<HashMap<Locator, TransportMulticastPeer>>>,
     // The multicast link
@@ -128,10 +139,25 @@ impl TransportMulticastInner {
             }
         }
 
+        let retransmission = if config.manager.config.multicast.is_reliable {
+            let mut retransmission = vec![];
+            for c in conduit_tx.iter() {
+                retransmission.push(ConduitRetransmission::make(
+                    c.priority,
+                    *ZN_RETRANSMISSION_WINDOW,
+                    config.manager.config.sn_resolution,
+                )?);
+            }
+            Some(retransmission.into_boxed_slice().into())
+        } else {
+            None
+        };
+
         let ti = TransportMulticastInner {
             manager: config.manager,
             locator: config.link.get_dst().to_owned(),
             conduit_tx: conduit_tx.into_boxed_slice().into(),
+            retransmission,
             peers: Arc::new(RwLock::new(HashMap::new())),
             link: Arc::new(RwLock::new(None)),
             callback: Arc::new(RwLock::new(None)),
@@ -167,6 +193,10 @@ impl TransportMulticastInner {
         false
     }
 
+    pub(crate) fn is_reliable(&self) -> bool {
+        self.retransmission.is_some()
+    }
+
     pub(crate) fn get_callback(&self) -> Option<Arc<dyn TransportMulticastEventHandler>> {
         zread!(self.callback).clone()
     }
@@ -273,7 +303,7 @@ impl TransportMulticastInner {
                     sn_resolution: self.manager.config.sn_resolution,
                     batch_size,
                 };
-                l.start_tx(config, self.conduit_tx.clone());
+                l.start_tx(config, self.conduit_tx.clone(), self.retransmission.clone());
                 Ok(())
             }
             None => {
@@ -383,6 +413,20 @@ impl TransportMulticastInner {
         }
         .into_boxed_slice();
 
+        let repair = if join.is_reliable {
+            let mut repair = Vec::with_capacity(conduit_rx.len());
+            for c in conduit_rx.iter() {
+                repair.push(Mutex::new(ConduitRepair::make(
+                    c.priority,
+                    *ZN_RETRANSMISSION_WINDOW,
+                    join.sn_resolution,
+                )?));
+            }
+            Some(repair.into_boxed_slice().into())
+        } else {
+            None
+        };
+
         // Create lease event
         let whatchdog = Arc::new(AtomicBool::new(false));
         let event = TransportMulticastPeerLeaseTimer {
@@ -404,23 +448,26 @@ impl TransportMulticastInner {
             whatchdog,
             handle,
             conduit_rx,
+            repair,
             handler,
         };
         {
             zwrite!(self.peers).insert(locator.clone(), peer);
         }
 
-        // Add the event to the timer
-        self.timer.add(event);
+        // Add the event to the timer without blocking: the timer task may wait for this thread
+        let timer = self.timer.clone();
+        task::spawn(async move { timer.add_async(event).await });
 
         log::debug!(
-                "New transport joined on {}: pid {}, whatami {}, sn resolution {}, locator {}, qos {}, initial sn: {}",
+                "New transport joined on {}: pid {}, whatami {}, sn resolution {}, locator {}, qos {}, reliable {}, initial sn: {}",
                 self.locator,
                 join.pid,
                 join.whatami,
                 join.sn_resolution,
                 locator,
                 join.is_qos(),
+                join.is_reliable,
                 join.next_sns,
             );
 
@@ -17,6 +17,7 @@ use super::protocol::proto::{
 use super::Primitives;
 use crate::TransportPeerEventHandler;
 use std::any::Any;
+use std::time::Duration;
 use zenoh_core::{bail, Result as ZResult};
 use zenoh_link::Link;
 
@@ -96,6 +97,7 @@ impl<P: 'static + Primitives> TransportPeerEventHandler for DeMux<P> {
                             rep.qid,
                             replier.kind,
                             replier.id,
+                            rep.is_error,
                             key,
                             data_info,
                             payload,
@@ -121,7 +123,7 @@ impl<P: 'static + Primitives> TransportPeerEventHandler for DeMux<P> {
                 qid,
                 target,
                 consolidation,
-                ..
+                timeout,
             }) => {
                 self.primitives.send_query(
                     &key,
@@ -129,6 +131,7 @@ impl<P: 'static + Primitives> TransportPeerEventHandler for DeMux<P> {
                     qid,
                     target.unwrap_or_default(),
                     consolidation,
+                    timeout.map(Duration::from_millis),
                     msg.routing_context,
                 );
             }
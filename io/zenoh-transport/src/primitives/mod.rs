@@ -23,6 +23,7 @@ use super::protocol::io::ZBuf;
 use super::protocol::proto::{DataInfo, RoutingContext};
 pub use demux::*;
 pub use mux::*;
+use std::time::Duration;
 
 pub trait Primitives: Send + Sync {
     fn decl_resource(&self, expr_id: ZInt, key_expr: &KeyExpr);
@@ -63,6 +64,7 @@ pub trait Primitives: Send + Sync {
         routing_context: Option<RoutingContext>,
     );
 
+    #[allow(clippy::too_many_arguments)]
     fn send_query(
         &self,
         key_expr: &KeyExpr,
@@ -70,14 +72,17 @@ pub trait Primitives: Send + Sync {
         qid: ZInt,
         target: QueryTarget,
         consolidation: ConsolidationStrategy,
+        timeout: Option<Duration>,
         routing_context: Option<RoutingContext>,
     );
 
+    #[allow(clippy::too_many_arguments)]
     fn send_reply_data(
         &self,
         qid: ZInt,
         replier_kind: ZInt,
         replier_id: PeerId,
+        is_error: bool,
         key_expr: KeyExpr,
         info: Option<DataInfo>,
         payload: ZBuf,
@@ -154,6 +159,7 @@ impl Primitives for DummyPrimitives {
         _qid: ZInt,
         _target: QueryTarget,
         _consolidation: ConsolidationStrategy,
+        _timeout: Option<Duration>,
         _routing_context: Option<RoutingContext>,
     ) {
     }
@@ -162,6 +168,7 @@ impl Primitives for DummyPrimitives {
         _qid: ZInt,
         _replier_kind: ZInt,
         _replier_id: PeerId,
+        _is_error: bool,
         _key_expr: KeyExpr,
         _info: Option<DataInfo>,
         _payload: ZBuf,
@@ -23,6 +23,7 @@ use super::protocol::proto::{
     Subscriber, ZenohMessage,
 };
 use super::Primitives;
+use std::time::Duration;
 
 pub struct Mux {
     handler: TransportUnicast,
@@ -162,6 +163,7 @@ impl Primitives for Mux {
         qid: ZInt,
         target: QueryTarget,
         consolidation: ConsolidationStrategy,
+        timeout: Option<Duration>,
         routing_context: Option<RoutingContext>,
     ) {
         let target_opt = if target == QueryTarget::default() {
@@ -175,6 +177,7 @@ impl Primitives for Mux {
             qid,
             target_opt,
             consolidation,
+            timeout.map(|timeout| timeout.as_millis() as ZInt),
             routing_context,
             None,
         ));
@@ -185,6 +188,7 @@ impl Primitives for Mux {
         qid: ZInt,
         replier_kind: ZInt,
         replier_id: PeerId,
+        is_error: bool,
         key_expr: KeyExpr,
         data_info: Option<DataInfo>,
         payload: ZBuf,
@@ -202,6 +206,7 @@ impl Primitives for Mux {
                     kind: replier_kind,
                     id: replier_id,
                 }),
+                is_error,
             )),
             None,
         ));
@@ -211,7 +216,7 @@ impl Primitives for Mux {
         let _ = self.handler.handle_message(ZenohMessage::make_unit(
             zmsg::default_channel::REPLY,
             zmsg::default_congestion_control::REPLY,
-            Some(ReplyContext::new(qid, None)),
+            Some(ReplyContext::new(qid, None, false)),
             None,
         ));
     }
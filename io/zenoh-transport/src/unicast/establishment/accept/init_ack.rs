@@ -12,7 +12,10 @@
 //   ADLINK zenoh team, <zenoh@adlink-labs.tech>
 //
 use crate::unicast::establishment::authenticator::AuthenticatedPeerLink;
-use crate::unicast::establishment::{attachment_from_properties, Cookie, EstablishmentProperties};
+use crate::unicast::establishment::{
+    attachment_from_properties, compression_from_property, Cookie, EstablishmentProperties,
+    COMPRESSION_PROPERTY,
+};
 use crate::TransportManager;
 use rand::Rng;
 use zenoh_core::{zasynclock, zasyncread};
@@ -90,6 +93,18 @@ pub(super) async fn send(
                 .map_err(|e| (e, None))?;
         }
     }
+
+    // Accept the compression requested by the other side if the algorithm is supported,
+    // and keep it in the cookie until the link is added to the transport
+    if let Some(p) = input.init_syn_properties.remove(COMPRESSION_PROPERTY) {
+        if compression_from_property(&p)
+            .map_err(|e| (e, Some(tmsg::close_reason::INVALID)))?
+            .is_some()
+        {
+            ps_cookie.insert(p.clone()).map_err(|e| (e, None))?;
+            ps_attachment.insert(p).map_err(|e| (e, None))?;
+        }
+    }
     let attachment = attachment_from_properties(&ps_attachment).ok();
 
     let encrypted = cookie
@@ -18,7 +18,7 @@ mod open_syn;
 
 use crate::unicast::establishment::authenticator::AuthenticatedPeerLink;
 use crate::unicast::establishment::{
-    close_link, transport_finalize, transport_init, InputFinalize,
+    close_link, session_secret, transport_finalize, transport_init, transport_link, InputFinalize,
 };
 use crate::TransportManager;
 use zenoh_core::Result as ZResult;
@@ -70,6 +70,7 @@ pub(crate) async fn accept_link(
         sn_resolution: output.cookie.sn_resolution,
         is_shm: output.is_shm,
         is_qos: output.cookie.is_qos,
+        auth_ids: super::auth_ids(manager, &pid).await,
     };
     let transport = step!(transport_init(manager, input).await);
 
@@ -92,10 +93,14 @@ pub(crate) async fn accept_link(
     }
 
     // Add the link to the transport
+    let direction = LinkUnicastDirection::Inbound;
+    let secret = session_secret(manager, auth_link).await;
+    let tlink = step!(transport_link(link, output.compression, secret, &direction)
+        .map_err(|e| (e, Some(tmsg::close_reason::INVALID))));
     let _ = step!(step!(transport
         .get_inner()
         .map_err(|e| (e, Some(tmsg::close_reason::INVALID))))
-    .add_link(link.clone(), LinkUnicastDirection::Inbound)
+    .add_link(tlink, direction)
     .map_err(|e| (e, Some(tmsg::close_reason::MAX_LINKS))));
 
     // Sync the RX sequence number
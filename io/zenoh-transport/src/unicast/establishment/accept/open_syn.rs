@@ -12,7 +12,10 @@
 //   ADLINK zenoh team, <zenoh@adlink-labs.tech>
 //
 use super::super::authenticator::AuthenticatedPeerLink;
-use super::super::{attachment_from_properties, properties_from_attachment};
+use super::super::{
+    attachment_from_properties, compression_from_property, properties_from_attachment,
+    COMPRESSION_PROPERTY,
+};
 use super::super::{Cookie, EstablishmentProperties};
 use super::AResult;
 use crate::TransportManager;
@@ -33,6 +36,7 @@ pub(super) struct Output {
     pub(super) initial_sn: ZInt,
     pub(super) lease: Duration,
     pub(super) is_shm: bool,
+    pub(super) compression: Option<usize>,
     pub(super) open_ack_attachment: Option<Attachment>,
 }
 #[allow(unused_mut)]
@@ -93,6 +97,14 @@ pub(super) async fn recv(
         None => EstablishmentProperties::new(),
     };
 
+    // The compression agreed in the InitAck
+    let compression = match ps_cookie.remove(COMPRESSION_PROPERTY) {
+        Some(p) => {
+            compression_from_property(&p).map_err(|e| (e, Some(tmsg::close_reason::INVALID)))?
+        }
+        None => None,
+    };
+
     let mut is_shm = false;
     let mut ps_attachment = EstablishmentProperties::new();
     for pa in zasyncread!(manager.state.unicast.peer_authenticator).iter() {
@@ -142,6 +154,7 @@ pub(super) async fn recv(
         initial_sn: open_syn.initial_sn,
         lease: open_syn.lease,
         is_shm,
+        compression,
         open_ack_attachment: attachment_from_properties(&ps_attachment).ok(),
     };
     Ok(output)
@@ -194,6 +194,25 @@ impl Hash for PeerAuthenticator {
     }
 }
 
+/// The identity a peer has been authenticated with.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum AuthId {
+    /// The name of a user authenticated with its password.
+    Username(String),
+    /// The fingerprint of an authenticated RSA public key, i.e. the hexadecimal
+    /// SHA3-256 digest of its PKCS#1 DER encoding.
+    PublicKey(String),
+}
+
+impl fmt::Display for AuthId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AuthId::Username(user) => write!(f, "user:{}", user),
+            AuthId::PublicKey(fingerprint) => write!(f, "pubkey:{}", fingerprint),
+        }
+    }
+}
+
 // Authenticated peer link
 #[derive(Debug)]
 pub struct AuthenticatedPeerLink {
@@ -305,6 +324,26 @@ pub trait PeerAuthenticatorTrait: Send + Sync {
     ///
     async fn handle_link_err(&self, link: &AuthenticatedPeerLink);
 
+    /// Return the secret agreed with the peer during the establishment of the link, if any.
+    /// The secret is used to encrypt the batches exchanged on the link and it is removed
+    /// from the authenticator once returned.
+    ///
+    /// # Arguments
+    /// * `link` - The [`AuthenticatedPeerLink`][AuthenticatedPeerLink] that has been established
+    ///
+    async fn take_session_secret(&self, _link: &AuthenticatedPeerLink) -> Option<Vec<u8>> {
+        None
+    }
+
+    /// Return the identity the peer has been authenticated with, if any.
+    ///
+    /// # Arguments
+    /// * `peer_id` - The [`PeerId`][PeerId] of the authenticated peer
+    ///
+    async fn get_auth_id(&self, _peer_id: &PeerId) -> Option<AuthId> {
+        None
+    }
+
     /// Handle any error on a link. This callback is mainly used to clean-up any internal state
     /// of the authenticator in such a way no unnecessary data is left around
     ///
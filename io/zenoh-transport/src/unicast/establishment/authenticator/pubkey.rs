@@ -12,28 +12,30 @@
 //   ADLINK zenoh team, <zenoh@adlink-labs.tech>
 //
 use super::{
-    AuthenticatedPeerLink, PeerAuthenticator, PeerAuthenticatorId, PeerAuthenticatorTrait,
+    AuthId, AuthenticatedPeerLink, PeerAuthenticator, PeerAuthenticatorId, PeerAuthenticatorTrait,
 };
-use super::{PeerId, WBuf, ZBuf, ZInt};
+use super::{Locator, PeerId, WBuf, ZBuf, ZInt};
 use crate::unicast::establishment::Cookie;
 use async_std::sync::{Arc, Mutex};
 use async_trait::async_trait;
-use rand::SeedableRng;
-use rsa::pkcs1::{FromRsaPrivateKey, FromRsaPublicKey};
+use rand::{RngCore, SeedableRng};
+use rsa::pkcs1::{FromRsaPrivateKey, FromRsaPublicKey, ToRsaPublicKey};
 use rsa::{BigUint, PaddingScheme, PublicKey, PublicKeyParts, RsaPrivateKey, RsaPublicKey};
 use std::collections::HashMap;
 use std::path::Path;
 use zenoh_buffers::reader::HasReader;
 use zenoh_buffers::{SplitBuffer, ZBufReader};
-use zenoh_cfg_properties::config::ZN_AUTH_RSA_KEY_SIZE_DEFAULT;
+use zenoh_cfg_properties::config::{ZN_AUTH_RSA_ENCRYPTION_DEFAULT, ZN_AUTH_RSA_KEY_SIZE_DEFAULT};
 use zenoh_config::Config;
 use zenoh_core::{bail, zparse, Result as ZResult};
 use zenoh_core::{zasynclock, zerror};
-use zenoh_crypto::PseudoRng;
+use zenoh_crypto::{hmac, PseudoRng};
 use zenoh_protocol::io::{WBufCodec, ZBufCodec};
 
 const WBUF_SIZE: usize = 64;
 const MULTILINK_VERSION: ZInt = 1;
+// The size of each share of the session secret
+const SESSION_KEY_SIZE: usize = 32;
 
 /// # Attachment decorator
 ///
@@ -65,9 +67,12 @@ const MULTILINK_VERSION: ZInt = 1;
 /// +---------------+
 /// ~  public key   ~
 /// +---------------+
+/// ~  encryption   ~ -- Optional, 1 if the encryption of the link is requested
+/// +---------------+
 struct InitSynProperty {
     version: ZInt,
     alice_pubkey: RsaPublicKey,
+    encryption: bool,
 }
 
 /*************************************/
@@ -81,9 +86,12 @@ struct InitSynProperty {
 /// +---------------+
 /// ~ ciphered nonce~
 /// +---------------+
+/// ~ ciphered key  ~ -- Optional, Bob's share of the session secret
+/// +---------------+
 struct InitAckProperty {
     bob_pubkey: RsaPublicKey,
     nonce_encrypted_with_alice_pubkey: Vec<u8>,
+    key_encrypted_with_alice_pubkey: Option<Vec<u8>>,
 }
 
 /*************************************/
@@ -95,8 +103,11 @@ struct InitAckProperty {
 /// +-+-+-+---------+
 /// ~ ciphered nonce~
 /// +---------------+
+/// ~ ciphered key  ~ -- Optional, Alice's share of the session secret
+/// +---------------+
 struct OpenSynProperty {
     nonce_encrypted_with_bob_pubkey: Vec<u8>,
+    key_encrypted_with_bob_pubkey: Option<Vec<u8>>,
 }
 
 trait WPubKey {
@@ -109,6 +120,7 @@ impl WPubKey for WBuf {
     fn write_init_syn_property_multilink(&mut self, init_syn_property: &InitSynProperty) -> bool {
         self.write_zint(init_syn_property.version)
             && self.write_rsa_pub_key(&init_syn_property.alice_pubkey)
+            && self.write_zint(if init_syn_property.encryption { 1 } else { 0 })
     }
     fn write_init_ack_property_multilink(&mut self, init_ack_property: &InitAckProperty) -> bool {
         self.write_rsa_pub_key(&init_ack_property.bob_pubkey)
@@ -117,9 +129,17 @@ impl WPubKey for WBuf {
                     .nonce_encrypted_with_alice_pubkey
                     .as_slice(),
             )
+            && match init_ack_property.key_encrypted_with_alice_pubkey.as_ref() {
+                Some(k) => self.write_bytes_array(k.as_slice()),
+                None => true,
+            }
     }
     fn write_open_syn_property_multilink(&mut self, open_syn_property: &OpenSynProperty) -> bool {
         self.write_bytes_array(open_syn_property.nonce_encrypted_with_bob_pubkey.as_slice())
+            && match open_syn_property.key_encrypted_with_bob_pubkey.as_ref() {
+                Some(k) => self.write_bytes_array(k.as_slice()),
+                None => true,
+            }
     }
     fn write_rsa_pub_key(&mut self, pub_key: &RsaPublicKey) -> bool {
         self.write_bytes_array(pub_key.n().to_bytes_le().as_slice())
@@ -137,24 +157,31 @@ impl ZPubKey for ZBufReader<'_> {
     fn read_init_syn_property_multilink(&mut self) -> Option<InitSynProperty> {
         let version = self.read_zint()?;
         let alice_pubkey = self.read_rsa_pub_key()?;
+        // Peers not supporting the encryption do not include the flag
+        let encryption = self.read_zint() == Some(1);
 
         Some(InitSynProperty {
             version,
             alice_pubkey,
+            encryption,
         })
     }
     fn read_init_ack_property_multilink(&mut self) -> Option<InitAckProperty> {
         let bob_pubkey = self.read_rsa_pub_key()?;
         let nonce_encrypted_with_alice_pubkey = self.read_bytes_array()?;
+        let key_encrypted_with_alice_pubkey = self.read_bytes_array();
         Some(InitAckProperty {
             bob_pubkey,
             nonce_encrypted_with_alice_pubkey,
+            key_encrypted_with_alice_pubkey,
         })
     }
     fn read_open_syn_property_multilink(&mut self) -> Option<OpenSynProperty> {
         let nonce_encrypted_with_bob_pubkey = self.read_bytes_array()?;
+        let key_encrypted_with_bob_pubkey = self.read_bytes_array();
         Some(OpenSynProperty {
             nonce_encrypted_with_bob_pubkey,
+            key_encrypted_with_bob_pubkey,
         })
     }
     fn read_rsa_pub_key(&mut self) -> Option<RsaPublicKey> {
@@ -165,6 +192,21 @@ impl ZPubKey for ZBufReader<'_> {
     }
 }
 
+/*************************************/
+/*          Session secret           */
+/*************************************/
+// Each side contributes a random share of the session secret, sent encrypted with the
+// public key of the other side.
+fn session_key(prng: &mut PseudoRng) -> Vec<u8> {
+    let mut key = vec![0_u8; SESSION_KEY_SIZE];
+    prng.fill_bytes(&mut key);
+    key
+}
+
+fn session_secret(alice_key: &[u8], bob_key: &[u8]) -> ZResult<Vec<u8>> {
+    hmac::sign(bob_key, alice_key)
+}
+
 /*************************************/
 /*          Authenticator            */
 /*************************************/
@@ -172,11 +214,14 @@ struct InnerState {
     prng: PseudoRng,
     known_keys: Option<Vec<RsaPublicKey>>,
     authenticated: HashMap<PeerId, Option<RsaPublicKey>>,
+    // The session secrets agreed on the links being established
+    secrets: HashMap<(Locator, Locator), Vec<u8>>,
 }
 
 pub struct PubKeyAuthenticator {
     pub_key: RsaPublicKey,
     pri_key: RsaPrivateKey,
+    encryption: bool,
     state: Mutex<InnerState>,
 }
 
@@ -185,10 +230,12 @@ impl PubKeyAuthenticator {
         PubKeyAuthenticator {
             pub_key,
             pri_key,
+            encryption: false,
             state: Mutex::new(InnerState {
                 prng: PseudoRng::from_entropy(),
                 known_keys: None,
                 authenticated: HashMap::new(),
+                secrets: HashMap::new(),
             }),
         }
     }
@@ -202,15 +249,24 @@ impl PubKeyAuthenticator {
         let pka = PubKeyAuthenticator {
             pub_key,
             pri_key,
+            encryption: false,
             state: Mutex::new(InnerState {
                 prng,
                 known_keys: None,
                 authenticated: HashMap::new(),
+                secrets: HashMap::new(),
             }),
         };
         Ok(pka)
     }
 
+    /// Request the encryption of the links with a session secret derived during the handshake.
+    /// When enabled, the links of the peers not requesting the encryption are rejected.
+    pub fn encryption(mut self, encryption: bool) -> Self {
+        self.encryption = encryption;
+        self
+    }
+
     pub async fn add_key(&self, key: RsaPublicKey) -> ZResult<()> {
         let mut guard = zasynclock!(self.state);
         match guard.known_keys.as_mut() {
@@ -239,6 +295,9 @@ impl PubKeyAuthenticator {
 
     pub async fn from_config(config: &Config) -> ZResult<Option<PubKeyAuthenticator>> {
         let c = config.transport().auth().pubkey();
+        let encryption = c
+            .encryption()
+            .unwrap_or(zparse!(ZN_AUTH_RSA_ENCRYPTION_DEFAULT)?);
 
         // @TODO: support PubKey keys import
 
@@ -249,7 +308,7 @@ impl PubKeyAuthenticator {
                     .map_err(|e| zerror!("Rsa Public Key: {}", e))?;
                 let pri_key = RsaPrivateKey::from_pkcs1_pem(private)
                     .map_err(|e| zerror!("Rsa Private Key: {}", e))?;
-                return Ok(Some(Self::new(pub_key, pri_key)));
+                return Ok(Some(Self::new(pub_key, pri_key).encryption(encryption)));
             }
             (Some(_), None) => {
                 bail!("Missing Rsa Private Key: PEM")
@@ -269,7 +328,7 @@ impl PubKeyAuthenticator {
                 let path = Path::new(private);
                 let pri_key = RsaPrivateKey::read_pkcs1_pem_file(path)
                     .map_err(|e| zerror!("Rsa Private Key: {}", e))?;
-                return Ok(Some(Self::new(pub_key, pri_key)));
+                return Ok(Some(Self::new(pub_key, pri_key).encryption(encryption)));
             }
             (Some(_), None) => {
                 bail!("Missing Rsa Private Key: file")
@@ -280,6 +339,12 @@ impl PubKeyAuthenticator {
             (None, None) => {}
         }
 
+        // The encryption does not require the keys to be known in advance: the handshake does not
+        // authenticate them, so the encryption only protects against passive eavesdroppers
+        if encryption {
+            return Ok(Some(Self::make()?.encryption(true)));
+        }
+
         Ok(None)
     }
 }
@@ -302,6 +367,7 @@ impl PeerAuthenticatorTrait for PubKeyAuthenticator {
         let init_syn_property = InitSynProperty {
             version: MULTILINK_VERSION,
             alice_pubkey: self.pub_key.clone(),
+            encryption: self.encryption,
         };
 
         let mut wbuf = WBuf::new(WBUF_SIZE, false);
@@ -334,6 +400,9 @@ impl PeerAuthenticatorTrait for PubKeyAuthenticator {
                 if init_syn_property.version != MULTILINK_VERSION {
                     bail!("PubKey version not supported on link: {}", link);
                 }
+                if self.encryption && !init_syn_property.encryption {
+                    bail!("Encryption not requested on link: {}", link);
+                }
 
                 // Check if the peer is already present
                 let mut guard = zasynclock!(self.state);
@@ -384,14 +453,33 @@ impl PeerAuthenticatorTrait for PubKeyAuthenticator {
                     &nonce_bytes.contiguous(),
                 )?;
 
+                // Generate Bob's share of the session secret if the encryption is requested
+                let bob_key = if init_syn_property.encryption {
+                    Some(session_key(&mut guard.prng))
+                } else {
+                    None
+                };
+                let key_encrypted_with_alice_pubkey = match bob_key.as_ref() {
+                    Some(bk) => Some(init_syn_property.alice_pubkey.encrypt(
+                        &mut guard.prng,
+                        PaddingScheme::PKCS1v15Encrypt,
+                        bk,
+                    )?),
+                    None => None,
+                };
+
                 let init_ack_property = InitAckProperty {
                     bob_pubkey: self.pub_key.clone(),
                     nonce_encrypted_with_alice_pubkey,
+                    key_encrypted_with_alice_pubkey,
                 };
 
-                // Store the public key in the cookie
+                // Store the public key and Bob's share of the session secret in the cookie
                 let mut wbuf = WBuf::new(WBUF_SIZE, false);
-                let res = wbuf.write_rsa_pub_key(&init_syn_property.alice_pubkey);
+                let mut res = wbuf.write_rsa_pub_key(&init_syn_property.alice_pubkey);
+                if let Some(bk) = bob_key.as_ref() {
+                    res = res && wbuf.write_bytes_array(bk.as_slice());
+                }
                 if !res {
                     bail!("Failed to serialize InitAck on link: {}", link);
                 }
@@ -412,6 +500,10 @@ impl PeerAuthenticatorTrait for PubKeyAuthenticator {
             }
             // The connecting zenoh peer does not want to do multilink
             None => {
+                if self.encryption {
+                    bail!("Encryption not requested on link: {}", link);
+                }
+
                 let guard = zasynclock!(self.state);
                 if guard.authenticated.get(&cookie.pid).is_some() {
                     // The peer is already present but no multilink intereset is declared.
@@ -434,7 +526,12 @@ impl PeerAuthenticatorTrait for PubKeyAuthenticator {
     ) -> ZResult<Option<Vec<u8>>> {
         let pk = match property {
             Some(pk) => pk,
-            None => return Ok(None),
+            None => {
+                if self.encryption {
+                    bail!("Encryption not supported on link: {}", link);
+                }
+                return Ok(None);
+            }
         };
 
         let zbuf: ZBuf = pk.into();
@@ -457,10 +554,35 @@ impl PeerAuthenticatorTrait for PubKeyAuthenticator {
             PaddingScheme::PKCS1v15Encrypt,
             &nonce[..],
         )?;
+
+        // Complete the session secret with Alice's share if the encryption was requested
+        let key_encrypted_with_bob_pubkey = if self.encryption {
+            let key_encrypted_with_alice_pubkey = init_ack_property
+                .key_encrypted_with_alice_pubkey
+                .ok_or_else(|| zerror!("Encryption not supported on link: {}", link))?;
+            let bob_key = self.pri_key.decrypt(
+                PaddingScheme::PKCS1v15Encrypt,
+                key_encrypted_with_alice_pubkey.as_slice(),
+            )?;
+            let alice_key = session_key(&mut guard.prng);
+            let key_encrypted_with_bob_pubkey = init_ack_property.bob_pubkey.encrypt(
+                &mut guard.prng,
+                PaddingScheme::PKCS1v15Encrypt,
+                &alice_key[..],
+            )?;
+            let secret = session_secret(&alice_key, &bob_key)?;
+            guard
+                .secrets
+                .insert((link.src.clone(), link.dst.clone()), secret);
+            Some(key_encrypted_with_bob_pubkey)
+        } else {
+            None
+        };
         drop(guard);
 
         let open_syn_property = OpenSynProperty {
             nonce_encrypted_with_bob_pubkey,
+            key_encrypted_with_bob_pubkey,
         };
 
         // Encode the OpenSyn property
@@ -506,11 +628,26 @@ impl PeerAuthenticatorTrait for PubKeyAuthenticator {
                 }
 
                 let zbuf: ZBuf = cke.into();
-                let alice_pubkey = zbuf
-                    .reader()
+                let mut reader = zbuf.reader();
+                let alice_pubkey = reader
                     .read_rsa_pub_key()
                     .ok_or_else(|| zerror!("Received invalid InitSyn on link: {}", link))?;
 
+                // Derive the session secret if the encryption was requested
+                let secret = match reader.read_bytes_array() {
+                    Some(bob_key) => {
+                        let key_encrypted_with_bob_pubkey = open_syn_property
+                            .key_encrypted_with_bob_pubkey
+                            .ok_or_else(|| zerror!("Missing session key on link: {}", link))?;
+                        let alice_key = self.pri_key.decrypt(
+                            PaddingScheme::PKCS1v15Encrypt,
+                            key_encrypted_with_bob_pubkey.as_slice(),
+                        )?;
+                        Some(session_secret(&alice_key, &bob_key)?)
+                    }
+                    None => None,
+                };
+
                 let mut guard = zasynclock!(self.state);
                 match guard.authenticated.get(&cookie.pid) {
                     Some(apk) => match apk {
@@ -530,6 +667,11 @@ impl PeerAuthenticatorTrait for PubKeyAuthenticator {
                         guard.authenticated.insert(cookie.pid, Some(alice_pubkey));
                     }
                 }
+                if let Some(secret) = secret {
+                    guard
+                        .secrets
+                        .insert((link.src.clone(), link.dst.clone()), secret);
+                }
             }
             (None, None) => {
                 // No multilink
@@ -558,12 +700,31 @@ impl PeerAuthenticatorTrait for PubKeyAuthenticator {
     }
 
     async fn handle_link_err(&self, link: &AuthenticatedPeerLink) {
+        let mut guard = zasynclock!(self.state);
+        guard.secrets.remove(&(link.src.clone(), link.dst.clone()));
         // Need to check if it authenticated and remove it if this is the last link
         if let Some(pid) = link.peer_id.as_ref() {
-            zasynclock!(self.state).authenticated.remove(pid);
+            guard.authenticated.remove(pid);
         }
     }
 
+    async fn take_session_secret(&self, link: &AuthenticatedPeerLink) -> Option<Vec<u8>> {
+        zasynclock!(self.state)
+            .secrets
+            .remove(&(link.src.clone(), link.dst.clone()))
+    }
+
+    async fn get_auth_id(&self, peer_id: &PeerId) -> Option<AuthId> {
+        let guard = zasynclock!(self.state);
+        let pub_key = guard.authenticated.get(peer_id)?.as_ref()?;
+        let der = pub_key.to_pkcs1_der().ok()?;
+        let fingerprint = hmac::digest(der.as_der())
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect();
+        Some(AuthId::PublicKey(fingerprint))
+    }
+
     async fn handle_close(&self, peer_id: &PeerId) {
         zasynclock!(self.state).authenticated.remove(peer_id);
     }
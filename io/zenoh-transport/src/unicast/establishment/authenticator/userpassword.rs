@@ -12,7 +12,7 @@
 //   ADLINK zenoh team, <zenoh@adlink-labs.tech>
 //
 use super::{
-    AuthenticatedPeerLink, PeerAuthenticator, PeerAuthenticatorId, PeerAuthenticatorTrait,
+    AuthId, AuthenticatedPeerLink, PeerAuthenticator, PeerAuthenticatorId, PeerAuthenticatorTrait,
 };
 use super::{Locator, PeerId, WBuf, ZBuf, ZInt};
 use crate::unicast::establishment::Cookie;
@@ -388,6 +388,12 @@ impl PeerAuthenticatorTrait for UserPasswordAuthenticator {
         }
     }
 
+    async fn get_auth_id(&self, peer_id: &PeerId) -> Option<AuthId> {
+        zasynclock!(self.authenticated).get(peer_id).map(|auth| {
+            AuthId::Username(String::from_utf8_lossy(&auth.credentials.user).into_owned())
+        })
+    }
+
     async fn handle_close(&self, peer_id: &PeerId) {
         zasynclock!(self.authenticated).remove(peer_id);
     }
@@ -20,9 +20,10 @@ use super::protocol::core::{PeerId, Property, WhatAmI, ZInt};
 use super::protocol::io::{WBuf, ZBuf};
 use super::protocol::proto::{Attachment, TransportMessage};
 use super::{TransportConfigUnicast, TransportPeer, TransportUnicast};
-use authenticator::AuthenticatedPeerLink;
+use authenticator::{AuthId, AuthenticatedPeerLink};
 use rand::Rng;
 use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
 use std::time::Duration;
 use zenoh_buffers::buffer::CopyBuffer;
 use zenoh_buffers::reader::{HasReader, Reader};
@@ -30,7 +31,9 @@ use zenoh_buffers::SplitBuffer;
 use zenoh_core::{bail, zerror};
 use zenoh_core::{zasynclock, zasyncread, Result as ZResult};
 use zenoh_crypto::{BlockCipher, PseudoRng};
-use zenoh_link::{Link, LinkUnicast};
+use zenoh_link::compression::{LinkUnicastCompressed, COMPRESSION_LZ4};
+use zenoh_link::encryption::LinkUnicastEncrypted;
+use zenoh_link::{Link, LinkUnicast, LinkUnicastDirection};
 use zenoh_protocol::io::{WBufCodec, ZBufCodec};
 
 const WBUF_SIZE: usize = 64;
@@ -96,6 +99,97 @@ pub(super) fn properties_from_attachment(att: Attachment) -> ZResult<Establishme
     Ok(EstablishmentProperties(ps))
 }
 
+/*************************************/
+/*          AUTHENTICATION           */
+/*************************************/
+// The identities the peer has been authenticated with by the peer authenticators
+pub(super) async fn auth_ids(manager: &TransportManager, pid: &PeerId) -> Vec<AuthId> {
+    let mut auth_ids = vec![];
+    for pa in zasyncread!(manager.state.unicast.peer_authenticator).iter() {
+        if let Some(id) = pa.get_auth_id(pid).await {
+            auth_ids.push(id);
+        }
+    }
+    auth_ids
+}
+
+/*************************************/
+/*           COMPRESSION             */
+/*************************************/
+// The key of the establishment property negotiating the batch compression of a link.
+// It does not overlap with the ones of the peer authenticators.
+pub(super) const COMPRESSION_PROPERTY: ZInt = 0x10;
+
+// The compression property carries the algorithm and the threshold requested by the opener
+pub(super) fn compression_to_property(threshold: usize) -> ZResult<Property> {
+    let mut wbuf = WBuf::new(WBUF_SIZE, false);
+    if !(wbuf.write_zint(COMPRESSION_LZ4 as ZInt) && wbuf.write_usize_as_zint(threshold)) {
+        bail!("Invalid compression property");
+    }
+    let value = ZBuf::from(wbuf).contiguous().into_owned();
+    Ok(Property {
+        key: COMPRESSION_PROPERTY,
+        value,
+    })
+}
+
+// Returns the negotiated threshold, if the requested algorithm is supported
+pub(super) fn compression_from_property(property: &Property) -> ZResult<Option<usize>> {
+    let zbuf = ZBuf::from(property.value.clone());
+    let mut reader = zbuf.reader();
+    let algorithm = reader
+        .read_zint()
+        .ok_or_else(|| zerror!("Invalid compression property"))?;
+    let threshold = reader
+        .read_zint_as_usize()
+        .ok_or_else(|| zerror!("Invalid compression property"))?;
+    if algorithm == COMPRESSION_LZ4 as ZInt {
+        Ok(Some(threshold))
+    } else {
+        Ok(None)
+    }
+}
+
+/*************************************/
+/*            ENCRYPTION             */
+/*************************************/
+// The session secret agreed by the peer authenticators on the link, if any
+pub(super) async fn session_secret(
+    manager: &TransportManager,
+    auth_link: &AuthenticatedPeerLink,
+) -> Option<Vec<u8>> {
+    let mut secret = None;
+    for pa in zasyncread!(manager.state.unicast.peer_authenticator).iter() {
+        if let Some(s) = pa.take_session_secret(auth_link).await {
+            secret = Some(s);
+        }
+    }
+    secret
+}
+
+// The link to add to the transport, encrypting and compressing the batches if negotiated.
+// The batches are compressed before being encrypted.
+pub(super) fn transport_link(
+    link: &LinkUnicast,
+    compression: Option<usize>,
+    secret: Option<Vec<u8>>,
+    direction: &LinkUnicastDirection,
+) -> ZResult<LinkUnicast> {
+    let mut link = link.clone();
+    if let Some(secret) = secret {
+        let is_initiator = direction == &LinkUnicastDirection::Outbound;
+        link = LinkUnicast(Arc::new(LinkUnicastEncrypted::new(
+            link,
+            &secret,
+            is_initiator,
+        )?));
+    }
+    if let Some(threshold) = compression {
+        link = LinkUnicast(Arc::new(LinkUnicastCompressed::new(link, threshold)));
+    }
+    Ok(link)
+}
+
 /*************************************/
 /*             COOKIE                */
 /*************************************/
@@ -209,6 +303,7 @@ pub(super) struct InputInit {
     pub(super) sn_resolution: ZInt,
     pub(super) is_shm: bool,
     pub(super) is_qos: bool,
+    pub(super) auth_ids: Vec<AuthId>,
 }
 async fn transport_init(
     manager: &TransportManager,
@@ -224,6 +319,7 @@ async fn transport_init(
         is_shm: input.is_shm,
         is_qos: input.is_qos,
         initial_sn_tx,
+        auth_ids: input.auth_ids,
     };
 
     manager.init_transport_unicast(config)
@@ -13,7 +13,10 @@
 //
 use crate::unicast::establishment::authenticator::PeerAuthenticatorId;
 use crate::unicast::establishment::open::OResult;
-use crate::unicast::establishment::{attachment_from_properties, properties_from_attachment};
+use crate::unicast::establishment::{
+    attachment_from_properties, compression_from_property, properties_from_attachment,
+    COMPRESSION_PROPERTY,
+};
 use crate::unicast::establishment::{
     authenticator::AuthenticatedPeerLink, EstablishmentProperties,
 };
@@ -33,6 +36,7 @@ pub(super) struct Output {
     pub(super) sn_resolution: ZInt,
     pub(super) is_qos: bool,
     pub(super) is_shm: bool,
+    pub(super) compression: Option<usize>,
     pub(super) cookie: ZSlice,
     pub(super) open_syn_attachment: Option<Attachment>,
 }
@@ -40,7 +44,7 @@ pub(super) async fn recv(
     link: &LinkUnicast,
     manager: &TransportManager,
     auth_link: &mut AuthenticatedPeerLink,
-    _input: super::init_syn::Output,
+    input: super::init_syn::Output,
 ) -> OResult<Output> {
     // Wait to read an InitAck
     let mut messages = link.read_transport_message().await.map_err(|e| (e, None))?;
@@ -111,6 +115,14 @@ pub(super) async fn recv(
         None => EstablishmentProperties::new(),
     };
 
+    // The compression is enabled only if requested and accepted by the other side
+    let compression = match init_ack_properties.remove(COMPRESSION_PROPERTY) {
+        Some(p) if input.compression.is_some() => {
+            compression_from_property(&p).map_err(|e| (e, Some(tmsg::close_reason::INVALID)))?
+        }
+        _ => None,
+    };
+
     let mut is_shm = false;
     let mut ps_attachment = EstablishmentProperties::new();
     for pa in zasyncread!(manager.state.unicast.peer_authenticator).iter() {
@@ -159,6 +171,7 @@ pub(super) async fn recv(
         sn_resolution,
         is_qos: init_ack.is_qos,
         is_shm,
+        compression,
         cookie: init_ack.cookie,
         open_syn_attachment: attachment_from_properties(&ps_attachment).ok(),
     };
@@ -13,7 +13,9 @@
 //
 use super::OResult;
 use crate::unicast::establishment::authenticator::AuthenticatedPeerLink;
-use crate::unicast::establishment::{attachment_from_properties, EstablishmentProperties};
+use crate::unicast::establishment::{
+    attachment_from_properties, compression_to_property, EstablishmentProperties,
+};
 use crate::TransportManager;
 use zenoh_core::zasyncread;
 use zenoh_link::LinkUnicast;
@@ -23,12 +25,19 @@ use zenoh_protocol::proto::TransportMessage;
 /*************************************/
 /*              OPEN                 */
 /*************************************/
-pub(super) struct Output;
+pub(super) struct Input {
+    pub(super) compression: Option<usize>,
+}
+
+pub(super) struct Output {
+    pub(super) compression: Option<usize>,
+}
 
 pub(super) async fn send(
     link: &LinkUnicast,
     manager: &TransportManager,
     auth_link: &mut AuthenticatedPeerLink,
+    input: Input,
 ) -> OResult<Output> {
     let mut ps_attachment = EstablishmentProperties::new();
     for pa in zasyncread!(manager.state.unicast.peer_authenticator).iter() {
@@ -46,6 +55,13 @@ pub(super) async fn send(
         }
     }
 
+    // Request the compression of the batches
+    if let Some(threshold) = input.compression {
+        ps_attachment
+            .insert(compression_to_property(threshold).map_err(|e| (e, None))?)
+            .map_err(|e| (e, None))?;
+    }
+
     // Build and send the InitSyn message
     let mut message = TransportMessage::make_init_syn(
         manager.config.version,
@@ -60,6 +76,8 @@ pub(super) async fn send(
         .await
         .map_err(|e| (e, None))?;
 
-    let output = Output;
+    let output = Output {
+        compression: input.compression,
+    };
     Ok(output)
 }
@@ -17,7 +17,9 @@ mod open_ack;
 mod open_syn;
 
 use super::authenticator::AuthenticatedPeerLink;
-use crate::unicast::establishment::{close_link, transport_finalize, InputFinalize, InputInit};
+use crate::unicast::establishment::{
+    close_link, session_secret, transport_finalize, transport_link, InputFinalize, InputInit,
+};
 use crate::{TransportManager, TransportUnicast};
 use zenoh_core::Result as ZResult;
 use zenoh_link::{LinkUnicast, LinkUnicastDirection};
@@ -30,6 +32,7 @@ pub(crate) async fn open_link(
     link: &LinkUnicast,
     manager: &TransportManager,
     auth_link: &mut AuthenticatedPeerLink,
+    compression: Option<usize>,
 ) -> ZResult<TransportUnicast> {
     // INIT handshake
     macro_rules! step {
@@ -44,7 +47,8 @@ pub(crate) async fn open_link(
         };
     }
 
-    let output = step!(init_syn::send(link, manager, auth_link).await);
+    let input = init_syn::Input { compression };
+    let output = step!(init_syn::send(link, manager, auth_link, input).await);
     let output = step!(init_ack::recv(link, manager, auth_link, output).await);
 
     // Initialize the transport
@@ -61,12 +65,15 @@ pub(crate) async fn open_link(
     }
 
     let pid = output.pid;
+    let compression = output.compression;
     let input = InputInit {
         pid,
         whatami: output.whatami,
         sn_resolution: output.sn_resolution,
         is_shm: output.is_shm,
         is_qos: output.is_qos,
+        // The peer authenticators only authenticate the opener of a transport
+        auth_ids: vec![],
     };
     let transport = step!(super::transport_init(manager, input).await);
 
@@ -103,10 +110,14 @@ pub(crate) async fn open_link(
 
     // Finalize the transport
     // Add the link to the transport
+    let direction = LinkUnicastDirection::Outbound;
+    let secret = session_secret(manager, auth_link).await;
+    let tlink = step!(transport_link(link, compression, secret, &direction)
+        .map_err(|e| (e, Some(tmsg::close_reason::INVALID))));
     let _ = step!(step!(transport
         .get_inner()
         .map_err(|e| (e, Some(tmsg::close_reason::INVALID))))
-    .add_link(link.clone(), LinkUnicastDirection::Outbound)
+    .add_link(tlink, direction)
     .map_err(|e| (e, Some(tmsg::close_reason::MAX_LINKS))));
 
     // Sync the RX sequence number
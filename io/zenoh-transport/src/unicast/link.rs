@@ -11,7 +11,11 @@
 // Contributors:
 //   ADLINK zenoh team, <zenoh@adlink-labs.tech>
 //
-use super::common::{conduit::TransportConduitTx, pipeline::TransmissionPipeline};
+use super::super::defaults::ZN_RETRANSMISSION_SYNC_PERIOD;
+use super::common::reliability::ConduitReliability;
+use super::common::{
+    batch::SerializationBatch, conduit::TransportConduitTx, pipeline::TransmissionPipeline,
+};
 use super::protocol::core::Priority;
 use super::protocol::io::{ZBuf, ZSlice};
 use super::protocol::proto::TransportMessage;
@@ -19,25 +23,58 @@ use super::transport::TransportUnicastInner;
 #[cfg(feature = "stats")]
 use super::TransportUnicastStatsAtomic;
 use async_std::prelude::*;
+use async_std::sync::Mutex as AsyncMutex;
 use async_std::task;
 use async_std::task::JoinHandle;
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use zenoh_buffers::buffer::InsertBuffer;
 use zenoh_buffers::reader::{HasReader, Reader};
 use zenoh_collections::RecyclingObjectPool;
 use zenoh_core::Result as ZResult;
-use zenoh_core::{bail, zerror};
+use zenoh_core::{bail, zasynclock, zerror};
 use zenoh_link::{LinkUnicast, LinkUnicastDirection};
 use zenoh_protocol::proto::MessageReader;
 use zenoh_sync::Signal;
 
+/// The writes on a link. They are serialized for the messages written directly on the link,
+/// like the AckNacks, not to interleave with the batches of the TX task on a streamed link.
+#[derive(Clone)]
+pub(super) struct LinkUnicastWriter {
+    link: LinkUnicast,
+    mutex: Arc<AsyncMutex<()>>,
+}
+
+impl LinkUnicastWriter {
+    fn new(link: LinkUnicast) -> LinkUnicastWriter {
+        LinkUnicastWriter {
+            link,
+            mutex: Arc::new(AsyncMutex::new(())),
+        }
+    }
+
+    pub(super) async fn write_all(&self, bytes: &[u8]) -> ZResult<()> {
+        let _guard = zasynclock!(self.mutex);
+        self.link.write_all(bytes).await
+    }
+
+    pub(super) async fn write_transport_message(
+        &self,
+        message: &mut TransportMessage,
+    ) -> ZResult<usize> {
+        let _guard = zasynclock!(self.mutex);
+        self.link.write_transport_message(message).await
+    }
+}
+
 #[derive(Clone)]
 pub(super) struct TransportLinkUnicast {
     // Inbound / outbound
     pub(super) direction: LinkUnicastDirection,
     // The underlying link
     pub(super) link: LinkUnicast,
+    // The writes on the underlying link
+    pub(super) writer: LinkUnicastWriter,
     // The transmission pipeline
     pub(super) pipeline: Option<Arc<TransmissionPipeline>>,
     // The transport this link is associated to
@@ -57,6 +94,7 @@ impl TransportLinkUnicast {
         TransportLinkUnicast {
             direction,
             transport,
+            writer: LinkUnicastWriter::new(link.clone()),
             link,
             pipeline: None,
             handle_tx: None,
@@ -82,14 +120,23 @@ impl TransportLinkUnicast {
             ));
             self.pipeline = Some(pipeline.clone());
 
+            // The reliable frames sent on a best-effort link are kept for their retransmission
+            let reliability = if self.link.is_reliable() || self.link.is_streamed() {
+                None
+            } else {
+                Some(self.transport.reliability.clone())
+            };
+
             // Spawn the TX task
             let c_link = self.link.clone();
+            let c_writer = self.writer.clone();
             let c_transport = self.transport.clone();
             let handle = task::spawn(async move {
                 let res = tx_task(
                     pipeline.clone(),
-                    c_link.clone(),
+                    c_writer,
                     keep_alive,
+                    reliability,
                     #[cfg(feature = "stats")]
                     c_transport.stats.clone(),
                 )
@@ -171,17 +218,47 @@ impl TransportLinkUnicast {
 /*************************************/
 async fn tx_task(
     pipeline: Arc<TransmissionPipeline>,
-    link: LinkUnicast,
+    writer: LinkUnicastWriter,
     keep_alive: Duration,
+    reliability: Option<Arc<[ConduitReliability]>>,
     #[cfg(feature = "stats")] stats: Arc<TransportUnicastStatsAtomic>,
 ) -> ZResult<()> {
+    let link = &writer.link;
+    // The batch to serialize the Syncs on, written directly on the link
+    let mut sync_batch = SerializationBatch::new(link.get_mtu(), link.is_streamed());
+    let sync_period = Duration::from_millis(*ZN_RETRANSMISSION_SYNC_PERIOD).min(keep_alive);
+    let mut last_sync = Instant::now();
     loop {
-        match pipeline.pull().timeout(keep_alive).await {
+        // Periodically solicit the acknowledgment of the reliable frames kept for retransmission
+        let is_unacknowledged = reliability
+            .as_ref()
+            .is_some_and(|r| r.iter().any(|c| c.has_unacknowledged()));
+        let timeout = if is_unacknowledged {
+            sync_period
+        } else {
+            keep_alive
+        };
+        match pipeline.pull().timeout(timeout).await {
             Ok(res) => match res {
                 Some((batch, priority)) => {
+                    // Wait for the reliable frames to fit in the window of the unacknowledged ones
+                    if let Some(reliability) = reliability.as_ref() {
+                        if let Some((sn, _)) = batch.reliable_frames().last() {
+                            while pipeline.is_active() {
+                                let waiter = match reliability[priority].window_waiter(sn) {
+                                    Some(waiter) => waiter,
+                                    None => break,
+                                };
+                                write_syncs(&writer, Some(reliability), &mut sync_batch).await?;
+                                last_sync = Instant::now();
+                                let _ = waiter.timeout(sync_period).await;
+                            }
+                        }
+                    }
+
                     // Send the buffer on the link
                     let bytes = batch.as_bytes();
-                    let _ = link.write_all(bytes).await?;
+                    writer.write_all(bytes).await?;
 
                     #[cfg(feature = "stats")]
                     {
@@ -189,11 +266,28 @@ async fn tx_task(
                         stats.inc_tx_bytes(bytes.len());
                     }
 
+                    // Keep the reliable frames until they are acknowledged
+                    if let Some(reliability) = reliability.as_ref() {
+                        for (sn, frame) in batch.reliable_frames() {
+                            reliability[priority].keep(sn, frame)?;
+                        }
+                    }
+
                     // Reinsert the batch into the queue
                     pipeline.refill(batch, priority);
+
+                    if is_unacknowledged && last_sync.elapsed() >= sync_period {
+                        write_syncs(&writer, reliability.as_deref(), &mut sync_batch).await?;
+                        last_sync = Instant::now();
+                    }
                 }
                 None => break,
             },
+            Err(_) if is_unacknowledged => {
+                // The Syncs also keep the link alive
+                write_syncs(&writer, reliability.as_deref(), &mut sync_batch).await?;
+                last_sync = Instant::now();
+            }
             Err(_) => {
                 let pid = None;
                 let attachment = None;
@@ -206,7 +300,7 @@ async fn tx_task(
     // Drain the transmission pipeline and write remaining bytes on the wire
     let mut batches = pipeline.drain();
     for (b, _) in batches.drain(..) {
-        let _ = link
+        let _ = writer
             .write_all(b.as_bytes())
             .timeout(keep_alive)
             .await
@@ -222,6 +316,23 @@ async fn tx_task(
     Ok(())
 }
 
+async fn write_syncs(
+    writer: &LinkUnicastWriter,
+    reliability: Option<&[ConduitReliability]>,
+    batch: &mut SerializationBatch,
+) -> ZResult<()> {
+    for c in reliability.iter().flat_map(|r| r.iter()) {
+        if let Some(mut message) = c.sync() {
+            batch.clear();
+            if batch.serialize_transport_message(&mut message) {
+                batch.write_len();
+                writer.write_all(batch.as_bytes()).await?;
+            }
+        }
+    }
+    Ok(())
+}
+
 async fn rx_task_stream(
     link: LinkUnicast,
     transport: TransportUnicastInner,
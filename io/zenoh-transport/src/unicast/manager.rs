@@ -25,10 +25,12 @@ use std::collections::{HashMap, HashSet};
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
 use zenoh_cfg_properties::config::*;
-use zenoh_config::Config;
+use zenoh_config::{Config, LinkScheduling};
 use zenoh_core::{
     bail, zasynclock, zasyncread, zasyncwrite, zerror, zlock, zparse, Result as ZResult,
 };
+use zenoh_link::compression::config::{COMPRESSION, COMPRESSION_THRESHOLD};
+use zenoh_link::fault::{FaultConfig, LinkUnicastFaulty};
 use zenoh_link::*;
 use zenoh_protocol::proto::tmsg;
 use zenoh_protocol_core::locators::LocatorProtocol;
@@ -43,6 +45,9 @@ pub struct TransportManagerConfigUnicast {
     pub accept_pending: usize,
     pub max_sessions: usize,
     pub max_links: usize,
+    pub link_scheduling: LinkScheduling,
+    pub is_compression: bool,
+    pub compression_threshold: usize,
     pub is_qos: bool,
     #[cfg(feature = "shared-memory")]
     pub is_shm: bool,
@@ -78,6 +83,9 @@ pub struct TransportManagerBuilderUnicast {
     pub(super) accept_pending: usize,
     pub(super) max_sessions: usize,
     pub(super) max_links: usize,
+    pub(super) link_scheduling: LinkScheduling,
+    pub(super) is_compression: bool,
+    pub(super) compression_threshold: usize,
     pub(super) is_qos: bool,
     #[cfg(feature = "shared-memory")]
     pub(super) is_shm: bool,
@@ -116,6 +124,21 @@ impl TransportManagerBuilderUnicast {
         self
     }
 
+    pub fn link_scheduling(mut self, link_scheduling: LinkScheduling) -> Self {
+        self.link_scheduling = link_scheduling;
+        self
+    }
+
+    pub fn compression(mut self, is_compression: bool) -> Self {
+        self.is_compression = is_compression;
+        self
+    }
+
+    pub fn compression_threshold(mut self, compression_threshold: usize) -> Self {
+        self.compression_threshold = compression_threshold;
+        self
+    }
+
     pub fn peer_authenticator(mut self, peer_authenticator: HashSet<PeerAuthenticator>) -> Self {
         self.peer_authenticator = peer_authenticator;
         self
@@ -159,6 +182,15 @@ impl TransportManagerBuilderUnicast {
         if let Some(v) = properties.transport().unicast().max_links() {
             self = self.max_links(*v);
         }
+        if let Some(v) = properties.transport().unicast().link_scheduling() {
+            self = self.link_scheduling(*v);
+        }
+        if let Some(v) = properties.transport().unicast().compression() {
+            self = self.compression(*v);
+        }
+        if let Some(v) = properties.transport().unicast().compression_threshold() {
+            self = self.compression_threshold(*v);
+        }
         if let Some(v) = properties.transport().qos() {
             self = self.qos(*v);
         }
@@ -183,6 +215,9 @@ impl TransportManagerBuilderUnicast {
             accept_pending: self.accept_pending,
             max_sessions: self.max_sessions,
             max_links: self.max_links,
+            link_scheduling: self.link_scheduling,
+            is_compression: self.is_compression,
+            compression_threshold: self.compression_threshold,
             is_qos: self.is_qos,
             #[cfg(feature = "shared-memory")]
             is_shm: self.is_shm,
@@ -233,6 +268,9 @@ impl Default for TransportManagerBuilderUnicast {
             accept_pending: zparse!(ZN_OPEN_INCOMING_PENDING_DEFAULT).unwrap(),
             max_sessions: zparse!(ZN_MAX_SESSIONS_UNICAST_DEFAULT).unwrap(),
             max_links: zparse!(ZN_MAX_LINKS_DEFAULT).unwrap(),
+            link_scheduling: zparse!(ZN_LINK_SCHEDULING_DEFAULT).unwrap(),
+            is_compression: zparse!(ZN_COMPRESSION_DEFAULT).unwrap(),
+            compression_threshold: zparse!(ZN_COMPRESSION_THRESHOLD_DEFAULT).unwrap(),
             is_qos: zparse!(ZN_QOS_DEFAULT).unwrap(),
             #[cfg(feature = "shared-memory")]
             is_shm: zparse!(ZN_SHM_DEFAULT).unwrap(),
@@ -436,6 +474,7 @@ impl TransportManager {
                     initial_sn_tx: config.initial_sn_tx,
                     is_shm: config.is_shm,
                     is_qos: config.is_qos,
+                    auth_ids: config.auth_ids,
                 };
                 let a_t = Arc::new(TransportUnicastInner::make(stc)?);
 
@@ -480,15 +519,40 @@ impl TransportManager {
             endpoint.extend_configuration(config.iter().map(|(k, v)| (k.clone(), v.clone())));
         };
 
+        // Request the compression of the batches if enabled for the endpoint
+        let compression = self.compression_unicast(&endpoint)?;
+
         // Create a new link associated by calling the Link Manager
-        let link = manager.new_link(endpoint).await?;
+        let faults = FaultConfig::from_endpoint(&endpoint)?;
+        let mut link = manager.new_link(endpoint).await?;
+        // Inject the faults configured on the endpoint, if any
+        if let Some(faults) = faults {
+            link = LinkUnicast(Arc::new(LinkUnicastFaulty::new(link, faults)));
+        }
         // Open the link
         let mut auth_link = AuthenticatedPeerLink {
             src: link.get_src().to_owned(),
-            dst: link.get_src().to_owned(),
+            dst: link.get_dst().to_owned(),
             peer_id: None,
         };
-        super::establishment::open::open_link(&link, self, &mut auth_link).await
+        super::establishment::open::open_link(&link, self, &mut auth_link, compression).await
+    }
+
+    // Returns the compression threshold if the compression is enabled for the endpoint
+    fn compression_unicast(&self, endpoint: &EndPoint) -> ZResult<Option<usize>> {
+        let config = endpoint.config.as_ref();
+        let is_compression = match config.and_then(|c| c.get(COMPRESSION)) {
+            Some(v) => zparse!(v)?,
+            None => self.config.unicast.is_compression,
+        };
+        if !is_compression {
+            return Ok(None);
+        }
+        let threshold = match config.and_then(|c| c.get(COMPRESSION_THRESHOLD)) {
+            Some(v) => zparse!(v)?,
+            None => self.config.unicast.compression_threshold,
+        };
+        Ok(Some(threshold))
     }
 
     pub fn get_transport_unicast(&self, peer: &PeerId) -> Option<TransportUnicast> {
@@ -25,9 +25,11 @@ use super::protocol;
 use super::protocol::core::{PeerId, WhatAmI, ZInt};
 use super::protocol::proto::{tmsg, ZenohMessage};
 use super::{TransportPeer, TransportPeerEventHandler};
+use establishment::authenticator::AuthId;
 pub use manager::*;
 use std::fmt;
 use std::sync::{Arc, Weak};
+use std::time::Duration;
 use transport::TransportUnicastInner;
 use zenoh_core::zerror;
 use zenoh_core::Result as ZResult;
@@ -77,7 +79,7 @@ stats_struct! {
 /*************************************/
 /*        TRANSPORT UNICAST          */
 /*************************************/
-#[derive(Clone, Copy)]
+#[derive(Clone)]
 pub(crate) struct TransportConfigUnicast {
     pub(crate) peer: PeerId,
     pub(crate) whatami: WhatAmI,
@@ -85,6 +87,7 @@ pub(crate) struct TransportConfigUnicast {
     pub(crate) initial_sn_tx: ZInt,
     pub(crate) is_shm: bool,
     pub(crate) is_qos: bool,
+    pub(crate) auth_ids: Vec<AuthId>,
 }
 
 /// [`TransportUnicast`] is the transport handler returned
@@ -130,6 +133,13 @@ impl TransportUnicast {
         Ok(transport.is_qos())
     }
 
+    /// Returns the identities the peer has been authenticated with when it opened the transport.
+    #[inline(always)]
+    pub fn get_auth_ids(&self) -> ZResult<Vec<AuthId>> {
+        let transport = self.get_inner()?;
+        Ok(transport.get_auth_ids())
+    }
+
     #[inline(always)]
     pub fn get_callback(&self) -> ZResult<Option<Arc<dyn TransportPeerEventHandler>>> {
         let transport = self.get_inner()?;
@@ -192,6 +202,18 @@ impl TransportUnicast {
         }
     }
 
+    /// Measures the round-trip time to the peer on the first link of the transport, with a Ping
+    /// answered by the peer. Fails if no answer is received before the timeout.
+    pub async fn get_rtt(&self, timeout: Duration) -> ZResult<Duration> {
+        let transport = self.get_inner()?;
+        let link = transport
+            .get_links()
+            .into_iter()
+            .next()
+            .ok_or_else(|| zerror!("No link to {}", transport.get_pid()))?;
+        transport.ping(&link, timeout).await
+    }
+
     #[inline(always)]
     pub fn handle_message(&self, message: ZenohMessage) -> ZResult<()> {
         self.schedule(message)
@@ -11,16 +11,20 @@
 // Contributors:
 //   ADLINK zenoh team, <zenoh@adlink-labs.tech>
 //
+use super::super::defaults::{ZN_RX_REORDER_TIMEOUT, ZN_RX_REORDER_WINDOW};
 use super::common::conduit::TransportChannelRx;
+use super::common::reliability::ReliabilityQueue;
 use super::protocol::core::{PeerId, Priority, Reliability, ZInt};
 #[cfg(feature = "stats")]
 use super::protocol::proto::ZenohBody;
 use super::protocol::proto::{
-    Close, Frame, FramePayload, KeepAlive, TransportBody, TransportMessage, ZenohMessage,
+    AckNack, Close, Frame, FramePayload, KeepAlive, Ping, Pong, Sync, TransportBody,
+    TransportMessage, ZenohMessage,
 };
 use super::transport::TransportUnicastInner;
 use async_std::task;
-use std::sync::MutexGuard;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
 #[cfg(feature = "stats")]
 use zenoh_buffers::SplitBuffer;
 use zenoh_core::{bail, zerror, zlock, zread, Result as ZResult};
@@ -120,7 +124,7 @@ impl TransportUnicastInner {
         &self,
         sn: ZInt,
         payload: FramePayload,
-        mut guard: MutexGuard<'_, TransportChannelRx>,
+        guard: &mut TransportChannelRx,
     ) -> ZResult<()> {
         let precedes = guard.sn.precedes(sn)?;
         if !precedes {
@@ -166,6 +170,292 @@ impl TransportUnicastInner {
         }
     }
 
+    fn handle_reliable_frame(
+        &self,
+        sn: ZInt,
+        payload: FramePayload,
+        guard: &mut TransportChannelRx,
+    ) -> ZResult<()> {
+        if guard.reorder.is_empty() && !self.is_reordering.load(Ordering::Acquire) {
+            return self.handle_frame(sn, payload, guard);
+        }
+        if !guard.sn.precedes(sn)? {
+            // The frame is dropped
+            return self.handle_frame(sn, payload, guard);
+        }
+
+        // The distance of the frame to the next expected one
+        let distance = (guard.sn.gap(sn)? - 1) as usize;
+        if distance >= *ZN_RX_REORDER_WINDOW {
+            self.flush_reorder(guard)?;
+            return self.handle_frame(sn, payload, guard);
+        }
+        if distance > 0 {
+            guard.reorder.hold(distance, sn, payload);
+            if guard
+                .reorder
+                .is_expired(Duration::from_millis(*ZN_RX_REORDER_TIMEOUT))
+            {
+                self.flush_reorder(guard)?;
+            }
+            return Ok(());
+        }
+
+        guard.reorder.advance();
+        self.handle_frame(sn, payload, guard)?;
+        while let Some((sn, payload)) = guard.reorder.pop_ready() {
+            self.handle_frame(sn, payload, guard)?;
+        }
+        Ok(())
+    }
+
+    /// Gives up the reliable frames missing for too long, on all the conduits. The expiration is
+    /// otherwise only checked when another frame is held back, so it is also checked on the
+    /// reception of the keep-alives, sent by the links on which no frame is sent anymore.
+    fn check_reorder_timeouts(&self) -> ZResult<()> {
+        if !self.is_reordering.load(Ordering::Acquire) {
+            return Ok(());
+        }
+        let timeout = Duration::from_millis(*ZN_RX_REORDER_TIMEOUT);
+        for c in self.conduit_rx.iter() {
+            let mut guard = zlock!(c.reliable);
+            if guard.reorder.is_expired(timeout) {
+                self.flush_reorder(&mut guard)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Gives up the missing reliable frames, and handles the frames received after them.
+    fn flush_reorder(&self, guard: &mut TransportChannelRx) -> ZResult<()> {
+        let frames = guard.reorder.drain();
+        if !frames.is_empty() {
+            log::debug!(
+                "Transport: {}. Reliable frames missing before SN {}: skipped.",
+                self.config.pid,
+                frames[0].0
+            );
+        }
+        for (sn, payload) in frames {
+            if guard.sn.gap(sn)? != 1 {
+                // Drop the fragments preceding the missing frames
+                guard.defrag.clear();
+            }
+            self.handle_frame(sn, payload, guard)?;
+        }
+        Ok(())
+    }
+
+    /// Handles a reliable frame received on a best-effort link: the frames received ahead of
+    /// the missing ones are held until these are retransmitted, and the new gaps are reported
+    /// with the returned AckNack.
+    fn handle_lossy_frame(
+        &self,
+        index: usize,
+        sn: ZInt,
+        payload: FramePayload,
+        guard: &mut TransportChannelRx,
+    ) -> ZResult<Option<TransportMessage>> {
+        if !guard.sn.precedes(sn)? {
+            // The frame is dropped, e.g. it has been retransmitted twice
+            self.handle_frame(sn, payload, guard)?;
+            return Ok(None);
+        }
+
+        let r = &self.reliability[index];
+        let mut queue = zlock!(r.rx);
+        let resolution = guard.sn.resolution();
+        if queue.is_empty() {
+            queue.set_base((guard.sn.get() + 1) % resolution)?;
+        }
+
+        // Give up the frames missing for too long to hold the frame
+        let capacity = queue.capacity() as ZInt;
+        if guard.sn.gap(sn)? > capacity {
+            let first = (sn + resolution - (capacity - 1)) % resolution;
+            self.skip_lost(guard, &mut queue, first)?;
+        }
+
+        let distance = (sn + resolution - queue.get_base()) % resolution;
+        queue.insert(payload, sn)?;
+        if distance == 0 {
+            self.pull_ready(guard, &mut queue)?;
+            return Ok(None);
+        }
+
+        // Report the missing frame preceding this one, if any
+        let mask = queue.get_mask();
+        if mask & (1 << (distance - 1)) != 0 {
+            let ack_nack = TransportMessage::make_ack_nack(
+                r.priority,
+                None,
+                queue.get_base(),
+                Some(mask),
+                None,
+            );
+            return Ok(Some(ack_nack));
+        }
+        Ok(None)
+    }
+
+    fn pull_ready(
+        &self,
+        guard: &mut TransportChannelRx,
+        queue: &mut ReliabilityQueue<FramePayload>,
+    ) -> ZResult<()> {
+        loop {
+            let sn = queue.get_base();
+            match queue.pull() {
+                Some(payload) => self.handle_frame(sn, payload, guard)?,
+                None => return Ok(()),
+            }
+        }
+    }
+
+    /// Gives up the reliable frames missing before the given SN, and handles the frames
+    /// received before it.
+    fn skip_lost(
+        &self,
+        guard: &mut TransportChannelRx,
+        queue: &mut ReliabilityQueue<FramePayload>,
+        sn: ZInt,
+    ) -> ZResult<()> {
+        let resolution = guard.sn.resolution();
+        let gap = (sn + resolution - queue.get_base()) % resolution;
+        for _ in 0..gap.min(queue.capacity() as ZInt) {
+            let base = queue.get_base();
+            match queue.pull() {
+                Some(payload) => self.handle_frame(base, payload, guard)?,
+                None => {
+                    log::debug!(
+                        "Transport: {}. Reliable frame lost: {}.",
+                        self.config.pid,
+                        base
+                    );
+                    queue.set_base((base + 1) % resolution)?;
+                    guard.sn.set(base)?;
+                    guard.defrag.clear();
+                }
+            }
+        }
+        if queue.get_base() != sn {
+            queue.set_base(sn)?;
+            guard.sn.set((sn + resolution - 1) % resolution)?;
+            guard.defrag.clear();
+        }
+        Ok(())
+    }
+
+    /// Handles a Sync soliciting the acknowledgment of the reliable frames sent on a
+    /// best-effort link, and returns the AckNack reporting the missing ones.
+    fn handle_sync(
+        &self,
+        index: usize,
+        sn: ZInt,
+        count: Option<ZInt>,
+        guard: &mut TransportChannelRx,
+    ) -> ZResult<TransportMessage> {
+        let r = &self.reliability[index];
+        let mut queue = zlock!(r.rx);
+        let resolution = guard.sn.resolution();
+        if queue.is_empty() {
+            queue.set_base((guard.sn.get() + 1) % resolution)?;
+        }
+
+        // The frames preceding the unacknowledged ones can't be retransmitted anymore
+        if let Some(count) = count {
+            let first = (sn + resolution - count % resolution) % resolution;
+            if first != queue.get_base() && guard.sn.precedes(first)? {
+                self.skip_lost(guard, &mut queue, first)?;
+            }
+        }
+
+        let mask = if guard.sn.precedes(sn)? {
+            queue.get_mask_until(sn)?
+        } else {
+            0
+        };
+        let mask = if mask != 0 { Some(mask) } else { None };
+        Ok(TransportMessage::make_ack_nack(
+            r.priority,
+            None,
+            queue.get_base(),
+            mask,
+            None,
+        ))
+    }
+
+    /// Handles an AckNack acknowledging the reliable frames sent on a best-effort link,
+    /// and retransmits the missing ones on the link.
+    fn handle_ack_nack(
+        &self,
+        link: &LinkUnicast,
+        index: usize,
+        sn: ZInt,
+        mask: Option<ZInt>,
+    ) -> ZResult<()> {
+        let frames = self.reliability[index].acknowledge(sn, mask)?;
+        if !frames.is_empty() {
+            log::trace!(
+                "Transport: {}. Retransmitting {} reliable frames on link {}.",
+                self.config.pid,
+                frames.len(),
+                link
+            );
+            let writer = match self.get_link_writer(link) {
+                Some(writer) => writer,
+                None => return Ok(()),
+            };
+            task::spawn(async move {
+                for frame in frames {
+                    if let Err(e) = writer.write_all(&frame).await {
+                        log::debug!("{}", e);
+                        break;
+                    }
+                }
+            });
+        }
+        Ok(())
+    }
+
+    /// Writes an AckNack directly on the link, like the Syncs: the TX task may be waiting for
+    /// the AckNacks of the peer to send more reliable frames, and so not pull its pipeline.
+    /// The Pongs are written the same way, not to delay them behind the queued batches.
+    /// The write takes turns with the batches of the TX task, not to interleave with them.
+    fn send_on_link(&self, link: &LinkUnicast, mut message: TransportMessage) {
+        let writer = match self.get_link_writer(link) {
+            Some(writer) => writer,
+            None => return,
+        };
+        task::spawn(async move {
+            if let Err(e) = writer.write_transport_message(&mut message).await {
+                log::debug!("{}", e);
+            }
+        });
+    }
+
+    fn conduit_index(&self, priority: Priority) -> ZResult<usize> {
+        if self.is_qos() {
+            Ok(priority as usize)
+        } else if priority == Priority::default() {
+            Ok(0)
+        } else {
+            bail!(
+                "Transport: {}. Unknown conduit: {:?}.",
+                self.config.pid,
+                priority
+            );
+        }
+    }
+
+    pub(super) fn flush_reordering(&self) {
+        for c in self.conduit_rx.iter() {
+            if let Err(e) = self.flush_reorder(&mut *zlock!(c.reliable)) {
+                log::debug!("Transport: {}. {}", self.config.pid, e);
+            }
+        }
+    }
+
     pub(super) fn receive_message(&self, msg: TransportMessage, link: &LinkUnicast) -> ZResult<()> {
         log::trace!("Received: {:?}", msg);
         // Process the received message
@@ -175,22 +465,23 @@ impl TransportUnicastInner {
                 sn,
                 payload,
             }) => {
-                let c = if self.is_qos() {
-                    &self.conduit_rx[channel.priority as usize]
-                } else if channel.priority == Priority::default() {
-                    &self.conduit_rx[0]
-                } else {
-                    bail!(
-                        "Transport: {}. Unknown conduit: {:?}.",
-                        self.config.pid,
-                        channel.priority
-                    );
-                };
+                let index = self.conduit_index(channel.priority)?;
+                let c = &self.conduit_rx[index];
 
                 match channel.reliability {
-                    Reliability::Reliable => self.handle_frame(sn, payload, zlock!(c.reliable)),
+                    Reliability::Reliable if !link.is_reliable() => {
+                        let ack_nack =
+                            self.handle_lossy_frame(index, sn, payload, &mut *zlock!(c.reliable))?;
+                        if let Some(ack_nack) = ack_nack {
+                            self.send_on_link(link, ack_nack);
+                        }
+                        Ok(())
+                    }
+                    Reliability::Reliable => {
+                        self.handle_reliable_frame(sn, payload, &mut *zlock!(c.reliable))
+                    }
                     Reliability::BestEffort => {
-                        self.handle_frame(sn, payload, zlock!(c.best_effort))
+                        self.handle_frame(sn, payload, &mut *zlock!(c.best_effort))
                     }
                 }
             }
@@ -199,7 +490,33 @@ impl TransportUnicastInner {
                 reason,
                 link_only,
             }) => self.handle_close(link, pid, reason, link_only),
-            TransportBody::KeepAlive(KeepAlive { .. }) => Ok(()),
+            TransportBody::Sync(Sync {
+                priority,
+                reliability: Reliability::Reliable,
+                sn,
+                count,
+            }) => {
+                let index = self.conduit_index(priority)?;
+                let c = &self.conduit_rx[index];
+                let ack_nack = self.handle_sync(index, sn, count, &mut *zlock!(c.reliable))?;
+                self.send_on_link(link, ack_nack);
+                Ok(())
+            }
+            TransportBody::AckNack(AckNack {
+                priority, sn, mask, ..
+            }) => {
+                let index = self.conduit_index(priority)?;
+                self.handle_ack_nack(link, index, sn, mask)
+            }
+            TransportBody::KeepAlive(KeepAlive { .. }) => self.check_reorder_timeouts(),
+            TransportBody::Ping(Ping { hash }) => {
+                self.send_on_link(link, TransportMessage::make_pong(hash, None));
+                Ok(())
+            }
+            TransportBody::Pong(Pong { hash }) => {
+                self.handle_pong(hash);
+                Ok(())
+            }
             _ => {
                 log::debug!(
                     "Transport: {}. Message handling not implemented: {:?}",
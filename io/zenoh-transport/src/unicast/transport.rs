@@ -11,21 +11,27 @@
 // Contributors:
 //   ADLINK zenoh team, <zenoh@adlink-labs.tech>
 //
+use super::super::defaults::ZN_RETRANSMISSION_WINDOW;
 use super::super::{TransportManager, TransportPeerEventHandler};
+use super::common::reliability::ConduitReliability;
 use super::common::{
     conduit::{TransportConduitRx, TransportConduitTx},
     pipeline::TransmissionPipeline,
 };
-use super::link::TransportLinkUnicast;
+use super::establishment::authenticator::AuthId;
+use super::link::{LinkUnicastWriter, TransportLinkUnicast};
 use super::protocol::core::{ConduitSn, PeerId, Priority, WhatAmI, ZInt};
 use super::protocol::proto::{TransportMessage, ZenohMessage};
 #[cfg(feature = "stats")]
 use super::TransportUnicastStatsAtomic;
+use async_std::prelude::FutureExt;
 use async_std::sync::{Arc as AsyncArc, Mutex as AsyncMutex, MutexGuard as AsyncMutexGuard};
+use std::collections::HashMap;
 use std::convert::TryInto;
-use std::sync::{Arc, RwLock};
-use std::time::Duration;
-use zenoh_core::{bail, zasynclock, zerror, zread, zwrite, Result as ZResult};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+use zenoh_core::{bail, zasynclock, zerror, zlock, zread, zwrite, Result as ZResult};
 use zenoh_link::{Link, LinkUnicast, LinkUnicastDirection};
 
 macro_rules! zlinkget {
@@ -58,6 +64,7 @@ pub(crate) struct TransportUnicastConfig {
     pub(crate) initial_sn_tx: ZInt,
     pub(crate) is_shm: bool,
     pub(crate) is_qos: bool,
+    pub(crate) auth_ids: Vec<AuthId>,
 }
 
 #[derive(Clone)]
@@ -68,12 +75,20 @@ pub(crate) struct TransportUnicastInner {
     pub(super) conduit_tx: Arc<[TransportConduitTx]>,
     // Rx conduits
     pub(super) conduit_rx: Arc<[TransportConduitRx]>,
+    // The retransmission of the reliable frames sent on best-effort links, for each conduit
+    pub(super) reliability: Arc<[ConduitReliability]>,
     // The links associated to the channel
     pub(super) links: Arc<RwLock<Box<[TransportLinkUnicast]>>>,
+    // The index of the next link to schedule a message on, with the round-robin scheduling
+    pub(super) next_link: Arc<AtomicUsize>,
+    // Whether the reliable frames may be received out of order, on several reliable links
+    pub(super) is_reordering: Arc<AtomicBool>,
     // The callback
     pub(super) callback: Arc<RwLock<Option<Arc<dyn TransportPeerEventHandler>>>>,
     // Mutex for notification
     pub(super) alive: AsyncArc<AsyncMutex<bool>>,
+    // The Pings waiting for the Pong of the peer, by hash
+    pub(super) pings: Arc<Mutex<HashMap<ZInt, flume::Sender<()>>>>,
     // Transport statistics
     #[cfg(feature = "stats")]
     pub(super) stats: Arc<TransportUnicastStatsAtomic>,
@@ -120,13 +135,26 @@ impl TransportUnicastInner {
             let _ = c.sync(initial_sn)?;
         }
 
+        let mut reliability = vec![];
+        for c in conduit_tx.iter() {
+            reliability.push(ConduitReliability::make(
+                c.priority,
+                *ZN_RETRANSMISSION_WINDOW,
+                config.sn_resolution,
+            )?);
+        }
+
         let t = TransportUnicastInner {
             config,
             conduit_tx: conduit_tx.into_boxed_slice().into(),
             conduit_rx: conduit_rx.into_boxed_slice().into(),
+            reliability: reliability.into_boxed_slice().into(),
             links: Arc::new(RwLock::new(vec![].into_boxed_slice())),
+            next_link: Arc::new(AtomicUsize::new(0)),
+            is_reordering: Arc::new(AtomicBool::new(false)),
             callback: Arc::new(RwLock::new(None)),
             alive: AsyncArc::new(AsyncMutex::new(false)),
+            pings: Arc::new(Mutex::new(HashMap::new())),
             #[cfg(feature = "stats")]
             stats: Arc::new(TransportUnicastStatsAtomic::default()),
         };
@@ -249,11 +277,17 @@ impl TransportUnicastInner {
         let mut links = Vec::with_capacity(guard.len() + 1);
         links.extend_from_slice(&guard);
         links.push(link);
+        self.update_reordering(&links);
         *guard = links.into_boxed_slice();
 
         Ok(())
     }
 
+    fn update_reordering(&self, links: &[TransportLinkUnicast]) {
+        let reliable = links.iter().filter(|l| l.link.is_reliable()).count();
+        self.is_reordering.store(reliable > 1, Ordering::Release);
+    }
+
     pub(super) fn start_tx(
         &self,
         link: &LinkUnicast,
@@ -348,8 +382,11 @@ impl TransportUnicastInner {
                     // Remove the link
                     let mut links = guard.to_vec();
                     let stl = links.remove(index);
+                    self.update_reordering(&links);
                     *guard = links.into_boxed_slice();
                     drop(guard);
+                    // The frames sent on the removed link won't be received
+                    self.flush_reordering();
                     // Notify the callback
                     if let Some(callback) = zread!(self.callback).as_ref() {
                         callback.del_link(Link::from(link));
@@ -396,6 +433,10 @@ impl TransportUnicastInner {
         self.config.is_qos
     }
 
+    pub(crate) fn get_auth_ids(&self) -> Vec<AuthId> {
+        self.config.auth_ids.clone()
+    }
+
     pub(crate) fn get_callback(&self) -> Option<Arc<dyn TransportPeerEventHandler>> {
         zread!(self.callback).clone()
     }
@@ -479,4 +520,45 @@ impl TransportUnicastInner {
     pub(crate) fn get_links(&self) -> Vec<LinkUnicast> {
         zread!(self.links).iter().map(|l| l.link.clone()).collect()
     }
+
+    pub(super) fn get_link_writer(&self, link: &LinkUnicast) -> Option<LinkUnicastWriter> {
+        zread!(self.links)
+            .iter()
+            .find(|l| l.link == *link)
+            .map(|l| l.writer.clone())
+    }
+
+    /*************************************/
+    /*               RTT                 */
+    /*************************************/
+    /// Measures the round-trip time of a link with a Ping written directly on it, taking turns
+    /// with the batches of its TX task. The Pong of the peer is awaited until the timeout.
+    pub(crate) async fn ping(&self, link: &LinkUnicast, timeout: Duration) -> ZResult<Duration> {
+        let writer = self
+            .get_link_writer(link)
+            .ok_or_else(|| zerror!("Transport: {}. Unknown link {}", self.config.pid, link))?;
+        let hash: ZInt = rand::random();
+        let (sender, receiver) = flume::bounded(1);
+        zlock!(self.pings).insert(hash, sender);
+
+        let start = Instant::now();
+        let mut message = TransportMessage::make_ping(hash, None);
+        let res = match writer.write_transport_message(&mut message).await {
+            Ok(_) => receiver
+                .recv_async()
+                .timeout(timeout)
+                .await
+                .map_err(|_| zerror!("No Pong from {} on link {}", self.config.pid, link).into())
+                .map(|_| start.elapsed()),
+            Err(e) => Err(e),
+        };
+        zlock!(self.pings).remove(&hash);
+        res
+    }
+
+    pub(super) fn handle_pong(&self, hash: ZInt) {
+        if let Some(sender) = zlock!(self.pings).remove(&hash) {
+            let _ = sender.try_send(());
+        }
+    }
 }
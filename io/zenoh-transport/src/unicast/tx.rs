@@ -11,54 +11,70 @@
 // Contributors:
 //   ADLINK zenoh team, <zenoh@adlink-labs.tech>
 //
+use super::common::pipeline::TransmissionPipeline;
+use super::link::TransportLinkUnicast;
 #[cfg(feature = "stats")]
 use super::protocol::proto::ZenohBody;
 use super::protocol::proto::ZenohMessage;
 use super::transport::TransportUnicastInner;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
 #[cfg(feature = "stats")]
 use zenoh_buffers::SplitBuffer;
+use zenoh_config::LinkScheduling;
 use zenoh_core::zread;
 
 impl TransportUnicastInner {
-    fn schedule_on_link(&self, msg: ZenohMessage) -> bool {
-        macro_rules! zpush {
-            ($guard:expr, $pipeline:expr, $msg:expr) => {
-                // Drop the guard before the push_zenoh_message since
-                // the link could be congested and this operation could
-                // block for fairly long time
-                let pl = $pipeline.clone();
-                drop($guard);
-                return pl.push_zenoh_message($msg);
-            };
-        }
-
-        let guard = zread!(self.links);
-        // First try to find the best match between msg and link reliability
-        for tl in guard.iter() {
-            if let Some(pipeline) = tl.pipeline.as_ref() {
-                if msg.is_reliable() && tl.link.is_reliable() {
-                    zpush!(guard, pipeline, msg);
-                }
-                if !msg.is_reliable() && !tl.link.is_reliable() {
-                    zpush!(guard, pipeline, msg);
+    fn select_pipeline<'a, I>(&self, mut links: I) -> Option<Arc<TransmissionPipeline>>
+    where
+        I: Iterator<Item = &'a TransportLinkUnicast> + Clone,
+    {
+        let pipeline = match self.config.manager.config.unicast.link_scheduling {
+            // The first link is the active one: the next ones are used once it's removed
+            LinkScheduling::ActiveStandby => links.next(),
+            LinkScheduling::RoundRobin => {
+                let count = links.clone().count();
+                if count == 0 {
+                    return None;
                 }
+                let index = self.next_link.fetch_add(1, Ordering::Relaxed);
+                links.nth(index % count)
             }
-        }
-
-        // No best match found, take the first available link
-        for tl in guard.iter() {
-            if let Some(pipeline) = tl.pipeline.as_ref() {
-                zpush!(guard, pipeline, msg);
+            LinkScheduling::LeastQueued => {
+                links.min_by_key(|tl| tl.pipeline.as_ref().map_or(0, |p| p.backlog()))
             }
-        }
+        };
+        pipeline.and_then(|tl| tl.pipeline.clone())
+    }
 
-        // No Link found
-        log::trace!(
-            "Message dropped because the transport has no links: {}",
-            msg
-        );
+    fn schedule_on_link(&self, msg: ZenohMessage) -> bool {
+        let guard = zread!(self.links);
+        let links = guard.iter().filter(|tl| tl.pipeline.is_some());
+        // First try to find the best match between msg and link reliability,
+        // or take any available link
+        let pipeline = self
+            .select_pipeline(
+                links
+                    .clone()
+                    .filter(|tl| tl.link.is_reliable() == msg.is_reliable()),
+            )
+            .or_else(|| self.select_pipeline(links));
+        // Drop the guard before the push_zenoh_message since
+        // the link could be congested and this operation could
+        // block for fairly long time
+        drop(guard);
 
-        false
+        match pipeline {
+            Some(pipeline) => pipeline.push_zenoh_message(msg),
+            None => {
+                // No Link found
+                log::trace!(
+                    "Message dropped because the transport has no links: {}",
+                    msg
+                );
+                false
+            }
+        }
     }
 
     #[allow(clippy::let_and_return)] // When feature "stats" is not enabled
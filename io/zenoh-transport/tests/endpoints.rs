@@ -225,6 +225,37 @@ fn endpoint_udp() {
     task::block_on(run(&endpoints));
 }
 
+#[cfg(feature = "transport_mem")]
+#[test]
+fn endpoint_mem() {
+    task::block_on(async {
+        zasync_executor_init!();
+    });
+
+    // Define the locators
+    let endpoints: Vec<EndPoint> = vec![
+        "mem/zenoh-test-endpoint-0".parse().unwrap(),
+        "mem/zenoh-test-endpoint-1".parse().unwrap(),
+    ];
+    task::block_on(run(&endpoints));
+}
+
+#[cfg(feature = "transport_ws")]
+#[test]
+fn endpoint_ws() {
+    task::block_on(async {
+        zasync_executor_init!();
+    });
+
+    // Define the locators
+    let endpoints: Vec<EndPoint> = vec![
+        "ws/127.0.0.1:9454".parse().unwrap(),
+        "ws/[::1]:9454".parse().unwrap(),
+        "ws/localhost:9455".parse().unwrap(),
+    ];
+    task::block_on(run(&endpoints));
+}
+
 #[cfg(all(feature = "transport_unixsock-stream", target_family = "unix"))]
 #[test]
 fn endpoint_unix() {
@@ -251,6 +282,55 @@ fn endpoint_unix() {
     let _ = std::fs::remove_file("zenoh-test-unix-socket-1.sock.lock");
 }
 
+#[cfg(all(feature = "transport_unixsock-dgram", target_family = "unix"))]
+#[test]
+fn endpoint_unix_dgram() {
+    task::block_on(async {
+        zasync_executor_init!();
+    });
+
+    // Remove the files if they still exists
+    let _ = std::fs::remove_file("zenoh-test-unix-socket-12.sock");
+    let _ = std::fs::remove_file("zenoh-test-unix-socket-13.sock");
+    // Define the locators
+    let endpoints: Vec<EndPoint> = vec![
+        "unixsock-dgram/zenoh-test-unix-socket-12.sock"
+            .parse()
+            .unwrap(),
+        "unixsock-dgram/zenoh-test-unix-socket-13.sock"
+            .parse()
+            .unwrap(),
+    ];
+    task::block_on(run(&endpoints));
+    let _ = std::fs::remove_file("zenoh-test-unix-socket-12.sock");
+    let _ = std::fs::remove_file("zenoh-test-unix-socket-13.sock");
+    let _ = std::fs::remove_file("zenoh-test-unix-socket-12.sock.lock");
+    let _ = std::fs::remove_file("zenoh-test-unix-socket-13.sock.lock");
+}
+
+#[cfg(all(
+    feature = "transport_unixsock-stream",
+    feature = "transport_unixsock-dgram",
+    target_os = "linux"
+))]
+#[test]
+fn endpoint_unix_abstract() {
+    task::block_on(async {
+        zasync_executor_init!();
+    });
+
+    // Define the locators
+    let endpoints: Vec<EndPoint> = vec![
+        "unixsock-stream/@zenoh-test-unix-abstract-1"
+            .parse()
+            .unwrap(),
+        "unixsock-dgram/@zenoh-test-unix-abstract-1"
+            .parse()
+            .unwrap(),
+    ];
+    task::block_on(run(&endpoints));
+}
+
 #[cfg(all(feature = "transport_tcp", feature = "transport_udp"))]
 #[test]
 fn endpoint_tcp_udp() {
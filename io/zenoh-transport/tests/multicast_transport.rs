@@ -12,24 +12,29 @@
 //   ADLINK zenoh team, <zenoh@adlink-labs.tech>
 //
 
-// Restricting to macos by default because of no IPv6 support
-// on GitHub CI actions on Linux and Windows.
-#[cfg(target_os = "macos")]
+// Restricting to macos and linux by default because of no IPv6 support
+// on GitHub CI actions on Windows.
+#[cfg(any(target_os = "macos", target_os = "linux"))]
 mod tests {
     use async_std::prelude::*;
     use async_std::sync::Arc;
     use async_std::task;
     use std::any::Any;
+    use std::collections::{HashMap, HashSet};
     use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Mutex;
     use std::time::Duration;
-    use zenoh_cfg_properties::config::*;
-    use zenoh_core::zasync_executor_init;
+    use zenoh_buffers::reader::HasReader;
+    use zenoh_buffers::reader::Reader;
     use zenoh_core::Result as ZResult;
-    use zenoh_link::EndPoint;
+    use zenoh_core::{zasync_executor_init, zlock};
     use zenoh_link::Link;
+    use zenoh_link::{EndPoint, LinkManagerBuilderMulticast, LinkMulticast, Locator};
     use zenoh_protocol::io::ZBuf;
-    use zenoh_protocol::proto::ZenohMessage;
-    use zenoh_protocol_core::{Channel, CongestionControl, PeerId, Priority, Reliability, WhatAmI};
+    use zenoh_protocol::proto::{AckNack, MessageReader, TransportBody, ZenohMessage};
+    use zenoh_protocol_core::{
+        Channel, CongestionControl, PeerId, Priority, Reliability, WhatAmI, ZInt,
+    };
     use zenoh_transport::{
         TransportEventHandler, TransportManager, TransportMulticast,
         TransportMulticastEventHandler, TransportPeer, TransportPeerEventHandler, TransportUnicast,
@@ -42,6 +47,10 @@ mod tests {
     const MSG_COUNT: usize = 1_000;
     const MSG_SIZE_NOFRAG: [usize; 1] = [1_024];
 
+    const RECEIVERS: usize = 3;
+    // Paced for the window of the sender to cover several repair timeouts
+    const SLEEP_SEND: Duration = Duration::from_millis(2);
+
     macro_rules! ztimeout {
         ($f:expr) => {
             $f.timeout(TIMEOUT).await.unwrap()
@@ -134,6 +143,7 @@ mod tests {
 
     async fn open_transport(
         endpoint: &EndPoint,
+        reliable: bool,
     ) -> (TransportMulticastPeer, TransportMulticastPeer) {
         // Define peer01 and peer02 IDs
         let peer01_id = PeerId::new(1, [0_u8; PeerId::MAX_SIZE]);
@@ -144,6 +154,7 @@ mod tests {
         let peer01_manager = TransportManager::builder()
             .pid(peer01_id)
             .whatami(WhatAmI::Peer)
+            .multicast(TransportManager::config_multicast().reliable(reliable))
             .build(peer01_handler.clone())
             .unwrap();
 
@@ -152,6 +163,7 @@ mod tests {
         let peer02_manager = TransportManager::builder()
             .whatami(WhatAmI::Peer)
             .pid(peer02_id)
+            .multicast(TransportManager::config_multicast().reliable(reliable))
             .build(peer02_handler.clone())
             .unwrap();
 
@@ -175,6 +187,7 @@ mod tests {
         let peer01_transport = peer01_manager
             .get_transport_multicast(&endpoint.locator)
             .unwrap();
+        assert_eq!(peer01_transport.is_reliable().unwrap(), reliable);
         ztimeout!(async {
             while peer01_transport.get_peers().unwrap().is_empty() {
                 task::sleep(SLEEP_COUNT).await;
@@ -211,13 +224,17 @@ mod tests {
     ) {
         // Close the peer01 transport
         println!("Closing transport with {}", endpoint);
-        let _ = ztimeout!(peer01.transport.close()).unwrap();
+        ztimeout!(peer01.transport.close()).unwrap();
         assert!(peer01.manager.get_transports_multicast().is_empty());
-        assert!(peer02.transport.get_peers().unwrap().is_empty());
+        ztimeout!(async {
+            while !peer02.transport.get_peers().unwrap().is_empty() {
+                task::sleep(SLEEP_COUNT).await;
+            }
+        });
 
         // Close the peer02 transport
         println!("Closing transport with {}", endpoint);
-        let _ = ztimeout!(peer02.transport.close()).unwrap();
+        ztimeout!(peer02.transport.close()).unwrap();
         assert!(peer02.manager.get_transports_multicast().is_empty());
 
         // Wait a little bit
@@ -277,8 +294,8 @@ mod tests {
         task::sleep(SLEEP).await;
     }
 
-    async fn run_single(endpoint: &EndPoint, channel: Channel, msg_size: usize) {
-        let (peer01, peer02) = open_transport(endpoint).await;
+    async fn run_single(endpoint: &EndPoint, channel: Channel, msg_size: usize, reliable: bool) {
+        let (peer01, peer02) = open_transport(endpoint, reliable).await;
         test_transport(&peer01, &peer02, channel, msg_size).await;
 
         #[cfg(feature = "stats")]
@@ -292,20 +309,20 @@ mod tests {
         close_transport(peer01, peer02, endpoint).await;
     }
 
-    async fn run(endpoints: &[EndPoint], channel: &[Channel], msg_size: &[usize]) {
+    async fn run(endpoints: &[EndPoint], channel: &[Channel], msg_size: &[usize], reliable: bool) {
         for e in endpoints.iter() {
             for ch in channel.iter() {
                 for ms in msg_size.iter() {
-                    run_single(e, *ch, *ms).await;
+                    run_single(e, *ch, *ms, reliable).await;
                 }
             }
         }
     }
 
-    #[cfg(feature = "transport_udp")]
+    #[cfg(all(feature = "transport_udp", target_os = "macos"))]
     #[test]
     fn transport_multicast_udp_only() {
-        env_logger::init();
+        let _ = env_logger::try_init();
 
         task::block_on(async {
             zasync_executor_init!();
@@ -313,9 +330,12 @@ mod tests {
 
         // Define the locator
         let endpoints: Vec<EndPoint> = vec![
-            format!("udp/{}", ZN_MULTICAST_IPV4_ADDRESS_DEFAULT)
-                .parse()
-                .unwrap(),
+            format!(
+                "udp/{}",
+                zenoh_cfg_properties::config::ZN_MULTICAST_IPV4_ADDRESS_DEFAULT
+            )
+            .parse()
+            .unwrap(),
             // Disabling by default because of no IPv6 support
             // on GitHub CI actions.
             // format!("udp/{}", ZN_MULTICAST_IPV6_ADDRESS_DEFAULT)
@@ -334,6 +354,163 @@ mod tests {
             },
         ];
         // Run
-        task::block_on(run(&endpoints, &channel, &MSG_SIZE_NOFRAG));
+        task::block_on(run(&endpoints, &channel, &MSG_SIZE_NOFRAG, false));
+    }
+
+    #[cfg(feature = "transport_udp")]
+    #[test]
+    fn transport_multicast_udp_reliable() {
+        let _ = env_logger::try_init();
+
+        task::block_on(async {
+            zasync_executor_init!();
+        });
+
+        // Define the locator
+        let endpoints: Vec<EndPoint> = vec!["udp/224.0.0.224:18449".parse().unwrap()];
+        // The reliable frames are retransmitted when reported as missing
+        let channel = [
+            Channel {
+                priority: Priority::default(),
+                reliability: Reliability::Reliable,
+            },
+            Channel {
+                priority: Priority::RealTime,
+                reliability: Reliability::Reliable,
+            },
+        ];
+        // Run
+        task::block_on(run(&endpoints, &channel, &MSG_SIZE_NOFRAG, true));
+    }
+
+    async fn open_peer(id: u8, endpoint: &EndPoint) -> TransportMulticastPeer {
+        let handler = Arc::new(SHPeer::default());
+        let manager = TransportManager::builder()
+            .pid(PeerId::new(1, [id; PeerId::MAX_SIZE]))
+            .whatami(WhatAmI::Peer)
+            .multicast(TransportManager::config_multicast().reliable(true))
+            .build(handler.clone())
+            .unwrap();
+        let transport = ztimeout!(manager.open_transport_multicast(endpoint.clone())).unwrap();
+        TransportMulticastPeer {
+            manager,
+            handler,
+            transport,
+        }
+    }
+
+    // Records the receivers reporting each reliable frame as missing, from the AckNacks
+    // observed on the multicast group
+    fn observe_nacks(link: LinkMulticast) -> Arc<Mutex<HashMap<ZInt, HashSet<Locator>>>> {
+        let reports = Arc::new(Mutex::new(HashMap::<ZInt, HashSet<Locator>>::new()));
+        let c_reports = reports.clone();
+        task::spawn(async move {
+            let mut buffer = vec![0_u8; link.get_mtu() as usize];
+            while let Ok((n, locator)) = link.read(&mut buffer).await {
+                let zbuf = ZBuf::from(buffer[..n].to_vec());
+                let mut reader = zbuf.reader();
+                while reader.can_read() {
+                    let msg = match reader.read_transport_message() {
+                        Some(msg) => msg,
+                        None => break,
+                    };
+                    if let TransportBody::AckNack(AckNack { sn, mask, .. }) = msg.body {
+                        let mut guard = zlock!(c_reports);
+                        let mask = mask.unwrap_or(0);
+                        for i in (0..ZInt::BITS as ZInt).filter(|i| mask & (1 << i) != 0) {
+                            guard
+                                .entry(sn + i)
+                                .or_default()
+                                .insert(locator.clone().into_owned());
+                        }
+                    }
+                }
+            }
+        });
+        reports
+    }
+
+    #[cfg(feature = "transport_udp")]
+    #[test]
+    fn transport_multicast_udp_lossy() {
+        let _ = env_logger::try_init();
+
+        task::block_on(async {
+            zasync_executor_init!();
+        });
+
+        task::block_on(async {
+            let endpoint: EndPoint = "udp/224.0.0.224:18453".parse().unwrap();
+            // The frames lost on the egress of the sender are missed by all the receivers
+            let faulty: EndPoint = "udp/224.0.0.224:18453#fault_loss=0.05;fault_seed=3"
+                .parse()
+                .unwrap();
+
+            let manager = LinkManagerBuilderMulticast::make(endpoint.locator.protocol()).unwrap();
+            let observer = ztimeout!(manager.new_link(&endpoint)).unwrap();
+            let reports = observe_nacks(observer.clone());
+
+            let sender = open_peer(0, &faulty).await;
+            let mut receivers = vec![];
+            for id in 1..=RECEIVERS {
+                receivers.push(open_peer(id as u8, &endpoint).await);
+            }
+            // Wait for all the peers to join each other
+            ztimeout!(async {
+                while receivers
+                    .iter()
+                    .chain(std::iter::once(&sender))
+                    .any(|p| p.transport.get_peers().unwrap().len() != RECEIVERS)
+                {
+                    task::sleep(SLEEP_COUNT).await;
+                }
+            });
+
+            let channel = Channel {
+                priority: Priority::default(),
+                reliability: Reliability::Reliable,
+            };
+            let message = ZenohMessage::make_data(
+                "/test".into(),
+                ZBuf::from(vec![0_u8; MSG_SIZE_NOFRAG[0]]),
+                channel,
+                CongestionControl::Block,
+                None,
+                None,
+                None,
+                None,
+            );
+            println!("Sending {} messages... {:?}", MSG_COUNT, channel);
+            for _ in 0..MSG_COUNT {
+                sender.transport.schedule(message.clone()).unwrap();
+                task::sleep(SLEEP_SEND).await;
+            }
+
+            // All the receivers get all the data despite the losses
+            ztimeout!(async {
+                while receivers.iter().any(|r| r.handler.get_count() != MSG_COUNT) {
+                    task::sleep(SLEEP_COUNT).await;
+                }
+            });
+
+            // Without NACK suppression, each lost frame would be reported by all the receivers
+            let (lost, reporters) = {
+                let guard = zlock!(reports);
+                let reporters: usize = guard.values().map(|r| r.len()).sum();
+                (guard.len(), reporters)
+            };
+            println!("\t{} lost frames reported by {} receivers", lost, reporters);
+            assert!(lost > 0);
+            assert!(reporters < lost * RECEIVERS);
+
+            for peer in receivers.into_iter().chain(std::iter::once(sender)) {
+                ztimeout!(peer.transport.close()).unwrap();
+                ztimeout!(peer.manager.close());
+            }
+            let _ = observer.close().await;
+
+            // Wait a little bit
+            task::sleep(SLEEP).await;
+        });
     }
 }
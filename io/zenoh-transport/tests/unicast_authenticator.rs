@@ -21,11 +21,21 @@ use std::any::Any;
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::iter::FromIterator;
+#[cfg(feature = "auth_pubkey")]
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::time::Duration;
+#[cfg(feature = "auth_pubkey")]
+use zenoh_buffers::SplitBuffer;
 use zenoh_core::zasync_executor_init;
 use zenoh_core::Result as ZResult;
 use zenoh_link::{EndPoint, Link};
+#[cfg(feature = "auth_pubkey")]
+use zenoh_protocol::core::{Channel, CongestionControl, Priority, Reliability};
 use zenoh_protocol::core::{PeerId, WhatAmI};
+#[cfg(feature = "auth_pubkey")]
+use zenoh_protocol::io::ZBuf;
+#[cfg(feature = "auth_pubkey")]
+use zenoh_protocol::proto::ZenohBody;
 use zenoh_protocol::proto::ZenohMessage;
 #[cfg(feature = "auth_pubkey")]
 use zenoh_transport::unicast::establishment::authenticator::PubKeyAuthenticator;
@@ -525,6 +535,178 @@ async fn authenticator_multilink(endpoint: &EndPoint) {
     task::sleep(SLEEP).await;
 }
 
+#[cfg(feature = "auth_pubkey")]
+struct SHRouterEncryption {
+    count: Arc<AtomicUsize>,
+}
+
+#[cfg(feature = "auth_pubkey")]
+impl TransportEventHandler for SHRouterEncryption {
+    fn new_unicast(
+        &self,
+        _peer: TransportPeer,
+        _transport: TransportUnicast,
+    ) -> ZResult<Arc<dyn TransportPeerEventHandler>> {
+        Ok(Arc::new(MHRouterEncryption {
+            count: self.count.clone(),
+        }))
+    }
+
+    fn new_multicast(
+        &self,
+        _transport: TransportMulticast,
+    ) -> ZResult<Arc<dyn TransportMulticastEventHandler>> {
+        panic!();
+    }
+}
+
+#[cfg(feature = "auth_pubkey")]
+struct MHRouterEncryption {
+    count: Arc<AtomicUsize>,
+}
+
+#[cfg(feature = "auth_pubkey")]
+impl TransportPeerEventHandler for MHRouterEncryption {
+    fn handle_message(&self, msg: ZenohMessage) -> ZResult<()> {
+        if let ZenohBody::Data(data) = msg.body {
+            // The payload is received in clear
+            assert!(data.payload.contiguous().iter().all(|b| *b == 0x5a));
+            self.count.fetch_add(1, Ordering::SeqCst);
+        }
+        Ok(())
+    }
+    fn new_link(&self, _link: Link) {}
+    fn del_link(&self, _link: Link) {}
+    fn closing(&self) {}
+    fn closed(&self) {}
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+#[cfg(feature = "auth_pubkey")]
+async fn authenticator_encryption(endpoint: &EndPoint) {
+    const MSG_COUNT: usize = 100;
+    const MSG_SIZE: usize = 1_024;
+
+    /* [ROUTER] */
+    let router_id = PeerId::new(1, [0_u8; PeerId::MAX_SIZE]);
+    let count = Arc::new(AtomicUsize::new(0));
+    let router_handler = Arc::new(SHRouterEncryption {
+        count: count.clone(),
+    });
+    let peer_auth_router = PubKeyAuthenticator::make().unwrap().encryption(true);
+    let unicast = TransportManager::config_unicast()
+        .peer_authenticator(HashSet::from_iter(vec![peer_auth_router.into()]));
+    let router_manager = TransportManager::builder()
+        .whatami(WhatAmI::Router)
+        .pid(router_id)
+        .unicast(unicast)
+        .build(router_handler)
+        .unwrap();
+
+    /* [CLIENT] */
+    let client01_id = PeerId::new(1, [1_u8; PeerId::MAX_SIZE]);
+    let peer_auth_client01 = PubKeyAuthenticator::make().unwrap().encryption(true);
+    let unicast = TransportManager::config_unicast()
+        .peer_authenticator(HashSet::from_iter(vec![peer_auth_client01.into()]));
+    let client01_manager = TransportManager::builder()
+        .whatami(WhatAmI::Client)
+        .pid(client01_id)
+        .unicast(unicast)
+        .build(Arc::new(SHClientAuthenticator))
+        .unwrap();
+
+    // The client 02 does not request the encryption
+    let client02_id = PeerId::new(1, [2_u8; PeerId::MAX_SIZE]);
+    let peer_auth_client02 = PubKeyAuthenticator::make().unwrap();
+    let unicast = TransportManager::config_unicast()
+        .peer_authenticator(HashSet::from_iter(vec![peer_auth_client02.into()]));
+    let client02_manager = TransportManager::builder()
+        .whatami(WhatAmI::Client)
+        .pid(client02_id)
+        .unicast(unicast)
+        .build(Arc::new(SHClientAuthenticator))
+        .unwrap();
+
+    /* [1] */
+    println!("\nTransport Authenticator Encryption [1a1]");
+    let _ = ztimeout!(router_manager.add_listener(endpoint.clone())).unwrap();
+
+    /* [2] */
+    // Open a transport without encryption from client02 to the router
+    // -> This should be rejected
+    println!("Transport Authenticator Encryption [2a1]");
+    let res = ztimeout!(client02_manager.open_transport(endpoint.clone()));
+    println!("Transport Authenticator Encryption [2a2]: {:?}", res);
+    assert!(res.is_err());
+
+    /* [3] */
+    // Open an encrypted transport from client01 to the router
+    // -> This should be accepted and the messages delivered
+    println!("Transport Authenticator Encryption [3a1]");
+    let res = ztimeout!(client01_manager.open_transport(endpoint.clone()));
+    println!("Transport Authenticator Encryption [3a2]: {:?}", res);
+    assert!(res.is_ok());
+    let c_ses1 = res.unwrap();
+
+    let message = ZenohMessage::make_data(
+        "/test".into(),
+        ZBuf::from(vec![0x5a_u8; MSG_SIZE]),
+        Channel {
+            priority: Priority::default(),
+            reliability: Reliability::Reliable,
+        },
+        CongestionControl::Block,
+        None,
+        None,
+        None,
+        None,
+    );
+    for _ in 0..MSG_COUNT {
+        c_ses1.schedule(message.clone()).unwrap();
+    }
+    ztimeout!(async {
+        while count.load(Ordering::SeqCst) != MSG_COUNT {
+            task::sleep(SLEEP).await;
+        }
+    });
+
+    /* [4] */
+    // Close the session
+    println!("Transport Authenticator Encryption [4a1]");
+    let res = ztimeout!(c_ses1.close());
+    println!("Transport Authenticator Encryption [4a2]: {:?}", res);
+    assert!(res.is_ok());
+
+    ztimeout!(async {
+        while !router_manager.get_transports().is_empty() {
+            task::sleep(SLEEP).await;
+        }
+    });
+
+    /* [5] */
+    // Perform clean up of the open locators
+    println!("Transport Authenticator Encryption [5a1]");
+    let res = ztimeout!(router_manager.del_listener(endpoint));
+    println!("Transport Authenticator Encryption [5a2]: {:?}", res);
+    assert!(res.is_ok());
+
+    ztimeout!(async {
+        while !router_manager.get_listeners().is_empty() {
+            task::sleep(SLEEP).await;
+        }
+    });
+
+    ztimeout!(router_manager.close());
+    ztimeout!(client01_manager.close());
+    ztimeout!(client02_manager.close());
+
+    // Wait a little bit
+    task::sleep(SLEEP).await;
+}
+
 #[cfg(feature = "auth_usrpwd")]
 async fn authenticator_user_password(endpoint: &EndPoint) {
     /* [CLIENT] */
@@ -787,6 +969,8 @@ async fn authenticator_shared_memory(endpoint: &EndPoint) {
 async fn run(endpoint: &EndPoint) {
     #[cfg(feature = "auth_pubkey")]
     authenticator_multilink(endpoint).await;
+    #[cfg(feature = "auth_pubkey")]
+    authenticator_encryption(endpoint).await;
     #[cfg(feature = "auth_usrpwd")]
     authenticator_user_password(endpoint).await;
     #[cfg(feature = "shared-memory")]
@@ -0,0 +1,327 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ADLINK zenoh team, <zenoh@adlink-labs.tech>
+//
+use async_std::prelude::*;
+use async_std::sync::Arc;
+use async_std::task;
+use std::any::Any;
+use std::convert::TryInto;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::time::Duration;
+use zenoh_config::LinkScheduling;
+use zenoh_core::zasync_executor_init;
+use zenoh_core::Result as ZResult;
+use zenoh_link::{EndPoint, Link};
+use zenoh_protocol::io::{SplitBuffer, ZBuf};
+use zenoh_protocol::proto::{ZenohBody, ZenohMessage};
+use zenoh_protocol_core::{Channel, CongestionControl, PeerId, Priority, Reliability, WhatAmI};
+use zenoh_transport::{
+    TransportEventHandler, TransportManager, TransportMulticast, TransportMulticastEventHandler,
+    TransportPeer, TransportPeerEventHandler, TransportUnicast,
+};
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+const SLEEP: Duration = Duration::from_secs(1);
+const SLEEP_COUNT: Duration = Duration::from_millis(10);
+
+const MSG_COUNT: usize = 1_000;
+const MSG_SIZE_ALL: [usize; 2] = [1_024, 131_072];
+
+macro_rules! ztimeout {
+    ($f:expr) => {
+        $f.timeout(TIMEOUT).await.unwrap()
+    };
+}
+
+// Transport Handler for the router
+#[derive(Default)]
+struct SHRouter {
+    count: Arc<AtomicUsize>,
+    in_order: Arc<AtomicBool>,
+}
+
+impl SHRouter {
+    fn reset(&self) {
+        self.count.store(0, Ordering::SeqCst);
+        self.in_order.store(true, Ordering::SeqCst);
+    }
+
+    fn get_count(&self) -> usize {
+        self.count.load(Ordering::SeqCst)
+    }
+
+    fn is_in_order(&self) -> bool {
+        self.in_order.load(Ordering::SeqCst)
+    }
+}
+
+impl TransportEventHandler for SHRouter {
+    fn new_unicast(
+        &self,
+        _peer: TransportPeer,
+        _transport: TransportUnicast,
+    ) -> ZResult<Arc<dyn TransportPeerEventHandler>> {
+        let arc = Arc::new(SCRouter {
+            count: self.count.clone(),
+            in_order: self.in_order.clone(),
+        });
+        Ok(arc)
+    }
+
+    fn new_multicast(
+        &self,
+        _transport: TransportMulticast,
+    ) -> ZResult<Arc<dyn TransportMulticastEventHandler>> {
+        panic!();
+    }
+}
+
+// Transport Callback for the router, checking that the messages are received in order
+pub struct SCRouter {
+    count: Arc<AtomicUsize>,
+    in_order: Arc<AtomicBool>,
+}
+
+impl TransportPeerEventHandler for SCRouter {
+    fn handle_message(&self, message: ZenohMessage) -> ZResult<()> {
+        if let ZenohBody::Data(data) = message.body {
+            let payload = data.payload.contiguous();
+            let index = u64::from_le_bytes(payload[..8].try_into().unwrap()) as usize;
+            let count = self.count.fetch_add(1, Ordering::SeqCst);
+            if index != count {
+                self.in_order.store(false, Ordering::SeqCst);
+            }
+        }
+        Ok(())
+    }
+
+    fn new_link(&self, _link: Link) {}
+    fn del_link(&self, _link: Link) {}
+    fn closing(&self) {}
+    fn closed(&self) {}
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+// Transport Handler for the client
+#[derive(Default)]
+struct SHClient;
+
+impl TransportEventHandler for SHClient {
+    fn new_unicast(
+        &self,
+        _peer: TransportPeer,
+        _transport: TransportUnicast,
+    ) -> ZResult<Arc<dyn TransportPeerEventHandler>> {
+        Ok(Arc::new(SCClient))
+    }
+
+    fn new_multicast(
+        &self,
+        _transport: TransportMulticast,
+    ) -> ZResult<Arc<dyn TransportMulticastEventHandler>> {
+        panic!();
+    }
+}
+
+// Transport Callback for the client
+#[derive(Default)]
+pub struct SCClient;
+
+impl TransportPeerEventHandler for SCClient {
+    fn handle_message(&self, _message: ZenohMessage) -> ZResult<()> {
+        Ok(())
+    }
+
+    fn new_link(&self, _link: Link) {}
+    fn del_link(&self, _link: Link) {}
+    fn closing(&self) {}
+    fn closed(&self) {}
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+async fn open_transport(
+    endpoints: &[EndPoint],
+    link_scheduling: LinkScheduling,
+) -> (
+    TransportManager,
+    Arc<SHRouter>,
+    TransportManager,
+    TransportUnicast,
+) {
+    // Define client and router IDs
+    let client_id = PeerId::new(1, [0_u8; PeerId::MAX_SIZE]);
+    let router_id = PeerId::new(1, [1_u8; PeerId::MAX_SIZE]);
+
+    // Create the router transport manager
+    let router_handler = Arc::new(SHRouter::default());
+    let unicast = TransportManager::config_unicast()
+        .max_links(endpoints.len())
+        .link_scheduling(link_scheduling);
+    let router_manager = TransportManager::builder()
+        .pid(router_id)
+        .whatami(WhatAmI::Router)
+        .unicast(unicast)
+        .build(router_handler.clone())
+        .unwrap();
+
+    // Create the client transport manager
+    let unicast = TransportManager::config_unicast()
+        .max_links(endpoints.len())
+        .link_scheduling(link_scheduling);
+    let client_manager = TransportManager::builder()
+        .whatami(WhatAmI::Client)
+        .pid(client_id)
+        .unicast(unicast)
+        .build(Arc::new(SHClient))
+        .unwrap();
+
+    // Create the listeners on the router
+    for e in endpoints.iter() {
+        println!("Add endpoint: {}", e);
+        let _ = ztimeout!(router_manager.add_listener(e.clone())).unwrap();
+    }
+
+    // Open a link on each endpoint with the client
+    for e in endpoints.iter() {
+        println!("Opening transport with {}", e);
+        let _ = ztimeout!(client_manager.open_transport(e.clone())).unwrap();
+    }
+
+    let client_transport = client_manager.get_transport(&router_id).unwrap();
+    assert_eq!(client_transport.get_links().unwrap().len(), endpoints.len());
+
+    (
+        router_manager,
+        router_handler,
+        client_manager,
+        client_transport,
+    )
+}
+
+async fn close_transport(
+    router_manager: TransportManager,
+    client_manager: TransportManager,
+    client_transport: TransportUnicast,
+    endpoints: &[EndPoint],
+) {
+    ztimeout!(client_transport.close()).unwrap();
+
+    ztimeout!(async {
+        while !router_manager.get_transports().is_empty() {
+            task::sleep(SLEEP).await;
+        }
+    });
+
+    for e in endpoints.iter() {
+        println!("Del locator: {}", e);
+        ztimeout!(router_manager.del_listener(e)).unwrap();
+    }
+
+    ztimeout!(router_manager.close());
+    ztimeout!(client_manager.close());
+
+    // Wait a little bit
+    task::sleep(SLEEP).await;
+}
+
+async fn test_transport(
+    router_handler: Arc<SHRouter>,
+    client_transport: TransportUnicast,
+    msg_size: usize,
+) {
+    let channel = Channel {
+        priority: Priority::default(),
+        reliability: Reliability::Reliable,
+    };
+
+    println!(
+        "Sending {} messages... {:?} {}",
+        MSG_COUNT, channel, msg_size
+    );
+    router_handler.reset();
+    for i in 0..MSG_COUNT {
+        // The payload starts with the index of the message
+        let mut payload = vec![0_u8; msg_size];
+        payload[..8].copy_from_slice(&(i as u64).to_le_bytes());
+        let message = ZenohMessage::make_data(
+            "/test".into(),
+            ZBuf::from(payload),
+            channel,
+            CongestionControl::Block,
+            None,
+            None,
+            None,
+            None,
+        );
+        client_transport.schedule(message).unwrap();
+    }
+
+    ztimeout!(async {
+        while router_handler.get_count() != MSG_COUNT {
+            task::sleep(SLEEP_COUNT).await;
+        }
+    });
+    assert!(router_handler.is_in_order());
+}
+
+async fn run(endpoints: &[EndPoint], link_scheduling: LinkScheduling) {
+    println!("Link scheduling: {:?}", link_scheduling);
+    let (router_manager, router_handler, client_manager, client_transport) =
+        open_transport(endpoints, link_scheduling).await;
+
+    for ms in MSG_SIZE_ALL.iter() {
+        test_transport(router_handler.clone(), client_transport.clone(), *ms).await;
+    }
+
+    // The traffic moves to the remaining link when a link is closed
+    let links = client_transport.get_links().unwrap();
+    ztimeout!(client_transport.close_link(&links[0])).unwrap();
+    ztimeout!(async {
+        while client_transport.get_links().unwrap().len() != links.len() - 1 {
+            task::sleep(SLEEP_COUNT).await;
+        }
+    });
+    for ms in MSG_SIZE_ALL.iter() {
+        test_transport(router_handler.clone(), client_transport.clone(), *ms).await;
+    }
+
+    close_transport(router_manager, client_manager, client_transport, endpoints).await;
+}
+
+#[cfg(feature = "transport_tcp")]
+#[test]
+fn transport_multilink_tcp() {
+    task::block_on(async {
+        zasync_executor_init!();
+    });
+
+    let endpoints: Vec<EndPoint> = vec![
+        "tcp/127.0.0.1:17447".parse().unwrap(),
+        "tcp/[::1]:17447".parse().unwrap(),
+    ];
+    task::block_on(async {
+        for link_scheduling in [
+            LinkScheduling::ActiveStandby,
+            LinkScheduling::RoundRobin,
+            LinkScheduling::LeastQueued,
+        ] {
+            run(&endpoints, link_scheduling).await;
+        }
+    });
+}
@@ -173,6 +173,12 @@ async fn openclose_transport(endpoint: &EndPoint) {
     });
     println!("Transport Open Close [1f2]: {:?}", res);
 
+    // Verify that the router answers the Pings on the established link
+    println!("Transport Open Close [1g1]");
+    let res = ztimeout!(c_ses1.get_rtt(TIMEOUT));
+    println!("Transport Open Close [1g2]: {:?}", res);
+    assert!(res.is_ok());
+
     /* [2] */
     // Open a second transport from the client to the router
     // -> This should be accepted
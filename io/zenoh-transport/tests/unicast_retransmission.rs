@@ -0,0 +1,582 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ADLINK zenoh team, <zenoh@adlink-labs.tech>
+//
+use async_std::net::{TcpListener, TcpStream, UdpSocket};
+use async_std::prelude::*;
+use async_std::sync::{Arc, Mutex};
+use async_std::task;
+use std::any::Any;
+use std::convert::TryInto;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::time::Duration;
+use zenoh_core::zasync_executor_init;
+use zenoh_core::Result as ZResult;
+use zenoh_link::{EndPoint, Link};
+use zenoh_protocol::io::{SplitBuffer, WBuf, ZBuf};
+use zenoh_protocol::proto::{tmsg, MessageWriter, TransportMessage, ZenohBody, ZenohMessage};
+use zenoh_protocol_core::{Channel, CongestionControl, PeerId, Priority, Reliability, WhatAmI};
+use zenoh_transport::{
+    TransportEventHandler, TransportManager, TransportMulticast, TransportMulticastEventHandler,
+    TransportPeer, TransportPeerEventHandler, TransportUnicast,
+};
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+const SLEEP: Duration = Duration::from_secs(1);
+const SLEEP_COUNT: Duration = Duration::from_millis(10);
+
+const MSG_COUNT: usize = 1_000;
+const MSG_SIZE_ALL: [usize; 2] = [1_024, 16_384];
+
+const PING_COUNT: usize = 10;
+
+// One reliable frame out of DROP_PERIOD is dropped by the proxy
+const DROP_PERIOD: usize = 4;
+
+macro_rules! ztimeout {
+    ($f:expr) => {
+        $f.timeout(TIMEOUT).await.unwrap()
+    };
+}
+
+// Transport Handler for the router
+#[derive(Default)]
+struct SHRouter {
+    count: Arc<AtomicUsize>,
+    in_order: Arc<AtomicBool>,
+}
+
+impl SHRouter {
+    fn reset(&self) {
+        self.count.store(0, Ordering::SeqCst);
+        self.in_order.store(true, Ordering::SeqCst);
+    }
+
+    fn get_count(&self) -> usize {
+        self.count.load(Ordering::SeqCst)
+    }
+
+    fn is_in_order(&self) -> bool {
+        self.in_order.load(Ordering::SeqCst)
+    }
+}
+
+impl TransportEventHandler for SHRouter {
+    fn new_unicast(
+        &self,
+        _peer: TransportPeer,
+        _transport: TransportUnicast,
+    ) -> ZResult<Arc<dyn TransportPeerEventHandler>> {
+        let arc = Arc::new(SCRouter {
+            count: self.count.clone(),
+            in_order: self.in_order.clone(),
+        });
+        Ok(arc)
+    }
+
+    fn new_multicast(
+        &self,
+        _transport: TransportMulticast,
+    ) -> ZResult<Arc<dyn TransportMulticastEventHandler>> {
+        panic!();
+    }
+}
+
+// Transport Callback for the router, checking that the messages are received in order
+pub struct SCRouter {
+    count: Arc<AtomicUsize>,
+    in_order: Arc<AtomicBool>,
+}
+
+impl TransportPeerEventHandler for SCRouter {
+    fn handle_message(&self, message: ZenohMessage) -> ZResult<()> {
+        if let ZenohBody::Data(data) = message.body {
+            let payload = data.payload.contiguous();
+            let index = u64::from_le_bytes(payload[..8].try_into().unwrap()) as usize;
+            let count = self.count.fetch_add(1, Ordering::SeqCst);
+            if index != count {
+                self.in_order.store(false, Ordering::SeqCst);
+            }
+        }
+        Ok(())
+    }
+
+    fn new_link(&self, _link: Link) {}
+    fn del_link(&self, _link: Link) {}
+    fn closing(&self) {}
+    fn closed(&self) {}
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+// Transport Handler for the client
+#[derive(Default)]
+struct SHClient;
+
+impl TransportEventHandler for SHClient {
+    fn new_unicast(
+        &self,
+        _peer: TransportPeer,
+        _transport: TransportUnicast,
+    ) -> ZResult<Arc<dyn TransportPeerEventHandler>> {
+        Ok(Arc::new(SCClient))
+    }
+
+    fn new_multicast(
+        &self,
+        _transport: TransportMulticast,
+    ) -> ZResult<Arc<dyn TransportMulticastEventHandler>> {
+        panic!();
+    }
+}
+
+// Transport Callback for the client
+#[derive(Default)]
+pub struct SCClient;
+
+impl TransportPeerEventHandler for SCClient {
+    fn handle_message(&self, _message: ZenohMessage) -> ZResult<()> {
+        Ok(())
+    }
+
+    fn new_link(&self, _link: Link) {}
+    fn del_link(&self, _link: Link) {}
+    fn closing(&self) {}
+    fn closed(&self) {}
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+// A UDP proxy forwarding the datagrams between the client and the router, dropping
+// periodically the datagrams starting with a reliable frame sent by the client
+async fn lossy_proxy(front: &str, back: &str) -> Arc<AtomicUsize> {
+    let dropped = Arc::new(AtomicUsize::new(0));
+    let front = Arc::new(UdpSocket::bind(front).await.unwrap());
+    let back_socket = Arc::new(UdpSocket::bind("127.0.0.1:0").await.unwrap());
+    back_socket.connect(back).await.unwrap();
+    let client: Arc<Mutex<Option<SocketAddr>>> = Arc::new(Mutex::new(None));
+
+    let c_front = front.clone();
+    let c_back = back_socket.clone();
+    let c_client = client.clone();
+    let c_dropped = dropped.clone();
+    task::spawn(async move {
+        let mut buffer = vec![0_u8; 65_535];
+        let mut frames = 0;
+        loop {
+            let (n, addr) = c_front.recv_from(&mut buffer).await.unwrap();
+            *c_client.lock().await = Some(addr);
+            let header = buffer[0];
+            if header & 0x1f == tmsg::id::FRAME && header & tmsg::flag::R != 0 {
+                frames += 1;
+                if frames % DROP_PERIOD == 0 {
+                    c_dropped.fetch_add(1, Ordering::SeqCst);
+                    continue;
+                }
+            }
+            let _ = c_back.send(&buffer[..n]).await;
+        }
+    });
+
+    task::spawn(async move {
+        let mut buffer = vec![0_u8; 65_535];
+        loop {
+            let n = back_socket.recv(&mut buffer).await.unwrap();
+            if let Some(addr) = *client.lock().await {
+                let _ = front.send_to(&buffer[..n], addr).await;
+            }
+        }
+    });
+
+    dropped
+}
+
+// A TCP proxy forwarding the batches between the client and the router and, once enabled,
+// injecting a Sync after each batch sent by the client, for the router to answer with an AckNack
+async fn sync_proxy(front: &str, back: &str, enabled: Arc<AtomicBool>) -> Arc<AtomicUsize> {
+    let injected = Arc::new(AtomicUsize::new(0));
+    let listener = TcpListener::bind(front).await.unwrap();
+    let back = back.to_string();
+
+    let c_injected = injected.clone();
+    task::spawn(async move {
+        let (mut front, _) = listener.accept().await.unwrap();
+        let mut back = TcpStream::connect(back).await.unwrap();
+
+        let (mut c_front, mut c_back) = (front.clone(), back.clone());
+        task::spawn(async move { async_std::io::copy(&mut c_back, &mut c_front).await });
+
+        let mut sync =
+            TransportMessage::make_sync(Priority::default(), Reliability::Reliable, 0, None, None);
+        let mut wbuf = WBuf::new(64, false);
+        assert!(wbuf.write_transport_message(&mut sync));
+        let mut sync = (wbuf.len() as u16).to_le_bytes().to_vec();
+        sync.extend_from_slice(&wbuf.contiguous());
+
+        let mut buffer = vec![0_u8; 65_535];
+        loop {
+            let mut length = [0_u8; 2];
+            if front.read_exact(&mut length).await.is_err() {
+                break;
+            }
+            let n = u16::from_le_bytes(length) as usize;
+            if front.read_exact(&mut buffer[..n]).await.is_err() {
+                break;
+            }
+            let _ = back.write_all(&length).await;
+            let _ = back.write_all(&buffer[..n]).await;
+            if enabled.load(Ordering::SeqCst) {
+                let _ = back.write_all(&sync).await;
+                c_injected.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+    });
+
+    injected
+}
+
+fn schedule_messages(transport: &TransportUnicast, msg_size: usize) {
+    let channel = Channel {
+        priority: Priority::default(),
+        reliability: Reliability::Reliable,
+    };
+
+    println!(
+        "Sending {} messages... {:?} {}",
+        MSG_COUNT, channel, msg_size
+    );
+    for i in 0..MSG_COUNT {
+        // The payload starts with the index of the message
+        let mut payload = vec![0_u8; msg_size];
+        payload[..8].copy_from_slice(&(i as u64).to_le_bytes());
+        let message = ZenohMessage::make_data(
+            "/test".into(),
+            ZBuf::from(payload),
+            channel,
+            CongestionControl::Block,
+            None,
+            None,
+            None,
+            None,
+        );
+        transport.schedule(message).unwrap();
+    }
+}
+
+async fn test_transport(
+    router_handler: Arc<SHRouter>,
+    client_transport: TransportUnicast,
+    msg_size: usize,
+) {
+    router_handler.reset();
+    schedule_messages(&client_transport, msg_size);
+
+    ztimeout!(async {
+        while router_handler.get_count() != MSG_COUNT {
+            task::sleep(SLEEP_COUNT).await;
+        }
+    });
+    assert!(router_handler.is_in_order());
+}
+
+#[cfg(feature = "transport_udp")]
+#[test]
+fn transport_retransmission_udp() {
+    task::block_on(async {
+        zasync_executor_init!();
+    });
+
+    task::block_on(async {
+        let listener: EndPoint = "udp/127.0.0.1:18447".parse().unwrap();
+        let proxy: EndPoint = "udp/127.0.0.1:18448".parse().unwrap();
+        let dropped = lossy_proxy("127.0.0.1:18448", "127.0.0.1:18447").await;
+
+        // Define client and router IDs
+        let client_id = PeerId::new(1, [0_u8; PeerId::MAX_SIZE]);
+        let router_id = PeerId::new(1, [1_u8; PeerId::MAX_SIZE]);
+
+        // Create the router transport manager
+        let router_handler = Arc::new(SHRouter::default());
+        let router_manager = TransportManager::builder()
+            .pid(router_id)
+            .whatami(WhatAmI::Router)
+            .build(router_handler.clone())
+            .unwrap();
+
+        // Create the client transport manager
+        let client_manager = TransportManager::builder()
+            .whatami(WhatAmI::Client)
+            .pid(client_id)
+            .build(Arc::new(SHClient))
+            .unwrap();
+
+        // Open the transport through the proxy
+        let _ = ztimeout!(router_manager.add_listener(listener.clone())).unwrap();
+        let client_transport = ztimeout!(client_manager.open_transport(proxy)).unwrap();
+
+        // The reliable messages are all received in order despite the dropped frames
+        for ms in MSG_SIZE_ALL.iter() {
+            test_transport(router_handler.clone(), client_transport.clone(), *ms).await;
+        }
+        assert!(dropped.load(Ordering::SeqCst) > 0);
+
+        ztimeout!(client_transport.close()).unwrap();
+        ztimeout!(async {
+            while !router_manager.get_transports().is_empty() {
+                task::sleep(SLEEP).await;
+            }
+        });
+        ztimeout!(router_manager.del_listener(&listener)).unwrap();
+        ztimeout!(router_manager.close());
+        ztimeout!(client_manager.close());
+
+        // Wait a little bit
+        task::sleep(SLEEP).await;
+    });
+}
+
+async fn run_with_faults(listener: EndPoint, endpoint: EndPoint) {
+    // Define client and router IDs
+    let client_id = PeerId::new(1, [0_u8; PeerId::MAX_SIZE]);
+    let router_id = PeerId::new(1, [1_u8; PeerId::MAX_SIZE]);
+
+    // Create the router transport manager
+    let router_handler = Arc::new(SHRouter::default());
+    let router_manager = TransportManager::builder()
+        .pid(router_id)
+        .whatami(WhatAmI::Router)
+        .build(router_handler.clone())
+        .unwrap();
+
+    // Create the client transport manager
+    let client_manager = TransportManager::builder()
+        .whatami(WhatAmI::Client)
+        .pid(client_id)
+        .build(Arc::new(SHClient))
+        .unwrap();
+
+    // Open the transport on the faulty link
+    let _ = ztimeout!(router_manager.add_listener(listener.clone())).unwrap();
+    let client_transport = ztimeout!(client_manager.open_transport(endpoint)).unwrap();
+
+    // Wait for the faults to kick in
+    task::sleep(SLEEP).await;
+
+    // The reliable messages are all received in order despite the faults
+    for ms in MSG_SIZE_ALL.iter() {
+        test_transport(router_handler.clone(), client_transport.clone(), *ms).await;
+    }
+
+    ztimeout!(client_transport.close()).unwrap();
+    ztimeout!(async {
+        while !router_manager.get_transports().is_empty() {
+            task::sleep(SLEEP).await;
+        }
+    });
+    ztimeout!(router_manager.del_listener(&listener)).unwrap();
+    ztimeout!(router_manager.close());
+    ztimeout!(client_manager.close());
+
+    // Wait a little bit
+    task::sleep(SLEEP).await;
+}
+
+#[cfg(feature = "transport_udp")]
+#[test]
+fn transport_retransmission_udp_faults() {
+    task::block_on(async {
+        zasync_executor_init!();
+    });
+
+    let listener: EndPoint = "udp/127.0.0.1:18450".parse().unwrap();
+    let endpoint: EndPoint = "udp/127.0.0.1:18450#fault_loss=0.05;fault_duplicate=0.05;fault_reorder=0.05;fault_jitter=2;fault_start=500;fault_seed=7"
+        .parse()
+        .unwrap();
+    task::block_on(run_with_faults(listener, endpoint));
+}
+
+#[cfg(feature = "transport_tcp")]
+#[test]
+fn transport_faults_tcp_delay() {
+    task::block_on(async {
+        zasync_executor_init!();
+    });
+
+    let listener: EndPoint = "tcp/127.0.0.1:18451".parse().unwrap();
+    let endpoint: EndPoint = "tcp/127.0.0.1:18451#fault_delay=20;fault_jitter=10"
+        .parse()
+        .unwrap();
+    task::block_on(run_with_faults(listener, endpoint));
+}
+
+#[cfg(feature = "transport_udp")]
+#[test]
+fn transport_retransmission_udp_bidirectional() {
+    task::block_on(async {
+        zasync_executor_init!();
+    });
+
+    task::block_on(async {
+        let listener: EndPoint = "udp/127.0.0.1:18452".parse().unwrap();
+        let endpoint: EndPoint =
+            "udp/127.0.0.1:18452#fault_loss=0.05;fault_reorder=0.05;fault_start=500;fault_seed=11"
+                .parse()
+                .unwrap();
+
+        // Define client and router IDs
+        let client_id = PeerId::new(1, [0_u8; PeerId::MAX_SIZE]);
+        let router_id = PeerId::new(1, [1_u8; PeerId::MAX_SIZE]);
+
+        // Both the router and the client check the order of the received messages
+        let router_handler = Arc::new(SHRouter::default());
+        let router_manager = TransportManager::builder()
+            .pid(router_id)
+            .whatami(WhatAmI::Router)
+            .build(router_handler.clone())
+            .unwrap();
+
+        let client_handler = Arc::new(SHRouter::default());
+        let client_manager = TransportManager::builder()
+            .whatami(WhatAmI::Client)
+            .pid(client_id)
+            .build(client_handler.clone())
+            .unwrap();
+
+        // Open the transport on the faulty link
+        let _ = ztimeout!(router_manager.add_listener(listener.clone())).unwrap();
+        let client_transport = ztimeout!(client_manager.open_transport(endpoint)).unwrap();
+        let router_transport = router_manager.get_transport(&client_id).unwrap();
+
+        // Wait for the faults to kick in
+        task::sleep(SLEEP).await;
+
+        // The reliable messages sent at the same time in both directions are all received in
+        // order: the AckNacks are not held back by the frames waiting for the window
+        for ms in MSG_SIZE_ALL.iter() {
+            router_handler.reset();
+            client_handler.reset();
+            let (c_client, c_router, c_ms) =
+                (client_transport.clone(), router_transport.clone(), *ms);
+            let client_task = task::spawn(async move { schedule_messages(&c_client, c_ms) });
+            let router_task = task::spawn(async move { schedule_messages(&c_router, c_ms) });
+            ztimeout!(client_task.join(router_task));
+            ztimeout!(async {
+                while router_handler.get_count() != MSG_COUNT
+                    || client_handler.get_count() != MSG_COUNT
+                {
+                    task::sleep(SLEEP_COUNT).await;
+                }
+            });
+            assert!(router_handler.is_in_order());
+            assert!(client_handler.is_in_order());
+        }
+
+        ztimeout!(client_transport.close()).unwrap();
+        ztimeout!(async {
+            while !router_manager.get_transports().is_empty() {
+                task::sleep(SLEEP).await;
+            }
+        });
+        ztimeout!(router_manager.del_listener(&listener)).unwrap();
+        ztimeout!(router_manager.close());
+        ztimeout!(client_manager.close());
+
+        // Wait a little bit
+        task::sleep(SLEEP).await;
+    });
+}
+
+#[cfg(feature = "transport_tcp")]
+#[test]
+fn transport_acknack_tcp_under_load() {
+    task::block_on(async {
+        zasync_executor_init!();
+    });
+
+    task::block_on(async {
+        let listener: EndPoint = "tcp/127.0.0.1:18453".parse().unwrap();
+        let proxy: EndPoint = "tcp/127.0.0.1:18454".parse().unwrap();
+        let enabled = Arc::new(AtomicBool::new(false));
+        let injected = sync_proxy("127.0.0.1:18454", "127.0.0.1:18453", enabled.clone()).await;
+
+        // Define client and router IDs
+        let client_id = PeerId::new(1, [0_u8; PeerId::MAX_SIZE]);
+        let router_id = PeerId::new(1, [1_u8; PeerId::MAX_SIZE]);
+
+        // Both the router and the client check the order of the received messages
+        let router_handler = Arc::new(SHRouter::default());
+        let router_manager = TransportManager::builder()
+            .pid(router_id)
+            .whatami(WhatAmI::Router)
+            .build(router_handler.clone())
+            .unwrap();
+
+        let client_handler = Arc::new(SHRouter::default());
+        let client_manager = TransportManager::builder()
+            .whatami(WhatAmI::Client)
+            .pid(client_id)
+            .build(client_handler.clone())
+            .unwrap();
+
+        // Open the transport through the proxy, then inject the Syncs
+        let _ = ztimeout!(router_manager.add_listener(listener.clone())).unwrap();
+        let client_transport = ztimeout!(client_manager.open_transport(proxy)).unwrap();
+        let router_transport = router_manager.get_transport(&client_id).unwrap();
+        enabled.store(true, Ordering::SeqCst);
+
+        // The AckNacks written by the router, and the Pings and Pongs, written while the batches
+        // are sent on the stream don't corrupt it: the messages are all received in order
+        for ms in MSG_SIZE_ALL.iter() {
+            router_handler.reset();
+            client_handler.reset();
+            let (c_client, c_router, c_ms) =
+                (client_transport.clone(), router_transport.clone(), *ms);
+            let client_task = task::spawn(async move { schedule_messages(&c_client, c_ms) });
+            let router_task = task::spawn(async move { schedule_messages(&c_router, c_ms) });
+            let c_client = client_transport.clone();
+            let ping_task = task::spawn(async move {
+                for _ in 0..PING_COUNT {
+                    c_client.get_rtt(TIMEOUT).await.unwrap();
+                }
+            });
+            ztimeout!(client_task.join(router_task).join(ping_task));
+            ztimeout!(async {
+                while router_handler.get_count() != MSG_COUNT
+                    || client_handler.get_count() != MSG_COUNT
+                {
+                    task::sleep(SLEEP_COUNT).await;
+                }
+            });
+            assert!(router_handler.is_in_order());
+            assert!(client_handler.is_in_order());
+        }
+        assert!(injected.load(Ordering::SeqCst) > 0);
+
+        ztimeout!(client_transport.close()).unwrap();
+        ztimeout!(async {
+            while !router_manager.get_transports().is_empty() {
+                task::sleep(SLEEP).await;
+            }
+        });
+        ztimeout!(router_manager.del_listener(&listener)).unwrap();
+        ztimeout!(router_manager.close());
+        ztimeout!(client_manager.close());
+
+        // Wait a little bit
+        task::sleep(SLEEP).await;
+    });
+}
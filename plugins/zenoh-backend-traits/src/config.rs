@@ -1,6 +1,7 @@
 use derive_more::{AsMut, AsRef};
 use serde_json::{Map, Value};
 use std::convert::TryFrom;
+use std::time::Duration;
 use zenoh::Result as ZResult;
 use zenoh_core::{bail, zerror, Error};
 
@@ -29,10 +30,40 @@ pub struct StorageConfig {
     pub name: String,
     pub key_expr: String,
     pub strip_prefix: String,
+    pub replication: Option<ReplicationConfig>,
+    pub retention: Option<RetentionConfig>,
     #[as_ref]
     #[as_mut]
     pub rest: Map<String, Value>,
 }
+/// The configuration of the anti-entropy protocol between the storages on the same key expression.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReplicationConfig {
+    /// The interval between 2 exchanges of digests with the other storages.
+    pub digest_interval: Duration,
+    /// The tolerated clock skew between storages: the samples with a timestamp more recent than
+    /// `now - clock_skew` are not yet considered in the exchanged digests.
+    pub clock_skew: Duration,
+}
+impl Default for ReplicationConfig {
+    fn default() -> Self {
+        ReplicationConfig {
+            digest_interval: Duration::from_secs(10),
+            clock_skew: Duration::from_secs(1),
+        }
+    }
+}
+/// The retention policy of a storage, enforced by the storages plugin whatever the backend.
+/// When a limit is exceeded, the oldest samples (according to their timestamps) are evicted first.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RetentionConfig {
+    /// The maximum age of a sample, relative to its timestamp.
+    pub ttl: Option<Duration>,
+    /// The maximum number of keys in the storage.
+    pub max_keys: Option<usize>,
+    /// The maximum total size of the payloads in the storage.
+    pub max_bytes: Option<usize>,
+}
 #[derive(Debug)]
 pub enum ConfigDiff {
     DeleteBackend(BackendConfig),
@@ -255,6 +286,31 @@ impl StorageConfig {
                 Value::String(self.strip_prefix.clone()),
             );
         }
+        if let Some(replication) = &self.replication {
+            let mut value = Map::new();
+            value.insert(
+                "digest_interval".into(),
+                replication.digest_interval.as_secs_f64().into(),
+            );
+            value.insert(
+                "clock_skew".into(),
+                replication.clock_skew.as_secs_f64().into(),
+            );
+            result.insert("replication".into(), Value::Object(value));
+        }
+        if let Some(retention) = &self.retention {
+            let mut value = Map::new();
+            if let Some(ttl) = retention.ttl {
+                value.insert("ttl".into(), ttl.as_secs_f64().into());
+            }
+            if let Some(max_keys) = retention.max_keys {
+                value.insert("max_keys".into(), max_keys.into());
+            }
+            if let Some(max_bytes) = retention.max_bytes {
+                value.insert("max_bytes".into(), max_bytes.into());
+            }
+            result.insert("retention".into(), Value::Object(value));
+        }
         Value::Object(result)
     }
     fn try_from<V: AsObject>(
@@ -284,15 +340,86 @@ impl StorageConfig {
             plugin_name,
             backend_name)
         };
+        let replication = match config.get("replication") {
+            None | Some(Value::Bool(false)) => None,
+            Some(Value::Bool(true)) => Some(ReplicationConfig::default()),
+            Some(Value::Object(replication)) => {
+                let mut result = ReplicationConfig::default();
+                for (k, v) in replication {
+                    let duration = match v.as_f64() {
+                        Some(secs) if secs.is_finite() && secs >= 0.0 => Duration::from_secs_f64(secs),
+                        _ => bail!("`replication.{}` field of `{}`'s `{}` storage configuration must be a positive number of seconds",
+                            k,
+                            backend_name,
+                            storage_name)
+                    };
+                    match k.as_str() {
+                        "digest_interval" if !duration.is_zero() => result.digest_interval = duration,
+                        "clock_skew" => result.clock_skew = duration,
+                        _ => bail!("unknown or invalid `replication.{}` field in `{}`'s `{}` storage configuration",
+                            k,
+                            backend_name,
+                            storage_name)
+                    }
+                }
+                Some(result)
+            }
+            _ => bail!("`replication` field of `{}`'s `{}` storage configuration must be a boolean or an object",
+            backend_name,
+            storage_name)
+        };
+        let retention = match config.get("retention") {
+            None => None,
+            Some(Value::Object(retention)) => {
+                let mut result = RetentionConfig::default();
+                for (k, v) in retention {
+                    match k.as_str() {
+                        "ttl" => match v.as_f64() {
+                            Some(secs) if secs.is_finite() && secs > 0.0 => result.ttl = Some(Duration::from_secs_f64(secs)),
+                            _ => bail!("`retention.ttl` field of `{}`'s `{}` storage configuration must be a strictly positive number of seconds",
+                                backend_name,
+                                storage_name)
+                        },
+                        "max_keys" | "max_bytes" => {
+                            let max = match v.as_u64() {
+                                Some(max) if max > 0 => Some(max as usize),
+                                _ => bail!("`retention.{}` field of `{}`'s `{}` storage configuration must be a strictly positive integer",
+                                    k,
+                                    backend_name,
+                                    storage_name)
+                            };
+                            if k == "max_keys" {
+                                result.max_keys = max;
+                            } else {
+                                result.max_bytes = max;
+                            }
+                        }
+                        _ => bail!("unknown `retention.{}` field in `{}`'s `{}` storage configuration",
+                            k,
+                            backend_name,
+                            storage_name)
+                    }
+                }
+                Some(result)
+            }
+            _ => bail!(
+                "`retention` field of `{}`'s `{}` storage configuration must be an object",
+                backend_name,
+                storage_name
+            ),
+        };
         Ok(StorageConfig {
             name: storage_name.into(),
             key_expr,
             strip_prefix,
+            replication,
+            retention,
             rest: config
                 .into_iter()
                 .filter_map(|(k, v)| {
-                    (!["key_expr", "strip_prefix"].contains(&k.as_str()))
-                        .then(|| (k.clone(), v.clone()))
+                    (!["key_expr", "strip_prefix", "replication", "retention"]
+                        .contains(&k.as_str()))
+                    .then(|| (k.clone(), v.clone()))
                 })
                 .collect(),
         })
@@ -172,6 +172,15 @@ pub trait Storage: Send + Sync {
     /// Function called for each incoming query matching this storage's keys exp.
     /// This storage should reply with data matching the query calling [`Query::reply()`].
     async fn on_query(&mut self, query: Query) -> ZResult<()>;
+
+    /// Returns the samples currently held by this storage, with their timestamps, including
+    /// a DELETE sample for each removed key that it still remembers.
+    /// It's called at the storage start, to let the replication know about the already stored data
+    /// (e.g. the data recovered by a persistent storage after a restart).
+    /// The default implementation returns no sample, i.e. the content of the storage is unknown.
+    async fn get_all_entries(&self) -> ZResult<Vec<Sample>> {
+        Ok(Vec::new())
+    }
 }
 
 /// A wrapper around the [`zenoh::queryable::Query`] allowing to call the
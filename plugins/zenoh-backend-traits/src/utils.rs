@@ -14,6 +14,8 @@
 
 //! Some useful functions for Backend/Storage implementations.
 
+use std::str::FromStr;
+use zenoh::time::{Timestamp, TimestampId, NTP64};
 use zenoh::utils::key_expr::*;
 
 /// Returns the longest prefix in a key selector that doesn't contain any '*' character.  
@@ -106,6 +108,32 @@ pub fn get_sub_key_selectors<'a>(key_selector: &'a str, prefix: &str) -> Vec<&'a
     }
 }
 
+/// Returns a string representation of a [`Timestamp`], that can be parsed back without loss
+/// with [`timestamp_from_str()`] (unlike the [`Timestamp`]'s `Display`, that formats its time as RFC3339).
+///
+/// Use this operation in a Storage that needs to persist the timestamps of the stored samples.
+///
+/// # Examples:
+/// ```
+/// # use zenoh::time::{Timestamp, TimestampId, NTP64};
+/// # use zenoh_backend_traits::utils::{timestamp_from_str, timestamp_to_string};
+/// let ts = Timestamp::new(NTP64(0x1234), TimestampId::new(1, [0x0a; TimestampId::MAX_SIZE]));
+/// assert_eq!("1234/0A", timestamp_to_string(&ts));
+/// assert_eq!(Some(ts), timestamp_from_str("1234/0A"));
+/// ```
+pub fn timestamp_to_string(ts: &Timestamp) -> String {
+    format!("{:x}/{}", ts.get_time().as_u64(), ts.get_id())
+}
+
+/// Parses a [`Timestamp`] formatted with [`timestamp_to_string()`].
+pub fn timestamp_from_str(s: &str) -> Option<Timestamp> {
+    let (time, id) = s.split_once('/')?;
+    Some(Timestamp::new(
+        NTP64(u64::from_str_radix(time, 16).ok()?),
+        TimestampId::from_str(id).ok()?,
+    ))
+}
+
 #[test]
 fn test_get_sub_key_exprs() {
     assert_eq!(
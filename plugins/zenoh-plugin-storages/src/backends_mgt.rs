@@ -30,11 +30,15 @@ pub(crate) async fn create_and_start_storage(
 ) -> ZResult<Sender<StorageMessage>> {
     trace!("Create storage {}", &admin_key);
     let key_expr = config.key_expr.clone();
+    let replication = config.replication.clone();
+    let retention = config.retention.clone();
     let storage = backend.create_storage(config).await?;
     start_storage(
         storage,
         admin_key,
         key_expr,
+        replication,
+        retention,
         in_interceptor,
         out_interceptor,
         zenoh,
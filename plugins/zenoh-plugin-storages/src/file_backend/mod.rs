@@ -0,0 +1,412 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ADLINK zenoh team, <zenoh@adlink-labs.tech>
+//
+use crate::memory_backend::MemoryStorage;
+use async_std::fs::{File, OpenOptions};
+use async_std::io::{ErrorKind, WriteExt};
+use async_std::sync::Arc;
+use async_trait::async_trait;
+use log::{debug, trace, warn};
+use serde_json::json;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use zenoh::buf::ZBuf;
+use zenoh::net::protocol::io::SplitBuffer;
+use zenoh::prelude::*;
+use zenoh_backend_traits::config::{BackendConfig, StorageConfig};
+use zenoh_backend_traits::utils::{timestamp_from_str, timestamp_to_string};
+use zenoh_backend_traits::*;
+use zenoh_core::Result as ZResult;
+use zenoh_core::{bail, zerror};
+
+/// The backend property configuring the directory where all the storages' directories are created.
+/// If not set, `$HOME/.zenoh/zbackend_file` is used.
+const PROP_BACKEND_ROOT: &str = "root";
+/// The storage property configuring the directory of the storage, relative to the backend's root.
+/// If not set, the storage name is used.
+const PROP_STORAGE_DIR: &str = "dir";
+/// The storage property configuring the minimal interval (in seconds) between 2 compactions of the log.
+const PROP_STORAGE_COMPACTION_INTERVAL: &str = "compaction_interval";
+
+const DEFAULT_COMPACTION_INTERVAL_S: u64 = 60;
+const LOG_FILE_NAME: &str = "samples.log";
+const COMPACTION_FILE_NAME: &str = "samples.log.compacting";
+
+pub fn create_file_backend(config: BackendConfig) -> ZResult<Box<dyn Backend>> {
+    let root = match config.rest.get(PROP_BACKEND_ROOT) {
+        Some(serde_json::Value::String(root)) => PathBuf::from(root),
+        None => match std::env::var_os("HOME") {
+            Some(home) => Path::new(&home).join(".zenoh").join("zbackend_file"),
+            None => bail!(
+                "`{}` property of `{}` backend is not set and $HOME is undefined",
+                PROP_BACKEND_ROOT,
+                config.name
+            ),
+        },
+        _ => bail!(
+            "`{}` property of `{}` backend must be a string",
+            PROP_BACKEND_ROOT,
+            config.name
+        ),
+    };
+    Ok(Box::new(FileBackend { config, root }))
+}
+
+pub struct FileBackend {
+    config: BackendConfig,
+    root: PathBuf,
+}
+
+#[async_trait]
+impl Backend for FileBackend {
+    fn get_admin_status(&self) -> serde_json::Value {
+        self.config.to_json_value()
+    }
+
+    async fn create_storage(&mut self, properties: StorageConfig) -> ZResult<Box<dyn Storage>> {
+        debug!("Create File Storage with configuration: {:?}", properties);
+        Ok(Box::new(FileStorage::new(&self.root, properties).await?))
+    }
+
+    fn incoming_data_interceptor(&self) -> Option<Arc<dyn Fn(Sample) -> Sample + Send + Sync>> {
+        None
+    }
+
+    fn outgoing_data_interceptor(&self) -> Option<Arc<dyn Fn(Sample) -> Sample + Send + Sync>> {
+        None
+    }
+}
+
+/// A storage persisting the received samples in an append-only log file.
+///
+/// The state of the storage is kept in a [`MemoryStorage`] (that implements the timestamp ordering
+/// of samples and the cleanup of removed keys). At startup, this state is recovered replaying the log.
+/// Periodically, the log is compacted, replacing it with the samples representing the current state.
+struct FileStorage {
+    config: StorageConfig,
+    memory: MemoryStorage,
+    dir: PathBuf,
+    log: File,
+    // number of records appended to the log since the last compaction
+    appended: usize,
+    compaction_interval: Duration,
+    last_compaction: Instant,
+}
+
+impl FileStorage {
+    async fn new(root: &Path, config: StorageConfig) -> ZResult<FileStorage> {
+        let dir = match config.rest.get(PROP_STORAGE_DIR) {
+            Some(serde_json::Value::String(dir)) => root.join(dir),
+            None => root.join(&config.name),
+            _ => bail!(
+                "`{}` property of storage `{}` must be a string",
+                PROP_STORAGE_DIR,
+                config.name
+            ),
+        };
+        let compaction_interval = match config.rest.get(PROP_STORAGE_COMPACTION_INTERVAL) {
+            Some(value) => match value.as_f64() {
+                Some(secs) if secs.is_finite() && secs >= 0.0 => Duration::from_secs_f64(secs),
+                _ => bail!(
+                    "`{}` property of storage `{}` must be a positive number of seconds",
+                    PROP_STORAGE_COMPACTION_INTERVAL,
+                    config.name
+                ),
+            },
+            None => Duration::from_secs(DEFAULT_COMPACTION_INTERVAL_S),
+        };
+        async_std::fs::create_dir_all(&dir).await.map_err(|e| {
+            zerror!(
+                "Failed to create directory {} for storage `{}`: {}",
+                dir.display(),
+                config.name,
+                e
+            )
+        })?;
+
+        let mut memory = MemoryStorage::new(config.clone()).await?;
+        // recover the state of the storage from its log (if any)
+        let log_path = dir.join(LOG_FILE_NAME);
+        match async_std::fs::read_to_string(&log_path).await {
+            Ok(content) => {
+                let mut count = 0;
+                for (i, line) in content.lines().enumerate() {
+                    if line.is_empty() {
+                        continue;
+                    }
+                    match record_to_sample(line, &config.strip_prefix) {
+                        Ok(sample) => {
+                            if let Err(e) = memory.on_sample(sample).await {
+                                debug!("Replay of record {} of {}: {}", i, log_path.display(), e);
+                            }
+                            count += 1;
+                        }
+                        // the last record might be truncated in case of crash while writing it
+                        Err(e) => {
+                            warn!("Skip invalid record {} of {}: {}", i, log_path.display(), e)
+                        }
+                    }
+                }
+                debug!(
+                    "Storage `{}` recovered {} records from {}",
+                    config.name,
+                    count,
+                    log_path.display()
+                );
+            }
+            Err(e) if e.kind() == ErrorKind::NotFound => (),
+            Err(e) => bail!("Failed to read {}: {}", log_path.display(), e),
+        }
+
+        let log = open_log(&log_path).await?;
+        let mut storage = FileStorage {
+            config,
+            memory,
+            dir,
+            log,
+            appended: 0,
+            compaction_interval,
+            last_compaction: Instant::now(),
+        };
+        // start from a compacted log, getting rid of the outdated and invalid records
+        storage.compact().await?;
+        Ok(storage)
+    }
+
+    async fn append(&mut self, sample: &Sample) -> ZResult<()> {
+        let mut record = sample_to_record(sample, &self.config.strip_prefix)?;
+        record.push('\n');
+        self.log.write_all(record.as_bytes()).await.map_err(|e| {
+            zerror!(
+                "Failed to write in log of storage `{}`: {}",
+                self.config.name,
+                e
+            )
+        })?;
+        self.log.flush().await.map_err(|e| {
+            zerror!(
+                "Failed to write in log of storage `{}`: {}",
+                self.config.name,
+                e
+            )
+        })?;
+        self.appended += 1;
+        Ok(())
+    }
+
+    // Writes the current state in a new log file, and atomically replaces the current log with it.
+    async fn compact(&mut self) -> ZResult<()> {
+        let samples = self.memory.snapshot().await;
+        trace!(
+            "Compact log of storage `{}`: {} records appended since last compaction, {} records after",
+            self.config.name,
+            self.appended,
+            samples.len()
+        );
+        let mut records = String::new();
+        for sample in samples.iter() {
+            records.push_str(&sample_to_record(sample, &self.config.strip_prefix)?);
+            records.push('\n');
+        }
+        let log_path = self.dir.join(LOG_FILE_NAME);
+        let compaction_path = self.dir.join(COMPACTION_FILE_NAME);
+        let write = async {
+            let mut file = File::create(&compaction_path).await?;
+            file.write_all(records.as_bytes()).await?;
+            file.sync_all().await?;
+            async_std::fs::rename(&compaction_path, &log_path).await
+        };
+        write.await.map_err(|e| {
+            zerror!(
+                "Failed to compact log of storage `{}`: {}",
+                self.config.name,
+                e
+            )
+        })?;
+        self.log = open_log(&log_path).await?;
+        self.appended = 0;
+        self.last_compaction = Instant::now();
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Storage for FileStorage {
+    fn get_admin_status(&self) -> serde_json::Value {
+        self.config.to_json_value()
+    }
+
+    async fn on_sample(&mut self, mut sample: Sample) -> ZResult<()> {
+        trace!("on_sample for {}", sample.key_expr);
+        // the timestamp must be logged, for the replay to re-order samples the same way
+        sample.ensure_timestamp();
+        // NOTE: all samples are logged, even those that will be dropped by the MemoryStorage.
+        // Replaying them in the same order leads to the same state.
+        self.append(&sample).await?;
+        let result = self.memory.on_sample(sample).await;
+        if self.appended > 0 && self.last_compaction.elapsed() >= self.compaction_interval {
+            self.compact().await?;
+        }
+        result
+    }
+
+    async fn on_query(&mut self, query: Query) -> ZResult<()> {
+        self.memory.on_query(query).await
+    }
+
+    async fn get_all_entries(&self) -> ZResult<Vec<Sample>> {
+        self.memory.get_all_entries().await
+    }
+}
+
+impl Drop for FileStorage {
+    fn drop(&mut self) {
+        trace!("FileStorage::drop()");
+    }
+}
+
+async fn open_log(path: &Path) -> ZResult<File> {
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .await
+        .map_err(|e| zerror!("Failed to open {}: {}", path.display(), e).into())
+}
+
+// A record is a JSON object on a single line, with the key stripped from the storage's prefix,
+// the timestamp as "<NTP64 time in hex>/<id in hex>" and the payload encoded in base64.
+fn sample_to_record(sample: &Sample, strip_prefix: &str) -> ZResult<String> {
+    let key = sample
+        .key_expr
+        .as_str()
+        .strip_prefix(strip_prefix)
+        .ok_or_else(|| {
+            zerror!(
+                "Received a sample for {} not starting with strip_prefix {}",
+                sample.key_expr,
+                strip_prefix
+            )
+        })?;
+    let ts = sample
+        .timestamp
+        .as_ref()
+        .ok_or_else(|| zerror!("Sample for {} has no timestamp", sample.key_expr))?;
+    Ok(json!({
+        "key": key,
+        "kind": sample.kind.to_string(),
+        "ts": timestamp_to_string(ts),
+        "encoding": sample.value.encoding.to_string(),
+        "payload": base64::encode(sample.value.payload.contiguous()),
+    })
+    .to_string())
+}
+
+fn record_to_sample(record: &str, strip_prefix: &str) -> ZResult<Sample> {
+    let record: serde_json::Value =
+        serde_json::from_str(record).map_err(|e| zerror!("Invalid record '{}': {}", record, e))?;
+    let field = |name: &str| {
+        record
+            .get(name)
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| zerror!("Invalid record '{}': missing field '{}'", record, name))
+    };
+    let kind = match field("kind")? {
+        "PUT" => SampleKind::Put,
+        "PATCH" => SampleKind::Patch,
+        "DELETE" => SampleKind::Delete,
+        kind => bail!("Invalid record '{}': unknown kind '{}'", record, kind),
+    };
+    let ts = field("ts")?;
+    let timestamp = timestamp_from_str(ts)
+        .ok_or_else(|| zerror!("Invalid record '{}': invalid timestamp", record))?;
+    let payload = base64::decode(field("payload")?)
+        .map_err(|e| zerror!("Invalid record '{}': invalid payload: {}", record, e))?;
+    let value =
+        Value::from(ZBuf::from(payload)).encoding(Encoding::from(field("encoding")?.to_string()));
+    let mut sample =
+        Sample::new(format!("{}{}", strip_prefix, field("key")?), value).with_timestamp(timestamp);
+    sample.kind = kind;
+    Ok(sample)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::TryFrom;
+    use zenoh::time::{Timestamp, TimestampId, NTP64};
+
+    #[test]
+    fn test_record() {
+        let timestamp = Timestamp::new(
+            NTP64(0x1234_5678_9abc_def0),
+            TimestampId::try_from(&[0x01, 0x02, 0x03][..]).unwrap(),
+        );
+        let mut sample = Sample::new("/demo/file/a/b", Value::from(serde_json::json!({"x": 1})))
+            .with_timestamp(timestamp);
+        sample.kind = SampleKind::Patch;
+
+        let record = sample_to_record(&sample, "/demo/file/").unwrap();
+        assert!(!record.contains('\n'));
+        let decoded = record_to_sample(&record, "/other/").unwrap();
+        assert_eq!(decoded.key_expr.as_str(), "/other/a/b");
+        assert_eq!(decoded.kind, SampleKind::Patch);
+        assert_eq!(decoded.timestamp, Some(timestamp));
+        assert_eq!(decoded.value.encoding, Encoding::APP_JSON);
+        assert_eq!(decoded.value.as_json(), Some(serde_json::json!({"x": 1})));
+
+        assert!(sample_to_record(&sample, "/other/").is_err());
+        assert!(record_to_sample(&record[..record.len() - 2], "/demo/file/").is_err());
+    }
+
+    #[test]
+    fn test_recovery() {
+        async_std::task::block_on(async {
+            let root =
+                std::env::temp_dir().join(format!("zbackend_file_test_{}", std::process::id()));
+            let mut rest = serde_json::Map::new();
+            rest.insert(PROP_STORAGE_COMPACTION_INTERVAL.into(), json!(0));
+            let config = StorageConfig {
+                name: "test".into(),
+                key_expr: "/demo/file/**".into(),
+                strip_prefix: "/demo/file/".into(),
+                replication: None,
+                retention: None,
+                rest,
+            };
+            let ts = |t: u64| Timestamp::new(NTP64(t), TimestampId::try_from(&[0x01][..]).unwrap());
+
+            let mut storage = FileStorage::new(&root, config.clone()).await.unwrap();
+            for (key, t) in [("a", 2), ("a", 1), ("b", 1)] {
+                let sample =
+                    Sample::new(format!("/demo/file/{}", key), t as i64).with_timestamp(ts(t));
+                storage.on_sample(sample).await.unwrap();
+            }
+            let mut delete = Sample::new("/demo/file/b", Value::empty()).with_timestamp(ts(3));
+            delete.kind = SampleKind::Delete;
+            storage.on_sample(delete).await.unwrap();
+            drop(storage);
+
+            // the recovered data is reported to the replication
+            let storage = FileStorage::new(&root, config).await.unwrap();
+            let samples = storage.get_all_entries().await.unwrap();
+            assert_eq!(samples.len(), 2);
+            assert_eq!(samples[0].key_expr.as_str(), "/demo/file/a");
+            assert_eq!(samples[0].value.as_integer(), Some(2));
+            assert_eq!(samples[1].key_expr.as_str(), "/demo/file/b");
+            assert_eq!(samples[1].kind, SampleKind::Delete);
+
+            std::fs::remove_dir_all(&root).unwrap();
+        });
+    }
+}
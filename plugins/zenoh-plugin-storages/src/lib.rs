@@ -16,6 +16,7 @@
 use async_std::channel::Sender;
 use async_std::sync::Arc;
 use async_std::task;
+use file_backend::create_file_backend;
 use libloading::Library;
 use log::debug;
 use memory_backend::create_memory_backend;
@@ -38,7 +39,10 @@ use zenoh_util::LibLoader;
 
 mod backends_mgt;
 use backends_mgt::*;
+mod file_backend;
 mod memory_backend;
+mod replication;
+mod retention;
 mod storages_mgt;
 
 zenoh_plugin_trait::declare_plugin!(StoragesPlugin);
@@ -151,6 +155,16 @@ impl StorageRuntimeInner {
                 }
                 Err(e) => bail!("{}", e),
             }
+        } else if backend_name == FILE_BACKEND_NAME {
+            match create_file_backend(config) {
+                Ok(backend) => {
+                    self.backends.insert(
+                        backend_name,
+                        BackendHandle::new(backend, None, "<static-file>".into()),
+                    );
+                }
+                Err(e) => bail!("{}", e),
+            }
         } else {
             match &config.paths {
                 Some(paths) => {
@@ -370,6 +384,7 @@ impl RunningPluginTrait for StorageRuntime {
 
 const BACKEND_LIB_PREFIX: &str = "zbackend_";
 const MEMORY_BACKEND_NAME: &str = "memory";
+const FILE_BACKEND_NAME: &str = "file";
 
 fn with_extended_string<R, F: FnMut(&mut String) -> R>(
     prefix: &mut String,
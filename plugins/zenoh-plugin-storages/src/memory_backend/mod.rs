@@ -15,7 +15,7 @@ use async_std::sync::{Arc, RwLock};
 use async_trait::async_trait;
 use log::{debug, trace, warn};
 use std::collections::hash_map::Entry;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::time::{Duration, Instant};
 use zenoh::prelude::*;
 use zenoh::time::Timestamp;
@@ -24,6 +24,7 @@ use zenoh_backend_traits::config::{BackendConfig, StorageConfig};
 use zenoh_backend_traits::*;
 use zenoh_collections::{Timed, TimedEvent, TimedHandle, Timer};
 use zenoh_core::Result as ZResult;
+use zenoh_core::{bail, zerror};
 
 pub fn create_memory_backend(config: BackendConfig) -> ZResult<Box<dyn Backend>> {
     Ok(Box::new(MemoryBackend { config }))
@@ -98,17 +99,65 @@ impl StoredValue {
 }
 use StoredValue::{Present, Removed};
 
-struct MemoryStorage {
+/// The bounded history of the samples stored for each key, ordered by timestamp.
+struct History {
+    size: usize,
+    samples: HashMap<String, BTreeMap<Timestamp, Sample>>,
+}
+
+impl History {
+    fn new(size: usize) -> Self {
+        History {
+            size,
+            samples: HashMap::new(),
+        }
+    }
+
+    // Inserts a sample in the history of its key, dropping the oldest ones if the history is full.
+    fn record(&mut self, sample: &Sample, ts: Timestamp) {
+        let samples = self.samples.entry(sample.key_expr.to_string()).or_default();
+        samples.insert(ts, sample.clone());
+        while samples.len() > self.size {
+            let oldest = *samples.keys().next().unwrap();
+            samples.remove(&oldest);
+        }
+    }
+
+    // Removes from the history of a key all the samples older than a deletion timestamp.
+    fn prune(&mut self, key: &str, ts: &Timestamp) {
+        if let Some(samples) = self.samples.get_mut(key) {
+            *samples = samples.split_off(ts);
+            if samples.is_empty() {
+                self.samples.remove(key);
+            }
+        }
+    }
+}
+
+pub(crate) struct MemoryStorage {
     config: StorageConfig,
     map: Arc<RwLock<HashMap<String, StoredValue>>>,
+    history: Option<History>,
     timer: Timer,
 }
 
 impl MemoryStorage {
-    async fn new(properties: StorageConfig) -> ZResult<MemoryStorage> {
+    pub(crate) async fn new(properties: StorageConfig) -> ZResult<MemoryStorage> {
+        let history = match properties.rest.get(PROP_STORAGE_HISTORY) {
+            None => None,
+            Some(value) => match value.as_u64() {
+                Some(size) if size > 0 => Some(History::new(size as usize)),
+                _ => bail!(
+                    "`{}` property of storage `{}` must be a positive integer",
+                    PROP_STORAGE_HISTORY,
+                    properties.name
+                ),
+            },
+        };
         Ok(MemoryStorage {
             config: properties,
             map: Arc::new(RwLock::new(HashMap::new())),
+            history,
             timer: Timer::new(false),
         })
     }
@@ -127,6 +176,37 @@ impl MemoryStorage {
         self.timer.add_async(event).await;
         handle
     }
+
+    /// Returns the samples allowing to rebuild the current state of this storage, ordered by timestamp:
+    /// its history (or only the latest sample of each key if not in history mode),
+    /// plus a DELETE sample for each removed key that is not yet cleaned-up.
+    pub(crate) async fn snapshot(&self) -> Vec<Sample> {
+        let mut samples = Vec::new();
+        for (key, stored_value) in self.map.read().await.iter() {
+            match stored_value {
+                Present { sample, ts: _ } => {
+                    if self.history.is_none() {
+                        samples.push(sample.clone());
+                    }
+                }
+                Removed {
+                    ts,
+                    cleanup_handle: _,
+                } => {
+                    let mut sample = Sample::new(key.clone(), Value::empty()).with_timestamp(*ts);
+                    sample.kind = SampleKind::Delete;
+                    samples.push(sample);
+                }
+            }
+        }
+        if let Some(history) = &self.history {
+            for history_samples in history.samples.values() {
+                samples.extend(history_samples.values().cloned());
+            }
+        }
+        samples.sort_by_key(|sample| sample.timestamp);
+        samples
+    }
 }
 
 #[async_trait]
@@ -142,6 +222,9 @@ impl Storage for MemoryStorage {
         match sample.kind {
             SampleKind::Put => match self.map.write().await.entry(sample.key_expr.to_string()) {
                 Entry::Vacant(v) => {
+                    if let Some(history) = &mut self.history {
+                        history.record(&sample, timestamp);
+                    }
                     v.insert(Present {
                         sample,
                         ts: timestamp,
@@ -158,10 +241,23 @@ impl Storage for MemoryStorage {
                             // cancel timed cleanup
                             cleanup_handle.clone().defuse();
                         }
+                        if let Some(history) = &mut self.history {
+                            history.record(&sample, timestamp);
+                        }
                         o.insert(Present {
                             sample,
                             ts: timestamp,
                         });
+                    } else if let (Present { sample: _, ts: _ }, Some(history)) =
+                        (old_val, &mut self.history)
+                    {
+                        // NOTE: an out-of-date PUT is not the latest value, but still a past value
+                        // that must be part of the history (unless the key was deleted since).
+                        debug!(
+                            "PUT on {} only added to history: out-of-date",
+                            sample.key_expr
+                        );
+                        history.record(&sample, timestamp);
                     } else {
                         debug!("PUT on {} dropped: out-of-date", sample.key_expr);
                     }
@@ -173,6 +269,9 @@ impl Storage for MemoryStorage {
                     // if ever a put with a lower timestamp arrive (e.g. msg inversion between put and remove)
                     // we must drop the put.
                     let cleanup_handle = self.schedule_cleanup(sample.key_expr.to_string()).await;
+                    if let Some(history) = &mut self.history {
+                        history.prune(sample.key_expr.as_str(), &timestamp);
+                    }
                     v.insert(Removed {
                         ts: timestamp,
                         cleanup_handle,
@@ -188,6 +287,9 @@ impl Storage for MemoryStorage {
                             if ts < &timestamp {
                                 let cleanup_handle =
                                     self.schedule_cleanup(sample.key_expr.to_string()).await;
+                                if let Some(history) = &mut self.history {
+                                    history.prune(sample.key_expr.as_str(), &timestamp);
+                                }
                                 o.insert(Removed {
                                     ts: timestamp,
                                     cleanup_handle,
@@ -199,25 +301,79 @@ impl Storage for MemoryStorage {
                     }
                 }
             },
-            SampleKind::Patch => {
-                warn!("Received PATCH for {}: not yet supported", sample.key_expr);
-            }
+            SampleKind::Patch => match self.map.write().await.entry(sample.key_expr.to_string()) {
+                Entry::Vacant(v) => {
+                    // NOTE: patching an unknown key is equivalent to patching an empty value
+                    sample.value = apply_patch(None, &sample.value)?;
+                    sample.kind = SampleKind::Put;
+                    if let Some(history) = &mut self.history {
+                        history.record(&sample, timestamp);
+                    }
+                    v.insert(Present {
+                        sample,
+                        ts: timestamp,
+                    });
+                }
+                Entry::Occupied(mut o) => {
+                    let old_val = o.get();
+                    if old_val.ts() < &timestamp {
+                        match old_val {
+                            Present { sample: old, ts: _ } => {
+                                sample.value = apply_patch(Some(&old.value), &sample.value)?;
+                            }
+                            Removed {
+                                ts: _,
+                                cleanup_handle,
+                            } => {
+                                sample.value = apply_patch(None, &sample.value)?;
+                                // cancel timed cleanup
+                                cleanup_handle.clone().defuse();
+                            }
+                        }
+                        sample.kind = SampleKind::Put;
+                        if let Some(history) = &mut self.history {
+                            history.record(&sample, timestamp);
+                        }
+                        o.insert(Present {
+                            sample,
+                            ts: timestamp,
+                        });
+                    } else {
+                        debug!("PATCH on {} dropped: out-of-date", sample.key_expr);
+                    }
+                }
+            },
         }
         Ok(())
     }
 
     async fn on_query(&mut self, query: Query) -> ZResult<()> {
         trace!("on_query for {}", query.key_selector());
-        if !query.key_selector().as_str().contains('*') {
-            if let Some(Present { sample, ts: _ }) =
+        let time_range = time_range(&query.selector());
+        if let (Some(time_range), Some(history)) = (time_range, &self.history) {
+            // time-range query in history mode: reply all the samples of the range
+            for (key, samples) in history.samples.iter() {
+                if key_expr::intersect(query.key_selector().as_str(), key) {
+                    for (ts, sample) in samples.iter() {
+                        if time_range.contains_timestamp(ts) {
+                            query.reply(sample.clone()).await;
+                        }
+                    }
+                }
+            }
+        } else if !query.key_selector().as_str().contains('*') {
+            if let Some(Present { sample, ts }) =
                 self.map.read().await.get(query.key_selector().as_str())
             {
-                query.reply(sample.clone()).await;
+                if time_range.iter().all(|range| range.contains_timestamp(ts)) {
+                    query.reply(sample.clone()).await;
+                }
             }
         } else {
             for (_, stored_value) in self.map.read().await.iter() {
-                if let Present { sample, ts: _ } = stored_value {
+                if let Present { sample, ts } = stored_value {
                     if key_expr::intersect(query.key_selector().as_str(), sample.key_expr.as_str())
+                        && time_range.iter().all(|range| range.contains_timestamp(ts))
                     {
                         let s: Sample = sample.clone();
                         query.reply(s).await;
@@ -227,6 +383,10 @@ impl Storage for MemoryStorage {
         }
         Ok(())
     }
+
+    async fn get_all_entries(&self) -> ZResult<Vec<Sample>> {
+        Ok(self.snapshot().await)
+    }
 }
 
 impl Drop for MemoryStorage {
@@ -238,6 +398,94 @@ impl Drop for MemoryStorage {
 
 const CLEANUP_TIMEOUT_MS: u64 = 5000;
 
+/// Returns the time range of a query selector, if any.
+/// A malformed value selector is ignored and the query is answered as if it had no time range.
+fn time_range(selector: &Selector) -> Option<TimeRange> {
+    match selector
+        .parse_value_selector()
+        .and_then(|value_selector| value_selector.time_range())
+    {
+        Ok(time_range) => time_range,
+        Err(e) => {
+            warn!("Ignoring the time range of the query {} : {}", selector, e);
+            None
+        }
+    }
+}
+
+/// The storage property configuring the number of samples kept in history for each key.
+/// If not set, only the latest sample of each key is kept.
+const PROP_STORAGE_HISTORY: &str = "history";
+
+/// Applies a `patch` Value onto a `stored` Value (`None` if the key has no current value)
+/// and returns the resulting Value.
+/// Supported encodings are:
+///  - `application/json` and `text/json`: the patch is applied as a JSON Merge Patch (RFC 7386)
+///  - `application/properties`: the patch properties are merged into the stored properties
+fn apply_patch(stored: Option<&Value>, patch: &Value) -> ZResult<Value> {
+    match patch.encoding.prefix() {
+        KnownEncoding::AppJson | KnownEncoding::TextJson => {
+            let patch_json = patch
+                .as_json()
+                .ok_or_else(|| zerror!("PATCH payload is not valid JSON"))?;
+            let mut json = match stored {
+                Some(value) => value.as_json().ok_or_else(|| {
+                    zerror!(
+                        "Cannot apply a JSON PATCH on a value with encoding {}",
+                        value.encoding
+                    )
+                })?,
+                None => serde_json::Value::Null,
+            };
+            json_merge_patch(&mut json, &patch_json);
+            Ok(Value::from(json).encoding(patch.encoding.clone()))
+        }
+        KnownEncoding::AppProperties => {
+            let patch_props = patch
+                .as_properties()
+                .ok_or_else(|| zerror!("PATCH payload is not valid properties"))?;
+            let mut props = match stored {
+                Some(value) => value.as_properties().ok_or_else(|| {
+                    zerror!(
+                        "Cannot apply a properties PATCH on a value with encoding {}",
+                        value.encoding
+                    )
+                })?,
+                None => Properties::default(),
+            };
+            props.extend(patch_props.0);
+            Ok(Value::from(props))
+        }
+        _ => bail!(
+            "PATCH is not supported for encoding {} (only JSON and properties are)",
+            patch.encoding
+        ),
+    }
+}
+
+/// Merges `patch` into `target` following the JSON Merge Patch semantic (RFC 7386):
+/// `null` members are removed, objects are merged recursively and other values are replaced.
+fn json_merge_patch(target: &mut serde_json::Value, patch: &serde_json::Value) {
+    if let serde_json::Value::Object(patch) = patch {
+        if !target.is_object() {
+            *target = serde_json::Value::Object(serde_json::Map::new());
+        }
+        let map = target.as_object_mut().unwrap();
+        for (key, value) in patch {
+            if value.is_null() {
+                map.remove(key);
+            } else {
+                json_merge_patch(
+                    map.entry(key.clone()).or_insert(serde_json::Value::Null),
+                    value,
+                );
+            }
+        }
+    } else {
+        *target = patch.clone();
+    }
+}
+
 struct TimedCleanup {
     map: Arc<RwLock<HashMap<String, StoredValue>>>,
     key: String,
@@ -249,3 +497,75 @@ impl Timed for TimedCleanup {
         self.map.write().await.remove(&self.key);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_merge_patch() {
+        let mut target = serde_json::json!({"a": "b", "c": {"d": "e", "f": "g"}});
+        json_merge_patch(
+            &mut target,
+            &serde_json::json!({"a": "z", "c": {"f": null}, "h": [1]}),
+        );
+        assert_eq!(
+            target,
+            serde_json::json!({"a": "z", "c": {"d": "e"}, "h": [1]})
+        );
+
+        let mut target = serde_json::json!([1, 2]);
+        json_merge_patch(&mut target, &serde_json::json!({"a": null, "b": 1}));
+        assert_eq!(target, serde_json::json!({"b": 1}));
+
+        let mut target = serde_json::json!({"a": 1});
+        json_merge_patch(&mut target, &serde_json::json!("text"));
+        assert_eq!(target, serde_json::json!("text"));
+    }
+
+    #[test]
+    fn test_apply_patch() {
+        let stored = Value::from(serde_json::json!({"a": 1, "b": 2}));
+        let patch = Value::from(serde_json::json!({"b": null, "c": 3}));
+        let result = apply_patch(Some(&stored), &patch).unwrap();
+        assert_eq!(result.as_json(), Some(serde_json::json!({"a": 1, "c": 3})));
+
+        let stored = Value::from(Properties::from("a=1;b=2"));
+        let patch = Value::from(Properties::from("b=3;c=4"));
+        let result = apply_patch(Some(&stored), &patch).unwrap();
+        assert_eq!(
+            result.as_properties(),
+            Some(Properties::from("a=1;b=3;c=4"))
+        );
+
+        assert!(apply_patch(None, &Value::from("text")).is_err());
+        assert!(apply_patch(Some(&Value::from(1_i64)), &patch).is_err());
+    }
+
+    #[test]
+    fn test_history() {
+        use zenoh::time::{TimestampId, NTP64};
+        let ts =
+            |t: u64| Timestamp::new(NTP64(t), TimestampId::new(1, [0_u8; TimestampId::MAX_SIZE]));
+        let mut history = History::new(3);
+        for t in [5, 1, 4, 2, 3] {
+            history.record(&Sample::new("/test/a", t as i64), ts(t));
+        }
+        let kept: Vec<&Timestamp> = history.samples["/test/a"].keys().collect();
+        assert_eq!(kept, vec![&ts(3), &ts(4), &ts(5)]);
+
+        history.prune("/test/a", &ts(5));
+        assert_eq!(history.samples["/test/a"].len(), 1);
+        history.prune("/test/a", &ts(6));
+        assert!(history.samples.is_empty());
+    }
+
+    #[test]
+    fn test_time_range() {
+        assert!(time_range(&Selector::from("/test/a")).is_none());
+        assert!(time_range(&Selector::from("/test/a?(starttime=0)")).is_some());
+        // a malformed time range is ignored rather than failing the query
+        assert!(time_range(&Selector::from("/test/a?(starttime=yesterday)")).is_none());
+        assert!(time_range(&Selector::from("/test/a?(stoptime=now()-x)")).is_none());
+    }
+}
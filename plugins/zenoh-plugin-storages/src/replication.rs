@@ -0,0 +1,450 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ADLINK zenoh team, <zenoh@adlink-labs.tech>
+//
+
+//! The anti-entropy protocol between the storages on the same key expression.
+//!
+//! Each storage configured for replication tracks the latest timestamp of each key it stored (or removed),
+//! and periodically queries the digests of the other storages on the same key expression.
+//! A digest splits the keys into [`NB_BUCKETS`] buckets (by hash of the key), each bucket being summarized
+//! by a hash of its (key, timestamp) pairs. Only the pairs older than a checkpoint (`now - clock_skew`)
+//! are considered, in order to not report the samples that are still in-flight.
+//! For each bucket that differs, the storage retrieves the remote entries of this bucket,
+//! and fetches the samples it misses with regular queries.
+
+use async_std::channel::{Receiver, Sender};
+use async_std::sync::{Arc, RwLock};
+use async_std::task;
+use futures::select;
+use futures::stream::StreamExt;
+use futures::FutureExt;
+use log::{debug, trace, warn};
+use serde_json::json;
+use std::collections::HashMap;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use zenoh::prelude::*;
+use zenoh::query::{QueryConsolidation, QueryTarget, Target};
+use zenoh::queryable;
+use zenoh::time::{Timestamp, NTP64};
+use zenoh::Session;
+use zenoh_backend_traits::config::ReplicationConfig;
+use zenoh_backend_traits::utils::{timestamp_from_str, timestamp_to_string};
+use zenoh_core::Result as ZResult;
+use zenoh_core::{bail, zerror};
+
+/// The prefix of the keys on which the storages answer to the anti-entropy queries.
+const REPLICAS_KEY_PREFIX: &str = "/@/storages/replicas";
+const PROP_CHECKPOINT: &str = "checkpoint";
+const PROP_BUCKETS: &str = "buckets";
+
+/// The number of buckets in a digest.
+const NB_BUCKETS: usize = 64;
+/// The time after which a removed key is forgotten, and its removal no longer propagated.
+const TOMBSTONES_LIFETIME: Duration = Duration::from_secs(24 * 3600);
+
+struct Entry {
+    ts: Timestamp,
+    deleted: bool,
+}
+
+/// The (key, timestamp) pairs of the samples stored in a storage.
+#[derive(Default)]
+pub(crate) struct ReplicaState {
+    entries: HashMap<String, Entry>,
+}
+
+impl ReplicaState {
+    /// Records a sample that has been passed to the storage.
+    pub(crate) fn update(&mut self, sample: &Sample) {
+        let ts = match sample.timestamp {
+            Some(ts) => ts,
+            None => return,
+        };
+        let deleted = sample.kind == SampleKind::Delete;
+        match self.entries.get_mut(sample.key_expr.as_str()) {
+            Some(entry) if entry.ts >= ts => (),
+            Some(entry) => *entry = Entry { ts, deleted },
+            None => {
+                self.entries
+                    .insert(sample.key_expr.to_string(), Entry { ts, deleted });
+            }
+        }
+    }
+
+    // Returns true if the given key/timestamp is unknown or more recent than the stored one.
+    fn is_missing(&self, key: &str, ts: &Timestamp) -> bool {
+        match self.entries.get(key) {
+            Some(entry) => &entry.ts < ts,
+            None => true,
+        }
+    }
+
+    fn prune_tombstones(&mut self, now: SystemTime) {
+        self.entries.retain(|_, entry| {
+            !entry.deleted || entry.ts.get_time().to_system_time() + TOMBSTONES_LIFETIME > now
+        });
+    }
+
+    fn digest(&self, checkpoint: &NTP64) -> Vec<u64> {
+        let mut digest = vec![0; NB_BUCKETS];
+        for (key, entry) in self.entries.iter() {
+            if entry.ts.get_time() <= checkpoint {
+                digest[bucket(key)] ^= entry_hash(key, entry);
+            }
+        }
+        digest
+    }
+
+    fn bucket_entries(&self, buckets: &[usize], checkpoint: &NTP64) -> Vec<serde_json::Value> {
+        self.entries
+            .iter()
+            .filter(|(key, entry)| {
+                entry.ts.get_time() <= checkpoint && buckets.contains(&bucket(key))
+            })
+            .map(|(key, entry)| {
+                json!({
+                    "key": key,
+                    "ts": timestamp_to_string(&entry.ts),
+                    "deleted": entry.deleted,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Starts the anti-entropy protocol for a storage. It stops when `stop` is closed.
+/// The samples missed by the storage are sent to `missing`.
+pub(crate) async fn start_replication(
+    zenoh: Arc<Session>,
+    admin_key: String,
+    key_expr: String,
+    config: ReplicationConfig,
+    state: Arc<RwLock<ReplicaState>>,
+    missing: Sender<Sample>,
+    stop: Receiver<()>,
+) -> ZResult<()> {
+    let replica_id = format!("{}-{:016x}", zenoh.id().await, fnv1a(admin_key.as_bytes()));
+    let replicas_key = format!(
+        "{}/{:016x}",
+        REPLICAS_KEY_PREFIX,
+        fnv1a(key_expr.as_bytes())
+    );
+    let replica_key = format!("{}/{}", replicas_key, replica_id);
+    let mut queryable = zenoh.queryable(&replica_key).kind(queryable::EVAL).await?;
+    debug!("Storage {} replicates as {}", admin_key, replica_key);
+
+    // answer to the other storages' digest and entries queries
+    let task_state = state.clone();
+    let task_stop = stop.clone();
+    let task_admin_key = admin_key.clone();
+    let task_key_expr = key_expr.clone();
+    let task_replica_id = replica_id.clone();
+    task::spawn(async move {
+        loop {
+            select!(
+                query = queryable.next() => {
+                    let query = match query {
+                        Some(query) => query,
+                        None => return,
+                    };
+                    // reply on the key of this replica for the queriers to tell the replicas apart
+                    match reply_data(&query, &task_state, &task_key_expr, &task_replica_id).await {
+                        Ok(value) => {
+                            query
+                                .reply_async(Sample::new(replica_key.clone(), value))
+                                .await
+                        }
+                        Err(e) => warn!("Storage {} received an invalid anti-entropy query: {}", task_admin_key, e),
+                    }
+                },
+                _ = task_stop.recv().fuse() => return,
+            );
+        }
+    });
+
+    // periodically compare the digests of the other storages with the local one
+    task::spawn(async move {
+        loop {
+            select!(
+                _ = task::sleep(config.digest_interval).fuse() => (),
+                _ = stop.recv().fuse() => return,
+            );
+            if let Err(e) = align(
+                &zenoh,
+                &replicas_key,
+                &replica_id,
+                &key_expr,
+                &config,
+                &state,
+                &missing,
+            )
+            .await
+            {
+                warn!(
+                    "Storage {} failed to align with other storages: {}",
+                    admin_key, e
+                );
+            }
+            if missing.is_closed() {
+                return;
+            }
+        }
+    });
+    Ok(())
+}
+
+async fn reply_data(
+    query: &zenoh::queryable::Query,
+    state: &RwLock<ReplicaState>,
+    key_expr: &str,
+    replica_id: &str,
+) -> ZResult<Value> {
+    let selector = query.selector();
+    let value_selector = selector.parse_value_selector()?;
+    let checkpoint = match value_selector.properties.get(PROP_CHECKPOINT) {
+        Some(checkpoint) => NTP64(
+            u64::from_str_radix(checkpoint, 16)
+                .map_err(|e| zerror!("invalid checkpoint '{}': {}", checkpoint, e))?,
+        ),
+        None => bail!("missing '{}' property", PROP_CHECKPOINT),
+    };
+    match value_selector.properties.get(PROP_BUCKETS) {
+        Some(buckets) => {
+            let buckets = buckets
+                .split(',')
+                .map(|b| b.parse::<usize>())
+                .collect::<Result<Vec<usize>, _>>()
+                .map_err(|e| zerror!("invalid buckets '{}': {}", buckets, e))?;
+            let entries = state.read().await.bucket_entries(&buckets, &checkpoint);
+            Ok(json!({ "entries": entries }).into())
+        }
+        None => {
+            let digest = state.read().await.digest(&checkpoint);
+            Ok(json!({
+                "replica": replica_id,
+                "key_expr": key_expr,
+                "digest": digest.iter().map(|h| format!("{:016x}", h)).collect::<Vec<String>>(),
+            })
+            .into())
+        }
+    }
+}
+
+async fn align(
+    zenoh: &Session,
+    replicas_key: &str,
+    replica_id: &str,
+    key_expr: &str,
+    config: &ReplicationConfig,
+    state: &RwLock<ReplicaState>,
+    missing: &Sender<Sample>,
+) -> ZResult<()> {
+    let now = SystemTime::now();
+    let checkpoint = NTP64::from(
+        now.checked_sub(config.clock_skew)
+            .unwrap_or(UNIX_EPOCH)
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default(),
+    );
+    let local_digest = {
+        let mut state = state.write().await;
+        state.prune_tombstones(now);
+        state.digest(&checkpoint)
+    };
+    let query_target = QueryTarget {
+        kind: queryable::EVAL,
+        target: Target::All,
+    };
+
+    let mut replies = zenoh
+        .get(format!(
+            "{}/*?({}={:x})",
+            replicas_key,
+            PROP_CHECKPOINT,
+            checkpoint.as_u64()
+        ))
+        .target(query_target.clone())
+        .consolidation(QueryConsolidation::none())
+        .await?;
+    let mut remotes = Vec::new();
+    while let Some(reply) = replies.next().await {
+        let sample = match reply.sample {
+            Ok(sample) => sample,
+            Err(_) => continue,
+        };
+        let digest = match sample.value.as_json() {
+            Some(digest) => digest,
+            None => continue,
+        };
+        if digest["replica"] == replica_id || digest["key_expr"] != key_expr {
+            continue;
+        }
+        let buckets: Vec<String> = match digest["digest"].as_array() {
+            Some(remote_digest) if remote_digest.len() == NB_BUCKETS => remote_digest
+                .iter()
+                .zip(local_digest.iter())
+                .enumerate()
+                .filter(|(_, (remote, local))| remote.as_str() != Some(&format!("{:016x}", local)))
+                .map(|(i, _)| i.to_string())
+                .collect(),
+            _ => continue,
+        };
+        if !buckets.is_empty() {
+            trace!(
+                "Digest of {} differs on buckets {:?}",
+                sample.key_expr,
+                buckets
+            );
+            remotes.push((sample.key_expr, buckets));
+        }
+    }
+
+    for (remote_key, buckets) in remotes {
+        let mut replies = zenoh
+            .get(format!(
+                "{}?({}={:x};{}={})",
+                remote_key,
+                PROP_CHECKPOINT,
+                checkpoint.as_u64(),
+                PROP_BUCKETS,
+                buckets.join(",")
+            ))
+            .target(query_target.clone())
+            .consolidation(QueryConsolidation::none())
+            .await?;
+        while let Some(reply) = replies.next().await {
+            let entries = match reply.sample.ok().and_then(|sample| sample.value.as_json()) {
+                Some(serde_json::Value::Object(mut entries)) => match entries.remove("entries") {
+                    Some(serde_json::Value::Array(entries)) => entries,
+                    _ => continue,
+                },
+                _ => continue,
+            };
+            for entry in entries {
+                let (key, ts) = match (
+                    entry["key"].as_str(),
+                    entry["ts"].as_str().and_then(timestamp_from_str),
+                ) {
+                    (Some(key), Some(ts)) => (key, ts),
+                    _ => continue,
+                };
+                if !state.read().await.is_missing(key, &ts) {
+                    continue;
+                }
+                if entry["deleted"].as_bool() == Some(true) {
+                    let mut sample =
+                        Sample::new(key.to_string(), Value::empty()).with_timestamp(ts);
+                    sample.kind = SampleKind::Delete;
+                    missing.send(sample).await?;
+                } else {
+                    fetch(zenoh, key, &ts, missing).await?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+// Fetches a sample from the storages, and sends it to `missing` if its timestamp is at least `ts`.
+async fn fetch(
+    zenoh: &Session,
+    key: &str,
+    ts: &Timestamp,
+    missing: &Sender<Sample>,
+) -> ZResult<()> {
+    let query_target = QueryTarget {
+        kind: queryable::STORAGE,
+        target: Target::All,
+    };
+    let mut replies = zenoh
+        .get(key)
+        .target(query_target)
+        .consolidation(QueryConsolidation::none())
+        .await?;
+    while let Some(reply) = replies.next().await {
+        if let Ok(sample) = reply.sample {
+            if matches!(sample.timestamp, Some(reply_ts) if &reply_ts >= ts) {
+                missing.send(sample).await?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn bucket(key: &str) -> usize {
+    (fnv1a(key.as_bytes()) % NB_BUCKETS as u64) as usize
+}
+
+fn entry_hash(key: &str, entry: &Entry) -> u64 {
+    let mut bytes = key.as_bytes().to_vec();
+    bytes.extend_from_slice(&entry.ts.get_time().as_u64().to_le_bytes());
+    bytes.extend_from_slice(entry.ts.get_id().as_slice());
+    bytes.push(entry.deleted as u8);
+    fnv1a(&bytes)
+}
+
+// The FNV-1a hash function: unlike the std's DefaultHasher, it's stable across processes and versions.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, b| {
+        (hash ^ *b as u64).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zenoh::time::TimestampId;
+
+    #[test]
+    fn test_digest() {
+        let ts =
+            |t: u64| Timestamp::new(NTP64(t), TimestampId::new(1, [0_u8; TimestampId::MAX_SIZE]));
+        let sample =
+            |key: &str, t: u64| Sample::new(key.to_string(), t as i64).with_timestamp(ts(t));
+
+        let mut a = ReplicaState::default();
+        let mut b = ReplicaState::default();
+        for t in 1..=10 {
+            a.update(&sample(&format!("/test/{}", t), t));
+            b.update(&sample(&format!("/test/{}", 11 - t), 11 - t));
+        }
+        assert_eq!(a.digest(&NTP64(10)), b.digest(&NTP64(10)));
+
+        // out-of-date samples are ignored
+        b.update(&sample("/test/1", 0));
+        assert_eq!(a.digest(&NTP64(10)), b.digest(&NTP64(10)));
+
+        // samples more recent than the checkpoint are ignored
+        a.update(&sample("/test/11", 12));
+        assert_eq!(a.digest(&NTP64(11)), b.digest(&NTP64(11)));
+        a.update(&sample("/test/1", 12));
+        b.update(&sample("/test/11", 12));
+        assert_ne!(a.digest(&NTP64(12)), b.digest(&NTP64(12)));
+
+        let differing: Vec<usize> = a
+            .digest(&NTP64(12))
+            .iter()
+            .zip(b.digest(&NTP64(12)).iter())
+            .enumerate()
+            .filter(|(_, (a, b))| a != b)
+            .map(|(i, _)| i)
+            .collect();
+        assert_eq!(differing, vec![bucket("/test/1")]);
+        let entries = a.bucket_entries(&differing, &NTP64(12));
+        assert!(entries
+            .iter()
+            .any(|e| e["key"] == "/test/1" && e["ts"] == timestamp_to_string(&ts(12))));
+        assert!(b.is_missing("/test/1", &ts(12)));
+        assert!(!b.is_missing("/test/2", &ts(2)));
+    }
+}
@@ -0,0 +1,225 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ADLINK zenoh team, <zenoh@adlink-labs.tech>
+//
+
+//! Enforcement of the [`RetentionConfig`] of a storage, whatever its backend.
+//!
+//! The storages plugin tracks the timestamp and the payload size of the latest sample of each key
+//! passed to the storage. When a limit is exceeded, it evicts the oldest keys by passing a DELETE
+//! sample to the storage, with a timestamp more recent than the evicted sample.
+//! The evictions are local to the storage: they're not propagated to the other storages by the replication.
+use serde_json::json;
+use std::collections::{BTreeSet, HashMap};
+use std::time::{Duration, SystemTime};
+use zenoh::net::protocol::io::SplitBuffer;
+use zenoh::prelude::*;
+use zenoh::time::{new_reception_timestamp, Timestamp};
+use zenoh_backend_traits::config::RetentionConfig;
+
+// The maximum period between 2 checks of the samples' TTL.
+const TTL_CHECK_PERIOD: Duration = Duration::from_secs(1);
+
+#[derive(Default)]
+struct Evictions {
+    ttl: u64,
+    max_keys: u64,
+    max_bytes: u64,
+}
+
+pub(crate) struct Retention {
+    config: RetentionConfig,
+    // the timestamp and payload size of the latest sample of each key
+    entries: HashMap<String, (Timestamp, usize)>,
+    // the keys, ordered by age
+    by_age: BTreeSet<(Timestamp, String)>,
+    bytes: usize,
+    evictions: Evictions,
+}
+
+impl Retention {
+    pub(crate) fn new(config: RetentionConfig) -> Self {
+        Retention {
+            config,
+            entries: HashMap::new(),
+            by_age: BTreeSet::new(),
+            bytes: 0,
+            evictions: Evictions::default(),
+        }
+    }
+
+    /// The period at which the TTL of the samples must be checked (if a TTL is configured).
+    pub(crate) fn check_period(&self) -> Option<Duration> {
+        self.config.ttl.map(|ttl| ttl.min(TTL_CHECK_PERIOD))
+    }
+
+    /// Records a sample passed to the storage (the sample must have a timestamp).
+    pub(crate) fn update(&mut self, sample: &Sample) {
+        let ts = match sample.timestamp {
+            Some(ts) => ts,
+            None => return,
+        };
+        let size = sample.value.payload.len();
+        let previous = match self.entries.get(sample.key_expr.as_str()) {
+            Some((previous_ts, _)) if *previous_ts >= ts => return,
+            Some((previous_ts, previous_size)) => Some((*previous_ts, *previous_size)),
+            None => None,
+        };
+        if let Some((previous_ts, previous_size)) = previous {
+            self.remove(sample.key_expr.as_str(), previous_ts, previous_size);
+        }
+        let size = match sample.kind {
+            SampleKind::Put => size,
+            SampleKind::Delete => return,
+            // the size of the patched value is unknown: approximate it
+            SampleKind::Patch => {
+                previous.map_or(size, |(_, previous_size)| previous_size.max(size))
+            }
+        };
+        self.entries.insert(sample.key_expr.to_string(), (ts, size));
+        self.by_age.insert((ts, sample.key_expr.to_string()));
+        self.bytes += size;
+    }
+
+    /// Returns the DELETE samples evicting the keys exceeding the retention limits.
+    pub(crate) fn evictions(&mut self, now: SystemTime) -> Vec<Sample> {
+        let mut evicted = Vec::new();
+        if let Some(ttl) = self.config.ttl {
+            while let Some((ts, key)) = self.oldest() {
+                if ts.get_time().to_system_time() + ttl > now {
+                    break;
+                }
+                self.evictions.ttl += 1;
+                evicted.push(self.evict(key, ts));
+            }
+        }
+        if let Some(max_keys) = self.config.max_keys {
+            while self.entries.len() > max_keys {
+                let (ts, key) = self.oldest().unwrap();
+                self.evictions.max_keys += 1;
+                evicted.push(self.evict(key, ts));
+            }
+        }
+        if let Some(max_bytes) = self.config.max_bytes {
+            while self.bytes > max_bytes {
+                let (ts, key) = self.oldest().unwrap();
+                self.evictions.max_bytes += 1;
+                evicted.push(self.evict(key, ts));
+            }
+        }
+        evicted
+    }
+
+    /// Returns the status of the retention, to be added to the admin status of the storage.
+    pub(crate) fn get_admin_status(&self) -> serde_json::Map<String, serde_json::Value> {
+        let mut status = serde_json::Map::new();
+        status.insert("keys".into(), self.entries.len().into());
+        status.insert("bytes".into(), self.bytes.into());
+        status.insert(
+            "evictions".into(),
+            json!({
+                "ttl": self.evictions.ttl,
+                "max_keys": self.evictions.max_keys,
+                "max_bytes": self.evictions.max_bytes,
+            }),
+        );
+        status
+    }
+
+    fn oldest(&self) -> Option<(Timestamp, String)> {
+        self.by_age.iter().next().cloned()
+    }
+
+    fn remove(&mut self, key: &str, ts: Timestamp, size: usize) {
+        self.entries.remove(key);
+        self.by_age.remove(&(ts, key.to_string()));
+        self.bytes -= size;
+    }
+
+    fn evict(&mut self, key: String, ts: Timestamp) -> Sample {
+        if let Some((_, size)) = self.entries.get(&key) {
+            let size = *size;
+            self.remove(&key, ts, size);
+        }
+        // the DELETE must be more recent than the evicted sample to be applied by the storage
+        let delete_ts =
+            new_reception_timestamp().max(Timestamp::new(*ts.get_time() + 1, *ts.get_id()));
+        let mut sample = Sample::new(key, Value::empty()).with_timestamp(delete_ts);
+        sample.kind = SampleKind::Delete;
+        sample
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zenoh::time::{TimestampId, NTP64};
+
+    fn sample(key: &str, time: SystemTime, payload: &str) -> Sample {
+        let ts = Timestamp::new(
+            NTP64::from(time.duration_since(std::time::UNIX_EPOCH).unwrap()),
+            TimestampId::new(1, [0_u8; TimestampId::MAX_SIZE]),
+        );
+        Sample::new(key.to_string(), payload).with_timestamp(ts)
+    }
+
+    #[test]
+    fn test_retention() {
+        let now = SystemTime::now();
+        let secs = |s: u64| Duration::from_secs(s);
+
+        let mut retention = Retention::new(RetentionConfig {
+            ttl: Some(secs(60)),
+            max_keys: Some(3),
+            max_bytes: Some(10),
+        });
+        retention.update(&sample("/test/a", now - secs(90), "aa"));
+        retention.update(&sample("/test/b", now - secs(30), "bb"));
+        retention.update(&sample("/test/c", now - secs(20), "cc"));
+        retention.update(&sample("/test/z", now - secs(15), "zz"));
+        retention.update(&sample("/test/d", now - secs(10), "dd"));
+        // an out-of-date sample is ignored
+        retention.update(&sample("/test/d", now - secs(50), "dddddd"));
+
+        // /test/a is expired, then /test/b exceeds max_keys
+        let evicted: Vec<_> = retention
+            .evictions(now)
+            .into_iter()
+            .map(|s| {
+                assert_eq!(s.kind, SampleKind::Delete);
+                s.key_expr.to_string()
+            })
+            .collect();
+        assert_eq!(evicted, vec!["/test/a", "/test/b"]);
+
+        // a larger value of /test/d makes /test/c and /test/z exceed max_bytes
+        retention.update(&sample("/test/d", now - secs(5), "ddddddddd"));
+        let evicted: Vec<_> = retention
+            .evictions(now)
+            .into_iter()
+            .map(|s| s.key_expr.to_string())
+            .collect();
+        assert_eq!(evicted, vec!["/test/c", "/test/z"]);
+
+        // a more recent DELETE removes the key from the tracked ones
+        let mut delete = sample("/test/d", now, "");
+        delete.kind = SampleKind::Delete;
+        retention.update(&delete);
+        assert!(retention.evictions(now + secs(120)).is_empty());
+
+        let status = retention.get_admin_status();
+        assert_eq!(status["keys"], 0);
+        assert_eq!(status["evictions"]["ttl"], 1);
+        assert_eq!(status["evictions"]["max_keys"], 1);
+        assert_eq!(status["evictions"]["max_bytes"], 2);
+    }
+}
@@ -11,18 +11,22 @@
 // Contributors:
 //   ADLINK zenoh team, <zenoh@adlink-labs.tech>
 //
+use crate::replication::{start_replication, ReplicaState};
+use crate::retention::Retention;
 use async_std::channel::{bounded, Sender};
-use async_std::sync::Arc;
+use async_std::sync::{Arc, RwLock};
 use async_std::task;
 use futures::select;
 use futures::stream::StreamExt;
 use futures::FutureExt;
 use log::{debug, error, trace, warn};
+use std::time::SystemTime;
 use zenoh::prelude::*;
 use zenoh::query::{QueryConsolidation, QueryTarget, Target};
 use zenoh::queryable;
 use zenoh::Session;
-use zenoh_backend_traits::Query;
+use zenoh_backend_traits::config::{ReplicationConfig, RetentionConfig};
+use zenoh_backend_traits::{Query, Storage};
 use zenoh_core::Result as ZResult;
 
 pub(crate) enum StorageMessage {
@@ -30,10 +34,13 @@ pub(crate) enum StorageMessage {
     GetStatus(Sender<serde_json::Value>),
 }
 
+#[allow(clippy::too_many_arguments)]
 pub(crate) async fn start_storage(
     mut storage: Box<dyn zenoh_backend_traits::Storage>,
     admin_key: String,
     key_expr: String,
+    replication: Option<ReplicationConfig>,
+    retention: Option<RetentionConfig>,
     in_interceptor: Option<Arc<dyn Fn(Sample) -> Sample + Send + Sync>>,
     out_interceptor: Option<Arc<dyn Fn(Sample) -> Sample + Send + Sync>>,
     zenoh: Arc<Session>,
@@ -42,6 +49,48 @@ pub(crate) async fn start_storage(
 
     let (tx, rx) = bounded(1);
     task::spawn(async move {
+        // the data already held by the storage (e.g. recovered by a persistent backend),
+        // to seed the replica state and the retention with
+        let entries = if replication.is_some() || retention.is_some() {
+            match storage.get_all_entries().await {
+                Ok(samples) => samples,
+                Err(e) => {
+                    warn!(
+                        "Storage {} failed to report its content to the replication and retention: {}",
+                        admin_key, e
+                    );
+                    Vec::new()
+                }
+            }
+        } else {
+            Vec::new()
+        };
+        // the state shared with the anti-entropy protocol (if replication is configured)
+        let replica = replication.as_ref().map(|_| {
+            let mut state = ReplicaState::default();
+            entries.iter().for_each(|sample| state.update(sample));
+            Arc::new(RwLock::new(state))
+        });
+        // the tracking of the stored keys (if a retention policy is configured)
+        let mut retention = retention.map(|config| {
+            let mut retention = Retention::new(config);
+            entries.iter().for_each(|sample| retention.update(sample));
+            retention
+        });
+        drop(entries);
+        // evict the data already held that exceeds the retention policy
+        if let Err(e) = enforce_retention(&mut storage, &mut retention).await {
+            warn!(
+                "Storage {} raised an error evicting a sample: {}",
+                admin_key, e
+            );
+        }
+        let retention_check_period = retention.as_ref().and_then(Retention::check_period);
+        // the channel receiving the samples missed by the storage, found by the anti-entropy protocol
+        let (missing_tx, missing_rx) = bounded(256);
+        // the channel stopping the anti-entropy protocol when dropped
+        let (_replication_stopper, replication_stop) = bounded::<()>(1);
+
         // subscribe on key_expr
         let mut storage_sub = match zenoh.subscribe(&key_expr).await {
             Ok(storage_sub) => storage_sub,
@@ -70,15 +119,25 @@ pub(crate) async fn start_storage(
             }
         };
         while let Some(reply) = replies.next().await {
-            log::trace!("Storage {} aligns data {}", admin_key, reply.data.key_expr);
+            let sample = match reply.sample {
+                Ok(sample) => sample,
+                Err(value) => {
+                    warn!(
+                        "Storage {} received an error while aligning: {}",
+                        admin_key, value
+                    );
+                    continue;
+                }
+            };
+            log::trace!("Storage {} aligns data {}", admin_key, sample.key_expr);
             // Call incoming data interceptor (if any)
             let sample = if let Some(ref interceptor) = in_interceptor {
-                interceptor(reply.data)
+                interceptor(sample)
             } else {
-                reply.data
+                sample
             };
             // Call storage
-            if let Err(e) = storage.on_sample(sample).await {
+            if let Err(e) = store_sample(&mut storage, &replica, &mut retention, sample).await {
                 warn!(
                     "Storage {} raised an error aligning a sample: {}",
                     admin_key, e
@@ -96,6 +155,27 @@ pub(crate) async fn start_storage(
             }
         };
 
+        // periodically align with other storages (if replication is configured)
+        if let (Some(config), Some(state)) = (replication, &replica) {
+            if let Err(e) = start_replication(
+                zenoh.clone(),
+                admin_key.clone(),
+                key_expr.clone(),
+                config,
+                state.clone(),
+                missing_tx,
+                replication_stop,
+            )
+            .await
+            {
+                error!(
+                    "Error starting replication of storage {} : {}",
+                    admin_key, e
+                );
+                return;
+            }
+        }
+
         loop {
             select!(
                 // on sample for key_expr
@@ -107,10 +187,26 @@ pub(crate) async fn start_storage(
                         sample.unwrap()
                     };
                     // Call storage
-                    if let Err(e) = storage.on_sample(sample).await {
+                    if let Err(e) = store_sample(&mut storage, &replica, &mut retention, sample).await {
                         warn!("Storage {} raised an error receiving a sample: {}", admin_key, e);
                     }
                 },
+                // on sample missed by the storage, found by the anti-entropy protocol
+                sample = missing_rx.recv().fuse() => {
+                    if let Ok(sample) = sample {
+                        trace!("Storage {} aligns data {}", admin_key, sample.key_expr);
+                        // Call incoming data interceptor (if any)
+                        let sample = if let Some(ref interceptor) = in_interceptor {
+                            interceptor(sample)
+                        } else {
+                            sample
+                        };
+                        // Call storage
+                        if let Err(e) = store_sample(&mut storage, &replica, &mut retention, sample).await {
+                            warn!("Storage {} raised an error aligning a sample: {}", admin_key, e);
+                        }
+                    }
+                },
                 // on query on key_expr
                 query = storage_queryable.next() => {
                     let q = query.unwrap();
@@ -121,6 +217,17 @@ pub(crate) async fn start_storage(
                         warn!("Storage {} raised an error receiving a query: {}", admin_key, e);
                     }
                 },
+                // on expiration of the samples' TTL (if configured)
+                _ = async {
+                    match retention_check_period {
+                        Some(period) => task::sleep(period).await,
+                        None => futures::future::pending().await,
+                    }
+                }.fuse() => {
+                    if let Err(e) = enforce_retention(&mut storage, &mut retention).await {
+                        warn!("Storage {} raised an error evicting a sample: {}", admin_key, e);
+                    }
+                },
                 // on storage handle drop
                 message = rx.recv().fuse() => {
                     match message {
@@ -129,7 +236,17 @@ pub(crate) async fn start_storage(
                             return
                         },
                         Ok(StorageMessage::GetStatus(tx)) => {
-                            std::mem::drop(tx.send(storage.get_admin_status()).await);
+                            let mut status = storage.get_admin_status();
+                            if let (Some(retention), Some(status)) = (&retention, status.as_object_mut()) {
+                                // complete the retention configuration with its current status
+                                match status.get_mut("retention").and_then(|r| r.as_object_mut()) {
+                                    Some(config) => config.extend(retention.get_admin_status()),
+                                    None => {
+                                        status.insert("retention".into(), retention.get_admin_status().into());
+                                    }
+                                }
+                            }
+                            std::mem::drop(tx.send(status).await);
                         }
                         Err(e) => {log::error!("Storage Message Channel Error: {}", e); return},
                     };
@@ -140,3 +257,44 @@ pub(crate) async fn start_storage(
 
     Ok(tx)
 }
+
+// Passes a sample to the storage, recording it in the replica state if replication is configured,
+// and evicting the samples exceeding the retention policy if configured.
+async fn store_sample(
+    storage: &mut Box<dyn Storage>,
+    replica: &Option<Arc<RwLock<ReplicaState>>>,
+    retention: &mut Option<Retention>,
+    mut sample: Sample,
+) -> ZResult<()> {
+    if replica.is_none() && retention.is_none() {
+        return storage.on_sample(sample).await;
+    }
+    // the storage, the replica state and the retention must use the same timestamp
+    sample.ensure_timestamp();
+    storage.on_sample(sample.clone()).await?;
+    if let Some(state) = replica {
+        state.write().await.update(&sample);
+    }
+    if let Some(retention) = retention {
+        retention.update(&sample);
+    }
+    enforce_retention(storage, retention).await
+}
+
+// Passes to the storage the DELETE samples evicting the samples exceeding the retention policy.
+// The retention is a local policy: the evictions are not recorded in the replica state, so that
+// the anti-entropy protocol neither propagates them to the other storages, nor fetches back the evicted samples.
+async fn enforce_retention(
+    storage: &mut Box<dyn Storage>,
+    retention: &mut Option<Retention>,
+) -> ZResult<()> {
+    let evicted = match retention {
+        Some(retention) => retention.evictions(SystemTime::now()),
+        None => return Ok(()),
+    };
+    for sample in evicted {
+        trace!("Evict {} from storage", sample.key_expr);
+        storage.on_sample(sample).await?;
+    }
+    Ok(())
+}
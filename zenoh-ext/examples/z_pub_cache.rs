@@ -22,7 +22,7 @@ async fn main() {
     // Initiate logging
     env_logger::init();
 
-    let (config, key_expr, value, history, prefix) = parse_args();
+    let (config, key_expr, value, history, max_age, prefix) = parse_args();
 
     println!("Opening session...");
     let session = zenoh::open(config).await.unwrap();
@@ -33,6 +33,9 @@ async fn main() {
 
     println!("Creating PublicationCache on {}", expr_id);
     let mut publication_cache_builder = session.publication_cache(expr_id).history(history);
+    if let Some(max_age) = max_age {
+        publication_cache_builder = publication_cache_builder.max_age(max_age);
+    }
     if let Some(prefix) = prefix {
         publication_cache_builder = publication_cache_builder.queryable_prefix(prefix);
     }
@@ -46,7 +49,14 @@ async fn main() {
     }
 }
 
-fn parse_args() -> (Config, String, String, usize, Option<String>) {
+fn parse_args() -> (
+    Config,
+    String,
+    String,
+    usize,
+    Option<Duration>,
+    Option<String>,
+) {
     let args = App::new("zenoh-ext pub cache example")
         .arg(
             Arg::from_usage("-m, --mode=[MODE] 'The zenoh session mode (peer by default).")
@@ -70,6 +80,9 @@ fn parse_args() -> (Config, String, String, usize, Option<String>) {
             Arg::from_usage("-h, --history=[SIZE] 'The number of publications to keep in cache'")
                 .default_value("1"),
         )
+        .arg(Arg::from_usage(
+            "-a, --max-age=[SECS] 'The maximum age in seconds of the publications kept in cache'",
+        ))
         .arg(Arg::from_usage(
             "-x, --prefix=[STRING] 'An optional queryable prefix'",
         ))
@@ -111,7 +124,10 @@ fn parse_args() -> (Config, String, String, usize, Option<String>) {
     let key_expr = args.value_of("key").unwrap().to_string();
     let value = args.value_of("value").unwrap().to_string();
     let history: usize = args.value_of("history").unwrap().parse().unwrap();
+    let max_age = args
+        .value_of("max-age")
+        .map(|secs| Duration::from_secs_f64(secs.parse().unwrap()));
     let prefix = args.value_of("prefix").map(String::from);
 
-    (config, key_expr, value, history, prefix)
+    (config, key_expr, value, history, max_age, prefix)
 }
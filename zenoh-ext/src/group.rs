@@ -245,9 +245,13 @@ async fn net_event_handler(z: Arc<Session>, state: Arc<GroupState>) {
                                 log::debug!("Issuing Query for {}", &qres);
                                 let mut receiver = z.get(&qres).consolidation(qc).await.unwrap();
 
-                                while let Some(sample) = receiver.next().await {
+                                while let Some(reply) = receiver.next().await {
+                                    let sample = match reply.sample {
+                                        Ok(sample) => sample,
+                                        Err(_) => continue,
+                                    };
                                     match bincode::deserialize::<Member>(
-                                        &sample.data.value.payload.contiguous(),
+                                        &sample.value.payload.contiguous(),
                                     ) {
                                         Ok(m) => {
                                             let mut expiry = Instant::now();
@@ -20,6 +20,7 @@ use futures::FutureExt;
 use futures_lite::StreamExt;
 use std::collections::{HashMap, VecDeque};
 use std::future::Future;
+use std::time::{Duration, SystemTime};
 use zenoh::prelude::*;
 use zenoh::queryable::Queryable;
 use zenoh::subscriber::Subscriber;
@@ -36,6 +37,7 @@ pub struct PublicationCacheBuilder<'a, 'b> {
     pub_key_expr: KeyExpr<'b>,
     queryable_prefix: Option<String>,
     history: usize,
+    max_age: Option<Duration>,
     resources_limit: Option<usize>,
 }
 
@@ -49,6 +51,7 @@ impl<'a, 'b> PublicationCacheBuilder<'a, 'b> {
             pub_key_expr,
             queryable_prefix: None,
             history: 1,
+            max_age: None,
             resources_limit: None,
         }
     }
@@ -65,6 +68,14 @@ impl<'a, 'b> PublicationCacheBuilder<'a, 'b> {
         self
     }
 
+    /// Change the maximum age of the cached publications, relative to their timestamp.
+    /// Older publications are removed from the cache, whatever the history size.
+    /// The publications without timestamp are timestamped on their reception by the cache.
+    pub fn max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = Some(max_age);
+        self
+    }
+
     /// Change the limit number of cached resources.
     pub fn resources_limit(mut self, limit: usize) -> Self {
         self.resources_limit = Some(limit);
@@ -97,9 +108,10 @@ impl<'a> PublicationCache<'a> {
 
     fn new(conf: PublicationCacheBuilder<'a, '_>) -> ZResult<PublicationCache<'a>> {
         log::debug!(
-            "Create PublicationCache on {} with history={} resource_limit={:?}",
+            "Create PublicationCache on {} with history={} max_age={:?} resource_limit={:?}",
             conf.pub_key_expr,
             conf.history,
+            conf.max_age,
             conf.resources_limit
         );
 
@@ -137,24 +149,37 @@ impl<'a> PublicationCache<'a> {
         let resources_limit = conf.resources_limit;
         let queryable_prefix = conf.queryable_prefix;
         let history = conf.history;
+        let max_age = conf.max_age;
 
         let (stoptx, mut stoprx) = bounded::<bool>(1);
         task::spawn(async move {
             let mut cache: HashMap<String, VecDeque<Sample>> =
                 HashMap::with_capacity(resources_limit.unwrap_or(32));
             let limit = resources_limit.unwrap_or(usize::MAX);
+            // created once, so that the cleanup also happens under a steady flow of publications
+            let mut cleanup = max_age.map(async_std::stream::interval);
 
             loop {
                 select!(
                     // on publication received by the local subscriber, store it
                     sample = sub_recv.next().fuse() => {
-                        if let Some(sample) = sample {
+                        if let Some(mut sample) = sample {
+                            // with a max age, the publications without timestamp are aged from their reception
+                            if max_age.is_some() && sample.timestamp.is_none() {
+                                sample.ensure_timestamp();
+                            }
                             let queryable_key_expr = if let Some(prefix) = &queryable_prefix {
                                 format!("{}{}", prefix, sample.key_expr)
                             } else {
                                 sample.key_expr.to_string()
                             };
 
+                            if let Some(max_age) = max_age {
+                                if cache.len() >= limit && !cache.contains_key(&queryable_key_expr) {
+                                    // free the resources with only expired publications
+                                    remove_expired(&mut cache, max_age);
+                                }
+                            }
                             if let Some(queue) = cache.get_mut(&queryable_key_expr) {
                                 if queue.len() >= history {
                                     queue.pop_front();
@@ -174,16 +199,31 @@ impl<'a> PublicationCache<'a> {
                     // on query, reply with cach content
                     query = quer_recv.next().fuse() => {
                         if let Some(query) = query {
+                            let time_range = match query.selector().parse_value_selector().and_then(|v| v.time_range()) {
+                                Ok(time_range) => time_range,
+                                Err(e) => {
+                                    log::warn!("PublicationCache on {}: invalid query {}: {}", pub_key_expr, query.selector(), e);
+                                    continue;
+                                }
+                            };
+                            if let Some(max_age) = max_age {
+                                remove_expired(&mut cache, max_age);
+                            }
+                            let in_range = |sample: &Sample| match (&time_range, &sample.timestamp) {
+                                (None, _) => true,
+                                (Some(time_range), Some(ts)) => time_range.contains_timestamp(ts),
+                                (Some(_), None) => false,
+                            };
                             if !query.selector().key_selector.as_str().contains('*') {
                                 if let Some(queue) = cache.get(query.selector().key_selector.as_str()) {
-                                    for sample in queue {
+                                    for sample in queue.iter().filter(|s| in_range(s)) {
                                         query.reply(sample.clone());
                                     }
                                 }
                             } else {
                                 for (key_expr, queue) in cache.iter() {
                                     if key_expr::intersect(query.selector().key_selector.as_str(), key_expr) {
-                                        for sample in queue {
+                                        for sample in queue.iter().filter(|s| in_range(s)) {
                                             query.reply(sample.clone());
                                         }
                                     }
@@ -192,6 +232,18 @@ impl<'a> PublicationCache<'a> {
                         }
                     },
 
+                    // periodically remove the expired publications (if a max_age is configured)
+                    _ = async {
+                        match cleanup.as_mut() {
+                            Some(cleanup) => { cleanup.next().await; }
+                            None => futures::future::pending().await,
+                        }
+                    }.fuse() => {
+                        if let Some(max_age) = max_age {
+                            remove_expired(&mut cache, max_age);
+                        }
+                    },
+
                     // When stoptx is dropped, stop the task
                     _ = stoprx.next().fuse() => {
                         return
@@ -214,3 +266,21 @@ impl<'a> PublicationCache<'a> {
         zready(Ok(()))
     }
 }
+
+// Removes from the cache the publications older than max_age, and the resources without publications.
+fn remove_expired(cache: &mut HashMap<String, VecDeque<Sample>>, max_age: Duration) {
+    let oldest = match SystemTime::now().checked_sub(max_age) {
+        Some(oldest) => oldest,
+        None => return,
+    };
+    cache.retain(|_, queue| {
+        // the publications are queued in the order of their timestamps
+        while let Some(Some(ts)) = queue.front().map(|s| s.timestamp) {
+            if ts.get_time().to_system_time() >= oldest {
+                break;
+            }
+            queue.pop_front();
+        }
+        !queue.is_empty()
+    });
+}
@@ -19,7 +19,7 @@ use std::future::Future;
 use std::sync::{Arc, RwLock};
 use std::time::{Duration, Instant};
 use zenoh::prelude::{KeyExpr, Receiver, Sample, Selector, ZFuture};
-use zenoh::query::{QueryConsolidation, QueryTarget, ReplyReceiver, Target};
+use zenoh::query::{QueryConsolidation, QueryTarget, Reply, ReplyReceiver, Target};
 use zenoh::queryable::STORAGE;
 use zenoh::subscriber::{Reliability, SampleReceiver, SubMode, Subscriber};
 use zenoh::sync::channel::{RecvError, RecvTimeoutError, TryRecvError};
@@ -403,6 +403,17 @@ struct InnerState {
     merge_queue: Vec<Sample>,
 }
 
+fn merge_reply(merge_queue: &mut Vec<Sample>, reply: Reply) {
+    match reply.sample {
+        Ok(mut sample) => {
+            log::trace!("Reply received: {}", sample.key_expr);
+            sample.ensure_timestamp();
+            merge_queue.push(sample);
+        }
+        Err(value) => log::warn!("Error reply received: {}", value),
+    }
+}
+
 impl Stream for InnerState {
     type Item = Sample;
 
@@ -416,11 +427,7 @@ impl Stream for InnerState {
             while i < mself.replies_recv_queue.len() {
                 loop {
                     match mself.replies_recv_queue[i].poll_next(cx) {
-                        Poll::Ready(Some(mut reply)) => {
-                            log::trace!("Reply received: {}", reply.data.key_expr);
-                            reply.data.ensure_timestamp();
-                            mself.merge_queue.push(reply.data);
-                        }
+                        Poll::Ready(Some(reply)) => merge_reply(&mut mself.merge_queue, reply),
                         Poll::Ready(None) => {
                             // query completed - remove the receiver and break loop
                             mself.replies_recv_queue.remove(i);
@@ -490,10 +497,8 @@ impl InnerState {
         if !self.replies_recv_queue.is_empty() {
             // get all replies and add them to merge_queue
             for recv in self.replies_recv_queue.drain(..) {
-                while let Ok(mut reply) = recv.recv() {
-                    log::trace!("Reply received: {}", reply.data.key_expr);
-                    reply.data.ensure_timestamp();
-                    self.merge_queue.push(reply.data);
+                while let Ok(reply) = recv.recv() {
+                    merge_reply(&mut self.merge_queue, reply);
                 }
             }
             log::debug!(
@@ -542,11 +547,7 @@ impl InnerState {
             while i < self.replies_recv_queue.len() {
                 loop {
                     match self.replies_recv_queue[i].try_recv() {
-                        Ok(mut reply) => {
-                            log::trace!("Reply received: {}", reply.data.key_expr);
-                            reply.data.ensure_timestamp();
-                            self.merge_queue.push(reply.data);
-                        }
+                        Ok(reply) => merge_reply(&mut self.merge_queue, reply),
                         Err(TryRecvError::Disconnected) => {
                             // query completed - remove the receiver and break loop
                             self.replies_recv_queue.remove(i);
@@ -613,11 +614,7 @@ impl InnerState {
             while i < self.replies_recv_queue.len() {
                 loop {
                     match self.replies_recv_queue[i].recv_deadline(deadline) {
-                        Ok(mut reply) => {
-                            log::trace!("Reply received: {}", reply.data.key_expr);
-                            reply.data.ensure_timestamp();
-                            self.merge_queue.push(reply.data);
-                        }
+                        Ok(reply) => merge_reply(&mut self.merge_queue, reply),
                         Err(RecvTimeoutError::Disconnected) => {
                             // query completed - remove the receiver and break loop
                             self.replies_recv_queue.remove(i);
@@ -0,0 +1,115 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ADLINK zenoh team, <zenoh@adlink-labs.tech>
+//
+use async_std::prelude::FutureExt;
+use async_std::task;
+use futures::StreamExt;
+use std::time::Duration;
+use zenoh::prelude::*;
+use zenoh::query::{QueryConsolidation, QueryTarget, Target};
+use zenoh::queryable;
+use zenoh::Session;
+use zenoh_ext::*;
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+const SLEEP: Duration = Duration::from_millis(100);
+const MAX_AGE: Duration = Duration::from_secs(1);
+
+async fn open_session() -> Session {
+    let mut config = config::peer();
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    config.set_add_timestamp(Some(true)).unwrap();
+    zenoh::open(config).timeout(TIMEOUT).await.unwrap().unwrap()
+}
+
+// Returns the values of the cached publications matching `selector`.
+async fn get(session: &Session, selector: &str) -> Vec<String> {
+    let mut values: Vec<String> = session
+        .get(selector)
+        .target(QueryTarget {
+            kind: queryable::ALL_KINDS,
+            target: Target::All,
+        })
+        .consolidation(QueryConsolidation::none())
+        .await
+        .unwrap()
+        .map(|reply| reply.sample.unwrap().value.to_string())
+        .collect()
+        .timeout(TIMEOUT)
+        .await
+        .unwrap();
+    values.sort();
+    values
+}
+
+#[test]
+fn publication_cache_max_age() {
+    task::block_on(async {
+        let session = open_session().await;
+        let cache = session
+            .publication_cache("/test/cache/max_age/*")
+            .history(10)
+            .max_age(MAX_AGE)
+            .await
+            .unwrap();
+
+        session.put("/test/cache/max_age/a", "old").await.unwrap();
+        task::sleep(MAX_AGE / 2).await;
+        session.put("/test/cache/max_age/b", "new").await.unwrap();
+        // let the cache store the publications before querying it
+        task::sleep(SLEEP).await;
+        assert_eq!(get(&session, "/test/cache/max_age/*").await, ["new", "old"]);
+
+        // only the publications younger than max_age are kept
+        task::sleep(MAX_AGE * 3 / 4).await;
+        assert_eq!(get(&session, "/test/cache/max_age/*").await, ["new"]);
+        task::sleep(MAX_AGE).await;
+        assert!(get(&session, "/test/cache/max_age/*").await.is_empty());
+
+        cache.close().await.unwrap();
+        session.close().await.unwrap();
+    });
+}
+
+#[test]
+fn publication_cache_time_range() {
+    task::block_on(async {
+        let session = open_session().await;
+        let cache = session
+            .publication_cache("/test/cache/time_range")
+            .history(10)
+            .await
+            .unwrap();
+
+        session.put("/test/cache/time_range", "old").await.unwrap();
+        task::sleep(Duration::from_secs(2)).await;
+        session.put("/test/cache/time_range", "new").await.unwrap();
+        task::sleep(SLEEP).await;
+
+        assert_eq!(
+            get(&session, "/test/cache/time_range").await,
+            ["new", "old"]
+        );
+        assert_eq!(
+            get(&session, "/test/cache/time_range?(starttime=now()-1s)").await,
+            ["new"]
+        );
+        assert_eq!(
+            get(&session, "/test/cache/time_range?(stoptime=now()-1s)").await,
+            ["old"]
+        );
+
+        cache.close().await.unwrap();
+        session.close().await.unwrap();
+    });
+}
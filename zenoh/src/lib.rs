@@ -69,7 +69,10 @@
 //!     let session = zenoh::open(config::default()).await.unwrap();
 //!     let mut replies = session.get("/key/expression").await.unwrap();
 //!     while let Some(reply) = replies.next().await {
-//!         println!(">> Received {}", reply.data);
+//!         match reply.sample {
+//!             Ok(sample) => println!(">> Received {}", sample),
+//!             Err(value) => println!(">> Received error {}", value),
+//!         }
 //!     }
 //! }
 //! ```
@@ -110,6 +113,7 @@ pub mod net;
 #[deprecated = "This module is now a separate crate. Use the crate directly for shorter compile-times"]
 pub use zenoh_config as config;
 pub mod info;
+pub mod liveliness;
 pub mod prelude;
 pub mod publication;
 pub mod query;
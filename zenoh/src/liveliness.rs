@@ -0,0 +1,183 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ADLINK zenoh team, <zenoh@adlink-labs.tech>
+//
+
+//! Liveliness primitives.
+//!
+//! A [`LivelinessToken`] declared on a key expression is alive until it's dropped,
+//! or until the session that declared it is closed or disconnected. The tokens are tracked by the
+//! zenoh routers, so no application-level lease is required.
+//!
+//! The tokens are exposed under the [`LIVELINESS_PREFIX`]: a token declared on `/my/app`
+//! is reported with the key `/@/liveliness/my/app`, with a PUT sample when it appears and a
+//! DELETE sample when it disappears.
+use crate::prelude::*;
+use crate::query::*;
+use crate::queryable::{Queryable, LIVELINESS};
+use crate::subscriber::SubscriberBuilder;
+use crate::sync::zready;
+use crate::Session;
+use async_std::task;
+use futures::StreamExt;
+use zenoh_core::Result as ZResult;
+
+/// The prefix of the key expressions of the liveliness tokens.
+pub const LIVELINESS_PREFIX: &str = "/@/liveliness";
+
+/// A structure with functions to declare a [`LivelinessToken`], query the existing
+/// tokens and subscribe to their changes.
+///
+/// # Examples
+/// ```
+/// # async_std::task::block_on(async {
+/// use zenoh::prelude::*;
+///
+/// let session = zenoh::open(config::peer()).await.unwrap();
+/// let token = session.liveliness().declare_token("/my/app").await.unwrap();
+/// # })
+/// ```
+pub struct Liveliness<'a> {
+    pub(crate) session: &'a Session,
+}
+
+impl<'a> Liveliness<'a> {
+    /// Declare a [`LivelinessToken`] on the given key expression, alive until it's dropped.
+    ///
+    /// # Examples
+    /// ```
+    /// # async_std::task::block_on(async {
+    /// use zenoh::prelude::*;
+    ///
+    /// let session = zenoh::open(config::peer()).await.unwrap();
+    /// let token = session.liveliness().declare_token("/my/app").await.unwrap();
+    /// token.undeclare().await.unwrap();
+    /// # })
+    /// ```
+    pub fn declare_token<'b, IntoKeyExpr>(
+        &self,
+        key_expr: IntoKeyExpr,
+    ) -> impl ZFuture<Output = ZResult<LivelinessToken<'a>>>
+    where
+        IntoKeyExpr: Into<KeyExpr<'b>>,
+    {
+        zready(self.declare_token_(key_expr.into()))
+    }
+
+    fn declare_token_(&self, key_expr: KeyExpr) -> ZResult<LivelinessToken<'a>> {
+        let key_expr = self.liveliness_key_expr(&key_expr)?;
+        log::trace!("declare_token({})", key_expr);
+        let mut queryable = self
+            .session
+            .queryable(key_expr.clone())
+            .kind(LIVELINESS)
+            .wait()?;
+        // reply to the liveliness queries until the token is undeclared
+        let mut queries = queryable.receiver().clone();
+        task::spawn(async move {
+            while let Some(query) = queries.next().await {
+                query
+                    .reply_async(Sample::new(key_expr.clone(), Value::empty()))
+                    .await;
+            }
+        });
+        Ok(LivelinessToken { queryable })
+    }
+
+    /// Subscribe to the appearance (PUT samples) and disappearance (DELETE samples)
+    /// of the liveliness tokens matching the given key expression.
+    ///
+    /// The tokens existing at the time of the subscription are not notified:
+    /// use [`get`](Liveliness::get) to retrieve them.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # async_std::task::block_on(async {
+    /// use futures::prelude::*;
+    /// use zenoh::prelude::*;
+    ///
+    /// let session = zenoh::open(config::peer()).await.unwrap();
+    /// let mut subscriber = session.liveliness().subscribe("/my/**").unwrap().await.unwrap();
+    /// while let Some(sample) = subscriber.next().await {
+    ///     match sample.kind {
+    ///         SampleKind::Delete => println!("{} is dead", sample.key_expr),
+    ///         _ => println!("{} is alive", sample.key_expr),
+    ///     }
+    /// }
+    /// # })
+    /// ```
+    pub fn subscribe<'b, IntoKeyExpr>(
+        &self,
+        key_expr: IntoKeyExpr,
+    ) -> ZResult<SubscriberBuilder<'a, 'static>>
+    where
+        IntoKeyExpr: Into<KeyExpr<'b>>,
+    {
+        let key_expr = self.liveliness_key_expr(&key_expr.into())?;
+        Ok(self.session.subscribe(key_expr).reliable())
+    }
+
+    /// Query the liveliness tokens alive and matching the given key expression.
+    ///
+    /// # Examples
+    /// ```
+    /// # async_std::task::block_on(async {
+    /// use futures::prelude::*;
+    /// use zenoh::prelude::*;
+    ///
+    /// let session = zenoh::open(config::peer()).await.unwrap();
+    /// let mut replies = session.liveliness().get("/my/**").unwrap().await.unwrap();
+    /// while let Some(reply) = replies.next().await {
+    ///     if let Ok(sample) = reply.sample {
+    ///         println!("{} is alive", sample.key_expr);
+    ///     }
+    /// }
+    /// # })
+    /// ```
+    pub fn get<'b, IntoKeyExpr>(&self, key_expr: IntoKeyExpr) -> ZResult<Getter<'a, 'static>>
+    where
+        IntoKeyExpr: Into<KeyExpr<'b>>,
+    {
+        let key_expr = self.liveliness_key_expr(&key_expr.into())?;
+        Ok(self
+            .session
+            .get(Selector::from(key_expr))
+            .target(QueryTarget {
+                kind: LIVELINESS,
+                target: Target::All,
+            })
+            .consolidation(QueryConsolidation::none()))
+    }
+
+    fn liveliness_key_expr(&self, key_expr: &KeyExpr) -> ZResult<KeyExpr<'static>> {
+        let expr = zread!(self.session.state).localkey_to_expr(key_expr)?;
+        Ok(KeyExpr::from(format!("{}{}", LIVELINESS_PREFIX, expr)))
+    }
+}
+
+/// A token that remains alive until it's dropped, or until its session is closed or
+/// disconnected. It can be observed by the other applications using the [`Liveliness`]
+/// subscription or query.
+#[derive(Debug)]
+pub struct LivelinessToken<'a> {
+    queryable: Queryable<'a>,
+}
+
+impl LivelinessToken<'_> {
+    /// Undeclare this token. Tokens are automatically undeclared when dropped,
+    /// but you may want to use this function to handle errors.
+    #[inline]
+    #[must_use = "ZFutures do nothing unless you `.wait()`, `.await` or poll them"]
+    pub fn undeclare(self) -> impl ZFuture<Output = ZResult<()>> {
+        self.queryable.close()
+    }
+}
@@ -0,0 +1,167 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ADLINK zenoh team, <zenoh@adlink-labs.tech>
+//
+use super::face::FaceState;
+use super::router::Tables;
+use crate::config::{AccessControlConf, AclMessage, AclRule, Permission};
+use std::net::{IpAddr, SocketAddr};
+use zenoh_core::Result as ZResult;
+use zenoh_protocol_core::{key_expr, KeyExpr};
+use zenoh_transport::unicast::establishment::authenticator::AuthId;
+use zenoh_transport::TransportUnicast;
+
+/// The identity of a remote session, to which the access control rules apply.
+pub(crate) struct Subject {
+    auth_ids: Vec<AuthId>,
+    pub(super) addresses: Vec<IpAddr>,
+}
+
+impl Subject {
+    pub(crate) fn new(transport: &TransportUnicast) -> Subject {
+        let auth_ids = transport.get_auth_ids().unwrap_or_default();
+        let addresses = transport
+            .get_links()
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|link| link.src.address().parse::<SocketAddr>().ok())
+            .map(|addr| addr.ip())
+            .collect();
+        Subject {
+            auth_ids,
+            addresses,
+        }
+    }
+}
+
+/// Returns the unicast addresses of the given network interfaces.
+pub(super) fn interfaces_addresses(interfaces: &[String]) -> ZResult<Vec<IpAddr>> {
+    let mut addresses = vec![];
+    for iface in interfaces {
+        let iface_addresses = zenoh_util::net::get_unicast_addresses_of_interface(iface)?;
+        if iface_addresses.is_empty() {
+            log::warn!("No address found for the interface {} of a rule", iface);
+        }
+        addresses.extend(iface_addresses);
+    }
+    Ok(addresses)
+}
+
+struct Rule {
+    permission: Permission,
+    messages: Vec<AclMessage>,
+    key_exprs: Vec<String>,
+    auth_ids: Vec<AuthId>,
+    addresses: Vec<IpAddr>,
+}
+
+impl Rule {
+    fn new(rule: &AclRule) -> ZResult<Rule> {
+        let auth_ids = rule
+            .users
+            .iter()
+            .map(|user| AuthId::Username(user.clone()))
+            .chain(
+                rule.public_keys
+                    .iter()
+                    .map(|key| AuthId::PublicKey(key.to_lowercase())),
+            )
+            .collect();
+        let addresses = interfaces_addresses(&rule.interfaces)?;
+        Ok(Rule {
+            permission: rule.permission,
+            messages: rule.messages.clone(),
+            key_exprs: rule.key_exprs.clone(),
+            auth_ids,
+            addresses,
+        })
+    }
+
+    fn applies(&self, subject: &Subject, message: AclMessage, key: &str) -> bool {
+        if !self.messages.is_empty() && !self.messages.contains(&message) {
+            return false;
+        }
+        let has_subjects = !self.auth_ids.is_empty() || !self.addresses.is_empty();
+        if has_subjects
+            && !subject.auth_ids.iter().any(|id| self.auth_ids.contains(id))
+            && !subject.addresses.iter().any(|a| self.addresses.contains(a))
+        {
+            return false;
+        }
+        match self.permission {
+            Permission::Allow => self.key_exprs.iter().any(|k| key_expr::include(k, key)),
+            Permission::Deny => self.key_exprs.iter().any(|k| key_expr::intersect(k, key)),
+        }
+    }
+}
+
+pub(crate) struct AccessControl {
+    default_permission: Permission,
+    rules: Vec<Rule>,
+}
+
+impl AccessControl {
+    /// Returns the access control configured by `conf`, if enabled.
+    pub(crate) fn from_config(conf: &AccessControlConf) -> ZResult<Option<AccessControl>> {
+        if !conf.enabled().unwrap_or(false) {
+            return Ok(None);
+        }
+        let rules = conf
+            .rules()
+            .iter()
+            .map(Rule::new)
+            .collect::<ZResult<Vec<Rule>>>()?;
+        Ok(Some(AccessControl {
+            default_permission: conf.default_permission().unwrap_or_default(),
+            rules,
+        }))
+    }
+
+    fn permission(&self, subject: &Subject, message: AclMessage, key: &str) -> Permission {
+        self.rules
+            .iter()
+            .find(|rule| rule.applies(subject, message, key))
+            .map_or(self.default_permission, |rule| rule.permission)
+    }
+}
+
+/// Returns whether the given message received from `face` on `expr` is allowed.
+/// The messages of the local sessions are always allowed.
+pub(crate) fn is_allowed(
+    tables: &Tables,
+    face: &FaceState,
+    message: AclMessage,
+    expr: &KeyExpr,
+) -> bool {
+    let (acl, subject) = match (&tables.access_control, &face.subject) {
+        (Some(acl), Some(subject)) => (acl, subject),
+        _ => return true,
+    };
+    let key = match tables.get_mapping(face, &expr.scope) {
+        Some(prefix) => [&prefix.expr(), expr.suffix.as_ref()].concat(),
+        None => {
+            log::error!(
+                "Access control of {:?} with unknown scope {}!",
+                message,
+                expr.scope
+            );
+            return false;
+        }
+    };
+    match acl.permission(subject, message, &key) {
+        Permission::Allow => true,
+        Permission::Deny => {
+            log::debug!("Deny {:?} on {} from {}", message, key, face);
+            false
+        }
+    }
+}
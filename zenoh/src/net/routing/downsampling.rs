@@ -0,0 +1,357 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ADLINK zenoh team, <zenoh@adlink-labs.tech>
+//
+use super::acl::interfaces_addresses;
+use super::face::FaceState;
+use super::resource::{Direction, Resource, Route};
+use crate::config::DownsamplingRule;
+use async_std::sync::Arc;
+use async_std::task;
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::str::FromStr;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use zenoh_buffers::SplitBuffer;
+use zenoh_collections::{Timed, TimedEvent, Timer};
+use zenoh_core::{bail, zlock, Result as ZResult};
+use zenoh_protocol::io::ZBuf;
+use zenoh_protocol::proto::DataInfo;
+use zenoh_protocol_core::{key_expr, Channel, CongestionControl, PeerId, ZInt};
+
+enum Limit {
+    // The minimum interval between two samples of a key expression
+    Rate(Duration),
+    // The maximum number of bytes per second
+    ByteRate(f64),
+}
+
+// A sample held until the end of the current downsampling period
+struct PendingData {
+    direction: Direction,
+    payload: ZBuf,
+    channel: Channel,
+    congestion_control: CongestionControl,
+    data_info: Option<DataInfo>,
+}
+
+impl PendingData {
+    fn send(self) {
+        let (outface, key_expr, context) = self.direction;
+        outface.primitives.send_data(
+            &key_expr,
+            self.payload,
+            self.channel,
+            self.congestion_control,
+            self.data_info,
+            context,
+        );
+    }
+}
+
+// The period of the removal of the idle throttles and buckets
+const EXPIRATION_PERIOD: Duration = Duration::from_secs(1);
+
+#[derive(Default)]
+struct Throttle {
+    last_sent: Option<Instant>,
+    pending: Option<PendingData>,
+}
+
+struct Bucket {
+    tokens: f64,
+    refilled: Instant,
+}
+
+#[derive(Default)]
+struct RuleState {
+    // per remote session and key
+    throttles: HashMap<(PeerId, String), Throttle>,
+    // per remote session
+    buckets: HashMap<PeerId, Bucket>,
+    dropped: HashMap<PeerId, ZInt>,
+    next_expiration: Option<Instant>,
+}
+
+impl RuleState {
+    // Removes the throttles and buckets in the same state as new ones,
+    // i.e. those that would admit the next sample right away.
+    fn expire(&mut self, limit: &Limit, now: Instant) {
+        match *limit {
+            Limit::Rate(interval) => self.throttles.retain(|_, throttle| {
+                throttle.pending.is_some()
+                    || matches!(throttle.last_sent, Some(last_sent) if now < last_sent + interval)
+            }),
+            Limit::ByteRate(rate) => self.buckets.retain(|_, bucket| {
+                let elapsed = now.duration_since(bucket.refilled).as_secs_f64();
+                bucket.tokens + rate * elapsed < rate
+            }),
+        }
+        self.next_expiration = Some(now + EXPIRATION_PERIOD);
+    }
+}
+
+struct Rule {
+    key_exprs: Vec<String>,
+    addresses: Vec<IpAddr>,
+    peer_ids: Vec<PeerId>,
+    limit: Limit,
+    state: Arc<Mutex<RuleState>>,
+}
+
+impl Rule {
+    fn new(rule: &DownsamplingRule) -> ZResult<Rule> {
+        let limit = match (rule.max_rate, rule.max_byte_rate) {
+            (Some(rate), None) if rate.is_finite() && rate > 0.0 => {
+                Limit::Rate(Duration::from_secs_f64(1.0 / rate))
+            }
+            (None, Some(rate)) if rate > 0 => Limit::ByteRate(rate as f64),
+            _ => bail!(
+                "Invalid downsampling rule for {:?}: exactly one of max_rate and max_byte_rate must be set to a positive value",
+                rule.key_exprs
+            ),
+        };
+        let peer_ids = rule
+            .peer_ids
+            .iter()
+            .map(|pid| PeerId::from_str(pid))
+            .collect::<ZResult<Vec<PeerId>>>()?;
+        Ok(Rule {
+            key_exprs: rule.key_exprs.clone(),
+            addresses: interfaces_addresses(&rule.interfaces)?,
+            peer_ids,
+            limit,
+            state: Arc::new(Mutex::new(RuleState::default())),
+        })
+    }
+
+    fn applies_to(&self, face: &FaceState) -> bool {
+        // the data sent to the local sessions is never downsampled
+        let subject = match &face.subject {
+            Some(subject) => subject,
+            None => return false,
+        };
+        (self.addresses.is_empty() && self.peer_ids.is_empty())
+            || self.peer_ids.contains(&face.pid)
+            || subject.addresses.iter().any(|a| self.addresses.contains(a))
+    }
+
+    fn matches(&self, key: &str) -> bool {
+        self.key_exprs.iter().any(|k| key_expr::include(k, key))
+    }
+
+    // Returns whether the data can be sent right away through `direction`.
+    #[allow(clippy::too_many_arguments)]
+    fn admit(
+        &self,
+        timer: &Timer,
+        direction: &Direction,
+        key: &str,
+        payload: &ZBuf,
+        channel: Channel,
+        congestion_control: CongestionControl,
+        data_info: &Option<DataInfo>,
+    ) -> bool {
+        let pid = direction.0.pid;
+        let now = Instant::now();
+        let mut state = zlock!(self.state);
+        if !matches!(state.next_expiration, Some(next) if now < next) {
+            state.expire(&self.limit, now);
+        }
+        let RuleState {
+            throttles,
+            buckets,
+            dropped,
+            ..
+        } = &mut *state;
+        let mut flush = None;
+        let (admitted, discarded) = match self.limit {
+            Limit::Rate(interval) => {
+                let throttle = throttles.entry((pid, key.to_string())).or_default();
+                match throttle.last_sent {
+                    Some(last_sent) if now < last_sent + interval => {
+                        // only the latest sample of the period is kept
+                        let pending = PendingData {
+                            direction: direction.clone(),
+                            payload: payload.clone(),
+                            channel,
+                            congestion_control,
+                            data_info: data_info.clone(),
+                        };
+                        let replaced = throttle.pending.replace(pending).is_some();
+                        if !replaced {
+                            flush = Some(TimedEvent::once(
+                                last_sent + interval,
+                                Flush {
+                                    state: self.state.clone(),
+                                    pid,
+                                    key: key.to_string(),
+                                },
+                            ));
+                        }
+                        (false, replaced)
+                    }
+                    _ => {
+                        throttle.last_sent = Some(now);
+                        // a late flush is superseded by this sample
+                        (true, throttle.pending.take().is_some())
+                    }
+                }
+            }
+            Limit::ByteRate(rate) => {
+                let bucket = buckets.entry(pid).or_insert(Bucket {
+                    tokens: rate,
+                    refilled: now,
+                });
+                let elapsed = now.duration_since(bucket.refilled).as_secs_f64();
+                bucket.tokens = (bucket.tokens + rate * elapsed).min(rate);
+                bucket.refilled = now;
+                // the bucket may go into debt, so that the samples larger than the bucket
+                // are still sent when it is full
+                if bucket.tokens >= 0.0 {
+                    bucket.tokens -= payload.len() as f64;
+                    (true, false)
+                } else {
+                    (false, true)
+                }
+            }
+        };
+        if discarded {
+            *dropped.entry(pid).or_insert(0) += 1;
+            log::trace!("Downsampling drops data on {} to {}", key, direction.0);
+        }
+        drop(state);
+        if let Some(event) = flush {
+            // The flush is scheduled out of the locks without blocking: the timer task may
+            // wait for the rule state or the routing tables, which the caller may hold
+            let timer = timer.clone();
+            task::spawn(async move { timer.add_async(event).await });
+        }
+        admitted
+    }
+}
+
+// Sends the sample kept for a key at the end of its downsampling period
+struct Flush {
+    state: Arc<Mutex<RuleState>>,
+    pid: PeerId,
+    key: String,
+}
+
+#[async_trait]
+impl Timed for Flush {
+    async fn run(&mut self) {
+        let pending = zlock!(self.state)
+            .throttles
+            .get_mut(&(self.pid, self.key.clone()))
+            .and_then(|throttle| {
+                let pending = throttle.pending.take();
+                if pending.is_some() {
+                    throttle.last_sent = Some(Instant::now());
+                }
+                pending
+            });
+        if let Some(pending) = pending {
+            // the data is sent from another task, so that the timer never waits for a congested face
+            task::spawn(async move { pending.send() });
+        }
+    }
+}
+
+pub(crate) struct Downsampling {
+    rules: Vec<Rule>,
+}
+
+impl Downsampling {
+    /// Returns the downsampling configured by `rules`, if any.
+    pub(crate) fn from_config(rules: &[DownsamplingRule]) -> ZResult<Option<Downsampling>> {
+        if rules.is_empty() {
+            return Ok(None);
+        }
+        let rules = rules
+            .iter()
+            .map(Rule::new)
+            .collect::<ZResult<Vec<Rule>>>()?;
+        Ok(Some(Downsampling { rules }))
+    }
+
+    /// Returns the directions of `route` through which the data on `prefix` + `suffix` can be
+    /// sent right away. The data that must wait for the end of a downsampling period is scheduled
+    /// on `timer`.
+    #[allow(clippy::too_many_arguments)]
+    pub(super) fn filter(
+        &self,
+        timer: &Timer,
+        route: Arc<Route>,
+        srcface: &FaceState,
+        prefix: &Resource,
+        suffix: &str,
+        payload: &ZBuf,
+        channel: Channel,
+        congestion_control: CongestionControl,
+        data_info: &Option<DataInfo>,
+    ) -> Arc<Route> {
+        let mut key: Option<String> = None;
+        let mut filtered: Option<Route> = None;
+        for (id, direction) in route.iter() {
+            if direction.0.id == srcface.id
+                || !self.rules.iter().any(|rule| rule.applies_to(&direction.0))
+            {
+                continue;
+            }
+            // the key is only built for the data sent to the faces with downsampling rules
+            let key = key.get_or_insert_with(|| [&prefix.expr(), suffix].concat());
+            let admitted = match self
+                .rules
+                .iter()
+                .find(|rule| rule.applies_to(&direction.0) && rule.matches(key))
+            {
+                Some(rule) => rule.admit(
+                    timer,
+                    direction,
+                    key,
+                    payload,
+                    channel,
+                    congestion_control,
+                    data_info,
+                ),
+                None => true,
+            };
+            if !admitted {
+                filtered.get_or_insert_with(|| (*route).clone()).remove(id);
+            }
+        }
+        filtered.map_or(route, Arc::new)
+    }
+
+    /// Drops the downsampling state of the remote session `pid`, e.g. when its face closes.
+    pub(crate) fn remove_peer(&self, pid: &PeerId) {
+        for rule in &self.rules {
+            let mut state = zlock!(rule.state);
+            state
+                .throttles
+                .retain(|(throttle_pid, _), _| throttle_pid != pid);
+            state.buckets.remove(pid);
+            state.dropped.remove(pid);
+        }
+    }
+
+    /// Returns the key expressions of each rule with the number of samples it dropped per remote session.
+    pub(crate) fn dropped(&self) -> Vec<(&[String], HashMap<PeerId, ZInt>)> {
+        self.rules
+            .iter()
+            .map(|rule| (&rule.key_exprs[..], zlock!(rule.state).dropped.clone()))
+            .collect()
+    }
+}
@@ -11,11 +11,14 @@
 // Contributors:
 //   ADLINK zenoh team, <zenoh@adlink-labs.tech>
 //
+use super::acl::{self, Subject};
 use super::router::*;
 use async_std::sync::Arc;
 use std::collections::{HashMap, HashSet};
 use std::fmt;
 use std::sync::RwLock;
+use std::time::Duration;
+use zenoh_config::AclMessage;
 use zenoh_protocol::io::ZBuf;
 use zenoh_protocol::proto::{DataInfo, RoutingContext};
 use zenoh_protocol_core::{
@@ -30,6 +33,8 @@ pub struct FaceState {
     pub(super) whatami: WhatAmI,
     pub(super) primitives: Arc<dyn Primitives + Send + Sync>,
     pub(super) link_id: usize,
+    // The identity of the remote session, None for the local sessions
+    pub(super) subject: Option<Subject>,
     pub(super) local_mappings: HashMap<ZInt, Arc<Resource>>,
     pub(super) remote_mappings: HashMap<ZInt, Arc<Resource>>,
     pub(super) local_subs: HashSet<Arc<Resource>>,
@@ -47,6 +52,7 @@ impl FaceState {
         whatami: WhatAmI,
         primitives: Arc<dyn Primitives + Send + Sync>,
         link_id: usize,
+        subject: Option<Subject>,
     ) -> Arc<FaceState> {
         Arc::new(FaceState {
             id,
@@ -54,6 +60,7 @@ impl FaceState {
             whatami,
             primitives,
             link_id,
+            subject,
             local_mappings: HashMap::new(),
             remote_mappings: HashMap::new(),
             local_subs: HashSet::new(),
@@ -181,6 +188,14 @@ impl Primitives for Face {
         routing_context: Option<RoutingContext>,
     ) {
         let mut tables = zwrite!(self.tables);
+        if !acl::is_allowed(
+            &tables,
+            &self.state,
+            AclMessage::DeclareSubscriber,
+            key_expr,
+        ) {
+            return;
+        }
         match (tables.whatami, self.state.whatami) {
             (WhatAmI::Router, WhatAmI::Router) => {
                 if let Some(router) = self.state.get_router(&tables, routing_context) {
@@ -251,6 +266,9 @@ impl Primitives for Face {
         routing_context: Option<RoutingContext>,
     ) {
         let mut tables = zwrite!(self.tables);
+        if !acl::is_allowed(&tables, &self.state, AclMessage::DeclareQueryable, key_expr) {
+            return;
+        }
         match (tables.whatami, self.state.whatami) {
             (WhatAmI::Router, WhatAmI::Router) => {
                 if let Some(router) = self.state.get_router(&tables, routing_context) {
@@ -352,6 +370,7 @@ impl Primitives for Face {
         qid: ZInt,
         target: QueryTarget,
         consolidation: ConsolidationStrategy,
+        timeout: Option<Duration>,
         routing_context: Option<RoutingContext>,
     ) {
         route_query(
@@ -362,6 +381,7 @@ impl Primitives for Face {
             qid,
             target,
             consolidation,
+            timeout,
             routing_context,
         );
     }
@@ -371,6 +391,7 @@ impl Primitives for Face {
         qid: ZInt,
         replier_kind: ZInt,
         replier_id: PeerId,
+        is_error: bool,
         key_expr: KeyExpr,
         info: Option<DataInfo>,
         payload: ZBuf,
@@ -382,6 +403,7 @@ impl Primitives for Face {
             qid,
             replier_kind,
             replier_id,
+            is_error,
             key_expr,
             info,
             payload,
@@ -0,0 +1,112 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ADLINK zenoh team, <zenoh@adlink-labs.tech>
+//
+//! Liveliness tokens are declared as queryables of kind [`queryable::LIVELINESS`].
+//! The routing tables track them as any other queryable, so they are undeclared when the
+//! declaring face closes. Each router (or peer) notifies the appearance and the disappearance
+//! of the tokens to its client faces subscribed to them, with PUT and DELETE data messages.
+use async_std::sync::Arc;
+use async_std::task;
+use flume::Sender;
+use std::collections::HashMap;
+use zenoh_protocol::io::ZBuf;
+use zenoh_protocol::proto::{data_kind, DataInfo};
+use zenoh_protocol_core::{queryable, Channel, CongestionControl, KeyExpr, WhatAmI};
+
+use super::face::FaceState;
+use super::resource::Resource;
+use super::router::Tables;
+
+pub(crate) type LivelinessNotification = (Arc<FaceState>, KeyExpr<'static>, DataInfo);
+
+/// Spawns the task sending the liveliness notifications. Those are sent out of the
+/// routing tables lock, so that they can be handled by re-entrant callbacks.
+pub(crate) fn liveliness_notifier() -> Sender<LivelinessNotification> {
+    let (sender, receiver) = flume::unbounded::<LivelinessNotification>();
+    task::spawn(async move {
+        while let Ok((face, key_expr, data_info)) = receiver.recv_async().await {
+            face.primitives.send_data(
+                &key_expr,
+                ZBuf::default(),
+                Channel::default(),
+                CongestionControl::Block,
+                Some(data_info),
+                None,
+            );
+        }
+    });
+    sender
+}
+
+fn has_token(res: &Arc<Resource>) -> bool {
+    res.context()
+        .router_qabls
+        .keys()
+        .chain(res.context().peer_qabls.keys())
+        .any(|(_, kind)| *kind == queryable::LIVELINESS)
+        || res
+            .session_ctxs
+            .values()
+            .any(|ctx| ctx.qabl.contains_key(&queryable::LIVELINESS))
+}
+
+/// Checks if a liveliness token appeared or disappeared on the given resource,
+/// and notifies it to the subscribed client faces.
+pub(crate) fn update_liveliness_token(tables: &mut Tables, res: &Arc<Resource>) {
+    if tables.whatami == WhatAmI::Client || res.context.is_none() {
+        // the router of a client notifies it
+        return;
+    }
+    let alive = has_token(res);
+    if alive == tables.liveliness_tokens.contains(res) {
+        return;
+    }
+    let expr = res.expr();
+    log::debug!(
+        "Liveliness token {} {}",
+        expr,
+        if alive { "declared" } else { "undeclared" }
+    );
+    if alive {
+        tables.liveliness_tokens.insert(res.clone());
+    } else {
+        tables.liveliness_tokens.remove(res);
+    }
+
+    let mut faces = HashMap::new();
+    for mres in Resource::get_matches(tables, &expr) {
+        let mres = mres.upgrade().unwrap();
+        for ctx in mres.session_ctxs.values() {
+            if ctx.subs.is_some() && ctx.face.whatami == WhatAmI::Client {
+                faces.insert(ctx.face.id, ctx.face.clone());
+            }
+        }
+    }
+    for face in faces.into_values() {
+        let key_expr = Resource::get_best_key(res, "", face.id).to_owned();
+        let mut data_info = DataInfo::new();
+        data_info.kind = Some(if alive {
+            data_kind::PUT
+        } else {
+            data_kind::DELETE
+        });
+        data_info.timestamp = tables.hlc.as_ref().map(|hlc| hlc.new_timestamp());
+        if tables
+            .liveliness_notifier
+            .send((face, key_expr, data_info))
+            .is_err()
+        {
+            log::error!("Liveliness notifier stopped: can't notify token {}", expr);
+        }
+    }
+}
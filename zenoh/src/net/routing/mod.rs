@@ -11,7 +11,10 @@
 // Contributors:
 //   ADLINK zenoh team, <zenoh@adlink-labs.tech>
 //
+pub mod acl;
+pub mod downsampling;
 pub mod face;
+pub mod liveliness;
 pub mod network;
 pub mod pubsub;
 pub mod queries;
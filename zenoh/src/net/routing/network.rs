@@ -14,6 +14,7 @@
 use super::runtime::Runtime;
 use petgraph::graph::NodeIndex;
 use petgraph::visit::{IntoNodeReferences, VisitMap, Visitable};
+use std::collections::HashMap;
 use std::convert::TryInto;
 use vec_map::VecMap;
 use zenoh_link::Locator;
@@ -21,12 +22,17 @@ use zenoh_protocol::core::{PeerId, WhatAmI, ZInt};
 use zenoh_protocol::proto::{LinkState, ZenohMessage};
 use zenoh_transport::TransportUnicast;
 
+// The weight of the links for which none of the two nodes configured a weight
+pub(crate) const DEFAULT_LINK_WEIGHT: ZInt = 100;
+
 pub(crate) struct Node {
     pub(crate) pid: PeerId,
     pub(crate) whatami: Option<WhatAmI>,
     pub(crate) locators: Option<Vec<Locator>>,
     pub(crate) sn: ZInt,
     pub(crate) links: Vec<PeerId>,
+    // The weights configured by the node for some of its links
+    pub(crate) link_weights: HashMap<PeerId, ZInt>,
 }
 
 impl std::fmt::Debug for Node {
@@ -86,6 +92,7 @@ pub(crate) struct Network {
     pub(crate) name: String,
     pub(crate) peers_autoconnect: bool,
     pub(crate) routers_autoconnect_gossip: bool,
+    pub(crate) link_weight: Option<ZInt>,
     pub(crate) idx: NodeIndex,
     pub(crate) links: VecMap<Link>,
     pub(crate) trees: Vec<Tree>,
@@ -101,6 +108,7 @@ impl Network {
         runtime: Runtime,
         peers_autoconnect: bool,
         routers_autoconnect_gossip: bool,
+        link_weight: Option<ZInt>,
     ) -> Self {
         let mut graph = petgraph::stable_graph::StableGraph::default();
         log::debug!("{} Add node (self) {}", name, pid);
@@ -110,11 +118,13 @@ impl Network {
             locators: None,
             sn: 1,
             links: vec![],
+            link_weights: HashMap::new(),
         });
         Network {
             name,
             peers_autoconnect,
             routers_autoconnect_gossip,
+            link_weight,
             idx,
             links: VecMap::new(),
             trees: vec![Tree {
@@ -190,13 +200,30 @@ impl Network {
         idx
     }
 
+    fn make_link_weights<'a, I>(&self, idx: NodeIndex, links: I) -> Option<Vec<ZInt>>
+    where
+        I: Iterator<Item = &'a PeerId>,
+    {
+        let link_weights = &self.graph[idx].link_weights;
+        if link_weights.is_empty() {
+            None
+        } else {
+            Some(
+                links
+                    .map(|pid| link_weights.get(pid).cloned().unwrap_or(0))
+                    .collect(),
+            )
+        }
+    }
+
     fn make_link_state(&self, idx: NodeIndex, details: bool) -> LinkState {
-        let links = self.graph[idx]
+        let (links, pids): (Vec<ZInt>, Vec<&PeerId>) = self.graph[idx]
             .links
             .iter()
             .filter_map(|pid| {
                 if let Some(idx2) = self.get_idx(pid) {
-                    Some(idx2.index().try_into().unwrap())
+                    let psid: ZInt = idx2.index().try_into().unwrap();
+                    Some((psid, pid))
                 } else {
                     log::error!(
                         "{} Internal error building link state: cannot get index of {}",
@@ -206,7 +233,7 @@ impl Network {
                     None
                 }
             })
-            .collect();
+            .unzip();
         LinkState {
             psid: idx.index().try_into().unwrap(),
             sn: self.graph[idx].sn,
@@ -222,6 +249,7 @@ impl Network {
                 self.graph[idx].locators.clone()
             },
             links,
+            link_weights: self.make_link_weights(idx, pids.into_iter()),
         }
     }
 
@@ -266,7 +294,15 @@ impl Network {
             hasher.write(self.graph[idx1].pid.as_slice());
             hasher.write(self.graph[idx2].pid.as_slice());
         }
-        let weight = 100.0 + ((hasher.finish() as u32) as f64) / u32::MAX as f64;
+        let weight = match (
+            self.graph[idx1].link_weights.get(&self.graph[idx2].pid),
+            self.graph[idx2].link_weights.get(&self.graph[idx1].pid),
+        ) {
+            (Some(weight1), Some(weight2)) => *weight1.max(weight2),
+            (Some(weight), None) | (None, Some(weight)) => *weight,
+            (None, None) => DEFAULT_LINK_WEIGHT,
+        };
+        let weight = weight as f64 + ((hasher.finish() as u32) as f64) / u32::MAX as f64;
         self.graph.update_edge(idx1, idx2, weight);
     }
 
@@ -308,6 +344,7 @@ impl Network {
                         link_state.locators,
                         link_state.sn,
                         link_state.links,
+                        link_state.link_weights,
                     ))
                 } else {
                     match src_link.get_pid(&link_state.psid) {
@@ -317,6 +354,7 @@ impl Network {
                             link_state.locators,
                             link_state.sn,
                             link_state.links,
+                            link_state.link_weights,
                         )),
                         None => {
                             log::error!(
@@ -335,11 +373,19 @@ impl Network {
         let src_link = self.get_link_from_pid(&src).unwrap();
         let link_states = link_states
             .into_iter()
-            .map(|(pid, wai, locs, sn, links)| {
+            .map(|(pid, wai, locs, sn, links, link_weights)| {
+                let mut weights = HashMap::new();
                 let links: Vec<PeerId> = links
                     .iter()
-                    .filter_map(|l| {
+                    .enumerate()
+                    .filter_map(|(i, l)| {
                         if let Some(pid) = src_link.get_pid(l) {
+                            match link_weights.as_ref().and_then(|w| w.get(i)) {
+                                Some(0) | None => (),
+                                Some(weight) => {
+                                    weights.insert(*pid, *weight);
+                                }
+                            }
                             Some(*pid)
                         } else {
                             log::error!(
@@ -352,7 +398,7 @@ impl Network {
                         }
                     })
                     .collect();
-                (pid, wai, locs, sn, links)
+                (pid, wai, locs, sn, links, weights)
             })
             .collect::<Vec<_>>();
 
@@ -374,14 +420,15 @@ impl Network {
         // Add nodes to graph & filter out up to date states
         let mut link_states = link_states
             .into_iter()
-            .filter_map(
-                |(pid, whatami, locators, sn, links)| match self.get_idx(&pid) {
+            .filter_map(|(pid, whatami, locators, sn, links, link_weights)| {
+                match self.get_idx(&pid) {
                     Some(idx) => {
                         let node = &mut self.graph[idx];
                         let oldsn = node.sn;
                         if oldsn < sn {
                             node.sn = sn;
                             node.links = links.clone();
+                            node.link_weights = link_weights;
                             if locators.is_some() {
                                 node.locators = locators;
                             }
@@ -401,13 +448,14 @@ impl Network {
                             locators,
                             sn,
                             links: links.clone(),
+                            link_weights,
                         };
                         log::debug!("{} Add node (state) {}", self.name, pid);
                         let idx = self.add_node(node);
                         Some((links, idx, true))
                     }
-                },
-            )
+                }
+            })
             .collect::<Vec<(Vec<PeerId>, NodeIndex, bool)>>();
 
         // Add/remove edges from graph
@@ -431,6 +479,7 @@ impl Network {
                         locators: None,
                         sn: 0,
                         links: vec![],
+                        link_weights: HashMap::new(),
                     };
                     log::debug!("{} Add node (reintroduced) {}", self.name, link.clone());
                     let idx = self.add_node(node);
@@ -550,11 +599,15 @@ impl Network {
                         locators: None,
                         sn: 0,
                         links: vec![],
+                        link_weights: HashMap::new(),
                     }),
                     true,
                 )
             }
         };
+        if let Some(weight) = self.link_weight {
+            self.graph[self.idx].link_weights.insert(pid, weight);
+        }
         if self.graph[idx].links.contains(&self.graph[self.idx].pid) {
             log::trace!("Update edge (link) {} {}", self.graph[self.idx].pid, pid);
             self.update_edge(self.idx, idx);
@@ -573,10 +626,31 @@ impl Network {
         free_index
     }
 
+    /// Sets the weight of the link with the given peer and advertises it.
+    /// Returns whether the routing graph changed.
+    pub(crate) fn set_link_weight(&mut self, pid: &PeerId, weight: ZInt) -> bool {
+        if !self.graph[self.idx].links.contains(pid)
+            || self.graph[self.idx].link_weights.insert(*pid, weight) == Some(weight)
+        {
+            return false;
+        }
+        log::debug!("{} Set weight {} to link {}", self.name, weight, pid);
+        if let Some(idx) = self.get_idx(pid) {
+            if self.graph[idx].links.contains(&self.graph[self.idx].pid) {
+                self.update_edge(self.idx, idx);
+            }
+        }
+        self.graph[self.idx].sn += 1;
+
+        self.send_on_links(vec![(self.idx, false)], |_| true);
+        true
+    }
+
     pub(crate) fn remove_link(&mut self, pid: &PeerId) -> Vec<(NodeIndex, Node)> {
         log::trace!("{} remove_link {}", self.name, pid);
         self.links.retain(|_, link| link.pid != *pid);
         self.graph[self.idx].links.retain(|link| *link != *pid);
+        self.graph[self.idx].link_weights.remove(pid);
 
         if let Some((edge, _)) = self
             .get_idx(pid)
@@ -602,6 +676,8 @@ impl Network {
                 whatami: self.graph[self.idx].whatami,
                 locators: Some(self.get_locators()),
                 links,
+                link_weights: self
+                    .make_link_weights(self.idx, self.links.values().map(|link| &link.pid)),
             }],
             None,
         );
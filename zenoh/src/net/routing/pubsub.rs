@@ -19,13 +19,15 @@ use std::sync::RwLock;
 use zenoh_core::zread;
 use zenoh_sync::get_mut_unchecked;
 
+use zenoh_config::AclMessage;
 use zenoh_protocol::io::ZBuf;
-use zenoh_protocol::proto::{DataInfo, RoutingContext};
+use zenoh_protocol::proto::{data_kind, DataInfo, RoutingContext};
 use zenoh_protocol_core::{
-    Channel, CongestionControl, KeyExpr, PeerId, Priority, Reliability, SubInfo, SubMode, WhatAmI,
-    ZInt,
+    key_expr, Channel, CongestionControl, KeyExpr, PeerId, Priority, Reliability, SubInfo, SubMode,
+    WhatAmI, ZInt,
 };
 
+use super::acl;
 use super::face::FaceState;
 use super::network::Network;
 use super::resource::{elect_router, PullCaches, Resource, Route, SessionContext};
@@ -907,6 +909,7 @@ pub(crate) fn compute_data_routes(tables: &mut Tables, res: &mut Arc<Resource>)
                 Some(compute_data_route(tables, res, "", None, WhatAmI::Client));
         }
         res_mut.context_mut().matching_pulls = compute_matching_pulls(tables, res, "");
+        update_matching_listeners(tables, res);
     }
 }
 
@@ -931,6 +934,93 @@ pub(crate) fn compute_matches_data_routes(tables: &mut Tables, res: &mut Arc<Res
     }
 }
 
+/// Called with the new number of subscribers matching a publication.
+/// Returns `false` once the notified listener is gone.
+pub(crate) type MatchingNotifier = Box<dyn Fn(usize) -> bool + Send + Sync>;
+
+/// A listener of the number of subscribers matching a publication of a face.
+pub(crate) struct MatchingListener {
+    pub(crate) face_id: usize,
+    pub(crate) expr: String,
+    pub(crate) matching_subscribers: usize,
+    pub(crate) notifier: MatchingNotifier,
+}
+
+/// Returns the number of distinct subscribers matching the given key expression:
+/// the remote routers and peers subscribed to it, plus the subscribed faces.
+pub(crate) fn matching_subscribers(tables: &Tables, expr: &str) -> usize {
+    let mut nodes = HashSet::new();
+    let mut faces = HashSet::new();
+    for mres in Resource::get_matches(tables, expr) {
+        let mres = mres.upgrade().unwrap();
+        if let Some(context) = mres.context.as_ref() {
+            nodes.extend(
+                context
+                    .router_subs
+                    .iter()
+                    .chain(context.peer_subs.iter())
+                    .filter(|pid| **pid != tables.pid)
+                    .cloned(),
+            );
+        }
+        faces.extend(
+            mres.session_ctxs
+                .values()
+                .filter(|ctx| ctx.subs.is_some())
+                .map(|ctx| ctx.face.id),
+        );
+    }
+    nodes.len() + faces.len()
+}
+
+pub(crate) fn declare_matching_listener(
+    tables: &mut Tables,
+    face: &Arc<FaceState>,
+    expr: &str,
+    notifier: MatchingNotifier,
+) -> usize {
+    let id = tables.matching_listener_counter;
+    tables.matching_listener_counter += 1;
+    let listener = MatchingListener {
+        face_id: face.id,
+        expr: expr.to_string(),
+        matching_subscribers: matching_subscribers(tables, expr),
+        notifier,
+    };
+    tables.matching_listeners.insert(id, listener);
+    id
+}
+
+pub(crate) fn undeclare_matching_listener(tables: &mut Tables, id: usize) {
+    tables.matching_listeners.remove(&id);
+}
+
+/// Notifies the listeners whose number of matching subscribers changed
+/// with the subscriptions of the given resource.
+fn update_matching_listeners(tables: &mut Tables, res: &Arc<Resource>) {
+    if tables.matching_listeners.is_empty() {
+        return;
+    }
+    let expr = res.expr();
+    let ids: Vec<usize> = tables
+        .matching_listeners
+        .iter()
+        .filter(|(_, listener)| key_expr::intersect(&listener.expr, &expr))
+        .map(|(id, _)| *id)
+        .collect();
+    for id in ids {
+        let count = matching_subscribers(tables, &tables.matching_listeners[&id].expr);
+        let listener = tables.matching_listeners.get_mut(&id).unwrap();
+        if count != listener.matching_subscribers {
+            listener.matching_subscribers = count;
+            // the notification fails if the listener was dropped without being undeclared
+            if !(listener.notifier)(count) {
+                tables.matching_listeners.remove(&id);
+            }
+        }
+    }
+}
+
 macro_rules! treat_timestamp {
     ($hlc:expr, $info:expr) => {
         // if an HLC was configured (via Config.add_timestamp),
@@ -1149,6 +1239,20 @@ pub fn route_data(
 
             if !(route.is_empty() && matching_pulls.is_empty()) {
                 let data_info = treat_timestamp!(&tables.hlc, info);
+                let route = match &tables.downsampling {
+                    Some(downsampling) => downsampling.filter(
+                        &tables.timer,
+                        route,
+                        face,
+                        &prefix,
+                        expr.suffix.as_ref(),
+                        &payload,
+                        channel,
+                        congestion_control,
+                        &data_info,
+                    ),
+                    None => route,
+                };
 
                 if route.len() == 1 && matching_pulls.len() == 0 {
                     send_to_first!(route, face, payload, channel, congestion_control, data_info);
@@ -1187,6 +1291,13 @@ pub fn full_reentrant_route_data(
     routing_context: Option<RoutingContext>,
 ) {
     let tables = zread!(tables_ref);
+    let message = match info.as_ref().and_then(|info| info.kind) {
+        Some(data_kind::DELETE) => AclMessage::Delete,
+        _ => AclMessage::Put,
+    };
+    if !acl::is_allowed(&tables, face, message, expr) {
+        return;
+    }
     match tables.get_mapping(face, &expr.scope).cloned() {
         Some(prefix) => {
             log::trace!(
@@ -1208,6 +1319,20 @@ pub fn full_reentrant_route_data(
 
             if !(route.is_empty() && matching_pulls.is_empty()) {
                 let data_info = treat_timestamp!(&tables.hlc, info);
+                let route = match &tables.downsampling {
+                    Some(downsampling) => downsampling.filter(
+                        &tables.timer,
+                        route,
+                        face,
+                        &prefix,
+                        expr.suffix.as_ref(),
+                        &payload,
+                        channel,
+                        congestion_control,
+                        &data_info,
+                    ),
+                    None => route,
+                };
 
                 if route.len() == 1 && matching_pulls.len() == 0 {
                     drop(tables);
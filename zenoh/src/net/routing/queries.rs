@@ -12,25 +12,28 @@
 //   ADLINK zenoh team, <zenoh@adlink-labs.tech>
 //
 use async_std::sync::Arc;
+use async_std::task;
 use async_trait::async_trait;
 use ordered_float::OrderedFloat;
 use petgraph::graph::NodeIndex;
 use std::borrow::Cow;
 use std::collections::HashMap;
 use std::sync::{RwLock, Weak};
-// use std::time::Instant;
-// use zenoh_collections::{Timed, TimedEvent};
-use zenoh_collections::Timed;
+use std::time::{Duration, Instant};
+use zenoh_collections::{Timed, TimedEvent};
+use zenoh_config::AclMessage;
 use zenoh_sync::get_mut_unchecked;
 
 use zenoh_protocol::io::ZBuf;
 use zenoh_protocol::proto::{DataInfo, RoutingContext};
 use zenoh_protocol_core::{
-    key_expr, queryable, ConsolidationStrategy, KeyExpr, PeerId, QueryTarget, QueryableInfo,
-    Target, WhatAmI, ZInt,
+    key_expr, queryable, ConsolidationMode, ConsolidationStrategy, KeyExpr, PeerId, QueryTarget,
+    QueryableInfo, Target, WhatAmI, ZInt,
 };
 
+use super::acl;
 use super::face::FaceState;
+use super::liveliness::update_liveliness_token;
 use super::network::Network;
 use super::resource::{
     elect_router, QueryRoute, Resource, SessionContext, TargetQabl, TargetQablSet,
@@ -40,6 +43,44 @@ use super::router::Tables;
 pub(crate) struct Query {
     src_face: Arc<FaceState>,
     src_qid: ZInt,
+    // The consolidation applied by this router on the replies
+    consolidation: ConsolidationMode,
+    // The newest reply received so far for each key expression
+    replies: HashMap<String, QueryReply>,
+}
+
+struct QueryReply {
+    replier_kind: ZInt,
+    replier_id: PeerId,
+    info: Option<DataInfo>,
+    payload: ZBuf,
+}
+
+impl Query {
+    // Returns whether the given reply is newer than the replies previously received for
+    // the same key expression, in which case it replaces them.
+    fn consolidate(&mut self, key_expr: &str, reply: &QueryReply) -> bool {
+        let timestamp = reply.info.as_ref().and_then(|i| i.timestamp.as_ref());
+        match self.replies.get(key_expr) {
+            Some(current) => timestamp > current.info.as_ref().and_then(|i| i.timestamp.as_ref()),
+            None => true,
+        }
+    }
+}
+
+// The consolidation to apply on the replies to a query received from the given face:
+// the last_router mode when the query comes from a client (including the local session)
+// and the first_routers mode otherwise.
+fn router_consolidation(
+    tables: &Tables,
+    face: &FaceState,
+    consolidation: &ConsolidationStrategy,
+) -> ConsolidationMode {
+    match (tables.whatami, face.whatami) {
+        (WhatAmI::Client, _) => ConsolidationMode::None,
+        (_, WhatAmI::Client) => consolidation.last_router,
+        _ => consolidation.first_routers,
+    }
 }
 
 #[cfg(feature = "complete_n")]
@@ -908,8 +949,8 @@ pub(crate) fn queries_remove_node(tables: &mut Tables, node: &PeerId, net_type:
         }
         WhatAmI::Peer => {
             let mut qabls = vec![];
-            for res in tables.router_qabls.iter() {
-                for (qabl, kind) in res.context().router_qabls.keys() {
+            for res in tables.peer_qabls.iter() {
+                for (qabl, kind) in res.context().peer_qabls.keys() {
                     if qabl == node {
                         qabls.push((res.clone(), *kind));
                     }
@@ -1192,6 +1233,7 @@ pub(crate) fn compute_query_routes(tables: &mut Tables, res: &mut Arc<Resource>)
             res_mut.context_mut().client_query_route =
                 Some(compute_query_route(tables, res, "", None, WhatAmI::Client));
         }
+        update_liveliness_token(tables, res);
     }
 }
 
@@ -1228,18 +1270,9 @@ fn compute_final_route(
             let mut route = HashMap::new();
             for qabl in qabls.iter() {
                 if qabl.direction.0.id != src_face.id && matching_kind(target.kind, qabl.kind) {
-                    #[cfg(feature = "complete_n")]
-                    {
-                        route
-                            .entry(qabl.direction.0.id)
-                            .or_insert_with(|| (qabl.direction.clone(), target.target.clone()));
-                    }
-                    #[cfg(not(feature = "complete_n"))]
-                    {
-                        route
-                            .entry(qabl.direction.0.id)
-                            .or_insert_with(|| qabl.direction.clone());
-                    }
+                    route
+                        .entry(qabl.direction.0.id)
+                        .or_insert_with(|| (qabl.direction.clone(), target.target.clone()));
                 }
             }
             route
@@ -1251,18 +1284,9 @@ fn compute_final_route(
                     && matching_kind(target.kind, qabl.kind)
                     && qabl.complete > 0
                 {
-                    #[cfg(feature = "complete_n")]
-                    {
-                        route
-                            .entry(qabl.direction.0.id)
-                            .or_insert_with(|| (qabl.direction.clone(), target.target.clone()));
-                    }
-                    #[cfg(not(feature = "complete_n"))]
-                    {
-                        route
-                            .entry(qabl.direction.0.id)
-                            .or_insert_with(|| qabl.direction.clone());
-                    }
+                    route
+                        .entry(qabl.direction.0.id)
+                        .or_insert_with(|| (qabl.direction.clone(), target.target.clone()));
                 }
             }
             route
@@ -1288,24 +1312,50 @@ fn compute_final_route(
             }
             route
         }
+        Target::Nearest(n) => {
+            // the queryables are sorted by distance: each face is asked for
+            // the number of nearest queryables that are reached through it
+            let mut route: QueryRoute = HashMap::new();
+            let mut remaining = *n;
+            for qabl in qabls.iter() {
+                if remaining == 0 {
+                    break;
+                }
+                if qabl.direction.0.id != src_face.id && matching_kind(target.kind, qabl.kind) {
+                    let (_, t) = route
+                        .entry(qabl.direction.0.id)
+                        .or_insert_with(|| (qabl.direction.clone(), Target::Nearest(0)));
+                    if let Target::Nearest(nb) = t {
+                        *nb += 1;
+                    }
+                    remaining -= 1;
+                }
+            }
+            // a face may lead to several queryables (e.g. a router declaring them
+            // all at once): the faces are also asked for the missing queryables,
+            // and the querier only keeps the replies of the first n repliers to answer,
+            // which may not be the nearest ones
+            if remaining > 0 {
+                for (_, t) in route.values_mut() {
+                    if let Target::Nearest(nb) = t {
+                        *nb += remaining;
+                    }
+                }
+            }
+            route
+        }
         Target::BestMatching => {
+            // the nearest complete queryable, or all the matching ones if none is complete
             if let Some(qabl) = qabls.iter().find(|qabl| {
                 qabl.direction.0.id != src_face.id
                     && qabl.complete > 0
                     && matching_kind(target.kind, qabl.kind)
             }) {
                 let mut route = HashMap::new();
-                #[cfg(feature = "complete_n")]
-                {
-                    route.insert(
-                        qabl.direction.0.id,
-                        (qabl.direction.clone(), target.target.clone()),
-                    );
-                }
-                #[cfg(not(feature = "complete_n"))]
-                {
-                    route.insert(qabl.direction.0.id, qabl.direction.clone());
-                }
+                route.insert(
+                    qabl.direction.0.id,
+                    (qabl.direction.clone(), target.target.clone()),
+                );
                 route
             } else {
                 compute_final_route(
@@ -1331,21 +1381,26 @@ struct QueryCleanup {
 #[async_trait]
 impl Timed for QueryCleanup {
     async fn run(&mut self) {
-        if let Some(mut face) = self.face.upgrade() {
-            let mut _tables = zwrite!(self.tables);
-            if let Some(query) = get_mut_unchecked(&mut face)
-                .pending_queries
-                .remove(&self.qid)
-            {
-                log::warn!(
-                    "Didn't receive final reply {}:{} from {}: Timeout!",
-                    query.src_face,
-                    self.qid,
-                    face
-                );
-                finalize_pending_query(&mut _tables, &query);
+        // the cleanup is spawned, so that the timer never waits for the routing tables lock,
+        // that may be held by a routing function adding a timed event
+        let cleanup = self.clone();
+        task::spawn(async move {
+            if let Some(mut face) = cleanup.face.upgrade() {
+                let mut tables = zwrite!(cleanup.tables);
+                if let Some(mut query) = get_mut_unchecked(&mut face)
+                    .pending_queries
+                    .remove(&cleanup.qid)
+                {
+                    log::warn!(
+                        "Didn't receive final reply {}:{} from {}: Timeout!",
+                        query.src_face,
+                        cleanup.qid,
+                        face
+                    );
+                    finalize_pending_query(&mut tables, &mut query);
+                }
             }
-        }
+        });
     }
 }
 
@@ -1358,9 +1413,15 @@ pub fn route_query(
     qid: ZInt,
     target: QueryTarget,
     consolidation: ConsolidationStrategy,
+    timeout: Option<Duration>,
     routing_context: Option<RoutingContext>,
 ) {
     let tables = zwrite!(tables_ref);
+    if !acl::is_allowed(&tables, face, AclMessage::Query, expr) {
+        log::debug!("Send final reply {}:{} (query denied)", face, qid);
+        face.primitives.clone().send_reply_final(qid);
+        return;
+    }
     match tables.get_mapping(face, &expr.scope) {
         Some(prefix) => {
             log::debug!(
@@ -1468,26 +1529,28 @@ pub fn route_query(
                 let query = Arc::new(Query {
                     src_face: face.clone(),
                     src_qid: qid,
+                    consolidation: router_consolidation(&tables, face, &consolidation),
+                    replies: HashMap::new(),
                 });
 
-                // let timer = tables.timer.clone();
-                // let timeout = tables.queries_default_timeout;
-                // drop(tables);
-                #[cfg(feature = "complete_n")]
+                // the query is finalized downstream if the upstream faces don't reply in time,
+                // and only the time remaining before this deadline is forwarded upstream
+                let deadline = Instant::now() + timeout.unwrap_or(tables.queries_default_timeout);
+
                 for ((outface, key_expr, context), t) in route.values() {
                     let mut outface = outface.clone();
                     let outface_mut = get_mut_unchecked(&mut outface);
                     outface_mut.next_qid += 1;
                     let qid = outface_mut.next_qid;
                     outface_mut.pending_queries.insert(qid, query.clone());
-                    // timer.add(TimedEvent::once(
-                    //     Instant::now() + timout,
-                    //     QueryCleanup {
-                    //         tables: tables_ref.clone(),
-                    //         face: Arc::downgrade(&outface),
-                    //         qid,
-                    //     },
-                    // ));
+                    tables.timer.add(TimedEvent::once(
+                        deadline,
+                        QueryCleanup {
+                            tables: tables_ref.clone(),
+                            face: Arc::downgrade(&outface),
+                            qid,
+                        },
+                    ));
 
                     log::trace!("Propagate query {}:{} to {}", query.src_face, qid, outface);
 
@@ -1500,34 +1563,7 @@ pub fn route_query(
                             target: t.clone(),
                         },
                         consolidation.clone(),
-                        *context,
-                    );
-                }
-
-                #[cfg(not(feature = "complete_n"))]
-                for (outface, key_expr, context) in route.values() {
-                    let mut outface = outface.clone();
-                    let outface_mut = get_mut_unchecked(&mut outface);
-                    outface_mut.next_qid += 1;
-                    let qid = outface_mut.next_qid;
-                    outface_mut.pending_queries.insert(qid, query.clone());
-                    // timer.add(TimedEvent::once(
-                    //     Instant::now() + timeout,
-                    //     QueryCleanup {
-                    //         tables: tables_ref.clone(),
-                    //         face: Arc::downgrade(&outface),
-                    //         qid,
-                    //     },
-                    // ));
-
-                    log::trace!("Propagate query {}:{} to {}", query.src_face, qid, outface);
-
-                    outface.primitives.send_query(
-                        key_expr,
-                        value_selector,
-                        qid,
-                        target.clone(),
-                        consolidation.clone(),
+                        Some(deadline.saturating_duration_since(Instant::now())),
                         *context,
                     );
                 }
@@ -1545,25 +1581,70 @@ pub fn route_query(
 
 #[allow(clippy::too_many_arguments)]
 pub(crate) fn route_send_reply_data(
-    _tables: &mut Tables,
+    tables: &mut Tables,
     face: &mut Arc<FaceState>,
     qid: ZInt,
     replier_kind: ZInt,
     replier_id: PeerId,
+    is_error: bool,
     key_expr: KeyExpr,
     info: Option<DataInfo>,
     payload: ZBuf,
 ) {
+    if !acl::is_allowed(tables, face, AclMessage::Reply, &key_expr) {
+        return;
+    }
     match face.pending_queries.get(&qid) {
         Some(query) => {
-            query.src_face.primitives.clone().send_reply_data(
-                query.src_qid,
+            // errors are not consolidated: each replier's error is reported
+            if query.consolidation == ConsolidationMode::None || is_error {
+                query.src_face.primitives.clone().send_reply_data(
+                    query.src_qid,
+                    replier_kind,
+                    replier_id,
+                    is_error,
+                    key_expr,
+                    info,
+                    payload,
+                );
+                return;
+            }
+
+            let name = match tables.get_mapping(face, &key_expr.scope) {
+                Some(prefix) => [&prefix.expr(), key_expr.suffix.as_ref()].concat(),
+                None => {
+                    log::error!(
+                        "Route reply {}:{} from {} with unknown scope {}!",
+                        query.src_face,
+                        qid,
+                        face,
+                        key_expr.scope
+                    );
+                    return;
+                }
+            };
+            let reply = QueryReply {
                 replier_kind,
                 replier_id,
-                key_expr,
                 info,
                 payload,
-            );
+            };
+            let mut query = query.clone();
+            let query = get_mut_unchecked(&mut query);
+            if query.consolidate(&name, &reply) {
+                if query.consolidation == ConsolidationMode::Lazy {
+                    query.src_face.primitives.clone().send_reply_data(
+                        query.src_qid,
+                        reply.replier_kind,
+                        reply.replier_id,
+                        false,
+                        KeyExpr::from(name.clone()),
+                        reply.info.clone(),
+                        reply.payload.clone(),
+                    );
+                }
+                query.replies.insert(name, reply);
+            }
         }
         None => log::warn!(
             "Route reply {}:{} from {}: Query nof found!",
@@ -1576,14 +1657,14 @@ pub(crate) fn route_send_reply_data(
 
 pub(crate) fn route_send_reply_final(_tables: &mut Tables, face: &mut Arc<FaceState>, qid: ZInt) {
     match get_mut_unchecked(face).pending_queries.remove(&qid) {
-        Some(query) => {
+        Some(mut query) => {
             log::debug!(
                 "Received final reply {}:{} from {}",
                 query.src_face,
                 qid,
                 face
             );
-            finalize_pending_query(_tables, &query);
+            finalize_pending_query(_tables, &mut query);
         }
         None => log::warn!(
             "Route final reply {}:{} from {}: Query nof found!",
@@ -1595,20 +1676,39 @@ pub(crate) fn route_send_reply_final(_tables: &mut Tables, face: &mut Arc<FaceSt
 }
 
 pub(crate) fn finalize_pending_queries(_tables: &mut Tables, face: &mut Arc<FaceState>) {
-    for query in face.pending_queries.values() {
+    let queries: Vec<Arc<Query>> = get_mut_unchecked(face)
+        .pending_queries
+        .drain()
+        .map(|(_, query)| query)
+        .collect();
+    for mut query in queries {
         log::debug!(
             "Finalize reply {}:{} for closing {}",
             query.src_face,
             query.src_qid,
             face
         );
-        finalize_pending_query(_tables, query);
+        finalize_pending_query(_tables, &mut query);
     }
-    get_mut_unchecked(face).pending_queries.clear();
 }
 
-pub(crate) fn finalize_pending_query(_tables: &mut Tables, query: &Arc<Query>) {
+pub(crate) fn finalize_pending_query(_tables: &mut Tables, query: &mut Arc<Query>) {
     if Arc::strong_count(query) == 1 {
+        let query = get_mut_unchecked(query);
+        // the fully consolidated replies are sent once all the upstream faces replied
+        if query.consolidation == ConsolidationMode::Full {
+            for (name, reply) in query.replies.drain() {
+                query.src_face.primitives.clone().send_reply_data(
+                    query.src_qid,
+                    reply.replier_kind,
+                    reply.replier_id,
+                    false,
+                    KeyExpr::from(name),
+                    reply.info,
+                    reply.payload,
+                );
+            }
+        }
         log::debug!("Propagate final reply {}:{}", query.src_face, query.src_qid);
         query
             .src_face
@@ -25,10 +25,8 @@ use zenoh_sync::get_mut_unchecked;
 
 pub(super) type Direction = (Arc<FaceState>, KeyExpr<'static>, Option<RoutingContext>);
 pub(super) type Route = HashMap<usize, Direction>;
-#[cfg(feature = "complete_n")]
+// The faces to forward a query to, with the target that each of them should apply
 pub(super) type QueryRoute = HashMap<usize, (Direction, zenoh_protocol_core::Target)>;
-#[cfg(not(feature = "complete_n"))]
-pub(super) type QueryRoute = Route;
 pub(super) struct TargetQabl {
     pub(super) direction: Direction,
     pub(super) kind: ZInt,
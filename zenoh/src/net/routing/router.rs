@@ -11,7 +11,10 @@
 // Contributors:
 //   ADLINK zenoh team, <zenoh@adlink-labs.tech>
 //
+use super::acl::{AccessControl, Subject};
+use super::downsampling::Downsampling;
 use super::face::{Face, FaceState};
+use super::liveliness::{liveliness_notifier, LivelinessNotification};
 use super::network::{shared_nodes, Network};
 pub use super::pubsub::*;
 pub use super::queries::*;
@@ -19,11 +22,13 @@ pub use super::resource::*;
 use super::runtime::Runtime;
 use async_std::sync::{Arc, Weak};
 use async_std::task::JoinHandle;
+use flume::Sender;
 use std::any::Any;
 use std::collections::{HashMap, HashSet};
 use std::sync::{Mutex, RwLock};
 use std::time::Duration;
 use uhlc::HLC;
+use zenoh_collections::Timer;
 use zenoh_link::Link;
 use zenoh_protocol::proto::{ZenohBody, ZenohMessage};
 use zenoh_protocol_core::{PeerId, WhatAmI, ZInt};
@@ -43,8 +48,8 @@ pub struct Tables {
     face_counter: usize,
     #[allow(dead_code)]
     pub(crate) hlc: Option<Arc<HLC>>,
-    // pub(crate) timer: Timer,
-    // pub(crate) queries_default_timeout: Duration,
+    pub(crate) timer: Timer,
+    pub(crate) queries_default_timeout: Duration,
     pub(crate) root_res: Arc<Resource>,
     pub(crate) faces: HashMap<usize, Arc<FaceState>>,
     pub(crate) pull_caches_lock: Mutex<()>,
@@ -52,11 +57,17 @@ pub struct Tables {
     pub(crate) peer_subs: HashSet<Arc<Resource>>,
     pub(crate) router_qabls: HashSet<Arc<Resource>>,
     pub(crate) peer_qabls: HashSet<Arc<Resource>>,
+    pub(crate) liveliness_tokens: HashSet<Arc<Resource>>,
+    pub(crate) liveliness_notifier: Sender<LivelinessNotification>,
+    pub(crate) matching_listener_counter: usize,
+    pub(crate) matching_listeners: HashMap<usize, MatchingListener>,
     pub(crate) routers_net: Option<Network>,
     pub(crate) peers_net: Option<Network>,
     pub(crate) shared_nodes: Vec<PeerId>,
     pub(crate) routers_trees_task: Option<JoinHandle<()>>,
     pub(crate) peers_trees_task: Option<JoinHandle<()>>,
+    pub(crate) access_control: Option<AccessControl>,
+    pub(crate) downsampling: Option<Downsampling>,
 }
 
 impl Tables {
@@ -64,15 +75,15 @@ impl Tables {
         pid: PeerId,
         whatami: WhatAmI,
         hlc: Option<Arc<HLC>>,
-        _queries_default_timeout: Duration,
+        queries_default_timeout: Duration,
     ) -> Self {
         Tables {
             pid,
             whatami,
             face_counter: 0,
             hlc,
-            // timer: Timer::new(true),
-            // queries_default_timeout,
+            timer: Timer::new(true),
+            queries_default_timeout,
             root_res: Resource::root(),
             faces: HashMap::new(),
             pull_caches_lock: Mutex::new(()),
@@ -80,11 +91,17 @@ impl Tables {
             peer_subs: HashSet::new(),
             router_qabls: HashSet::new(),
             peer_qabls: HashSet::new(),
+            liveliness_tokens: HashSet::new(),
+            liveliness_notifier: liveliness_notifier(),
+            matching_listener_counter: 0,
+            matching_listeners: HashMap::new(),
             routers_net: None,
             peers_net: None,
             shared_nodes: vec![],
             routers_trees_task: None,
             peers_trees_task: None,
+            access_control: None,
+            downsampling: None,
         }
     }
 
@@ -130,13 +147,16 @@ impl Tables {
         whatami: WhatAmI,
         primitives: Arc<dyn Primitives + Send + Sync>,
         link_id: usize,
+        subject: Option<Subject>,
     ) -> Weak<FaceState> {
         let fid = self.face_counter;
         self.face_counter += 1;
         let mut newface = self
             .faces
             .entry(fid)
-            .or_insert_with(|| FaceState::new(fid, pid, whatami, primitives.clone(), link_id))
+            .or_insert_with(|| {
+                FaceState::new(fid, pid, whatami, primitives.clone(), link_id, subject)
+            })
             .clone();
         log::debug!("New {}", newface);
 
@@ -152,7 +172,7 @@ impl Tables {
         whatami: WhatAmI,
         primitives: Arc<dyn Primitives + Send + Sync>,
     ) -> Weak<FaceState> {
-        self.open_net_face(pid, whatami, primitives, 0)
+        self.open_net_face(pid, whatami, primitives, 0, None)
     }
 
     pub fn close_face(&mut self, face: &Weak<FaceState>) {
@@ -183,6 +203,11 @@ impl Tables {
                     undeclare_client_queryable(self, &mut face_clone, &mut res, kind);
                     Resource::clean(&mut res);
                 }
+                self.matching_listeners
+                    .retain(|_, listener| listener.face_id != face.id);
+                if let Some(downsampling) = &self.downsampling {
+                    downsampling.remove_peer(&face.pid);
+                }
                 self.faces.remove(&face.id);
             }
             None => log::error!("Face already closed!"),
@@ -274,6 +299,7 @@ impl Router {
         runtime: Runtime,
         peers_autoconnect: bool,
         routers_autoconnect_gossip: bool,
+        link_weight: Option<ZInt>,
     ) {
         let mut tables = zwrite!(self.tables);
         tables.peers_net = Some(Network::new(
@@ -282,6 +308,7 @@ impl Router {
             runtime.clone(),
             peers_autoconnect,
             routers_autoconnect_gossip,
+            link_weight,
         ));
         if runtime.whatami == WhatAmI::Router {
             tables.routers_net = Some(Network::new(
@@ -290,6 +317,7 @@ impl Router {
                 runtime,
                 peers_autoconnect,
                 routers_autoconnect_gossip,
+                link_weight,
             ));
             tables.shared_nodes = shared_nodes(
                 tables.routers_net.as_ref().unwrap(),
@@ -342,6 +370,7 @@ impl Router {
             );
         }
 
+        let subject = Subject::new(&transport);
         let handler = Arc::new(LinkStateInterceptor::new(
             transport.clone(),
             self.tables.clone(),
@@ -353,6 +382,7 @@ impl Router {
                         whatami,
                         Arc::new(Mux::new(transport)),
                         link_id,
+                        Some(subject),
                     )
                     .upgrade()
                     .unwrap(),
@@ -372,6 +402,37 @@ impl Router {
         }
         Ok(handler)
     }
+
+    pub fn set_link_weight(&self, transport: &TransportUnicast, weight: ZInt) -> ZResult<()> {
+        let pid = transport.get_pid()?;
+        let whatami = transport.get_whatami()?;
+        let mut tables = zwrite!(self.tables);
+        let net_type = match (self.whatami, whatami) {
+            (WhatAmI::Router, WhatAmI::Router) => WhatAmI::Router,
+            (WhatAmI::Router, WhatAmI::Peer)
+            | (WhatAmI::Peer, WhatAmI::Router)
+            | (WhatAmI::Peer, WhatAmI::Peer) => WhatAmI::Peer,
+            _ => return Ok(()),
+        };
+        let changed = match net_type {
+            WhatAmI::Router => tables.routers_net.as_mut(),
+            _ => tables.peers_net.as_mut(),
+        }
+        .map(|net| net.set_link_weight(&pid, weight))
+        .unwrap_or(false);
+        if changed {
+            tables.schedule_compute_trees(self.tables.clone(), net_type);
+        }
+        Ok(())
+    }
+
+    pub(crate) fn set_access_control(&self, access_control: Option<AccessControl>) {
+        zwrite!(self.tables).access_control = access_control;
+    }
+
+    pub(crate) fn set_downsampling(&self, downsampling: Option<Downsampling>) {
+        zwrite!(self.tables).downsampling = downsampling;
+    }
 }
 
 pub struct LinkStateInterceptor {
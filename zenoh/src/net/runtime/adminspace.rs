@@ -21,6 +21,7 @@ use log::{error, trace};
 use serde_json::json;
 use std::collections::HashMap;
 use std::sync::Mutex;
+use std::time::Duration;
 use zenoh_buffers::{SplitBuffer, ZBuf};
 use zenoh_protocol::proto::{data_kind, DataInfo, RoutingContext};
 use zenoh_protocol_core::{
@@ -349,6 +350,7 @@ impl Primitives for AdminSpace {
         qid: ZInt,
         target: QueryTarget,
         _consolidation: ConsolidationStrategy,
+        _timeout: Option<Duration>,
         _routing_context: Option<RoutingContext>,
     ) {
         trace!(
@@ -393,6 +395,7 @@ impl Primitives for AdminSpace {
                         qid,
                         EVAL,
                         pid,
+                        false,
                         key.into(),
                         Some(data_info),
                         payload,
@@ -412,6 +415,7 @@ impl Primitives for AdminSpace {
                             qid,
                             EVAL,
                             pid,
+                            false,
                             key.into(),
                             Some(data_info),
                             payload.into(),
@@ -431,15 +435,17 @@ impl Primitives for AdminSpace {
         qid: ZInt,
         replier_kind: ZInt,
         replier_id: PeerId,
+        is_error: bool,
         key_expr: KeyExpr,
         info: Option<DataInfo>,
         payload: ZBuf,
     ) {
         trace!(
-            "recv ReplyData {:?} {:?} {:?} {:?} {:?} {:?}",
+            "recv ReplyData {:?} {:?} {:?} {:?} {:?} {:?} {:?}",
             qid,
             replier_kind,
             replier_id,
+            is_error,
             key_expr,
             info,
             payload
@@ -509,6 +515,10 @@ pub async fn router_data(
                 |_| Vec::new(),
                 |links| links.iter().map(|link| link.dst.to_string()).collect()
             ),
+            "auth": transport.get_auth_ids().map_or_else(
+                |_| Vec::new(),
+                |ids| ids.iter().map(|id| id.to_string()).collect()
+            ),
         });
         #[cfg(feature = "stats")]
         {
@@ -534,12 +544,34 @@ pub async fn router_data(
         .map(transport_to_json)
         .collect();
 
+    // downsampling info
+    let downsampling: Vec<serde_json::Value> = zread!(context.runtime.router.tables)
+        .downsampling
+        .as_ref()
+        .map_or_else(Vec::new, |downsampling| {
+            downsampling
+                .dropped()
+                .into_iter()
+                .map(|(key_exprs, dropped)| {
+                    let dropped: serde_json::Map<String, serde_json::Value> = dropped
+                        .into_iter()
+                        .map(|(pid, count)| (pid.to_string(), json!(count)))
+                        .collect();
+                    json!({
+                        "key_exprs": key_exprs,
+                        "dropped": dropped,
+                    })
+                })
+                .collect()
+        });
+
     let json = json!({
         "pid": context.pid_str,
         "version": context.version,
         "locators": locators,
         "sessions": transports,
         "plugins": plugins,
+        "downsampling": downsampling,
     });
     log::trace!("AdminSpace router_data: {:?}", json);
     (
@@ -15,6 +15,8 @@ mod adminspace;
 pub mod orchestrator;
 
 use super::routing;
+use super::routing::acl::AccessControl;
+use super::routing::downsampling::Downsampling;
 use super::routing::pubsub::full_reentrant_route_data;
 use super::routing::router::{LinkStateInterceptor, Router};
 use crate::config::{Config, Notifier};
@@ -101,12 +103,22 @@ impl Runtime {
                 .unwrap()
         });
 
+        let link_weight = match config.routing().link_weight() {
+            Some(0) => bail!("Invalid link weight 0: the link weights must be greater than 0"),
+            weight => *weight,
+        };
+
+        let access_control = AccessControl::from_config(config.access_control())?;
+        let downsampling = Downsampling::from_config(config.downsampling())?;
+
         let router = Arc::new(Router::new(
             pid,
             whatami,
             hlc.clone(),
             Duration::from_millis(queries_default_timeout),
         ));
+        router.set_access_control(access_control);
+        router.set_downsampling(downsampling);
 
         let handler = Arc::new(RuntimeTransportEventHandler {
             runtime: std::sync::RwLock::new(None),
@@ -138,6 +150,7 @@ impl Runtime {
                 runtime.clone(),
                 peers_autoconnect,
                 routers_autoconnect_gossip,
+                link_weight,
             );
         }
 
@@ -147,10 +160,15 @@ impl Runtime {
             async move {
                 let mut stream = receiver.into_stream();
                 while let Some(event) = stream.next().await {
-                    if &*event == "peers" {
+                    let key = event.strip_prefix('/').unwrap_or(&event);
+                    if key == "peers" {
                         if let Err(e) = runtime2.update_peers().await {
                             log::error!("Error updating peers : {}", e);
                         }
+                    } else if key.starts_with("access_control") {
+                        runtime2.update_access_control();
+                    } else if key.starts_with("downsampling") {
+                        runtime2.update_downsampling();
                     }
                 }
             }
@@ -162,6 +180,27 @@ impl Runtime {
         }
     }
 
+    fn update_access_control(&self) {
+        let access_control = AccessControl::from_config(self.config.lock().access_control());
+        match access_control {
+            Ok(access_control) => self.router.set_access_control(access_control),
+            Err(e) => log::error!(
+                "Error updating access control, keeping the previous rules : {}",
+                e
+            ),
+        }
+    }
+
+    fn update_downsampling(&self) {
+        match Downsampling::from_config(self.config.lock().downsampling()) {
+            Ok(downsampling) => self.router.set_downsampling(downsampling),
+            Err(e) => log::error!(
+                "Error updating downsampling, keeping the previous rules : {}",
+                e
+            ),
+        }
+    }
+
     #[inline(always)]
     pub fn manager(&self) -> &TransportManager {
         &self.manager
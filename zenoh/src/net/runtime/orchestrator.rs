@@ -11,6 +11,7 @@
 // Contributors:
 //   ADLINK zenoh team, <zenoh@adlink-labs.tech>
 //
+use super::routing::network::DEFAULT_LINK_WEIGHT;
 use super::{Runtime, RuntimeSession};
 use async_std::net::UdpSocket;
 use futures::prelude::*;
@@ -27,7 +28,7 @@ use zenoh_link::Locator;
 use zenoh_protocol::io::{WBuf, ZBuf};
 use zenoh_protocol::proto::{Hello, Scout, TransportBody, TransportMessage};
 use zenoh_protocol::proto::{MessageReader, MessageWriter};
-use zenoh_protocol_core::{whatami::WhatAmIMatcher, PeerId, WhatAmI};
+use zenoh_protocol_core::{whatami::WhatAmIMatcher, PeerId, WhatAmI, ZInt};
 use zenoh_transport::TransportUnicast;
 
 const RCV_BUF_SIZE: usize = 65536;
@@ -40,6 +41,8 @@ const CONNECTION_RETRY_MAX_PERIOD: u64 = 4000; //ms
 const CONNECTION_RETRY_PERIOD_INCREASE_FACTOR: u64 = 2;
 const ROUTER_DEFAULT_LISTENER: &str = "tcp/0.0.0.0:7447";
 const PEER_DEFAULT_LISTENER: &str = "tcp/0.0.0.0:0";
+const LINK_RTT_SAMPLES: usize = 3;
+const LINK_RTT_TIMEOUT: u64 = 1000; //ms
 
 pub enum Loop {
     Continue,
@@ -472,6 +475,7 @@ impl Runtime {
             let endpoint = peer.clone();
             if let Ok(transport) = self.manager().open_transport(endpoint).await {
                 log::debug!("Successfully connected to configured peer {}", peer);
+                self.set_link_weight(&peer, &transport).await;
                 if let Some(orch_transport) = transport
                     .get_callback()
                     .unwrap()
@@ -496,6 +500,74 @@ impl Runtime {
         }
     }
 
+    // Sets the weight of a newly opened link from the endpoint configuration or,
+    // if enabled, from its round-trip time scaled so that DEFAULT_LINK_WEIGHT stands for 1ms
+    async fn set_link_weight(&self, endpoint: &EndPoint, transport: &TransportUnicast) {
+        let weight = match endpoint
+            .config
+            .as_ref()
+            .and_then(|c| c.get(ZN_LINK_WEIGHT_STR))
+        {
+            Some(weight) => match weight.parse::<ZInt>() {
+                Ok(weight) if weight > 0 => Some(weight),
+                _ => {
+                    log::warn!("Invalid link weight {} for endpoint {}", weight, endpoint);
+                    None
+                }
+            },
+            None => {
+                let link_weight_rtt = self
+                    .config
+                    .lock()
+                    .routing()
+                    .link_weight_rtt()
+                    .unwrap_or(false);
+                if link_weight_rtt {
+                    self.link_rtt(endpoint, transport)
+                        .await
+                        .map(|rtt| (DEFAULT_LINK_WEIGHT * rtt.as_micros() as ZInt / 1000).max(1))
+                } else {
+                    None
+                }
+            }
+        };
+        if let Some(weight) = weight {
+            if let Err(e) = self.router.set_link_weight(transport, weight) {
+                log::warn!(
+                    "Unable to set the weight of the link to {} : {}",
+                    endpoint,
+                    e
+                );
+            }
+        }
+    }
+
+    // Returns the lowest of a few round-trip times measured on the link, if the peer answers
+    async fn link_rtt(
+        &self,
+        endpoint: &EndPoint,
+        transport: &TransportUnicast,
+    ) -> Option<Duration> {
+        let mut rtt: Option<Duration> = None;
+        for _ in 0..LINK_RTT_SAMPLES {
+            match transport
+                .get_rtt(Duration::from_millis(LINK_RTT_TIMEOUT))
+                .await
+            {
+                Ok(sample) => rtt = Some(rtt.map_or(sample, |rtt| rtt.min(sample))),
+                Err(e) => {
+                    log::debug!(
+                        "Unable to measure the RTT of the link to {} : {}",
+                        endpoint,
+                        e
+                    );
+                    break;
+                }
+            }
+        }
+        rtt
+    }
+
     pub async fn scout<Fut, F>(
         sockets: &[UdpSocket],
         matcher: WhatAmIMatcher,
@@ -579,9 +651,12 @@ impl Runtime {
 
     async fn connect(&self, locators: &[Locator]) -> Option<TransportUnicast> {
         for locator in locators {
-            let endpoint = locator.clone().into();
-            match self.manager().open_transport(endpoint).await {
-                Ok(transport) => return Some(transport),
+            let endpoint: EndPoint = locator.clone().into();
+            match self.manager().open_transport(endpoint.clone()).await {
+                Ok(transport) => {
+                    self.set_link_weight(&endpoint, &transport).await;
+                    return Some(transport);
+                }
                 Err(e) => log::trace!("Failed to connect to {} : {}", locator, e),
             }
         }
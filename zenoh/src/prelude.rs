@@ -35,8 +35,9 @@ use regex::Regex;
 use std::borrow::Cow;
 use std::convert::TryFrom;
 use std::fmt;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use zenoh_buffers::SplitBuffer;
-use zenoh_core::bail;
+use zenoh_core::{bail, zerror};
 pub use zenoh_protocol::io::{WBufCodec, ZBufCodec};
 use zenoh_protocol::proto::DataInfo;
 pub use zenoh_protocol::proto::{MessageReader, MessageWriter};
@@ -523,6 +524,95 @@ pub const PROP_STARTTIME: &str = "starttime";
 /// The "stoptime" property key for time-range selection
 pub const PROP_STOPTIME: &str = "stoptime";
 
+/// A time range, as specified with the `"starttime"` and `"stoptime"` properties
+/// of a [`ValueSelector`]. Both bounds are inclusive and `None` means unbounded.
+///
+/// # Examples
+/// ```
+/// use std::convert::TryInto;
+/// use std::time::{Duration, SystemTime, UNIX_EPOCH};
+/// use zenoh::prelude::*;
+///
+/// let value_selector: ValueSelector = "?(starttime=0;stoptime=now()-10s)".try_into().unwrap();
+/// let time_range = value_selector.time_range().unwrap().unwrap();
+/// assert_eq!(time_range.start, Some(UNIX_EPOCH));
+/// assert!(time_range.contains(SystemTime::now() - Duration::from_secs(60)));
+/// assert!(!time_range.contains(SystemTime::now()));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeRange {
+    /// The lower bound of this time range, if any.
+    pub start: Option<SystemTime>,
+    /// The upper bound of this time range, if any.
+    pub stop: Option<SystemTime>,
+}
+
+impl TimeRange {
+    /// Returns true if the given time is within this time range.
+    pub fn contains(&self, time: SystemTime) -> bool {
+        self.start.iter().all(|start| *start <= time) && self.stop.iter().all(|stop| time <= *stop)
+    }
+
+    /// Returns true if the time of the given [`Timestamp`] is within this time range.
+    pub fn contains_timestamp(&self, timestamp: &Timestamp) -> bool {
+        self.contains(timestamp.get_time().to_system_time())
+    }
+}
+
+/// Parses a time expression, as used in the `"starttime"` and `"stoptime"` properties.
+///
+/// The supported forms are:
+///  - `now()`, optionally followed by `+` or `-` and a duration (e.g. `now()-10s`, `now()-1h 30m`)
+///  - a number of seconds since the UNIX epoch (e.g. `0` or `1634567890.5`)
+///  - an RFC3339 date (e.g. `2021-10-18T09:00:00Z`)
+///
+/// # Examples
+/// ```
+/// use std::time::{Duration, UNIX_EPOCH};
+/// use zenoh::prelude::*;
+///
+/// assert_eq!(parse_time_expr("10.5").unwrap(), UNIX_EPOCH + Duration::from_millis(10500));
+/// assert_eq!(parse_time_expr("1970-01-01T00:01:00Z").unwrap(), UNIX_EPOCH + Duration::from_secs(60));
+/// assert!(parse_time_expr("now()-10s").is_ok());
+/// assert!(parse_time_expr("yesterday").is_err());
+/// ```
+pub fn parse_time_expr(s: &str) -> crate::Result<SystemTime> {
+    let s = s.trim();
+    if let Some(offset) = s.strip_prefix("now()") {
+        let now = SystemTime::now();
+        let offset = offset.trim();
+        let parse_duration = |d: &str| {
+            humantime::parse_duration(d.trim())
+                .map_err(|e| zerror!("Invalid duration in time expression '{}': {}", s, e))
+        };
+        if offset.is_empty() {
+            Ok(now)
+        } else if let Some(d) = offset.strip_prefix('+') {
+            Ok(now + parse_duration(d)?)
+        } else if let Some(d) = offset.strip_prefix('-') {
+            now.checked_sub(parse_duration(d)?)
+                .ok_or_else(|| zerror!("Time expression '{}' is out of range", s).into())
+        } else {
+            bail!(
+                "Invalid time expression '{}': expected '+' or '-' after 'now()'",
+                s
+            )
+        }
+    } else if let Ok(secs) = s.parse::<f64>() {
+        if secs.is_finite() && secs >= 0.0 {
+            Ok(UNIX_EPOCH + Duration::from_secs_f64(secs))
+        } else {
+            bail!(
+                "Invalid time expression '{}': negative or non-finite time",
+                s
+            )
+        }
+    } else {
+        humantime::parse_rfc3339_weak(s)
+            .map_err(|e| zerror!("Invalid time expression '{}': {}", s, e).into())
+    }
+}
+
 #[derive(Clone, Debug, PartialEq)]
 /// An expression identifying a selection of resources.
 ///
@@ -752,6 +842,27 @@ impl<'a> ValueSelector<'a> {
     pub fn has_time_range(&self) -> bool {
         self.properties.contains_key(PROP_STARTTIME) || self.properties.contains_key(PROP_STOPTIME)
     }
+
+    /// Returns the time range specified in the properties of this `ValueSelector`
+    /// (i.e. using `"starttime"` and/or `"stoptime"`), or `None` if it doesn't specify any.
+    ///
+    /// See [`parse_time_expr`] for the supported time expressions.
+    pub fn time_range(&self) -> crate::Result<Option<TimeRange>> {
+        if !self.has_time_range() {
+            return Ok(None);
+        }
+        let start = self
+            .properties
+            .get(PROP_STARTTIME)
+            .map(|s| parse_time_expr(s))
+            .transpose()?;
+        let stop = self
+            .properties
+            .get(PROP_STOPTIME)
+            .map(|s| parse_time_expr(s))
+            .transpose()?;
+        Ok(Some(TimeRange { start, stop }))
+    }
 }
 
 impl fmt::Display for ValueSelector<'_> {
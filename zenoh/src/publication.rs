@@ -17,12 +17,16 @@
 use crate::net::transport::Primitives;
 use crate::prelude::*;
 use crate::subscriber::Reliability;
+use crate::sync::channel::Receiver;
 use crate::Encoding;
 use crate::Session;
+use flume::r#async::RecvFut;
+use flume::{Iter, RecvError, RecvTimeoutError, TryIter, TryRecvError};
+use std::fmt;
 use zenoh_core::zread;
 use zenoh_protocol::proto::{data_kind, DataInfo, Options};
 use zenoh_protocol_core::Channel;
-use zenoh_sync::{derive_zfuture, Runnable};
+use zenoh_sync::{derive_zfuture, zreceiver, Runnable};
 
 /// The kind of congestion control.
 pub use zenoh_protocol_core::CongestionControl;
@@ -236,3 +240,96 @@ where
         Poll::Ready(Ok(()))
     }
 }
+
+/// The matching status of a publication, i.e. the number of subscribers matching it.
+///
+/// It's derived from the subscriptions known by the routing tables of the session: the remote
+/// routers and peers, or the clients and local subscribers, subscribed to a matching key expression.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MatchingStatus {
+    pub matching_subscribers: usize,
+}
+
+impl MatchingStatus {
+    /// Returns `true` if at least one subscriber matches the publication.
+    #[inline]
+    pub fn is_matching(&self) -> bool {
+        self.matching_subscribers > 0
+    }
+}
+
+zreceiver! {
+    /// A listener of the changes of the [`MatchingStatus`] of a publication,
+    /// returned by [`matching_listener`](Session::matching_listener).
+    ///
+    /// `MatchingListener` implements the `Stream` trait as well as the
+    /// [`Receiver`](crate::prelude::Receiver) trait which allows to access the matching statuses.
+    ///
+    /// Matching listeners are automatically closed when dropped.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # async_std::task::block_on(async {
+    /// use futures::prelude::*;
+    /// use zenoh::prelude::*;
+    ///
+    /// let session = zenoh::open(config::peer()).await.unwrap();
+    /// session.declare_publication("/key/expression").await.unwrap();
+    /// let mut listener = session.matching_listener("/key/expression").await.unwrap();
+    /// while let Some(status) = listener.next().await {
+    ///     if status.is_matching() {
+    ///         println!(">> Publication has matching subscribers");
+    ///     } else {
+    ///         println!(">> Publication has NO MORE matching subscribers");
+    ///     }
+    /// }
+    /// # })
+    /// ```
+    pub struct MatchingListener<'a> : Receiver<MatchingStatus> {
+        pub(crate) session: &'a Session,
+        pub(crate) id: usize,
+        pub(crate) alive: bool,
+    }
+}
+
+impl MatchingListener<'_> {
+    /// Close a [`MatchingListener`] previously created with
+    /// [`matching_listener`](Session::matching_listener).
+    ///
+    /// Matching listeners are automatically closed when dropped, but you may want to use this
+    /// function to handle errors or close the listener asynchronously.
+    ///
+    /// # Examples
+    /// ```
+    /// # async_std::task::block_on(async {
+    /// use zenoh::prelude::*;
+    ///
+    /// let session = zenoh::open(config::peer()).await.unwrap();
+    /// session.declare_publication("/key/expression").await.unwrap();
+    /// let listener = session.matching_listener("/key/expression").await.unwrap();
+    /// listener.close().await.unwrap();
+    /// # })
+    /// ```
+    #[inline]
+    #[must_use = "ZFutures do nothing unless you `.wait()`, `.await` or poll them"]
+    pub fn close(mut self) -> impl ZFuture<Output = zenoh_core::Result<()>> {
+        self.alive = false;
+        self.session.close_matching_listener(self.id)
+    }
+}
+
+impl Drop for MatchingListener<'_> {
+    fn drop(&mut self) {
+        if self.alive {
+            let _ = self.session.close_matching_listener(self.id).wait();
+        }
+    }
+}
+
+impl fmt::Debug for MatchingListener<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("MatchingListener")
+            .field("id", &self.id)
+            .finish()
+    }
+}
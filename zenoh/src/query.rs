@@ -19,12 +19,14 @@ use crate::prelude::*;
 use crate::sync::channel::Receiver;
 use crate::Session;
 use crate::API_REPLY_RECEPTION_CHANNEL_SIZE;
+use async_std::task;
 use flume::r#async::RecvFut;
 use flume::{bounded, Iter, RecvError, RecvTimeoutError, Sender, TryIter, TryRecvError};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::pin::Pin;
 use std::sync::atomic::Ordering;
 use std::task::{Context, Poll};
+use std::time::Duration;
 use zenoh_sync::{derive_zfuture, zreceiver, Runnable};
 
 /// The [`Queryable`](crate::queryable::Queryable)s that should be target of a [`get`](Session::get).
@@ -116,8 +118,9 @@ impl Default for QueryConsolidation {
 /// Structs returned by a [`get`](Session::get).
 #[derive(Clone, Debug)]
 pub struct Reply {
-    /// The [`Sample`] for this Reply.
-    pub data: Sample,
+    /// The result of this Reply: the replied [`Sample`],
+    /// or the error [`Value`] reported by the replier.
+    pub sample: Result<Sample, Value>,
     /// The kind of [`Queryable`](crate::queryable::Queryable) that answered this Reply.
     pub replier_kind: ZInt,
     /// The id of the zenoh instance that answered this Reply.
@@ -130,6 +133,38 @@ pub(crate) struct QueryState {
     pub(crate) reception_mode: ConsolidationMode,
     pub(crate) replies: Option<HashMap<String, Reply>>,
     pub(crate) rep_sender: Sender<Reply>,
+    // the maximum number of repliers of a query targeting the nearest queryables,
+    // the first ones to answer
+    pub(crate) max_repliers: Option<usize>,
+    pub(crate) repliers: HashSet<PeerId>,
+}
+
+impl QueryState {
+    /// Returns whether the replies of `replier_id` are accepted. The routers may reach more than
+    /// n queryables for a `Target::Nearest(n)`: only the replies of the first n repliers are kept,
+    /// in order of arrival and not of routing distance.
+    pub(crate) fn accept_replier(&mut self, replier_id: &PeerId) -> bool {
+        match self.max_repliers {
+            Some(max_repliers) if !self.repliers.contains(replier_id) => {
+                if self.repliers.len() >= max_repliers {
+                    return false;
+                }
+                self.repliers.insert(*replier_id);
+                true
+            }
+            _ => true,
+        }
+    }
+
+    /// Sends the consolidated replies (if any). The reception of the replies ends
+    /// when the `QueryState` is dropped.
+    pub(crate) fn finalize(self) {
+        if self.reception_mode == ConsolidationMode::Full {
+            for (_, reply) in self.replies.unwrap().into_iter() {
+                let _ = self.rep_sender.send(reply);
+            }
+        }
+    }
 }
 
 zreceiver! {
@@ -153,7 +188,10 @@ zreceiver! {
     ///
     /// let mut replies = session.get("/key/expression").wait().unwrap();
     /// while let Ok(reply) = replies.recv() {
-    ///     println!(">> Received {:?}", reply.data);
+    ///     match reply.sample {
+    ///         Ok(sample) => println!(">> Received {:?}", sample),
+    ///         Err(value) => println!(">> Received error {:?}", value),
+    ///     }
     /// }
     /// ```
     ///
@@ -166,7 +204,10 @@ zreceiver! {
     ///
     /// let mut replies = session.get("/key/expression").await.unwrap();
     /// while let Some(reply) = replies.next().await {
-    ///     println!(">> Received {:?}", reply.data);
+    ///     match reply.sample {
+    ///         Ok(sample) => println!(">> Received {:?}", sample),
+    ///         Err(value) => println!(">> Received error {:?}", value),
+    ///     }
     /// }
     /// # })
     /// ```
@@ -193,6 +234,7 @@ derive_zfuture! {
     ///     .get("/key/expression?value>1")
     ///     .target(QueryTarget{ kind: queryable::ALL_KINDS, target: Target::All })
     ///     .consolidation(QueryConsolidation::none())
+    ///     .timeout(std::time::Duration::from_secs(5))
     ///     .await
     ///     .unwrap();
     /// # })
@@ -204,6 +246,7 @@ derive_zfuture! {
         pub(crate) target: Option<QueryTarget>,
         pub(crate) consolidation: Option<QueryConsolidation>,
         pub(crate) local_routing: Option<bool>,
+        pub(crate) timeout: Option<Duration>,
     }
 }
 
@@ -228,6 +271,16 @@ impl<'a, 'b> Getter<'a, 'b> {
         self.local_routing = Some(local_routing);
         self
     }
+
+    /// Change the timeout of the query: the replies received after the timeout are dropped.
+    ///
+    /// The timeout is carried by the query, so that the routers time it out as well.
+    /// If not set, the routers apply their `queries_default_timeout`.
+    #[inline]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
 }
 
 impl Runnable for Getter<'_, '_> {
@@ -235,10 +288,11 @@ impl Runnable for Getter<'_, '_> {
 
     fn run(&mut self) -> Self::Output {
         log::trace!(
-            "get({}, {:?}, {:?})",
+            "get({}, {:?}, {:?}, {:?})",
             self.selector,
             self.target,
-            self.consolidation
+            self.consolidation,
+            self.timeout
         );
         let mut state = zwrite!(self.session.state);
         let target = self.target.take().unwrap();
@@ -268,6 +322,11 @@ impl Runnable for Getter<'_, '_> {
                     None
                 },
                 rep_sender,
+                max_repliers: match target.target {
+                    Target::Nearest(n) => Some(n as usize),
+                    _ => None,
+                },
+                repliers: HashSet::new(),
             },
         );
 
@@ -280,6 +339,7 @@ impl Runnable for Getter<'_, '_> {
             qid,
             target.clone(),
             consolidation.clone(),
+            self.timeout,
             None,
         );
         if local_routing {
@@ -292,6 +352,13 @@ impl Runnable for Getter<'_, '_> {
                 consolidation,
             );
         }
+        if let Some(timeout) = self.timeout {
+            let session = self.session.clone();
+            task::spawn(async move {
+                task::sleep(timeout).await;
+                session.timeout_query(qid);
+            });
+        }
 
         Ok(ReplyReceiver::new(rep_receiver))
     }
@@ -85,6 +85,18 @@ impl Query {
     pub async fn reply_async(&'_ self, msg: Sample) {
         self.replies_sender.send_async(msg).await;
     }
+
+    /// Sends an error reply to this Query, for the queryable to report that it failed to reply.
+    #[inline(always)]
+    pub fn reply_err(&'_ self, value: Value) {
+        self.replies_sender.send_err(value);
+    }
+
+    /// Sends an error reply to this Query asynchronously.
+    #[inline(always)]
+    pub async fn reply_err_async(&'_ self, value: Value) {
+        self.replies_sender.send_err_async(value).await;
+    }
 }
 
 impl fmt::Debug for Query {
@@ -269,14 +281,22 @@ impl fmt::Debug for Queryable<'_> {
 #[derive(Clone)]
 pub struct RepliesSender {
     pub(crate) kind: ZInt,
-    pub(crate) sender: Sender<(ZInt, Sample)>,
+    pub(crate) sender: Sender<(ZInt, Result<Sample, Value>)>,
 }
 
 impl RepliesSender {
     #[inline(always)]
     /// Send a reply.
     pub fn send(&'_ self, msg: Sample) {
-        if let Err(e) = self.sender.send((self.kind, msg)) {
+        if let Err(e) = self.sender.send((self.kind, Ok(msg))) {
+            log::error!("Error sending reply: {}", e);
+        }
+    }
+
+    #[inline(always)]
+    /// Send an error reply.
+    pub fn send_err(&'_ self, value: Value) {
+        if let Err(e) = self.sender.send((self.kind, Err(value))) {
             log::error!("Error sending reply: {}", e);
         }
     }
@@ -284,10 +304,13 @@ impl RepliesSender {
     /// Attempt to send a reply. If the channel is full, an error is returned.
     #[inline(always)]
     pub fn try_send(&self, msg: Sample) -> core::result::Result<(), TrySendError<Sample>> {
-        match self.sender.try_send((self.kind, msg)) {
+        match self.sender.try_send((self.kind, Ok(msg))) {
             Ok(()) => Ok(()),
-            Err(TrySendError::Full(sample)) => Err(TrySendError::Full(sample.1)),
-            Err(TrySendError::Disconnected(sample)) => Err(TrySendError::Disconnected(sample.1)),
+            Err(TrySendError::Full((_, Ok(sample)))) => Err(TrySendError::Full(sample)),
+            Err(TrySendError::Disconnected((_, Ok(sample)))) => {
+                Err(TrySendError::Disconnected(sample))
+            }
+            Err(_) => unreachable!(),
         }
     }
 
@@ -319,7 +342,16 @@ impl RepliesSender {
     /// Asynchronously send a reply. If the channel is full, the returned future
     /// will yield to the async runtime.
     pub async fn send_async(&self, msg: Sample) {
-        if let Err(e) = self.sender.send_async((self.kind, msg)).await {
+        if let Err(e) = self.sender.send_async((self.kind, Ok(msg))).await {
+            log::error!("Error sending reply: {}", e);
+        }
+    }
+
+    #[inline(always)]
+    /// Asynchronously send an error reply. If the channel is full, the returned future
+    /// will yield to the async runtime.
+    pub async fn send_err_async(&self, value: Value) {
+        if let Err(e) = self.sender.send_async((self.kind, Err(value))).await {
             log::error!("Error sending reply: {}", e);
         }
     }
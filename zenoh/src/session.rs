@@ -15,7 +15,11 @@ use crate::config::Config;
 use crate::config::Notifier;
 use crate::data_kind;
 use crate::info::*;
+use crate::liveliness::Liveliness;
 use crate::net::routing::face::Face;
+use crate::net::routing::pubsub::{
+    declare_matching_listener, matching_subscribers, undeclare_matching_listener,
+};
 use crate::net::runtime::Runtime;
 use crate::net::transport::Primitives;
 use crate::prelude::EntityFactory;
@@ -24,6 +28,7 @@ use crate::query::*;
 use crate::queryable::*;
 use crate::subscriber::*;
 use crate::sync::zready;
+use crate::Encoding;
 use crate::Id;
 use crate::Priority;
 use crate::Sample;
@@ -709,6 +714,116 @@ impl Session {
         }))
     }
 
+    /// Get the [`MatchingStatus`] of a publication previously declared
+    /// with [`declare_publication`](Session::declare_publication).
+    ///
+    /// # Arguments
+    ///
+    /// * `key_expr` - The key expression of the publication
+    ///
+    /// # Examples
+    /// ```
+    /// # async_std::task::block_on(async {
+    /// use zenoh::prelude::*;
+    ///
+    /// let session = zenoh::open(config::peer()).await.unwrap();
+    /// session.declare_publication("/key/expression").await.unwrap();
+    /// if session.matching_status("/key/expression").await.unwrap().is_matching() {
+    ///     session.put("/key/expression", "value").await.unwrap();
+    /// }
+    /// # })
+    /// ```
+    pub fn matching_status<'a, IntoKeyExpr>(
+        &self,
+        key_expr: IntoKeyExpr,
+    ) -> impl ZFuture<Output = ZResult<MatchingStatus>>
+    where
+        IntoKeyExpr: Into<KeyExpr<'a>>,
+    {
+        let key_expr = key_expr.into();
+        trace!("matching_status({:?})", key_expr);
+        zready(
+            self.publication_face(&key_expr)
+                .map(|(face, key_expr_str)| {
+                    let tables = zread!(face.tables);
+                    MatchingStatus {
+                        matching_subscribers: matching_subscribers(&tables, &key_expr_str),
+                    }
+                }),
+        )
+    }
+
+    /// Create a [`MatchingListener`] notified of the changes of the [`MatchingStatus`]
+    /// of a publication previously declared with [`declare_publication`](Session::declare_publication).
+    ///
+    /// # Arguments
+    ///
+    /// * `key_expr` - The key expression of the publication
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # async_std::task::block_on(async {
+    /// use futures::prelude::*;
+    /// use zenoh::prelude::*;
+    ///
+    /// let session = zenoh::open(config::peer()).await.unwrap();
+    /// session.declare_publication("/key/expression").await.unwrap();
+    /// let mut listener = session.matching_listener("/key/expression").await.unwrap();
+    /// while let Some(status) = listener.next().await {
+    ///     println!(">> {} matching subscribers", status.matching_subscribers);
+    /// }
+    /// # })
+    /// ```
+    pub fn matching_listener<'a, IntoKeyExpr>(
+        &self,
+        key_expr: IntoKeyExpr,
+    ) -> impl ZFuture<Output = ZResult<MatchingListener<'_>>>
+    where
+        IntoKeyExpr: Into<KeyExpr<'a>>,
+    {
+        let key_expr = key_expr.into();
+        trace!("matching_listener({:?})", key_expr);
+        zready(
+            self.publication_face(&key_expr)
+                .map(|(face, key_expr_str)| {
+                    let (sender, receiver) = flume::unbounded();
+                    let notifier = Box::new(move |matching_subscribers| {
+                        sender
+                            .send(MatchingStatus {
+                                matching_subscribers,
+                            })
+                            .is_ok()
+                    });
+                    let mut tables = zwrite!(face.tables);
+                    let id = declare_matching_listener(
+                        &mut tables,
+                        &face.state,
+                        &key_expr_str,
+                        notifier,
+                    );
+                    MatchingListener::new(self, id, true, receiver)
+                }),
+        )
+    }
+
+    pub(crate) fn close_matching_listener(&self, id: usize) -> impl ZFuture<Output = ZResult<()>> {
+        trace!("close_matching_listener({})", id);
+        let face = zread!(self.state).primitives.as_ref().unwrap().clone();
+        undeclare_matching_listener(&mut *zwrite!(face.tables), id);
+        zready(Ok(()))
+    }
+
+    // Returns the face of the session in the routing tables and the expression
+    // of the given key expression, if a publication was declared for it.
+    fn publication_face(&self, key_expr: &KeyExpr) -> ZResult<(Arc<Face>, String)> {
+        let state = zread!(self.state);
+        let key_expr_str = state.localkey_to_expr(key_expr)?;
+        if !state.publications.contains(&key_expr_str) {
+            bail!("Unable to find publication {}", key_expr_str)
+        }
+        Ok((state.primitives.as_ref().unwrap().clone(), key_expr_str))
+    }
+
     pub(crate) fn declare_any_subscriber(
         &self,
         key_expr: &KeyExpr,
@@ -1261,7 +1376,10 @@ impl Session {
     /// let session = zenoh::open(config::peer()).await.unwrap();
     /// let mut replies = session.get("/key/expression").await.unwrap();
     /// while let Some(reply) = replies.next().await {
-    ///     println!(">> Received {:?}", reply.data);
+    ///     match reply.sample {
+    ///         Ok(sample) => println!(">> Received {:?}", sample),
+    ///         Err(value) => println!(">> Received error {:?}", value),
+    ///     }
     /// }
     /// # })
     /// ```
@@ -1276,9 +1394,26 @@ impl Session {
             target: Some(QueryTarget::default()),
             consolidation: Some(QueryConsolidation::default()),
             local_routing: None,
+            timeout: None,
         }
     }
 
+    /// Obtain a [`Liveliness`] struct tied to this Zenoh [`Session`], to declare liveliness tokens
+    /// and observe the tokens declared by the other applications.
+    ///
+    /// # Examples
+    /// ```
+    /// # async_std::task::block_on(async {
+    /// use zenoh::prelude::*;
+    ///
+    /// let session = zenoh::open(config::peer()).await.unwrap();
+    /// let token = session.liveliness().declare_token("/my/app").await.unwrap();
+    /// # })
+    /// ```
+    pub fn liveliness(&self) -> Liveliness<'_> {
+        Liveliness { session: self }
+    }
+
     pub(crate) fn handle_query(
         &self,
         local: bool,
@@ -1343,18 +1478,21 @@ impl Session {
             });
         }
         drop(rep_sender); // all senders need to be dropped for the channel to close
+        let query_key_expr = KeyExpr::from(key_expr);
 
         // router is not re-entrant
 
         if local {
             let this = self.clone();
             task::spawn(async move {
-                while let Some((replier_kind, sample)) = rep_receiver.stream().next().await {
-                    let (key_expr, payload, data_info) = sample.split();
+                while let Some((replier_kind, reply)) = rep_receiver.stream().next().await {
+                    let (is_error, (key_expr, payload, data_info)) =
+                        split_reply(&query_key_expr, reply);
                     this.send_reply_data(
                         qid,
                         replier_kind,
                         pid,
+                        is_error,
                         key_expr,
                         Some(data_info),
                         payload,
@@ -1364,12 +1502,14 @@ impl Session {
             });
         } else {
             task::spawn(async move {
-                while let Some((replier_kind, sample)) = rep_receiver.stream().next().await {
-                    let (key_expr, payload, data_info) = sample.split();
+                while let Some((replier_kind, reply)) = rep_receiver.stream().next().await {
+                    let (is_error, (key_expr, payload, data_info)) =
+                        split_reply(&query_key_expr, reply);
                     primitives.send_reply_data(
                         qid,
                         replier_kind,
                         pid,
+                        is_error,
                         key_expr,
                         Some(data_info),
                         payload,
@@ -1380,6 +1520,13 @@ impl Session {
         }
     }
 
+    pub(crate) fn timeout_query(&self, qid: ZInt) {
+        if let Some(query) = zwrite!(self.state).queries.remove(&qid) {
+            warn!("Didn't receive final reply for query {}: Timeout!", qid);
+            query.finalize();
+        }
+    }
+
     pub fn key_expr_to_expr(&self, key_expr: &KeyExpr) -> ZResult<String> {
         let state = zread!(self.state);
         state.remotekey_to_expr(key_expr)
@@ -1550,6 +1697,7 @@ impl Primitives for Session {
         qid: ZInt,
         target: QueryTarget,
         consolidation: ConsolidationStrategy,
+        _timeout: Option<Duration>,
         _routing_context: Option<RoutingContext>,
     ) {
         trace!(
@@ -1567,15 +1715,17 @@ impl Primitives for Session {
         qid: ZInt,
         replier_kind: ZInt,
         replier_id: PeerId,
+        is_error: bool,
         key_expr: KeyExpr,
         data_info: Option<DataInfo>,
         payload: ZBuf,
     ) {
         trace!(
-            "recv ReplyData {:?} {:?} {:?} {:?} {:?} {:?}",
+            "recv ReplyData {:?} {:?} {:?} {:?} {:?} {:?} {:?}",
             qid,
             replier_kind,
             replier_id,
+            is_error,
             key_expr,
             data_info,
             payload
@@ -1590,11 +1740,35 @@ impl Primitives for Session {
         };
         match state.queries.get_mut(&qid) {
             Some(query) => {
+                if !query.accept_replier(&replier_id) {
+                    trace!(
+                        "Drop ReplyData of {} for query {}: too many repliers",
+                        replier_id,
+                        qid
+                    );
+                    return;
+                }
+                if is_error {
+                    // errors are not consolidated: each replier's error is reported
+                    let value = Value {
+                        payload,
+                        encoding: data_info
+                            .and_then(|info| info.encoding)
+                            .unwrap_or(Encoding::APP_OCTET_STREAM),
+                    };
+                    let _ = query.rep_sender.send(Reply {
+                        sample: Err(value),
+                        replier_kind,
+                        replier_id,
+                    });
+                    return;
+                }
                 let new_reply = Reply {
-                    data: Sample::with_info(key_expr.into(), payload, data_info),
+                    sample: Ok(Sample::with_info(key_expr.into(), payload, data_info)),
                     replier_kind,
                     replier_id,
                 };
+                let new_sample = new_reply.sample.as_ref().unwrap();
                 match query.reception_mode {
                     ConsolidationMode::None => {
                         let _ = query.rep_sender.send(new_reply);
@@ -1604,14 +1778,17 @@ impl Primitives for Session {
                             .replies
                             .as_ref()
                             .unwrap()
-                            .get(new_reply.data.key_expr.as_str())
+                            .get(new_sample.key_expr.as_str())
                         {
                             Some(reply) => {
-                                if new_reply.data.timestamp > reply.data.timestamp {
-                                    query.replies.as_mut().unwrap().insert(
-                                        new_reply.data.key_expr.to_string(),
-                                        new_reply.clone(),
-                                    );
+                                if new_sample.timestamp
+                                    > reply.sample.as_ref().ok().and_then(|s| s.timestamp)
+                                {
+                                    query
+                                        .replies
+                                        .as_mut()
+                                        .unwrap()
+                                        .insert(new_sample.key_expr.to_string(), new_reply.clone());
                                     let _ = query.rep_sender.send(new_reply);
                                 }
                             }
@@ -1620,7 +1797,7 @@ impl Primitives for Session {
                                     .replies
                                     .as_mut()
                                     .unwrap()
-                                    .insert(new_reply.data.key_expr.to_string(), new_reply.clone());
+                                    .insert(new_sample.key_expr.to_string(), new_reply.clone());
                                 let _ = query.rep_sender.send(new_reply);
                             }
                         }
@@ -1630,14 +1807,17 @@ impl Primitives for Session {
                             .replies
                             .as_ref()
                             .unwrap()
-                            .get(new_reply.data.key_expr.as_str())
+                            .get(new_sample.key_expr.as_str())
                         {
                             Some(reply) => {
-                                if new_reply.data.timestamp > reply.data.timestamp {
-                                    query.replies.as_mut().unwrap().insert(
-                                        new_reply.data.key_expr.to_string(),
-                                        new_reply.clone(),
-                                    );
+                                if new_sample.timestamp
+                                    > reply.sample.as_ref().ok().and_then(|s| s.timestamp)
+                                {
+                                    query
+                                        .replies
+                                        .as_mut()
+                                        .unwrap()
+                                        .insert(new_sample.key_expr.to_string(), new_reply.clone());
                                 }
                             }
                             None => {
@@ -1645,7 +1825,7 @@ impl Primitives for Session {
                                     .replies
                                     .as_mut()
                                     .unwrap()
-                                    .insert(new_reply.data.key_expr.to_string(), new_reply.clone());
+                                    .insert(new_sample.key_expr.to_string(), new_reply.clone());
                             }
                         };
                     }
@@ -1665,11 +1845,7 @@ impl Primitives for Session {
                 query.nb_final -= 1;
                 if query.nb_final == 0 {
                     let query = state.queries.remove(&qid).unwrap();
-                    if query.reception_mode == ConsolidationMode::Full {
-                        for (_, reply) in query.replies.unwrap().into_iter() {
-                            let _ = query.rep_sender.send(reply);
-                        }
-                    }
+                    query.finalize();
                     trace!("Close query {}", qid);
                 }
             }
@@ -1713,3 +1889,19 @@ impl fmt::Debug for Session {
         write!(f, "Session{{...}}")
     }
 }
+
+// Splits a reply of a queryable into the parts of a reply message. An error reply is sent
+// with the key expression of the query, and flagged as an error.
+fn split_reply(
+    query_key_expr: &KeyExpr<'static>,
+    reply: Result<Sample, Value>,
+) -> (bool, (KeyExpr<'static>, ZBuf, DataInfo)) {
+    match reply {
+        Ok(sample) => (false, sample.split()),
+        Err(value) => {
+            let mut data_info = DataInfo::new();
+            data_info.encoding = Some(value.encoding);
+            (true, (query_key_expr.clone(), value.payload, data_info))
+        }
+    }
+}
@@ -0,0 +1,122 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ADLINK zenoh team, <zenoh@adlink-labs.tech>
+//
+use async_std::prelude::FutureExt;
+use async_std::task;
+use futures::StreamExt;
+use zenoh::subscriber::Subscriber;
+use zenoh::Session;
+
+mod common;
+use common::*;
+
+async fn open_router(listen: &str, access_control: &str) -> Session {
+    let mut config = router_config(&[listen], &[]);
+    config
+        .insert_json5("access_control", access_control)
+        .unwrap();
+    open(config).await
+}
+
+// Puts on both keys and returns the keys of the received samples.
+async fn put_both(publisher: &Session, subscriber: &mut Subscriber<'_>) -> Vec<String> {
+    publisher.put("/test/acl/a", "a").await.unwrap();
+    publisher.put("/test/acl/b", "b").await.unwrap();
+    // the last put is always allowed and marks the end of the exchange
+    publisher.put("/test/acl/end", "end").await.unwrap();
+    let mut keys = vec![];
+    loop {
+        let sample = subscriber
+            .receiver()
+            .next()
+            .timeout(TIMEOUT)
+            .await
+            .unwrap()
+            .unwrap();
+        if sample.key_expr.as_str() == "/test/acl/end" {
+            return keys;
+        }
+        keys.push(sample.key_expr.as_str().to_string());
+    }
+}
+
+#[test]
+fn access_control() {
+    task::block_on(async {
+        let endpoint = "tcp/127.0.0.1:47480";
+        let router = open_router(
+            endpoint,
+            r#"{
+                enabled: true,
+                default_permission: "allow",
+                rules: [{ permission: "deny", messages: ["put"], key_exprs: ["/test/acl/b"] }],
+            }"#,
+        )
+        .await;
+        let publisher = open_client(endpoint).await;
+        let subscriber_session = open_client(endpoint).await;
+        let mut subscriber = subscriber_session.subscribe("/test/acl/**").await.unwrap();
+        task::sleep(SLEEP).await;
+
+        assert_eq!(
+            put_both(&publisher, &mut subscriber).await,
+            vec!["/test/acl/a"]
+        );
+
+        // the rules are reloaded when the configuration changes
+        router
+            .config()
+            .await
+            .insert_json5(
+                "access_control/rules",
+                r#"[{ permission: "deny", messages: ["put"], key_exprs: ["/test/acl/a"] }]"#,
+            )
+            .unwrap();
+        task::sleep(SLEEP).await;
+        assert_eq!(
+            put_both(&publisher, &mut subscriber).await,
+            vec!["/test/acl/b"]
+        );
+
+        // the declarations are also controlled
+        router
+            .config()
+            .await
+            .insert_json5(
+                "access_control/rules",
+                r#"[{ permission: "deny", messages: ["declare_subscriber"], key_exprs: ["/test/acl/**"] }]"#,
+            )
+            .unwrap();
+        task::sleep(SLEEP).await;
+        let other_session = open_client(endpoint).await;
+        let mut other_subscriber = other_session.subscribe("/test/acl/a").await.unwrap();
+        task::sleep(SLEEP).await;
+        assert_eq!(
+            put_both(&publisher, &mut subscriber).await,
+            vec!["/test/acl/a", "/test/acl/b"]
+        );
+        assert!(other_subscriber
+            .receiver()
+            .next()
+            .timeout(SLEEP)
+            .await
+            .is_err());
+
+        drop(other_subscriber);
+        drop(subscriber);
+        for session in [other_session, subscriber_session, publisher] {
+            session.close().await.unwrap();
+        }
+        router.close().await.unwrap();
+    });
+}
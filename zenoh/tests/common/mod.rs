@@ -0,0 +1,108 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ADLINK zenoh team, <zenoh@adlink-labs.tech>
+//
+//! The helpers shared by the integration tests. Each test only uses some of them.
+#![allow(dead_code)]
+use async_std::prelude::FutureExt;
+use async_std::task;
+use futures::StreamExt;
+use std::time::Duration;
+use zenoh::config::Config;
+use zenoh::net::protocol::io::SplitBuffer;
+use zenoh::prelude::*;
+use zenoh::query::{QueryConsolidation, QueryTarget, Target};
+use zenoh::queryable::{self, Queryable};
+use zenoh::scouting::WhatAmI;
+use zenoh::Session;
+
+pub const TIMEOUT: Duration = Duration::from_secs(10);
+pub const SLEEP: Duration = Duration::from_secs(1);
+
+/// Returns the configuration of a peer without multicast scouting.
+pub fn peer_config(listen: &[&str], connect: &[&str]) -> Config {
+    let mut config = config::peer();
+    config.listen.endpoints = listen.iter().map(|e| e.parse().unwrap()).collect();
+    config.connect.endpoints = connect.iter().map(|e| e.parse().unwrap()).collect();
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    config
+}
+
+pub async fn open(config: Config) -> Session {
+    zenoh::open(config).timeout(TIMEOUT).await.unwrap().unwrap()
+}
+
+/// Opens two peers, the second one connecting to the first one on `endpoint`.
+pub async fn open_peers(endpoint: &str) -> (Session, Session) {
+    let peer01 = open(peer_config(&[endpoint], &[])).await;
+    let peer02 = open(peer_config(&[], &[endpoint])).await;
+    (peer01, peer02)
+}
+
+/// Returns the configuration of a router without multicast scouting.
+pub fn router_config(listen: &[&str], connect: &[&str]) -> Config {
+    let mut config = config::default();
+    config.set_mode(Some(WhatAmI::Router)).unwrap();
+    config.listen.endpoints = listen.iter().map(|e| e.parse().unwrap()).collect();
+    config.connect.endpoints = connect.iter().map(|e| e.parse().unwrap()).collect();
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    config
+}
+
+pub async fn open_router(listen: &str, connect: &[&str]) -> Session {
+    open(router_config(&[listen], connect)).await
+}
+
+pub async fn open_client(router: &str) -> Session {
+    let config = config::client(vec![router.parse::<config::EndPoint>().unwrap()]);
+    open(config).await
+}
+
+/// Declares a queryable on `key_expr` replying `value` to every query.
+pub async fn declare_queryable<'a>(
+    session: &'a Session,
+    key_expr: &str,
+    value: &'static str,
+) -> Queryable<'a> {
+    let mut queryable = session.queryable(key_expr).await.unwrap();
+    let mut queries = queryable.receiver().clone();
+    task::spawn(async move {
+        while let Some(query) = queries.next().await {
+            let sample = Sample::new(query.key_selector().to_owned(), value);
+            query.reply_async(sample).await;
+        }
+    });
+    queryable
+}
+
+/// Returns the sorted values replied to a query on `selector` with the given target.
+pub async fn get_values(session: &Session, selector: &str, target: Target) -> Vec<String> {
+    let mut values: Vec<String> = session
+        .get(selector)
+        .target(QueryTarget {
+            kind: queryable::ALL_KINDS,
+            target,
+        })
+        .consolidation(QueryConsolidation::none())
+        .await
+        .unwrap()
+        .map(|reply| {
+            let payload = reply.sample.unwrap().value.payload;
+            String::from_utf8(payload.contiguous().to_vec()).unwrap()
+        })
+        .collect()
+        .timeout(TIMEOUT)
+        .await
+        .unwrap();
+    values.sort();
+    values
+}
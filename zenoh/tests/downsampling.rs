@@ -0,0 +1,133 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ADLINK zenoh team, <zenoh@adlink-labs.tech>
+//
+use async_std::prelude::FutureExt;
+use async_std::task;
+use futures::StreamExt;
+use zenoh::net::protocol::io::SplitBuffer;
+use zenoh::prelude::*;
+use zenoh::subscriber::Subscriber;
+use zenoh::Session;
+
+mod common;
+use common::*;
+
+const MSG_COUNT: usize = 100;
+
+async fn open_router(listen: &str, downsampling: &str) -> Session {
+    let mut config = router_config(&[listen], &[]);
+    config.insert_json5("downsampling", downsampling).unwrap();
+    open(config).await
+}
+
+// Publishes MSG_COUNT samples on `key` and returns the values received until the subscriber stays idle.
+async fn publish(publisher: &Session, subscriber: &mut Subscriber<'_>, key: &str) -> Vec<String> {
+    for i in 0..MSG_COUNT {
+        publisher.put(key, format!("{:0100}", i)).await.unwrap();
+    }
+    let mut values = vec![];
+    while let Ok(Some(sample)) = subscriber.receiver().next().timeout(SLEEP).await {
+        assert_eq!(sample.key_expr.as_str(), key);
+        let payload = sample.value.payload.contiguous().to_vec();
+        values.push(String::from_utf8(payload).unwrap());
+    }
+    values
+}
+
+#[test]
+fn downsampling() {
+    task::block_on(async {
+        let endpoint = "tcp/127.0.0.1:47481";
+        let router = open_router(
+            endpoint,
+            r#"[
+                { key_exprs: ["/test/downsampling/rate"], max_rate: 10 },
+                { key_exprs: ["/test/downsampling/bytes"], max_byte_rate: 1000 },
+            ]"#,
+        )
+        .await;
+        let publisher = open_client(endpoint).await;
+        let subscriber_session = open_client(endpoint).await;
+        let mut subscriber = subscriber_session
+            .subscribe("/test/downsampling/*")
+            .await
+            .unwrap();
+        task::sleep(SLEEP).await;
+
+        // the latest sample of each period is kept
+        let values = publish(&publisher, &mut subscriber, "/test/downsampling/rate").await;
+        assert!(values.len() < MSG_COUNT / 2);
+        assert_eq!(values.last().unwrap(), &format!("{:0100}", MSG_COUNT - 1));
+
+        // the samples exceeding the byte rate are dropped
+        let values = publish(&publisher, &mut subscriber, "/test/downsampling/bytes").await;
+        assert!(!values.is_empty());
+        assert!(values.len() < MSG_COUNT / 2);
+
+        // the rules are reloaded when the configuration changes
+        router
+            .config()
+            .await
+            .insert_json5("downsampling", "[]")
+            .unwrap();
+        task::sleep(SLEEP).await;
+        let values = publish(&publisher, &mut subscriber, "/test/downsampling/rate").await;
+        assert_eq!(values.len(), MSG_COUNT);
+
+        drop(subscriber);
+        for session in [subscriber_session, publisher] {
+            session.close().await.unwrap();
+        }
+        router.close().await.unwrap();
+    });
+}
+
+#[test]
+fn downsampling_closed_face() {
+    task::block_on(async {
+        let endpoint = "tcp/127.0.0.1:47482";
+        // at most one sample every 10 seconds
+        let router = open_router(
+            endpoint,
+            r#"[{ key_exprs: ["/test/downsampling/rate"], max_rate: 0.1 }]"#,
+        )
+        .await;
+        let publisher = open_client(endpoint).await;
+        let open_subscriber = || async {
+            let mut config = config::client(vec![endpoint.parse::<config::EndPoint>().unwrap()]);
+            config.set_id(Some("a1b2c3d4".to_string())).unwrap();
+            open(config).await
+        };
+
+        for _ in 0..2 {
+            let subscriber_session = open_subscriber().await;
+            let mut subscriber = subscriber_session
+                .subscribe("/test/downsampling/rate")
+                .await
+                .unwrap();
+            task::sleep(SLEEP).await;
+
+            // the downsampling state of a remote session is dropped with its face,
+            // so that the first sample is sent right away to the reconnected session
+            let values = publish(&publisher, &mut subscriber, "/test/downsampling/rate").await;
+            assert_eq!(values.first().unwrap(), &format!("{:0100}", 0));
+
+            drop(subscriber);
+            subscriber_session.close().await.unwrap();
+            task::sleep(SLEEP).await;
+        }
+
+        publisher.close().await.unwrap();
+        router.close().await.unwrap();
+    });
+}
@@ -0,0 +1,70 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ADLINK zenoh team, <zenoh@adlink-labs.tech>
+//
+use async_std::task;
+use zenoh::query::Target;
+use zenoh::Session;
+
+mod common;
+use common::*;
+
+async fn get_nearest(session: &Session) -> Vec<String> {
+    get_values(session, "/test/weights", Target::Nearest(1)).await
+}
+
+#[test]
+fn weighted_routes() {
+    task::block_on(async {
+        // router03 and router04 are both connected to router01 and router02,
+        // each of them through one expensive link
+        let endpoint01 = "tcp/127.0.0.1:47470";
+        let endpoint02 = "tcp/127.0.0.1:47471";
+        let endpoint03 = "tcp/127.0.0.1:47472";
+        let endpoint04 = "tcp/127.0.0.1:47473";
+        let router01 = open_router(endpoint01, &[]).await;
+        let router02 = open_router(endpoint02, &[endpoint01]).await;
+        let router03 = open_router(
+            endpoint03,
+            &["tcp/127.0.0.1:47470#link_weight=1000", endpoint02],
+        )
+        .await;
+        let router04 = open_router(
+            endpoint04,
+            &[endpoint01, "tcp/127.0.0.1:47471#link_weight=1000"],
+        )
+        .await;
+        task::sleep(SLEEP).await;
+
+        let client01 = open_client(endpoint01).await;
+        let client02 = open_client(endpoint02).await;
+        let querier03 = open_client(endpoint03).await;
+        let querier04 = open_client(endpoint04).await;
+
+        let queryable01 = declare_queryable(&client01, "/test/weights", "01").await;
+        let queryable02 = declare_queryable(&client02, "/test/weights", "02").await;
+        task::sleep(SLEEP).await;
+
+        // the path through the other router is cheaper than the expensive link
+        assert_eq!(get_nearest(&querier03).await, vec!["02"]);
+        assert_eq!(get_nearest(&querier04).await, vec!["01"]);
+
+        drop(queryable01);
+        drop(queryable02);
+        for session in [client01, client02, querier03, querier04] {
+            session.close().await.unwrap();
+        }
+        for router in [router04, router03, router02, router01] {
+            router.close().await.unwrap();
+        }
+    });
+}
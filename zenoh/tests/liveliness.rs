@@ -0,0 +1,136 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ADLINK zenoh team, <zenoh@adlink-labs.tech>
+//
+use async_std::prelude::FutureExt;
+use async_std::task;
+use futures::StreamExt;
+use zenoh::prelude::*;
+use zenoh::Session;
+
+mod common;
+use common::*;
+
+#[test]
+fn liveliness_tokens() {
+    task::block_on(async {
+        let (peer01, peer02) = open_peers("tcp/127.0.0.1:47447").await;
+        task::sleep(SLEEP).await;
+
+        let mut subscriber = peer02
+            .liveliness()
+            .subscribe("/test/liveliness/**")
+            .unwrap()
+            .await
+            .unwrap();
+        task::sleep(SLEEP).await;
+
+        // the token is notified when declared
+        let token = peer01
+            .liveliness()
+            .declare_token("/test/liveliness/1")
+            .await
+            .unwrap();
+        let sample = subscriber.next().timeout(TIMEOUT).await.unwrap().unwrap();
+        assert_eq!(sample.kind, SampleKind::Put);
+        assert_eq!(sample.key_expr.as_str(), "/@/liveliness/test/liveliness/1");
+
+        // the token is returned by queries
+        let replies: Vec<_> = peer02
+            .liveliness()
+            .get("/test/liveliness/**")
+            .unwrap()
+            .await
+            .unwrap()
+            .collect()
+            .timeout(TIMEOUT)
+            .await
+            .unwrap();
+        assert_eq!(replies.len(), 1);
+        assert_eq!(
+            replies[0].sample.as_ref().unwrap().key_expr.as_str(),
+            "/@/liveliness/test/liveliness/1"
+        );
+
+        // the token disappears when undeclared
+        token.undeclare().await.unwrap();
+        let sample = subscriber.next().timeout(TIMEOUT).await.unwrap().unwrap();
+        assert_eq!(sample.kind, SampleKind::Delete);
+        assert_eq!(sample.key_expr.as_str(), "/@/liveliness/test/liveliness/1");
+
+        // the token disappears when its session is closed
+        let token = peer01
+            .liveliness()
+            .declare_token("/test/liveliness/2")
+            .await
+            .unwrap();
+        let sample = subscriber.next().timeout(TIMEOUT).await.unwrap().unwrap();
+        assert_eq!(sample.kind, SampleKind::Put);
+        // don't undeclare the token, so that only the closing of the session removes it
+        std::mem::forget(token);
+        peer01.close().await.unwrap();
+        let sample = subscriber.next().timeout(TIMEOUT).await.unwrap().unwrap();
+        assert_eq!(sample.kind, SampleKind::Delete);
+        assert_eq!(sample.key_expr.as_str(), "/@/liveliness/test/liveliness/2");
+
+        drop(subscriber);
+        peer02.close().await.unwrap();
+    });
+}
+
+async fn open_peer(listen: &[&str], connect: &[&str]) -> Session {
+    let mut config = peer_config(listen, connect);
+    // keep the peers in a chain
+    config.scouting.set_peers_autoconnect(Some(false)).unwrap();
+    open(config).await
+}
+
+#[test]
+fn liveliness_tokens_of_removed_peer() {
+    task::block_on(async {
+        // peer03 only learns the departure of peer01 from the link states of peer02
+        let peer01 = open_peer(&["tcp/127.0.0.1:47451"], &[]).await;
+        let peer02 = open_peer(&["tcp/127.0.0.1:47452"], &["tcp/127.0.0.1:47451"]).await;
+        let peer03 = open_peer(&[], &["tcp/127.0.0.1:47452"]).await;
+        task::sleep(SLEEP).await;
+
+        let mut subscriber = peer03
+            .liveliness()
+            .subscribe("/test/liveliness/**")
+            .unwrap()
+            .await
+            .unwrap();
+        task::sleep(SLEEP).await;
+
+        let token = peer01
+            .liveliness()
+            .declare_token("/test/liveliness/removed")
+            .await
+            .unwrap();
+        let sample = subscriber.next().timeout(TIMEOUT).await.unwrap().unwrap();
+        assert_eq!(sample.kind, SampleKind::Put);
+
+        // the queryables of peer01 are removed with it from the peers network
+        std::mem::forget(token);
+        peer01.close().await.unwrap();
+        let sample = subscriber.next().timeout(TIMEOUT).await.unwrap().unwrap();
+        assert_eq!(sample.kind, SampleKind::Delete);
+        assert_eq!(
+            sample.key_expr.as_str(),
+            "/@/liveliness/test/liveliness/removed"
+        );
+
+        drop(subscriber);
+        peer03.close().await.unwrap();
+        peer02.close().await.unwrap();
+    });
+}
@@ -0,0 +1,67 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ADLINK zenoh team, <zenoh@adlink-labs.tech>
+//
+use async_std::prelude::FutureExt;
+use async_std::task;
+use futures::StreamExt;
+
+mod common;
+use common::*;
+
+#[test]
+fn matching_status() {
+    task::block_on(async {
+        let (peer01, peer02) = open_peers("tcp/127.0.0.1:47448").await;
+        task::sleep(SLEEP).await;
+
+        // the publication must be declared
+        assert!(peer01.matching_status("/test/matching/1").await.is_err());
+        peer01
+            .declare_publication("/test/matching/1")
+            .await
+            .unwrap();
+        let status = peer01.matching_status("/test/matching/1").await.unwrap();
+        assert!(!status.is_matching());
+
+        let mut listener = peer01.matching_listener("/test/matching/1").await.unwrap();
+
+        // a remote subscriber is notified
+        let subscriber = peer02.subscribe("/test/matching/*").await.unwrap();
+        let status = listener.next().timeout(TIMEOUT).await.unwrap().unwrap();
+        assert_eq!(status.matching_subscribers, 1);
+        let status = peer01.matching_status("/test/matching/1").await.unwrap();
+        assert_eq!(status.matching_subscribers, 1);
+
+        // a local subscriber is notified
+        let local_subscriber = peer01.subscribe("/test/matching/**").await.unwrap();
+        let status = listener.next().timeout(TIMEOUT).await.unwrap().unwrap();
+        assert_eq!(status.matching_subscribers, 2);
+
+        // a non-matching subscriber is ignored
+        let other_subscriber = peer02.subscribe("/test/other").await.unwrap();
+
+        // the removal of the subscribers is notified
+        drop(local_subscriber);
+        let status = listener.next().timeout(TIMEOUT).await.unwrap().unwrap();
+        assert_eq!(status.matching_subscribers, 1);
+        drop(subscriber);
+        let status = listener.next().timeout(TIMEOUT).await.unwrap().unwrap();
+        assert!(!status.is_matching());
+        assert!(listener.next().timeout(SLEEP).await.is_err());
+
+        listener.close().await.unwrap();
+        drop(other_subscriber);
+        peer01.close().await.unwrap();
+        peer02.close().await.unwrap();
+    });
+}
@@ -0,0 +1,88 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ADLINK zenoh team, <zenoh@adlink-labs.tech>
+//
+use async_std::task;
+use zenoh::query::Target;
+use zenoh::Session;
+
+mod common;
+use common::*;
+
+async fn get(session: &Session, target: Target) -> Vec<String> {
+    get_values(session, "/test/target/1", target).await
+}
+
+#[test]
+fn nearest_queryables() {
+    task::block_on(async {
+        // three routers, with the queryables "a" and "c" on the first one, "b" on the second one
+        // and "d" and "e" on the third one, both connected to the second one
+        let endpoint01 = "tcp/127.0.0.1:47460";
+        let endpoint02 = "tcp/127.0.0.1:47461";
+        let endpoint03 = "tcp/127.0.0.1:47462";
+        let router01 = open_router(endpoint01, &[]).await;
+        let router02 = open_router(endpoint02, &[endpoint01]).await;
+        let router03 = open_router(endpoint03, &[endpoint02]).await;
+        task::sleep(SLEEP).await;
+
+        let client_a = open_client(endpoint01).await;
+        let client_b = open_client(endpoint02).await;
+        let client_c = open_client(endpoint01).await;
+        let client_d = open_client(endpoint03).await;
+        let client_e = open_client(endpoint03).await;
+        let querier01 = open_client(endpoint01).await;
+        let querier02 = open_client(endpoint02).await;
+
+        let queryable_a = declare_queryable(&client_a, "/test/target/*", "a").await;
+        let queryable_b = declare_queryable(&client_b, "/test/target/*", "b").await;
+        let queryable_c = declare_queryable(&client_c, "/test/target/*", "c").await;
+        let queryable_d = declare_queryable(&client_d, "/test/target/*", "d").await;
+        let queryable_e = declare_queryable(&client_e, "/test/target/*", "e").await;
+        task::sleep(SLEEP).await;
+
+        // the nearest complete queryable is the one connected to the same router
+        let values = get(&querier01, Target::BestMatching).await;
+        assert_eq!(values.len(), 1);
+        assert_ne!(values[0], "b");
+        assert_eq!(get(&querier02, Target::BestMatching).await, vec!["b"]);
+
+        assert_eq!(get(&querier01, Target::Nearest(2)).await, vec!["a", "c"]);
+        let values = get(&querier02, Target::Nearest(2)).await;
+        assert_eq!(values.len(), 2);
+        assert!(values.contains(&"b".to_string()));
+        let values = get(&querier02, Target::Nearest(3)).await;
+        assert_eq!(values.len(), 3);
+        assert!(values.contains(&"b".to_string()));
+        // the routers ask for more queryables than visible, but only n of them reply
+        assert_eq!(get(&querier02, Target::Nearest(4)).await.len(), 4);
+        assert_eq!(
+            get(&querier02, Target::Nearest(5)).await,
+            vec!["a", "b", "c", "d", "e"]
+        );
+        assert!(get(&querier02, Target::Nearest(0)).await.is_empty());
+
+        drop(queryable_a);
+        drop(queryable_b);
+        drop(queryable_c);
+        drop(queryable_d);
+        drop(queryable_e);
+        for session in [
+            client_a, client_b, client_c, client_d, client_e, querier01, querier02,
+        ] {
+            session.close().await.unwrap();
+        }
+        for router in [router03, router02, router01] {
+            router.close().await.unwrap();
+        }
+    });
+}
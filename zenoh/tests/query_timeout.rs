@@ -0,0 +1,90 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ADLINK zenoh team, <zenoh@adlink-labs.tech>
+//
+use async_std::prelude::FutureExt;
+use async_std::task;
+use futures::StreamExt;
+use std::time::{Duration, Instant};
+use zenoh::prelude::*;
+use zenoh::Session;
+
+mod common;
+use common::*;
+
+const QUERY_TIMEOUT: Duration = Duration::from_secs(1);
+// well above QUERY_TIMEOUT, so that both timeouts can be told apart
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+
+async fn open_peers(endpoint: &str, queries_default_timeout: ZInt) -> (Session, Session) {
+    let peer01 = open(peer_config(&[endpoint], &[])).await;
+    let mut config = peer_config(&[], &[endpoint]);
+    config
+        .set_queries_default_timeout(Some(queries_default_timeout))
+        .unwrap();
+    let peer02 = open(config).await;
+    (peer01, peer02)
+}
+
+#[test]
+fn query_timeout() {
+    task::block_on(async {
+        let (peer01, peer02) =
+            open_peers("tcp/127.0.0.1:47450", DEFAULT_TIMEOUT.as_millis() as ZInt).await;
+        task::sleep(SLEEP).await;
+
+        // a queryable replying once, then never sending its final reply
+        let mut queryable = peer01.queryable("/test/timeout/*").await.unwrap();
+        let mut queries = queryable.receiver().clone();
+        task::spawn(async move {
+            let mut pending = vec![];
+            while let Some(query) = queries.next().await {
+                let sample = Sample::new(query.key_selector().to_owned(), "reply");
+                query.reply_async(sample).await;
+                pending.push(query);
+            }
+        });
+        task::sleep(SLEEP).await;
+
+        // the queries are timed out by the router with its default timeout
+        let start = Instant::now();
+        let replies: Vec<_> = peer02
+            .get("/test/timeout/1")
+            .await
+            .unwrap()
+            .collect()
+            .timeout(TIMEOUT)
+            .await
+            .unwrap();
+        assert_eq!(replies.len(), 1);
+        assert!(start.elapsed() >= DEFAULT_TIMEOUT);
+
+        // the queries are timed out with the timeout of the getter
+        let start = Instant::now();
+        let replies: Vec<_> = peer02
+            .get("/test/timeout/2")
+            .timeout(QUERY_TIMEOUT)
+            .await
+            .unwrap()
+            .collect()
+            .timeout(TIMEOUT)
+            .await
+            .unwrap();
+        assert_eq!(replies.len(), 1);
+        assert!(start.elapsed() >= QUERY_TIMEOUT);
+        assert!(start.elapsed() < DEFAULT_TIMEOUT);
+
+        drop(queryable);
+        peer01.close().await.unwrap();
+        peer02.close().await.unwrap();
+    });
+}
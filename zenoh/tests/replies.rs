@@ -0,0 +1,165 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ADLINK zenoh team, <zenoh@adlink-labs.tech>
+//
+use async_std::prelude::FutureExt;
+use async_std::task;
+use futures::StreamExt;
+use uhlc::HLC;
+use zenoh::net::protocol::io::SplitBuffer;
+use zenoh::prelude::*;
+use zenoh::query::{
+    ConsolidationMode, ConsolidationStrategy, QueryConsolidation, QueryTarget, Reply, Target,
+};
+use zenoh::queryable;
+use zenoh::Session;
+
+mod common;
+use common::*;
+
+async fn get(session: &Session, selector: &str, consolidation: QueryConsolidation) -> Vec<Reply> {
+    get_target(session, selector, consolidation, Target::default()).await
+}
+
+async fn get_target(
+    session: &Session,
+    selector: &str,
+    consolidation: QueryConsolidation,
+    target: Target,
+) -> Vec<Reply> {
+    session
+        .get(selector)
+        .target(QueryTarget {
+            kind: queryable::ALL_KINDS,
+            target,
+        })
+        .consolidation(consolidation)
+        .await
+        .unwrap()
+        .collect()
+        .timeout(TIMEOUT)
+        .await
+        .unwrap()
+}
+
+#[test]
+fn error_replies() {
+    task::block_on(async {
+        let (peer01, peer02) = open_peers("tcp/127.0.0.1:47449").await;
+        task::sleep(SLEEP).await;
+
+        // a queryable failing for the queries with a "fail" value selector
+        let mut queryable = peer01.queryable("/test/replies/*").await.unwrap();
+        let mut queries = queryable.receiver().clone();
+        task::spawn(async move {
+            while let Some(query) = queries.next().await {
+                if query.value_selector().contains("fail") {
+                    let error = Value::from("failure").encoding(Encoding::TEXT_PLAIN);
+                    query.reply_err_async(error).await;
+                } else {
+                    let sample = Sample::new(query.key_selector().to_owned(), "success");
+                    query.reply_async(sample).await;
+                }
+            }
+        });
+        task::sleep(SLEEP).await;
+
+        for consolidation in [QueryConsolidation::none(), QueryConsolidation::full()] {
+            let replies = get(&peer02, "/test/replies/1", consolidation.clone()).await;
+            assert_eq!(replies.len(), 1);
+            let sample = replies[0].sample.as_ref().unwrap();
+            assert_eq!(sample.key_expr.as_str(), "/test/replies/1");
+            assert_eq!(sample.value.payload.contiguous().as_ref(), b"success");
+
+            let replies = get(&peer02, "/test/replies/1?fail", consolidation).await;
+            assert_eq!(replies.len(), 1);
+            let error = replies[0].sample.as_ref().unwrap_err();
+            assert_eq!(error.encoding, Encoding::TEXT_PLAIN);
+            assert_eq!(error.payload.contiguous().as_ref(), b"failure");
+            assert_eq!(replies[0].replier_id.to_string(), peer01.id().await);
+        }
+
+        drop(queryable);
+        peer01.close().await.unwrap();
+        peer02.close().await.unwrap();
+    });
+}
+
+#[test]
+fn router_consolidation() {
+    task::block_on(async {
+        let endpoint = "tcp/127.0.0.1:47453";
+        let router = open_router(endpoint, &[]).await;
+
+        let mut clients = vec![];
+        for _ in 0..3 {
+            clients.push(open_client(endpoint).await);
+        }
+        task::sleep(SLEEP).await;
+
+        // two replicas replying on the same key with different timestamps
+        let hlc = HLC::default();
+        let replicas = [(hlc.new_timestamp(), "old"), (hlc.new_timestamp(), "new")];
+        let mut queryables = vec![];
+        for (client, (timestamp, value)) in clients.iter().zip(replicas) {
+            let mut queryable = client.queryable("/test/consolidation/*").await.unwrap();
+            let mut queries = queryable.receiver().clone();
+            task::spawn(async move {
+                while let Some(query) = queries.next().await {
+                    let sample = Sample::new(query.key_selector().to_owned(), value)
+                        .with_timestamp(timestamp);
+                    query.reply_async(sample).await;
+                }
+            });
+            queryables.push(queryable);
+        }
+        task::sleep(SLEEP).await;
+
+        // no consolidation at reception, so that only the router consolidates
+        let strategy = |mode| ConsolidationStrategy {
+            first_routers: mode,
+            last_router: mode,
+            reception: ConsolidationMode::None,
+        };
+
+        let replies = get_target(
+            &clients[2],
+            "/test/consolidation/1",
+            QueryConsolidation::Manual(strategy(ConsolidationMode::None)),
+            Target::All,
+        )
+        .await;
+        assert_eq!(replies.len(), 2);
+
+        for mode in [ConsolidationMode::Lazy, ConsolidationMode::Full] {
+            let replies = get_target(
+                &clients[2],
+                "/test/consolidation/1",
+                QueryConsolidation::Manual(strategy(mode)),
+                Target::All,
+            )
+            .await;
+            let newest = replies.last().unwrap().sample.as_ref().unwrap();
+            assert_eq!(newest.key_expr.as_str(), "/test/consolidation/1");
+            assert_eq!(newest.value.payload.contiguous().as_ref(), b"new");
+            if mode == ConsolidationMode::Full {
+                assert_eq!(replies.len(), 1);
+            }
+        }
+
+        drop(queryables);
+        for client in clients {
+            client.close().await.unwrap();
+        }
+        router.close().await.unwrap();
+    });
+}
@@ -467,6 +467,7 @@ impl Primitives for ClientPrimitives {
         _qid: ZInt,
         _target: QueryTarget,
         _consolidation: ConsolidationStrategy,
+        _timeout: Option<Duration>,
         _routing_context: Option<RoutingContext>,
     ) {
     }
@@ -476,6 +477,7 @@ impl Primitives for ClientPrimitives {
         _qid: ZInt,
         _replier_kind: ZInt,
         _replier_id: PeerId,
+        _is_error: bool,
         _key_expr: KeyExpr,
         _info: Option<DataInfo>,
         _payload: ZBuf,